@@ -44,6 +44,7 @@ struct LastBevyKeypress(pub KeyboardInput);
 #[derive(Resource, Deref, DerefMut)]
 struct BevyKeypresses(pub Vec<KeyCode>);
 
+#[allow(clippy::too_many_arguments)]
 fn draw_scene_system(
     mut context: ResMut<RatatuiContext>,
     kitty_enabled: Option<Res<KittyEnabled>>,
@@ -140,7 +141,7 @@ fn hotkeys(
     } else if input.just_pressed(KeyP) {
         // Mutate the policy to ensure that the Emulate marker is removed
         // (however briefly).
-        *policy = *policy;
+        policy.set_changed();
     }
 }
 
@@ -1,14 +1,10 @@
 use core::panic;
 use std::time::Duration;
 
-use bevy::{
-    app::{AppExit, ScheduleRunnerPlugin},
-    core::FrameCount,
-    prelude::*,
-    state::app::StatesPlugin,
-};
+use bevy::{app::ScheduleRunnerPlugin, core::FrameCount, prelude::*, state::app::StatesPlugin};
 use bevy_ratatui::{
-    error::exit_on_error, event::KeyEvent, terminal::RatatuiContext, RatatuiPlugins,
+    error::exit_on_error, event::KeyEvent, quit::QuitPlugin, terminal::RatatuiContext,
+    RatatuiPlugins,
 };
 use crossterm::event::KeyCode;
 use ratatui::{
@@ -23,6 +19,7 @@ fn main() {
     let frame_rate = Duration::from_secs_f64(1. / 60.);
     App::new()
         .add_plugins(RatatuiPlugins::default())
+        .add_plugins(QuitPlugin)
         .add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(frame_rate)))
         .add_plugins(StatesPlugin)
         .add_systems(PreUpdate, keyboard_input_system)
@@ -58,14 +55,10 @@ fn ui_system(
 
 fn keyboard_input_system(
     mut events: EventReader<KeyEvent>,
-    mut app_exit: EventWriter<AppExit>,
     mut counter_events: EventWriter<CounterEvent>,
 ) {
     for event in events.read() {
         match event.code {
-            KeyCode::Char('q') | KeyCode::Esc => {
-                app_exit.send_default();
-            }
             KeyCode::Char('p') => {
                 panic!("Panic!");
             }
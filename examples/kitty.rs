@@ -1,10 +1,7 @@
-use bevy::{
-    app::{AppExit, ScheduleRunnerPlugin},
-    prelude::*,
-};
+use bevy::{app::ScheduleRunnerPlugin, prelude::*};
 use bevy_ratatui::{
-    error::exit_on_error, event::KeyEvent, kitty::KittyEnabled, terminal::RatatuiContext,
-    RatatuiPlugins,
+    error::exit_on_error, event::KeyEvent, kitty::KittyEnabled, quit::QuitPlugin,
+    terminal::RatatuiContext, RatatuiPlugins,
 };
 use crossterm::event::KeyEventKind;
 use ratatui::text::Text;
@@ -13,6 +10,7 @@ fn main() {
     let wait_duration = std::time::Duration::from_secs_f64(1. / 60.); // 60 FPS
     App::new()
         .add_plugins(RatatuiPlugins::default())
+        .add_plugins(QuitPlugin)
         .add_plugins(ScheduleRunnerPlugin::run_loop(wait_duration))
         .add_systems(PreUpdate, keyboard_input_system)
         .add_systems(Update, draw_scene_system.pipe(exit_on_error))
@@ -52,20 +50,8 @@ fn draw_scene_system(
     Ok(())
 }
 
-fn keyboard_input_system(
-    mut events: EventReader<KeyEvent>,
-    mut exit: EventWriter<AppExit>,
-    mut commands: Commands,
-) {
-    use crossterm::event::KeyCode;
+fn keyboard_input_system(mut events: EventReader<KeyEvent>, mut commands: Commands) {
     for event in events.read() {
-        match event.code {
-            KeyCode::Char('q') | KeyCode::Esc => {
-                exit.send_default();
-            }
-            _ => {
-                commands.insert_resource(LastKeypress(event.clone()));
-            }
-        }
+        commands.insert_resource(LastKeypress(event.clone()));
     }
 }
@@ -1,10 +1,8 @@
-use bevy::{
-    app::{AppExit, ScheduleRunnerPlugin},
-    prelude::*,
-};
+use bevy::{app::ScheduleRunnerPlugin, prelude::*};
 use bevy_ratatui::{
-    event::{KeyEvent, MouseEvent},
-    terminal::RatatuiContext,
+    event::MouseEvent,
+    quit::QuitPlugin,
+    terminal::{RatatuiContext, TerminalInfo},
     RatatuiPlugins,
 };
 use crossterm::event::MouseEventKind;
@@ -17,26 +15,14 @@ fn main() {
             enable_mouse_capture: true,
             ..default()
         })
+        .add_plugins(QuitPlugin)
         .add_plugins(ScheduleRunnerPlugin::run_loop(wait_duration))
-        .add_systems(PreUpdate, keyboard_input_system)
         .add_systems(Update, mouse_input_system)
         .add_systems(Update, (move_balls, bounce_balls.chain()))
         .add_systems(PostUpdate, draw_balls)
         .run();
 }
 
-fn keyboard_input_system(mut events: EventReader<KeyEvent>, mut exit: EventWriter<AppExit>) {
-    use crossterm::event::KeyCode;
-    for event in events.read() {
-        match event.code {
-            KeyCode::Char('q') | KeyCode::Esc => {
-                exit.send_default();
-            }
-            _ => {}
-        }
-    }
-}
-
 #[derive(Debug, Component)]
 struct Ball;
 
@@ -127,13 +113,13 @@ fn draw_balls(mut context: ResMut<RatatuiContext>, query: Query<(&Ball, &Positio
 fn mouse_input_system(
     mut events: EventReader<MouseEvent>,
     mut commands: Commands,
-    context: Res<RatatuiContext>,
+    terminal: Res<TerminalInfo>,
 ) {
     for event in events.read() {
         let crossterm::event::MouseEvent {
             kind, column, row, ..
         } = event.0;
-        let size = context.size().unwrap(); // TODO: handle error properly
+        let size = terminal.size;
         let column = column as f32 / size.width as f32;
         let row = row as f32 / size.height as f32;
         if let MouseEventKind::Moved = kind {
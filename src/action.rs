@@ -0,0 +1,210 @@
+//! Binding keys and gamepad input to app actions.
+//!
+//! An [`ActionMap`] binds keys, gamepad buttons and gamepad stick directions to the actions of an
+//! app, and [`ActionPlugin`] sends an [`ActionEvent`] whenever a binding is pressed. Systems that
+//! read actions rather than keys work with the keyboard and with a controller alike.
+//!
+//! Gamepads are read from bevy's [`Gamepad`] components, which `bevy_gilrs` keeps up to date when
+//! its plugin is added. Without it the gamepad bindings never fire and only the keys are used.
+//!
+//! ```rust,no_run
+//! use bevy::{input::gamepad::GamepadButton, prelude::*};
+//! use bevy_ratatui::action::{ActionEvent, ActionMap, ActionPlugin};
+//! use crossterm::event::KeyCode;
+//!
+//! #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+//! enum Action {
+//!     Jump,
+//!     Pause,
+//! }
+//!
+//! App::new()
+//!     .add_plugins(ActionPlugin::<Action>::default())
+//!     .insert_resource(
+//!         ActionMap::default()
+//!             .bind_key(KeyCode::Char(' '), Action::Jump)
+//!             .bind_button(GamepadButton::South, Action::Jump)
+//!             .bind_key(KeyCode::Char('p'), Action::Pause)
+//!             .bind_button(GamepadButton::Start, Action::Pause),
+//!     )
+//!     .add_systems(Update, handle_actions);
+//!
+//! fn handle_actions(mut actions: EventReader<ActionEvent<Action>>) {
+//!     for event in actions.read() {
+//!         info!("{:?} from {:?}", event.action, event.source);
+//!     }
+//! }
+//! ```
+use std::{hash::Hash, marker::PhantomData};
+
+use bevy::{
+    input::{
+        gamepad::{GamepadAxis, GamepadButton},
+        InputSystem,
+    },
+    prelude::*,
+    utils::HashSet,
+};
+
+use crate::{
+    event::{InputSet, KeyEvent},
+    quit::KeyBinding,
+};
+
+/// A plugin that sends [`ActionEvent<A>`]s for the bindings in the [`ActionMap<A>`].
+pub struct ActionPlugin<A>(PhantomData<A>);
+
+impl<A> Default for ActionPlugin<A> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A: Clone + Eq + Hash + Send + Sync + 'static> Plugin for ActionPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionMap<A>>()
+            .add_event::<ActionEvent<A>>()
+            .add_systems(
+                PreUpdate,
+                (send_key_actions::<A>, send_gamepad_actions::<A>)
+                    .chain()
+                    .in_set(InputSet::Post)
+                    .after(InputSystem),
+            );
+    }
+}
+
+/// Where an action came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ActionSource {
+    /// A key of the terminal.
+    Key,
+    /// The gamepad entity.
+    Gamepad(Entity),
+}
+
+/// Sent when a binding of the action is pressed.
+#[derive(Debug, Clone, Event, PartialEq, Eq, Hash)]
+pub struct ActionEvent<A> {
+    pub action: A,
+    pub source: ActionSource,
+}
+
+/// The direction a stick is pushed in along an axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+/// A gamepad axis pushed past a threshold in a direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxisBinding {
+    pub axis: GamepadAxis,
+    pub direction: AxisDirection,
+    /// How far the axis must be pushed, between `0.0` and `1.0`.
+    pub threshold: f32,
+}
+
+impl AxisBinding {
+    fn is_pressed(&self, value: f32) -> bool {
+        match self.direction {
+            AxisDirection::Positive => value >= self.threshold,
+            AxisDirection::Negative => value <= -self.threshold,
+        }
+    }
+}
+
+/// The bindings of the actions `A`. An action can have any number of bindings.
+#[derive(Resource, Debug, Clone)]
+pub struct ActionMap<A> {
+    pub keys: Vec<(KeyBinding, A)>,
+    pub buttons: Vec<(GamepadButton, A)>,
+    pub axes: Vec<(AxisBinding, A)>,
+}
+
+impl<A> Default for ActionMap<A> {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            buttons: Vec::new(),
+            axes: Vec::new(),
+        }
+    }
+}
+
+impl<A> ActionMap<A> {
+    /// Binds a key to the action.
+    pub fn bind_key(mut self, key: impl Into<KeyBinding>, action: A) -> Self {
+        self.keys.push((key.into(), action));
+        self
+    }
+
+    /// Binds a gamepad button to the action.
+    pub fn bind_button(mut self, button: GamepadButton, action: A) -> Self {
+        self.buttons.push((button, action));
+        self
+    }
+
+    /// Binds pushing a gamepad axis at least halfway in a direction to the action. The action is
+    /// sent once each time the axis crosses the threshold.
+    pub fn bind_axis(mut self, axis: GamepadAxis, direction: AxisDirection, action: A) -> Self {
+        self.axes.push((
+            AxisBinding {
+                axis,
+                direction,
+                threshold: 0.5,
+            },
+            action,
+        ));
+        self
+    }
+}
+
+fn send_key_actions<A: Clone + Send + Sync + 'static>(
+    mut keys: EventReader<KeyEvent>,
+    map: Res<ActionMap<A>>,
+    mut actions: EventWriter<ActionEvent<A>>,
+) {
+    for key in keys.read() {
+        for (binding, action) in &map.keys {
+            if binding.matches(key) {
+                actions.send(ActionEvent {
+                    action: action.clone(),
+                    source: ActionSource::Key,
+                });
+            }
+        }
+    }
+}
+
+fn send_gamepad_actions<A: Clone + Send + Sync + 'static>(
+    gamepads: Query<(Entity, &Gamepad)>,
+    map: Res<ActionMap<A>>,
+    mut pushed_axes: Local<HashSet<(Entity, usize)>>,
+    mut actions: EventWriter<ActionEvent<A>>,
+) {
+    for (entity, gamepad) in &gamepads {
+        let source = ActionSource::Gamepad(entity);
+        for (button, action) in &map.buttons {
+            if gamepad.just_pressed(*button) {
+                actions.send(ActionEvent {
+                    action: action.clone(),
+                    source,
+                });
+            }
+        }
+        for (index, (binding, action)) in map.axes.iter().enumerate() {
+            let value = gamepad.get(binding.axis).unwrap_or_default();
+            if !binding.is_pressed(value) {
+                pushed_axes.remove(&(entity, index));
+            } else if pushed_axes.insert((entity, index)) {
+                actions.send(ActionEvent {
+                    action: action.clone(),
+                    source,
+                });
+            }
+        }
+    }
+    pushed_axes.retain(|(entity, _)| gamepads.contains(*entity));
+}
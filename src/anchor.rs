@@ -0,0 +1,159 @@
+//! Positioning widgets by anchor instead of by layout.
+//!
+//! Some widgets, such as HUD elements and toasts, sit at a fixed place on screen rather than in a
+//! constraint layout. Give such a widget entity an [`Anchored`] component and [`AnchorPlugin`]
+//! keeps its [`AnchoredArea`] up to date, relative to the terminal or to the area of an
+//! [`AnchorParent`], recalculating it when the terminal is resized. Draw the widget in that area.
+//!
+//! ```rust
+//! use bevy_ratatui::anchor::{Anchor, Anchored};
+//! use ratatui::layout::{Rect, Size};
+//!
+//! let toast = Anchored::new(Anchor::TopRight, Size::new(20, 3)).with_offset(-1, 1);
+//! assert_eq!(toast.resolve(Rect::new(0, 0, 80, 24)), Rect::new(59, 1, 20, 3));
+//! ```
+use bevy::{prelude::*, utils::HashMap};
+use ratatui::layout::{Position, Rect, Size};
+
+use crate::{event::InputSet, terminal::RatatuiContext};
+
+/// A plugin that computes the [`AnchoredArea`] of [`Anchored`] entities.
+pub struct AnchorPlugin;
+
+impl Plugin for AnchorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PreUpdate, update_anchored_areas.after(InputSet::Post));
+    }
+}
+
+/// The point of the parent area that an [`Anchored`] widget is attached to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+/// Positions a widget entity at an anchor of the terminal, or of its [`AnchorParent`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Anchored {
+    pub anchor: Anchor,
+    /// Moves the widget from the anchor by this many columns and rows.
+    pub offset: (i16, i16),
+    pub size: Size,
+}
+
+impl Anchored {
+    pub fn new(anchor: Anchor, size: Size) -> Self {
+        Self {
+            anchor,
+            offset: (0, 0),
+            size,
+        }
+    }
+
+    /// Sets the offset from the anchor.
+    pub fn with_offset(mut self, x: i16, y: i16) -> Self {
+        self.offset = (x, y);
+        self
+    }
+
+    /// The area of the widget within the parent area, shrunk to fit inside it.
+    pub fn resolve(&self, parent: Rect) -> Rect {
+        let width = self.size.width.min(parent.width);
+        let height = self.size.height.min(parent.height);
+        let (free_x, free_y) = (parent.width - width, parent.height - height);
+        let (x, y) = match self.anchor {
+            Anchor::TopLeft => (0, 0),
+            Anchor::Top => (free_x / 2, 0),
+            Anchor::TopRight => (free_x, 0),
+            Anchor::Left => (0, free_y / 2),
+            Anchor::Center => (free_x / 2, free_y / 2),
+            Anchor::Right => (free_x, free_y / 2),
+            Anchor::BottomLeft => (0, free_y),
+            Anchor::Bottom => (free_x / 2, free_y),
+            Anchor::BottomRight => (free_x, free_y),
+        };
+        let x = x.saturating_add_signed(self.offset.0).min(free_x);
+        let y = y.saturating_add_signed(self.offset.1).min(free_y);
+        Rect::new(parent.x + x, parent.y + y, width, height)
+    }
+}
+
+/// Positions an [`Anchored`] widget relative to the [`AnchoredArea`] of another entity rather than
+/// the terminal.
+///
+/// The parent can be anchored itself, or be an entity such as a pane whose [`AnchoredArea`] the app
+/// sets when it computes its layout.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash, Deref)]
+pub struct AnchorParent(pub Entity);
+
+/// The area computed for an [`Anchored`] widget.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deref)]
+pub struct AnchoredArea(pub Rect);
+
+type AnchoredQuery<'a> = (
+    Entity,
+    &'a Anchored,
+    Option<&'a AnchorParent>,
+    Option<&'a AnchoredArea>,
+);
+
+fn update_anchored_areas(
+    mut commands: Commands,
+    context: Option<Res<RatatuiContext>>,
+    anchored: Query<AnchoredQuery>,
+    parents: Query<&AnchoredArea, Without<Anchored>>,
+) {
+    let Some(Ok(size)) = context.map(|context| context.size()) else {
+        return;
+    };
+    let screen = Rect::from((Position::ORIGIN, size));
+    let mut resolved = HashMap::new();
+    for (entity, ..) in &anchored {
+        let area = resolve_area(entity, screen, &anchored, &parents, &mut resolved, 0);
+        let current = anchored
+            .get(entity)
+            .ok()
+            .and_then(|(.., area)| area.copied());
+        if current != Some(AnchoredArea(area)) {
+            commands.entity(entity).insert(AnchoredArea(area));
+        }
+    }
+}
+
+/// Parent chains longer than this are assumed to be cycles and resolved against the terminal.
+const MAX_DEPTH: usize = 32;
+
+/// Resolves the area of an anchored entity, resolving its parents first.
+fn resolve_area(
+    entity: Entity,
+    screen: Rect,
+    anchored: &Query<AnchoredQuery>,
+    parents: &Query<&AnchoredArea, Without<Anchored>>,
+    resolved: &mut HashMap<Entity, Rect>,
+    depth: usize,
+) -> Rect {
+    if let Some(area) = resolved.get(&entity) {
+        return *area;
+    }
+    let Ok((_, anchor, parent, _)) = anchored.get(entity) else {
+        return parents.get(entity).map_or(screen, |area| area.0);
+    };
+    let parent_area = match parent {
+        Some(parent) if depth < MAX_DEPTH => {
+            resolve_area(parent.0, screen, anchored, parents, resolved, depth + 1)
+        }
+        _ => screen,
+    };
+    let area = anchor.resolve(parent_area);
+    resolved.insert(entity, area);
+    area
+}
@@ -0,0 +1,105 @@
+//! Sound feedback for UI events.
+//!
+//! [`AudioCuePlugin`] plays a sound for each [`UiCue`] event that has a sound set in [`AudioCues`].
+//! Focus changes of the terminal are sent as cues by the plugin, and apps send the others, e.g.
+//! when showing a toast or an error.
+//!
+//! Sounds are played with `bevy_audio`, so the app also needs bevy's `AudioPlugin` and
+//! `AssetPlugin`, and the bevy features for the file formats it uses. Without an `AudioPlugin`, or
+//! without an audio device, cues are silently dropped.
+//!
+//! This module is only available with the `audio` feature.
+//!
+//! ```rust,no_run
+//! use bevy::{audio::AudioPlugin, prelude::*};
+//! use bevy_ratatui::{
+//!     audio::{AudioCuePlugin, AudioCues, UiCue},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((
+//!         RatatuiPlugins::default(),
+//!         AssetPlugin::default(),
+//!         AudioPlugin::default(),
+//!         AudioCuePlugin,
+//!     ))
+//!     .add_systems(Startup, load_cues);
+//!
+//! fn load_cues(assets: Res<AssetServer>, mut cues: ResMut<AudioCues>) {
+//!     cues.insert(UiCue::Error, assets.load("sounds/error.ogg"));
+//!     cues.insert(UiCue::Bell, assets.load("sounds/bell.ogg"));
+//! }
+//! ```
+use bevy::{
+    audio::{AudioPlayer, AudioSink, AudioSource, PlaybackSettings},
+    prelude::*,
+    utils::HashMap,
+};
+
+use crate::event::{FocusEvent, InputSet};
+
+/// A plugin that plays the sounds of [`UiCue`] events.
+pub struct AudioCuePlugin;
+
+impl Plugin for AudioCuePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AudioCues>()
+            .add_event::<UiCue>()
+            .add_systems(PreUpdate, send_focus_cues.in_set(InputSet::Post))
+            .add_systems(PostUpdate, play_cues);
+    }
+}
+
+/// A UI event that can have a sound.
+#[derive(Debug, Clone, Copy, Event, PartialEq, Eq, Hash)]
+pub enum UiCue {
+    /// Something needs the user's attention.
+    Bell,
+    /// An error was shown.
+    Error,
+    /// A toast or other notification was shown.
+    Toast,
+    /// The terminal gained focus. Sent by [`AudioCuePlugin`].
+    FocusGained,
+    /// The terminal lost focus. Sent by [`AudioCuePlugin`].
+    FocusLost,
+}
+
+/// The sound played for each [`UiCue`]. Cues without a sound are not played.
+#[derive(Resource, Debug, Clone, Default, Deref, DerefMut)]
+pub struct AudioCues(pub HashMap<UiCue, Handle<AudioSource>>);
+
+/// Marks the entity that plays the sound of a cue.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CueSound(UiCue);
+
+fn send_focus_cues(mut focus: EventReader<FocusEvent>, mut cues: EventWriter<UiCue>) {
+    cues.send_batch(focus.read().map(|event| match event {
+        FocusEvent::Gained => UiCue::FocusGained,
+        FocusEvent::Lost => UiCue::FocusLost,
+    }));
+}
+
+fn play_cues(
+    mut commands: Commands,
+    mut events: EventReader<UiCue>,
+    cues: Res<AudioCues>,
+    pending: Query<(Entity, &CueSound), Without<AudioSink>>,
+) {
+    for cue in events.read() {
+        let Some(sound) = cues.get(cue) else {
+            continue;
+        };
+        // Sounds that never started playing, because there is no audio output, are replaced
+        // rather than piling up.
+        for (entity, _) in pending.iter().filter(|(_, pending)| pending.0 == *cue) {
+            commands.entity(entity).despawn();
+        }
+        commands.spawn((
+            AudioPlayer(sound.clone()),
+            PlaybackSettings::DESPAWN,
+            CueSound(*cue),
+        ));
+    }
+}
@@ -0,0 +1,165 @@
+//! Converting rendered buffers to text.
+//!
+//! These helpers turn a ratatui [`Buffer`] (such as
+//! [`RatatuiContext::last_frame`](crate::terminal::RatatuiContext::last_frame)) into a string,
+//! either as plain text or with ANSI escape codes for the colors and modifiers. This is useful for
+//! including what was on screen in logs, error reports and assertion messages, and for copying the
+//! screen, or a part of it with [`area_to_plain_lines`], to the clipboard.
+//!
+//! ```rust
+//! use bevy_ratatui::buffer::to_plain_string;
+//! use ratatui::{buffer::Buffer, layout::Rect, text::Line, widgets::Widget};
+//!
+//! let mut buffer = Buffer::empty(Rect::new(0, 0, 5, 1));
+//! Line::from("hello").render(buffer.area, &mut buffer);
+//! assert_eq!(to_plain_string(&buffer), "hello");
+//! ```
+use std::io::{self, Write};
+
+use crossterm::{
+    style::{Attribute, Print, SetAttribute, SetBackgroundColor, SetForegroundColor},
+    QueueableCommand,
+};
+use ratatui::{
+    buffer::{Buffer, Cell},
+    layout::Rect,
+    style::Modifier,
+};
+use unicode_width::UnicodeWidthStr;
+
+/// Converts the buffer to plain text, joining the rows from [`to_plain_lines`] with newlines.
+pub fn to_plain_string(buffer: &Buffer) -> String {
+    to_plain_lines(buffer).join("\n")
+}
+
+/// Converts the buffer to plain text, returning each row as a separate line.
+///
+/// This is the text a user sees on screen, suitable for copying to the clipboard or passing to a
+/// screen reader. Styles are dropped, the cells hidden behind wide characters are skipped, cells
+/// with the [`Modifier::HIDDEN`] modifier are replaced with spaces and trailing whitespace is
+/// trimmed from each row.
+///
+/// ```rust
+/// use bevy_ratatui::buffer::to_plain_lines;
+/// use ratatui::{buffer::Buffer, layout::Rect, text::Line, widgets::Widget};
+///
+/// let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 2));
+/// Line::from("名前 x").render(buffer.area, &mut buffer);
+/// assert_eq!(to_plain_lines(&buffer), ["名前 x", ""]);
+/// ```
+pub fn to_plain_lines(buffer: &Buffer) -> Vec<String> {
+    area_to_plain_lines(buffer, buffer.area)
+}
+
+/// Converts the part of the buffer inside `area` to plain text, one line per row.
+///
+/// This is the same as [`to_plain_lines`], but only includes the characters that start inside the
+/// area, e.g. to copy the text of a single widget.
+pub fn area_to_plain_lines(buffer: &Buffer, area: Rect) -> Vec<String> {
+    let area = area.intersection(buffer.area);
+    let width = buffer.area.width as usize;
+    let (left, right) = (
+        area.left() - buffer.area.left(),
+        area.right() - buffer.area.left(),
+    );
+    rows(buffer)
+        .skip((area.top() - buffer.area.top()) as usize)
+        .take(area.height as usize)
+        .map(|row| {
+            let mut line = String::with_capacity(width);
+            for (x, cell) in visible_cells_with_columns(row) {
+                if x < left as usize || x >= right as usize {
+                    continue;
+                }
+                if cell.modifier.contains(Modifier::HIDDEN) {
+                    line.extend(std::iter::repeat_n(' ', cell.symbol().width().max(1)));
+                } else {
+                    line.push_str(cell.symbol());
+                }
+            }
+            line.truncate(line.trim_end().len());
+            line
+        })
+        .collect()
+}
+
+/// Converts the buffer to text with ANSI escape codes for the colors and modifiers.
+///
+/// Trailing blank rows are skipped. Each row ends with a reset and a `\r\n`.
+pub fn to_ansi_string(buffer: &Buffer) -> String {
+    let mut output = Vec::new();
+    write_ansi(&mut output, buffer).expect("writing to a Vec cannot fail");
+    String::from_utf8(output).expect("buffer symbols are valid UTF-8")
+}
+
+/// Writes the buffer as styled lines using ANSI escape codes.
+///
+/// Trailing blank rows are skipped so that a mostly empty frame does not push the shell prompt
+/// down by a full screen when it is printed.
+pub fn write_ansi<W: Write>(writer: &mut W, buffer: &Buffer) -> io::Result<()> {
+    let rows: Vec<&[Cell]> = rows(buffer).collect();
+    let height = rows
+        .iter()
+        .rposition(|row| row.iter().any(|cell| *cell != Cell::EMPTY))
+        .map_or(0, |last| last + 1);
+    for row in &rows[..height] {
+        let mut style = None;
+        for cell in visible_cells(row) {
+            if style != Some((cell.fg, cell.bg, cell.modifier)) {
+                style = Some((cell.fg, cell.bg, cell.modifier));
+                writer
+                    .queue(SetAttribute(Attribute::Reset))?
+                    .queue(SetForegroundColor(cell.fg.into()))?
+                    .queue(SetBackgroundColor(cell.bg.into()))?;
+                for attribute in modifier_attributes(cell.modifier) {
+                    writer.queue(SetAttribute(attribute))?;
+                }
+            }
+            writer.queue(Print(cell.symbol()))?;
+        }
+        writer
+            .queue(SetAttribute(Attribute::Reset))?
+            .queue(Print("\r\n"))?;
+    }
+    Ok(())
+}
+
+fn rows(buffer: &Buffer) -> impl Iterator<Item = &[Cell]> {
+    buffer.content.chunks(buffer.area.width.max(1) as usize)
+}
+
+/// The cells of a row that are not covered by a preceding wide character.
+fn visible_cells(row: &[Cell]) -> impl Iterator<Item = &Cell> {
+    visible_cells_with_columns(row).map(|(_, cell)| cell)
+}
+
+/// The cells of a row that are not covered by a preceding wide character, with their columns.
+fn visible_cells_with_columns(row: &[Cell]) -> impl Iterator<Item = (usize, &Cell)> {
+    let mut to_skip = 0;
+    row.iter().enumerate().filter(move |(_, cell)| {
+        if to_skip > 0 {
+            to_skip -= 1;
+            return false;
+        }
+        to_skip = cell.symbol().width().saturating_sub(1);
+        true
+    })
+}
+
+/// Converts a ratatui [`Modifier`] into the equivalent crossterm attributes.
+fn modifier_attributes(modifier: Modifier) -> impl Iterator<Item = Attribute> {
+    [
+        (Modifier::BOLD, Attribute::Bold),
+        (Modifier::DIM, Attribute::Dim),
+        (Modifier::ITALIC, Attribute::Italic),
+        (Modifier::UNDERLINED, Attribute::Underlined),
+        (Modifier::SLOW_BLINK, Attribute::SlowBlink),
+        (Modifier::RAPID_BLINK, Attribute::RapidBlink),
+        (Modifier::REVERSED, Attribute::Reverse),
+        (Modifier::HIDDEN, Attribute::Hidden),
+        (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+    ]
+    .into_iter()
+    .filter(move |(flag, _)| modifier.contains(*flag))
+    .map(|(_, attribute)| attribute)
+}
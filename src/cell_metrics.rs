@@ -0,0 +1,193 @@
+//! The size of a terminal cell in pixels.
+//!
+//! Terminal cells are not square, so anything that maps pixels or world units onto cells, such as
+//! a camera or an image, must correct for the shape of the cells or everything looks stretched.
+//! [`CellMetricsPlugin`] measures the cells from the pixel and cell size that the terminal reports,
+//! and keeps the [`CellMetrics`] resource up to date when the terminal is resized. Zooming the font
+//! changes the size of the cells, but not always the number of cells, so the cells are also
+//! measured again every [`CellMetricsPollInterval`]. Whenever the size of a cell changes, a
+//! [`CellSizeChanged`] event is sent, so that anything drawn at the old aspect ratio, such as an
+//! image or a camera view, can be scaled again.
+//!
+//! Terminals that don't report their pixel size, and headless terminals, get cells twice as tall
+//! as they are wide, which is close for most fonts.
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::cell_metrics::{CellMetrics, CellSizeChanged};
+//!
+//! /// Draws a circle with half block characters, two dots per cell.
+//! fn circle(metrics: Res<CellMetrics>) {
+//!     let correction = metrics.correction(UVec2::new(1, 2));
+//!     let radius = Vec2::splat(10.0) * correction;
+//!     // ...
+//! }
+//!
+//! /// Scales images again when the font is zoomed.
+//! fn rescale(mut changes: EventReader<CellSizeChanged>) {
+//!     for change in changes.read() {
+//!         let aspect_ratio = change.current.aspect_ratio();
+//!         // ...
+//!     }
+//! }
+//! ```
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+
+use crate::{
+    event::{InputSet, ResizeEvent},
+    terminal::{is_headless, RatatuiContext},
+};
+
+/// A plugin that keeps the [`CellMetrics`] up to date.
+///
+/// This is part of [`RatatuiPlugins`](crate::RatatuiPlugins).
+pub struct CellMetricsPlugin;
+
+impl Plugin for CellMetricsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CellMetrics>()
+            .init_resource::<CellMetricsPollInterval>()
+            .add_event::<CellSizeChanged>()
+            .add_systems(
+                PreUpdate,
+                measure_cells
+                    .run_if(not(is_headless).and(resource_exists::<RatatuiContext>))
+                    .in_set(InputSet::Post),
+            );
+    }
+}
+
+/// How often the cells are measured again when the terminal has not been resized, or `None` to
+/// only measure them on resizes. Defaults to one second.
+///
+/// Measuring asks the terminal for its size, which is cheap.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct CellMetricsPollInterval(pub Option<Duration>);
+
+impl Default for CellMetricsPollInterval {
+    fn default() -> Self {
+        Self(Some(Duration::from_secs(1)))
+    }
+}
+
+/// Sent when the size of a cell in pixels changes, e.g. because the font was zoomed.
+///
+/// This is not sent for the first measurement, when the app starts.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct CellSizeChanged {
+    /// The metrics before the change.
+    pub previous: CellMetrics,
+    /// The metrics after the change, which are also in the [`CellMetrics`] resource.
+    pub current: CellMetrics,
+}
+
+/// The size of a terminal cell in pixels.
+///
+/// The cell size is the size of the terminal window in pixels divided by the number of columns and
+/// rows, so it includes any spacing between lines.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct CellMetrics {
+    /// The width of a cell in pixels.
+    pub cell_width: f32,
+    /// The height of a cell in pixels.
+    pub cell_height: f32,
+    /// Whether the size was measured, rather than assumed because the terminal does not report its
+    /// pixel size.
+    pub measured: bool,
+}
+
+impl Default for CellMetrics {
+    fn default() -> Self {
+        Self {
+            cell_width: 8.0,
+            cell_height: 16.0,
+            measured: false,
+        }
+    }
+}
+
+impl CellMetrics {
+    /// Measures the cells of the terminal, or `None` if the terminal does not report its pixel
+    /// size.
+    pub fn measure() -> Option<Self> {
+        let size = crossterm::terminal::window_size().ok()?;
+        if size.width == 0 || size.height == 0 || size.columns == 0 || size.rows == 0 {
+            return None;
+        }
+        Some(Self {
+            cell_width: f32::from(size.width) / f32::from(size.columns),
+            cell_height: f32::from(size.height) / f32::from(size.rows),
+            measured: true,
+        })
+    }
+
+    /// The size of a cell in pixels.
+    pub fn cell_size(&self) -> Vec2 {
+        Vec2::new(self.cell_width, self.cell_height)
+    }
+
+    /// How many times taller a cell is than it is wide.
+    pub fn aspect_ratio(&self) -> f32 {
+        self.cell_height / self.cell_width
+    }
+
+    /// The factors to scale a size in dots by so that it looks the same width and height, when each
+    /// cell is drawn as `subdivision` dots, e.g. `(1, 2)` for half blocks or `(2, 4)` for braille.
+    ///
+    /// The width is never scaled, so a square of 10 by 10 dots becomes `10` dots wide and
+    /// `10 * correction.y` dots tall.
+    ///
+    /// ```rust
+    /// use bevy::math::{UVec2, Vec2};
+    /// use bevy_ratatui::cell_metrics::CellMetrics;
+    ///
+    /// let metrics = CellMetrics {
+    ///     cell_width: 10.0,
+    ///     cell_height: 20.0,
+    ///     measured: true,
+    /// };
+    /// assert_eq!(metrics.correction(UVec2::ONE), Vec2::new(1.0, 0.5));
+    /// assert_eq!(metrics.correction(UVec2::new(1, 2)), Vec2::ONE);
+    /// ```
+    pub fn correction(&self, subdivision: UVec2) -> Vec2 {
+        let dot = self.cell_size() / subdivision.max(UVec2::ONE).as_vec2();
+        Vec2::new(1.0, dot.x / dot.y)
+    }
+
+    /// Converts a size or position in pixels to cells.
+    pub fn pixels_to_cells(&self, pixels: Vec2) -> Vec2 {
+        pixels / self.cell_size()
+    }
+
+    /// Converts a size or position in cells to pixels.
+    pub fn cells_to_pixels(&self, cells: Vec2) -> Vec2 {
+        cells * self.cell_size()
+    }
+}
+
+/// Measures the cells when the app starts, when the terminal is resized and when the poll interval
+/// has passed.
+fn measure_cells(
+    mut metrics: ResMut<CellMetrics>,
+    mut resizes: EventReader<ResizeEvent>,
+    interval: Res<CellMetricsPollInterval>,
+    mut measured_at: Local<Option<Instant>>,
+    mut changes: EventWriter<CellSizeChanged>,
+) {
+    let resized = resizes.read().count() > 0;
+    let due = interval.0.is_some_and(|interval| {
+        measured_at.is_some_and(|measured_at| measured_at.elapsed() >= interval)
+    });
+    let first = measured_at.is_none();
+    if !first && !resized && !due {
+        return;
+    }
+    *measured_at = Some(Instant::now());
+    let previous = *metrics;
+    let current = CellMetrics::measure().unwrap_or_default();
+    if metrics.set_if_neq(current) && !first && previous.cell_size() != current.cell_size() {
+        changes.send(CellSizeChanged { previous, current });
+    }
+}
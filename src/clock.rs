@@ -0,0 +1,356 @@
+//! Clock and countdown timer widgets for dashboards.
+//!
+//! A [`Clock`] entity shows the current time in a time zone, and a [`CountdownTimer`] entity shows
+//! the time left until it runs out, each on the first line of its [`AnchoredArea`]. Both keep the
+//! text they show and only change it when the displayed value changes, so that with the
+//! [`RedrawOnDemandPlugin`](crate::redraw::RedrawOnDemandPlugin) a clock that shows seconds asks for
+//! one redraw per second, and one that shows minutes for one per minute.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//!
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     anchor::AnchoredArea,
+//!     clock::{Clock, ClockPlugin, ClockZone, CountdownTimer, TimerFinished},
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::layout::Rect;
+//!
+//! App::new()
+//!     .add_plugins((MinimalPlugins, RatatuiPlugins::default(), ClockPlugin))
+//!     .add_systems(Startup, |mut commands: Commands| {
+//!         commands.spawn((Clock::new(ClockZone::Local), AnchoredArea(Rect::new(0, 0, 20, 1))));
+//!         commands.spawn((
+//!             CountdownTimer::new(Duration::from_secs(25 * 60)),
+//!             AnchoredArea(Rect::new(0, 1, 20, 1)),
+//!         ));
+//!     })
+//!     .add_systems(Update, |mut finished: EventReader<TimerFinished>| {
+//!         for _ in finished.read() {
+//!             println!("\x07");
+//!         }
+//!     })
+//!     .run();
+//! ```
+//!
+//! Countdown timers are driven by [`Time`], so they need the `TimePlugin`, which is part of
+//! `MinimalPlugins`, and they stop while the virtual time is paused. Clocks read the system clock
+//! through [chrono].
+//!
+//! [chrono]: https://crates.io/crates/chrono
+use std::{fmt::Write, time::Duration};
+
+use bevy::prelude::*;
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use ratatui::{layout::Alignment, style::Style, text::Line, widgets::Widget};
+
+use crate::{
+    anchor::AnchoredArea,
+    redraw::RedrawRequested,
+    render::{RenderBuffer, RenderPlugin, RenderSet},
+    widget::{draw_root_widget, WidgetVisibility},
+};
+
+/// A plugin that updates and draws [`Clock`]s and [`CountdownTimer`]s.
+///
+/// This requires the [`RenderPlugin`], and adds it if it is missing.
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RenderPlugin>() {
+            app.add_plugins(RenderPlugin);
+        }
+        app.add_event::<TimerFinished>()
+            .add_systems(PreUpdate, (tick_clocks, tick_countdown_timers))
+            .add_systems(
+                PostUpdate,
+                draw_clocks
+                    .after(draw_root_widget)
+                    .in_set(RenderSet::Widgets),
+            );
+    }
+}
+
+/// The time zone that a [`Clock`] shows the time in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ClockZone {
+    /// The local time zone of the system, including its daylight saving time.
+    #[default]
+    Local,
+    /// Coordinated Universal Time.
+    Utc,
+    /// A fixed offset from UTC, e.g. for a clock that shows the time in another office.
+    Fixed(FixedOffset),
+}
+
+/// A widget entity that shows the current time.
+///
+/// The time is formatted with a [chrono format string], `%H:%M:%S` by default, and drawn on the
+/// first line of the entity's [`AnchoredArea`].
+///
+/// ```rust
+/// use bevy_ratatui::clock::{Clock, ClockZone};
+/// use chrono::{FixedOffset, TimeZone, Utc};
+///
+/// let tokyo = ClockZone::Fixed(FixedOffset::east_opt(9 * 3600).unwrap());
+/// let clock = Clock::new(tokyo).with_format("%H:%M %:z");
+/// let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 30, 0).unwrap();
+/// assert_eq!(clock.text_at(now), "21:30 +09:00");
+/// ```
+///
+/// [chrono format string]: https://docs.rs/chrono/latest/chrono/format/strftime/index.html
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+#[require(AnchoredArea)]
+pub struct Clock {
+    /// The time zone to show the time in.
+    pub zone: ClockZone,
+    /// How to format the time.
+    pub format: String,
+    /// The style of the text.
+    pub style: Style,
+    /// Where the text is placed within the area.
+    pub alignment: Alignment,
+    /// The text shown, as of the last update.
+    text: String,
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Self {
+            zone: ClockZone::default(),
+            format: "%H:%M:%S".into(),
+            style: Style::default(),
+            alignment: Alignment::default(),
+            text: String::new(),
+        }
+    }
+}
+
+impl Clock {
+    /// Creates a clock that shows the time in `zone`.
+    pub fn new(zone: ClockZone) -> Self {
+        Self { zone, ..default() }
+    }
+
+    /// Sets the [chrono format string] that the time is formatted with.
+    ///
+    /// [chrono format string]: https://docs.rs/chrono/latest/chrono/format/strftime/index.html
+    pub fn with_format(mut self, format: impl Into<String>) -> Self {
+        self.format = format.into();
+        self
+    }
+
+    /// Sets the style of the text.
+    pub fn with_style(mut self, style: impl Into<Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    /// Sets where the text is placed within the area.
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// The text shown, as of the last update.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The text that the clock shows at `now`. An invalid format shows as `invalid format`.
+    pub fn text_at(&self, now: DateTime<Utc>) -> String {
+        let mut text = String::new();
+        let written = match self.zone {
+            ClockZone::Local => write!(text, "{}", now.with_timezone(&Local).format(&self.format)),
+            ClockZone::Utc => write!(text, "{}", now.format(&self.format)),
+            ClockZone::Fixed(offset) => {
+                write!(text, "{}", now.with_timezone(&offset).format(&self.format))
+            }
+        };
+        if written.is_err() {
+            return "invalid format".into();
+        }
+        text
+    }
+}
+
+/// A widget entity that counts down from a duration, drawn on the first line of its
+/// [`AnchoredArea`] as `MM:SS`, or `H:MM:SS` from an hour up.
+///
+/// The time left is rounded up to whole seconds, so that `00:00` is only shown once the timer has
+/// finished, when a [`TimerFinished`] event is sent.
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use bevy_ratatui::clock::CountdownTimer;
+///
+/// let timer = CountdownTimer::new(Duration::from_millis(5_400_500));
+/// assert_eq!(timer.text(), "1:30:01");
+/// assert_eq!(CountdownTimer::new(Duration::from_secs(90)).text(), "01:30");
+/// ```
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+#[require(AnchoredArea)]
+pub struct CountdownTimer {
+    duration: Duration,
+    remaining: Duration,
+    paused: bool,
+    /// The style of the text.
+    pub style: Style,
+    /// Where the text is placed within the area.
+    pub alignment: Alignment,
+    /// The text shown, which only changes when the time left passes a whole second.
+    text: String,
+}
+
+impl CountdownTimer {
+    /// Creates a running timer that finishes after `duration`.
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            remaining: duration,
+            paused: false,
+            style: Style::default(),
+            alignment: Alignment::default(),
+            text: countdown_text(duration),
+        }
+    }
+
+    /// Sets the style of the text.
+    pub fn with_style(mut self, style: impl Into<Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    /// Sets where the text is placed within the area.
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    /// The duration that the timer counts down from.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// The time left until the timer finishes.
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    /// The text shown.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.remaining.is_zero()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Stops the countdown until [`Self::resume`].
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    /// Counts down from the full duration again, whether or not the timer had finished.
+    pub fn restart(&mut self) {
+        self.remaining = self.duration;
+        self.text = countdown_text(self.remaining);
+    }
+}
+
+/// Sent when a [`CountdownTimer`] finishes.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerFinished {
+    /// The timer entity.
+    pub timer: Entity,
+}
+
+/// Formats the time left, rounded up to whole seconds.
+fn countdown_text(remaining: Duration) -> String {
+    let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes:02}:{seconds:02}")
+    }
+}
+
+/// Updates the text of each clock, and requests a redraw if any of them changed.
+fn tick_clocks(mut clocks: Query<&mut Clock>, redraw: Option<ResMut<RedrawRequested>>) {
+    let now = Utc::now();
+    let mut changed = false;
+    for mut clock in &mut clocks {
+        let text = clock.text_at(now);
+        if clock.text != text {
+            clock.text = text;
+            changed = true;
+        }
+    }
+    if let (true, Some(mut redraw)) = (changed, redraw) {
+        redraw.request();
+    }
+}
+
+/// Counts the running timers down, and requests a redraw if the text of any of them changed.
+fn tick_countdown_timers(
+    time: Res<Time>,
+    mut timers: Query<(Entity, &mut CountdownTimer)>,
+    mut finished: EventWriter<TimerFinished>,
+    redraw: Option<ResMut<RedrawRequested>>,
+) {
+    let mut changed = false;
+    for (entity, mut timer) in &mut timers {
+        if timer.paused || timer.is_finished() {
+            continue;
+        }
+        // Bypassing change detection, as a timer whose text has not changed looks the same.
+        let ticked = timer.bypass_change_detection();
+        ticked.remaining = ticked.remaining.saturating_sub(time.delta());
+        let text = countdown_text(ticked.remaining);
+        if ticked.text != text {
+            ticked.text = text;
+            timer.set_changed();
+            changed = true;
+        }
+        if timer.is_finished() {
+            finished.send(TimerFinished { timer: entity });
+        }
+    }
+    if let (true, Some(mut redraw)) = (changed, redraw) {
+        redraw.request();
+    }
+}
+
+fn draw_clocks(
+    mut buffer: ResMut<RenderBuffer>,
+    clocks: Query<(&Clock, &AnchoredArea, Option<&WidgetVisibility>)>,
+    timers: Query<(&CountdownTimer, &AnchoredArea, Option<&WidgetVisibility>)>,
+) {
+    let bounds = buffer.area;
+    let clocks = clocks.iter().map(|(clock, area, visibility)| {
+        (&clock.text, clock.style, clock.alignment, area, visibility)
+    });
+    let timers = timers.iter().map(|(timer, area, visibility)| {
+        (&timer.text, timer.style, timer.alignment, area, visibility)
+    });
+    for (text, style, alignment, area, visibility) in clocks.chain(timers) {
+        if !visibility.is_none_or(|visibility| visibility.is_visible()) {
+            continue;
+        }
+        Line::styled(text.as_str(), style)
+            .alignment(alignment)
+            .render(area.intersection(bounds), &mut buffer);
+    }
+}
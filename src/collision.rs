@@ -0,0 +1,200 @@
+//! Grid-based collision for small terminal games.
+//!
+//! [`CollisionPlugin`] moves entities that have a [`CellPosition`] and a [`CellVelocity`] in
+//! [`FixedUpdate`], and stops them at walls rather than letting them pass through. Walls are the
+//! cells in the [`SolidCells`] resource, and the [`CellCollider`]s of entities marked [`Solid`].
+//! Each blocked move sends a [`CellCollision`], and each pair of colliders that overlap after
+//! moving sends a [`CellOverlap`], e.g. for pickups.
+//!
+//! Colliders are axis-aligned boxes in cell space ([`CellAabb`]) whose top left corner is the
+//! cell of the [`CellPosition`]. Systems that set velocities should run before
+//! [`CollisionSet`].
+//!
+//! ```rust
+//! use bevy::math::{IVec2, UVec2};
+//! use bevy_ratatui::collision::CellAabb;
+//!
+//! let player = CellAabb::new(IVec2::new(2, 2), UVec2::new(2, 1));
+//! assert!(player.contains(IVec2::new(3, 2)));
+//! assert!(!player.intersects(&CellAabb::new(IVec2::new(4, 2), UVec2::ONE)));
+//! ```
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::interpolation::CellPosition;
+
+/// A plugin that moves entities by their [`CellVelocity`] and resolves collisions.
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SolidCells>()
+            .add_event::<CellCollision>()
+            .add_event::<CellOverlap>()
+            .add_systems(
+                FixedUpdate,
+                (move_and_collide, detect_overlaps)
+                    .chain()
+                    .in_set(CollisionSet),
+            );
+    }
+}
+
+/// The set in [`FixedUpdate`] that moves entities and sends collision events.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CollisionSet;
+
+/// An axis-aligned box of cells.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CellAabb {
+    /// The top left cell.
+    pub min: IVec2,
+    /// The width and height in cells.
+    pub size: UVec2,
+}
+
+impl CellAabb {
+    pub fn new(min: IVec2, size: UVec2) -> Self {
+        Self { min, size }
+    }
+
+    /// The cell one past the bottom right cell.
+    pub fn max(&self) -> IVec2 {
+        self.min + self.size.as_ivec2()
+    }
+
+    pub fn contains(&self, cell: IVec2) -> bool {
+        cell.cmpge(self.min).all() && cell.cmplt(self.max()).all()
+    }
+
+    pub fn intersects(&self, other: &CellAabb) -> bool {
+        self.min.cmplt(other.max()).all() && other.min.cmplt(self.max()).all()
+    }
+
+    /// The cells covered by the box, row by row.
+    pub fn cells(&self) -> impl Iterator<Item = IVec2> {
+        let (min, max) = (self.min, self.max());
+        (min.y..max.y).flat_map(move |y| (min.x..max.x).map(move |x| IVec2::new(x, y)))
+    }
+}
+
+/// The size of the box an entity occupies, starting at its [`CellPosition`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CellCollider {
+    pub size: UVec2,
+}
+
+impl Default for CellCollider {
+    fn default() -> Self {
+        Self { size: UVec2::ONE }
+    }
+}
+
+impl CellCollider {
+    /// The box of the collider at the given position.
+    pub fn aabb(&self, position: Vec2) -> CellAabb {
+        CellAabb::new(position.floor().as_ivec2(), self.size)
+    }
+}
+
+/// Marks a collider that other colliders cannot move into.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Solid;
+
+/// The velocity of an entity in cells per second.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct CellVelocity(pub Vec2);
+
+/// Cells that block movement, such as the walls of a map.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Deref, DerefMut)]
+pub struct SolidCells(pub HashSet<IVec2>);
+
+/// What a moving entity ran into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CollisionTarget {
+    /// A cell in [`SolidCells`].
+    Cell(IVec2),
+    /// A [`Solid`] entity.
+    Entity(Entity),
+}
+
+/// Sent when an entity is stopped while moving. The velocity along the blocked axis is set to
+/// zero.
+#[derive(Debug, Clone, Copy, Event, PartialEq, Eq, Hash)]
+pub struct CellCollision {
+    pub entity: Entity,
+    pub target: CollisionTarget,
+}
+
+/// Sent every tick for each pair of colliders that overlap.
+#[derive(Debug, Clone, Copy, Event, PartialEq, Eq, Hash)]
+pub struct CellOverlap(pub Entity, pub Entity);
+
+fn blocker(
+    aabb: &CellAabb,
+    entity: Entity,
+    cells: &SolidCells,
+    solids: &[(Entity, CellAabb)],
+) -> Option<CollisionTarget> {
+    if let Some(cell) = aabb.cells().find(|cell| cells.contains(cell)) {
+        return Some(CollisionTarget::Cell(cell));
+    }
+    solids
+        .iter()
+        .find(|(other, other_aabb)| *other != entity && aabb.intersects(other_aabb))
+        .map(|(other, _)| CollisionTarget::Entity(*other))
+}
+
+fn move_and_collide(
+    time: Res<Time>,
+    cells: Res<SolidCells>,
+    mut movers: Query<(
+        Entity,
+        &mut CellPosition,
+        &mut CellVelocity,
+        Option<&CellCollider>,
+    )>,
+    solids: Query<(Entity, &CellPosition, &CellCollider), With<Solid>>,
+    mut collisions: EventWriter<CellCollision>,
+) {
+    let solids: Vec<_> = solids
+        .iter()
+        .map(|(entity, position, collider)| (entity, collider.aabb(position.0)))
+        .collect();
+    let dt = time.delta_secs();
+    for (entity, mut position, mut velocity, collider) in &mut movers {
+        if velocity.0 == Vec2::ZERO {
+            continue;
+        }
+        let collider = collider.copied().unwrap_or_default();
+        // Each axis is moved separately so that an entity slides along a wall.
+        for axis in [Vec2::X, Vec2::Y] {
+            let step = velocity.0 * axis * dt;
+            if step == Vec2::ZERO {
+                continue;
+            }
+            let moved = position.0 + step;
+            match blocker(&collider.aabb(moved), entity, &cells, &solids) {
+                Some(target) => {
+                    velocity.0 *= Vec2::ONE - axis;
+                    collisions.send(CellCollision { entity, target });
+                }
+                None => position.0 = moved,
+            }
+        }
+    }
+}
+
+fn detect_overlaps(
+    colliders: Query<(Entity, &CellPosition, &CellCollider)>,
+    mut overlaps: EventWriter<CellOverlap>,
+) {
+    for [(a, a_position, a_collider), (b, b_position, b_collider)] in colliders.iter_combinations()
+    {
+        if a_collider
+            .aabb(a_position.0)
+            .intersects(&b_collider.aabb(b_position.0))
+        {
+            overlaps.send(CellOverlap(a, b));
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Color support levels.
+//!
+//! Terminals differ in how many colors they can display. When a [`ColorLevel`] resource is present,
+//! every frame drawn with [`RatatuiContext::draw`](crate::terminal::RatatuiContext::draw) has its
+//! colors converted to the nearest color available at that level before it is written to the
+//! terminal. Without the resource, colors are written exactly as they were rendered.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{color::ColorLevel, RatatuiPlugins};
+//!
+//! App::new()
+//!     .insert_resource(ColorLevel::Ansi256)
+//!     .add_plugins(RatatuiPlugins::default());
+//! ```
+use std::{env, str::FromStr};
+
+use bevy::prelude::*;
+use ratatui::{buffer::Buffer, style::Color};
+
+/// The number of colors the terminal is able to display.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ColorLevel {
+    /// No colors at all, only the terminal's default foreground and background.
+    NoColor,
+    /// The 16 standard ANSI colors.
+    Ansi16,
+    /// The 256 color xterm palette.
+    Ansi256,
+    /// 24-bit RGB colors.
+    TrueColor,
+}
+
+impl ColorLevel {
+    /// Guesses the color level from the `NO_COLOR`, `COLORTERM` and `TERM` environment variables.
+    pub fn detect() -> Self {
+        if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            return ColorLevel::NoColor;
+        }
+        if env::var("COLORTERM").is_ok_and(|value| value == "truecolor" || value == "24bit") {
+            return ColorLevel::TrueColor;
+        }
+        match env::var("TERM") {
+            Ok(term) if term == "dumb" => ColorLevel::NoColor,
+            Ok(term) if term.contains("256color") => ColorLevel::Ansi256,
+            _ => ColorLevel::Ansi16,
+        }
+    }
+
+    /// Converts a color to the nearest color available at this level.
+    pub fn convert(self, color: Color) -> Color {
+        match (self, color) {
+            (_, Color::Reset) | (ColorLevel::TrueColor, _) => color,
+            (ColorLevel::NoColor, _) => Color::Reset,
+            (ColorLevel::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_indexed(r, g, b)),
+            (ColorLevel::Ansi256, _) => color,
+            (ColorLevel::Ansi16, Color::Rgb(r, g, b)) => nearest_ansi16((r, g, b)),
+            (ColorLevel::Ansi16, Color::Indexed(index)) => nearest_ansi16(indexed_to_rgb(index)),
+            (ColorLevel::Ansi16, _) => color,
+        }
+    }
+
+    /// Converts the colors of every cell in the buffer to the nearest color available at this
+    /// level.
+    pub fn convert_buffer(self, buffer: &mut Buffer) {
+        if self == ColorLevel::TrueColor {
+            return;
+        }
+        for cell in &mut buffer.content {
+            cell.fg = self.convert(cell.fg);
+            cell.bg = self.convert(cell.bg);
+        }
+    }
+}
+
+impl FromStr for ColorLevel {
+    type Err = String;
+
+    /// Parses `none`, `16`, `256` or `truecolor` (also `24bit`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "none" | "0" => Ok(ColorLevel::NoColor),
+            "16" | "ansi" | "ansi16" => Ok(ColorLevel::Ansi16),
+            "256" | "ansi256" => Ok(ColorLevel::Ansi256),
+            "truecolor" | "24bit" => Ok(ColorLevel::TrueColor),
+            _ => Err(format!("unknown color level {s:?}")),
+        }
+    }
+}
+
+/// The 16 ANSI colors and their usual RGB values (as used by xterm).
+const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::Red, (205, 0, 0)),
+    (Color::Green, (0, 205, 0)),
+    (Color::Yellow, (205, 205, 0)),
+    (Color::Blue, (0, 0, 238)),
+    (Color::Magenta, (205, 0, 205)),
+    (Color::Cyan, (0, 205, 205)),
+    (Color::Gray, (229, 229, 229)),
+    (Color::DarkGray, (127, 127, 127)),
+    (Color::LightRed, (255, 0, 0)),
+    (Color::LightGreen, (0, 255, 0)),
+    (Color::LightYellow, (255, 255, 0)),
+    (Color::LightBlue, (92, 92, 255)),
+    (Color::LightMagenta, (255, 0, 255)),
+    (Color::LightCyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+/// The channel values used by the 6x6x6 color cube of the 256 color palette.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let dr = r1.abs_diff(r2) as u32;
+    let dg = g1.abs_diff(g2) as u32;
+    let db = b1.abs_diff(b2) as u32;
+    dr * dr + dg * dg + db * db
+}
+
+fn nearest_ansi16(rgb: (u8, u8, u8)) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, value)| distance(rgb, *value))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+fn nearest_cube_index(value: u8) -> u8 {
+    (0..CUBE_LEVELS.len() as u8)
+        .min_by_key(|i| CUBE_LEVELS[*i as usize].abs_diff(value))
+        .unwrap_or_default()
+}
+
+/// Finds the closest entry of the 256 color palette, picking between the color cube and the
+/// grayscale ramp.
+fn rgb_to_indexed(r: u8, g: u8, b: u8) -> u8 {
+    let (ri, gi, bi) = (
+        nearest_cube_index(r),
+        nearest_cube_index(g),
+        nearest_cube_index(b),
+    );
+    let cube = 16 + 36 * ri + 6 * gi + bi;
+    let average = ((r as u16 + g as u16 + b as u16) / 3) as u8;
+    let gray = 232 + (average.saturating_sub(3) / 10).min(23);
+    if distance((r, g, b), indexed_to_rgb(gray)) < distance((r, g, b), indexed_to_rgb(cube)) {
+        gray
+    } else {
+        cube
+    }
+}
+
+/// The RGB value of a color, or `None` for [`Color::Reset`].
+pub(crate) fn to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(index) => Some(indexed_to_rgb(index)),
+        _ => ANSI16
+            .iter()
+            .find(|(named, _)| *named == color)
+            .map(|(_, rgb)| *rgb),
+    }
+}
+
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI16[index as usize].1,
+        16..=231 => {
+            let index = index - 16;
+            (
+                CUBE_LEVELS[(index / 36) as usize],
+                CUBE_LEVELS[(index / 6 % 6) as usize],
+                CUBE_LEVELS[(index % 6) as usize],
+            )
+        }
+        232..=255 => {
+            let value = 8 + (index - 232) * 10;
+            (value, value, value)
+        }
+    }
+}
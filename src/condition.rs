@@ -0,0 +1,64 @@
+//! Run conditions for terminal events.
+//!
+//! These can be used to run a system only when a certain terminal event was received this frame,
+//! instead of reading and matching the events inside every system.
+//!
+//! ```rust
+//! use bevy::{app::AppExit, prelude::*};
+//! use bevy_ratatui::condition::{on_key_pressed, on_resize};
+//! use crossterm::event::KeyCode;
+//!
+//! fn quit(mut exit: EventWriter<AppExit>) {
+//!     exit.send_default();
+//! }
+//!
+//! fn relayout() {}
+//!
+//! App::new()
+//!     .add_systems(Update, quit.run_if(on_key_pressed(KeyCode::Char('q'))))
+//!     .add_systems(Update, relayout.run_if(on_resize()));
+//! ```
+//!
+//! Each condition reads events with its own cursor, so using a condition does not hide events from
+//! other systems or conditions.
+use bevy::prelude::*;
+use crossterm::event::{KeyCode, KeyEventKind, MouseEventKind};
+
+use crate::event::{CrosstermEvent, KeyEvent, MouseEvent, ResizeEvent};
+
+/// A run condition that is true if the given key was pressed this frame.
+pub fn on_key_pressed(code: KeyCode) -> impl FnMut(EventReader<KeyEvent>) -> bool + Clone {
+    on_key(move |event| event.code == code && event.kind == KeyEventKind::Press)
+}
+
+/// A run condition that is true if a key event matching the predicate was received this frame.
+pub fn on_key<F>(mut predicate: F) -> impl FnMut(EventReader<KeyEvent>) -> bool + Clone
+where
+    F: FnMut(&crossterm::event::KeyEvent) -> bool + Clone,
+{
+    move |mut events: EventReader<KeyEvent>| {
+        // Count rather than using `any` so that all the events are marked as read.
+        events.read().filter(|event| predicate(event)).count() > 0
+    }
+}
+
+/// A run condition that is true if the terminal was resized this frame.
+pub fn on_resize() -> impl FnMut(EventReader<ResizeEvent>) -> bool + Clone {
+    |mut events: EventReader<ResizeEvent>| events.read().count() > 0
+}
+
+/// A run condition that is true if a mouse button was pressed this frame.
+pub fn on_mouse_click() -> impl FnMut(EventReader<MouseEvent>) -> bool + Clone {
+    |mut events: EventReader<MouseEvent>| {
+        events
+            .read()
+            .filter(|event| matches!(event.kind, MouseEventKind::Down(_)))
+            .count()
+            > 0
+    }
+}
+
+/// A run condition that is true if any terminal event was received this frame.
+pub fn input_just_received() -> impl FnMut(EventReader<CrosstermEvent>) -> bool + Clone {
+    |mut events: EventReader<CrosstermEvent>| events.read().count() > 0
+}
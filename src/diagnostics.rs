@@ -0,0 +1,70 @@
+//! Diagnostics for terminal input.
+//!
+//! [`EventDiagnosticsPlugin`] publishes the per-frame [`EventStats`] as bevy diagnostics, which
+//! helps to spot input floods such as mouse move storms or giant pastes.
+//!
+//! ```rust,no_run
+//! use bevy::{diagnostic::DiagnosticsPlugin, prelude::*};
+//! use bevy_ratatui::{diagnostics::EventDiagnosticsPlugin, RatatuiPlugins};
+//!
+//! App::new()
+//!     .add_plugins(RatatuiPlugins::default())
+//!     .add_plugins((DiagnosticsPlugin, EventDiagnosticsPlugin));
+//! ```
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+};
+
+use crate::event::{update_queue_depth, EventStats};
+
+/// A plugin that publishes terminal event counts as bevy diagnostics.
+pub struct EventDiagnosticsPlugin;
+
+impl EventDiagnosticsPlugin {
+    /// The number of events read from the terminal each frame.
+    pub const EVENTS: DiagnosticPath = DiagnosticPath::const_new("bevy_ratatui/events");
+    /// The number of key events read each frame.
+    pub const KEY_EVENTS: DiagnosticPath = DiagnosticPath::const_new("bevy_ratatui/key_events");
+    /// The number of mouse events read each frame.
+    pub const MOUSE_EVENTS: DiagnosticPath = DiagnosticPath::const_new("bevy_ratatui/mouse_events");
+    /// The number of bytes pasted each frame.
+    pub const PASTE_BYTES: DiagnosticPath = DiagnosticPath::const_new("bevy_ratatui/paste_bytes");
+    /// The number of events coalesced into other events each frame.
+    pub const COALESCED_EVENTS: DiagnosticPath =
+        DiagnosticPath::const_new("bevy_ratatui/coalesced_events");
+    /// The number of events still waiting to be read at the end of each frame.
+    pub const QUEUE_DEPTH: DiagnosticPath = DiagnosticPath::const_new("bevy_ratatui/queue_depth");
+}
+
+impl Plugin for EventDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(Self::EVENTS))
+            .register_diagnostic(Diagnostic::new(Self::KEY_EVENTS))
+            .register_diagnostic(Diagnostic::new(Self::MOUSE_EVENTS))
+            .register_diagnostic(Diagnostic::new(Self::PASTE_BYTES).with_suffix("B"))
+            .register_diagnostic(Diagnostic::new(Self::COALESCED_EVENTS))
+            .register_diagnostic(Diagnostic::new(Self::QUEUE_DEPTH))
+            .add_systems(
+                Last,
+                diagnostic_system
+                    .run_if(resource_exists::<EventStats>)
+                    .after(update_queue_depth),
+            );
+    }
+}
+
+fn diagnostic_system(mut diagnostics: Diagnostics, stats: Res<EventStats>) {
+    diagnostics.add_measurement(&EventDiagnosticsPlugin::EVENTS, || stats.polled as f64);
+    diagnostics.add_measurement(&EventDiagnosticsPlugin::KEY_EVENTS, || stats.keys as f64);
+    diagnostics.add_measurement(&EventDiagnosticsPlugin::MOUSE_EVENTS, || stats.mouse as f64);
+    diagnostics.add_measurement(&EventDiagnosticsPlugin::PASTE_BYTES, || {
+        stats.paste_bytes as f64
+    });
+    diagnostics.add_measurement(&EventDiagnosticsPlugin::COALESCED_EVENTS, || {
+        stats.coalesced as f64
+    });
+    diagnostics.add_measurement(&EventDiagnosticsPlugin::QUEUE_DEPTH, || {
+        stats.queue_depth as f64
+    });
+}
@@ -0,0 +1,272 @@
+//! Drawing by queueing widgets.
+//!
+//! Systems push a [`DrawCommand`] for each widget into the [`DrawQueue`], with the area to render
+//! it in and its [`RenderLayer`], and never touch the terminal themselves. Once per frame the
+//! queued widgets are rendered into the [`RenderBuffer`] from the lowest layer to the highest, so
+//! popups and debug HUDs end up on top of the base content no matter which system queued them
+//! first. Widgets in the same layer are rendered in the order they were queued.
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     draw_queue::{DrawQueue, RenderLayer},
+//!     terminal::RatatuiContext,
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::{layout::Rect, widgets::Paragraph};
+//!
+//! fn popup(mut queue: ResMut<DrawQueue>) {
+//!     queue.push(Rect::new(0, 0, 5, 1), RenderLayer::POPUP, Paragraph::new("popup"));
+//! }
+//!
+//! fn background(mut queue: ResMut<DrawQueue>) {
+//!     queue.push(Rect::new(0, 0, 10, 1), RenderLayer::BASE, Paragraph::new("background"));
+//! }
+//!
+//! let mut app = App::new();
+//! app.add_plugins(RatatuiPlugins {
+//!     headless: true,
+//!     ..default()
+//! })
+//! .add_systems(Update, (popup, background));
+//! app.update();
+//! let context = app.world().resource::<RatatuiContext>();
+//! assert!(context.screen_lines()[0].starts_with("popupround"));
+//! ```
+//!
+//! # Widget entities
+//!
+//! Entities with a [`LayeredWidget`] are drawn along with the queue every frame, in the layer of
+//! their [`RenderLayer`] component, or [`RenderLayer::BASE`] without one. Within a layer they are
+//! drawn after the queued commands. Entities whose [`WidgetVisibility`] is not visible are skipped.
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::draw_queue::{LayeredWidget, RenderLayer};
+//! use ratatui::{layout::Rect, widgets::Paragraph};
+//!
+//! fn spawn_hud(mut commands: Commands) {
+//!     commands.spawn((
+//!         LayeredWidget::new(Paragraph::new("fps: 60")).with_area(Rect::new(0, 0, 8, 1)),
+//!         RenderLayer::DEBUG,
+//!     ));
+//! }
+//! ```
+//!
+//! The queue and the widget entities are composited after [`RenderSet::Widgets`] and before
+//! [`RenderSet::Overlay`]. The queue is emptied every frame, whether or not a draw was due.
+use std::fmt;
+
+use bevy::prelude::*;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    widgets::{Widget, WidgetRef},
+};
+
+use crate::{
+    redraw::RedrawRequested,
+    render::{RenderBuffer, RenderPlugin, RenderSet},
+    terminal::RatatuiContext,
+    throttle::{draw_due, DrawThrottle},
+    widget::WidgetVisibility,
+};
+
+/// A plugin that composites the [`DrawQueue`] into the [`RenderBuffer`].
+///
+/// This is part of [`RatatuiPlugins`](crate::RatatuiPlugins). It requires the [`RenderPlugin`],
+/// and adds it if it is missing.
+pub struct DrawQueuePlugin;
+
+impl Plugin for DrawQueuePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RenderPlugin>() {
+            app.add_plugins(RenderPlugin);
+        }
+        app.init_resource::<DrawQueue>().add_systems(
+            PostUpdate,
+            composite_draw_queue
+                .after(RenderSet::Widgets)
+                .before(RenderSet::Overlay),
+        );
+    }
+}
+
+/// The layer a widget is drawn in. Higher layers are drawn later, on top of lower ones.
+///
+/// Any `i32` is a layer. The constants leave room between them for layers of the app's own.
+#[derive(
+    Component, Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deref, DerefMut,
+)]
+pub struct RenderLayer(pub i32);
+
+impl RenderLayer {
+    /// The main content.
+    pub const BASE: Self = Self(0);
+    /// Popups, menus and dialogs.
+    pub const POPUP: Self = Self(100);
+    /// Notifications and status lines that stay on top of popups.
+    pub const OVERLAY: Self = Self(200);
+    /// Debug HUDs, on top of everything else.
+    pub const DEBUG: Self = Self(1000);
+}
+
+impl From<i32> for RenderLayer {
+    fn from(layer: i32) -> Self {
+        Self(layer)
+    }
+}
+
+/// A widget to render in an area of the next frame.
+pub struct DrawCommand {
+    /// Where to render the widget.
+    pub area: Rect,
+    /// The layer to render the widget in.
+    pub layer: RenderLayer,
+    widget: RenderFn,
+}
+
+type RenderFn = Box<dyn FnOnce(Rect, &mut Buffer) + Send + Sync>;
+
+impl DrawCommand {
+    pub fn new<W: Widget + Send + Sync + 'static>(
+        area: Rect,
+        layer: impl Into<RenderLayer>,
+        widget: W,
+    ) -> Self {
+        Self {
+            area,
+            layer: layer.into(),
+            widget: Box::new(move |area, buf| widget.render(area, buf)),
+        }
+    }
+}
+
+impl fmt::Debug for DrawCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DrawCommand")
+            .field("area", &self.area)
+            .field("layer", &self.layer)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The widgets queued for the next frame.
+#[derive(Resource, Debug, Default)]
+pub struct DrawQueue {
+    commands: Vec<DrawCommand>,
+}
+
+impl DrawQueue {
+    /// Queues `widget` to be rendered in `area` in `layer`.
+    pub fn push<W: Widget + Send + Sync + 'static>(
+        &mut self,
+        area: Rect,
+        layer: impl Into<RenderLayer>,
+        widget: W,
+    ) {
+        self.commands.push(DrawCommand::new(area, layer, widget));
+    }
+
+    /// Queues a command.
+    pub fn push_command(&mut self, command: DrawCommand) {
+        self.commands.push(command);
+    }
+
+    /// The number of queued commands.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Whether nothing is queued.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+}
+
+/// A widget entity that is drawn every frame in the layer of its [`RenderLayer`].
+#[derive(Component)]
+pub struct LayeredWidget {
+    /// Where to render the widget, or `None` for the whole frame.
+    pub area: Option<Rect>,
+    widget: Box<dyn WidgetRef + Send + Sync>,
+}
+
+impl LayeredWidget {
+    /// A widget drawn over the whole frame.
+    pub fn new<W: WidgetRef + Send + Sync + 'static>(widget: W) -> Self {
+        Self {
+            area: None,
+            widget: Box::new(widget),
+        }
+    }
+
+    /// Draws the widget in `area` instead of the whole frame.
+    pub fn with_area(mut self, area: Rect) -> Self {
+        self.area = Some(area);
+        self
+    }
+}
+
+impl fmt::Debug for LayeredWidget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LayeredWidget")
+            .field("area", &self.area)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Something to render while compositing, in the order of the variants within a layer.
+enum Layered<'a> {
+    Command(DrawCommand),
+    Entity(&'a LayeredWidget),
+}
+
+fn composite_draw_queue(
+    mut queue: ResMut<DrawQueue>,
+    mut buffer: ResMut<RenderBuffer>,
+    widgets: Query<(
+        &LayeredWidget,
+        Option<&RenderLayer>,
+        Option<&WidgetVisibility>,
+    )>,
+    context: Option<Res<RatatuiContext>>,
+    throttle: Option<Res<DrawThrottle>>,
+    redraw: Option<Res<RedrawRequested>>,
+) {
+    let commands = std::mem::take(&mut queue.commands);
+    if commands.is_empty() && widgets.is_empty() {
+        return;
+    }
+    if context.is_none() || !draw_due(throttle, redraw) {
+        return;
+    }
+    let mut layered: Vec<_> = commands
+        .into_iter()
+        .map(|command| (command.layer, Layered::Command(command)))
+        .collect();
+    layered.extend(
+        widgets
+            .iter()
+            .filter(|(_, _, visibility)| {
+                visibility.is_none_or(|visibility| visibility.is_visible())
+            })
+            .map(|(widget, layer, _)| {
+                (layer.copied().unwrap_or_default(), Layered::Entity(widget))
+            }),
+    );
+    // The sort is stable, so within a layer commands keep their order and come before entities.
+    layered.sort_by_key(|(layer, _)| *layer);
+    let bounds = buffer.area;
+    for (_, item) in layered {
+        match item {
+            Layered::Command(command) => {
+                (command.widget)(command.area.intersection(bounds), &mut buffer);
+            }
+            Layered::Entity(widget) => {
+                let area = widget.area.map_or(bounds, |area| area.intersection(bounds));
+                widget.widget.render_ref(area, &mut buffer);
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Environment variable overrides.
+//!
+//! [`RatatuiPlugins`](crate::RatatuiPlugins) reads the following environment variables when it is
+//! built, so that the behavior of a compiled app can be adjusted without recompiling it:
+//!
+//! - `BEVY_RATATUI_DISABLE_KITTY`: set to `1` or `true` to not use the kitty keyboard protocol.
+//! - `BEVY_RATATUI_DISABLE_MOUSE`: set to `1` or `true` to not capture the mouse.
+//! - `BEVY_RATATUI_COLOR_LEVEL`: one of `none`, `16`, `256` or `truecolor`. Inserts the matching
+//!   [`ColorLevel`] resource, overriding one inserted by the app.
+//! - `BEVY_RATATUI_FRAME_RATE`: the number of frames per second. Replaces the app runner with a
+//!   [`ScheduleRunnerPlugin`] loop at that rate.
+//! - `BEVY_RATATUI_LOG_FILE`: a path that log messages are appended to. This is only used when no
+//!   other logger has been set up (e.g. by bevy's `LogPlugin`).
+//!
+//! ```shell
+//! BEVY_RATATUI_DISABLE_KITTY=1 BEVY_RATATUI_FRAME_RATE=30 cargo run --example demo
+//! ```
+use std::{env, fs::OpenOptions, path::PathBuf, str::FromStr, sync::Mutex, time::Duration};
+
+use bevy::{
+    app::ScheduleRunnerPlugin,
+    log::tracing_subscriber::{self, EnvFilter},
+    prelude::*,
+    utils::tracing::subscriber,
+};
+
+use crate::color::ColorLevel;
+
+/// Overrides for the plugin defaults read from `BEVY_RATATUI_*` environment variables.
+///
+/// See the [module documentation](self) for the list of variables.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EnvOverrides {
+    /// `BEVY_RATATUI_DISABLE_KITTY`
+    pub disable_kitty: bool,
+    /// `BEVY_RATATUI_DISABLE_MOUSE`
+    pub disable_mouse: bool,
+    /// `BEVY_RATATUI_COLOR_LEVEL`
+    pub color_level: Option<ColorLevel>,
+    /// `BEVY_RATATUI_FRAME_RATE`
+    pub frame_rate: Option<f64>,
+    /// `BEVY_RATATUI_LOG_FILE`
+    pub log_file: Option<PathBuf>,
+}
+
+impl EnvOverrides {
+    /// Reads the overrides from the environment.
+    ///
+    /// Values that cannot be parsed are ignored.
+    pub fn from_env() -> Self {
+        Self {
+            disable_kitty: flag("BEVY_RATATUI_DISABLE_KITTY"),
+            disable_mouse: flag("BEVY_RATATUI_DISABLE_MOUSE"),
+            color_level: parse("BEVY_RATATUI_COLOR_LEVEL"),
+            frame_rate: parse::<f64>("BEVY_RATATUI_FRAME_RATE").filter(|rate| *rate > 0.0),
+            log_file: env::var_os("BEVY_RATATUI_LOG_FILE")
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from),
+        }
+    }
+}
+
+fn flag(name: &str) -> bool {
+    env::var(name).is_ok_and(|value| {
+        matches!(
+            value.trim().to_ascii_lowercase().as_str(),
+            "1" | "true" | "yes" | "on"
+        )
+    })
+}
+
+fn parse<T: FromStr>(name: &str) -> Option<T> {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// A plugin that applies the overrides that are not simple plugin toggles.
+///
+/// This is added by [`RatatuiPlugins`](crate::RatatuiPlugins) automatically.
+pub struct EnvPlugin(pub EnvOverrides);
+
+impl Plugin for EnvPlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(path) = &self.0.log_file {
+            if let Err(err) = init_file_logger(path) {
+                warn!("Could not log to {}: {err}", path.display());
+            }
+        }
+        if let Some(color_level) = self.0.color_level {
+            app.insert_resource(color_level);
+        }
+    }
+
+    fn finish(&self, app: &mut App) {
+        // Done in `finish` so that it replaces any runner set by plugins added after this one.
+        if let Some(frame_rate) = self.0.frame_rate {
+            ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1.0 / frame_rate)).build(app);
+        }
+    }
+}
+
+fn init_file_logger(path: &PathBuf) -> std::io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let logger = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_ansi(false)
+        .with_writer(Mutex::new(file))
+        .finish();
+    subscriber::set_global_default(logger)
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::AlreadyExists, err))
+}
@@ -7,7 +7,13 @@
 //! The `exit_on_error` function is used to exit the app if an error occurs. It is used to pipe
 //! results from functions that return `Result` to the `exit_on_error` system. If the result is an
 //! error, the error is logged and the app is exited.
-use std::panic;
+use std::{
+    panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, Once,
+    },
+};
 
 use bevy::{app::AppExit, prelude::*};
 use color_eyre::{
@@ -16,7 +22,10 @@ use color_eyre::{
     eyre, Result,
 };
 
-use crate::terminal::RatatuiContext;
+use crate::{
+    exit::ExitCodes,
+    terminal::{end_transition, HeadlessTerminal, RatatuiContext, TerminalSet, TerminalStartup},
+};
 
 /// A plugin that sets up error handling.
 ///
@@ -31,7 +40,10 @@ pub struct ErrorPlugin;
 /// is restored before printing the panic or error message.
 impl Plugin for ErrorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup.pipe(exit_on_error));
+        app.add_systems(
+            TerminalStartup,
+            setup.pipe(exit_on_error).in_set(TerminalSet::Hooks),
+        );
     }
 }
 
@@ -40,18 +52,48 @@ impl Plugin for ErrorPlugin {
 /// Makes the app resilient to panics and errors by restoring the terminal before printing the
 /// panic or error message. This prevents error messages from being messed up by the terminal
 /// state.
-pub fn setup() -> Result<()> {
-    let (panic_hook, eyre_hook) = HookBuilder::default().into_hooks();
-    set_panic_hook(panic_hook);
-    set_error_hook(eyre_hook)?;
-    Ok(())
+///
+/// A [headless](HeadlessTerminal) terminal is never set up, so the hooks leave it alone.
+///
+/// The hooks can only be installed once per process. An app that is started again in the same
+/// process, e.g. by the [supervisor](crate::supervisor), keeps the hooks of the first one, which
+/// restore the terminal if the latest app set it up.
+pub fn setup(headless: Option<Res<HeadlessTerminal>>) -> Result<()> {
+    RESTORE.store(headless.is_none(), Ordering::SeqCst);
+    let mut result = Ok(());
+    INSTALL_HOOKS.call_once(|| {
+        let (panic_hook, eyre_hook) = HookBuilder::default().into_hooks();
+        set_panic_hook(panic_hook);
+        result = set_error_hook(eyre_hook);
+    });
+    result
+}
+
+static INSTALL_HOOKS: Once = Once::new();
+
+/// Whether the hooks restore the terminal.
+static RESTORE: AtomicBool = AtomicBool::new(false);
+
+/// The message and location of the last panic, for the [supervisor](crate::supervisor) to log.
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+/// Takes the message and location of the last panic since this was last called, if the panic hook
+/// installed by [`setup`] saw one.
+pub(crate) fn take_last_panic() -> Option<String> {
+    LAST_PANIC.lock().ok()?.take()
 }
 
 /// Install a panic hook that restores the terminal before printing the panic.
 fn set_panic_hook(panic_hook: PanicHook) {
     let panic_hook = panic_hook.into_panic_hook();
     panic::set_hook(Box::new(move |panic_info| {
-        let _ = RatatuiContext::restore();
+        if RESTORE.load(Ordering::SeqCst) {
+            let _ = end_transition();
+            let _ = RatatuiContext::restore();
+        }
+        if let Ok(mut last_panic) = LAST_PANIC.lock() {
+            *last_panic = Some(panic_info.to_string());
+        }
         panic_hook(panic_info);
     }));
 }
@@ -60,7 +102,10 @@ fn set_panic_hook(panic_hook: PanicHook) {
 fn set_error_hook(eyre_hook: EyreHook) -> Result<()> {
     let eyre_hook = eyre_hook.into_eyre_hook();
     eyre::set_hook(Box::new(move |error| {
-        let _ = RatatuiContext::restore();
+        if RESTORE.load(Ordering::SeqCst) {
+            let _ = end_transition();
+            let _ = RatatuiContext::restore();
+        }
         eyre_hook(error)
     }))?;
     Ok(())
@@ -69,10 +114,15 @@ fn set_error_hook(eyre_hook: EyreHook) -> Result<()> {
 /// Exits the app if an error occurs.
 ///
 /// This is used to pipe results from functions that return `Result` to the `exit_on_error` system.
-/// If the result is an error, the error is logged and the app is exited.
-pub fn exit_on_error(In(result): In<Result<()>>, mut app_exit: EventWriter<AppExit>) {
+/// If the result is an error, the error is logged and the app is exited with the
+/// [`ExitCodes::error`] code.
+pub fn exit_on_error(
+    In(result): In<Result<()>>,
+    mut app_exit: EventWriter<AppExit>,
+    codes: Option<Res<ExitCodes>>,
+) {
     if let Err(err) = result {
         error!("Error: {:?}", err);
-        app_exit.send_default();
+        app_exit.send(ExitCodes::error_exit(codes.as_deref()));
     }
 }
@@ -21,16 +21,38 @@
 //!     }
 //! }
 //! ```
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use bevy::{app::AppExit, prelude::*};
+use bevy::{
+    app::AppExit,
+    ecs::{
+        event::EventUpdates,
+        schedule::{InternedScheduleLabel, ScheduleLabel},
+    },
+    input::InputSystem,
+    prelude::*,
+};
 use color_eyre::Result;
 use crossterm::event::{self, Event::Key, KeyCode, KeyEventKind, KeyModifiers};
-use ratatui::layout::Size;
+use ratatui::layout::{Position, Size};
 
-use crate::error::exit_on_error;
+#[cfg(feature = "termwiz")]
+use crate::termwiz::TermwizInput;
+use crate::{
+    error::exit_on_error,
+    exit::ExitCodes,
+    handshake::{handshake_finished, HandshakeInput},
+    input_thread::InputThread,
+    latency::SimulatedLatency,
+    mouse::MousePassthroughAreas,
+    paste::PendingPastes,
+    terminal::{is_headless, RatatuiContext},
+};
 
 /// InputSet defines when the input events are emitted.
+///
+/// The sets are chained in [`PreUpdate`], and also in the schedule that [`EventPlugin`] reads events
+/// in if that is different.
 #[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum InputSet {
     /// Run before any input events are emitted.
@@ -40,6 +62,8 @@ pub enum InputSet {
     /// Check for emulation
     CheckEmulation,
     /// Emit the bevy events if [crate::input_forwarding::KeyboardPlugin] has been added.
+    /// The terminal is also resized here after a [`ResizeEvent`], and pastes are sent as
+    /// [`PasteChunk`](crate::paste::PasteChunk)s.
     EmitBevy,
     /// Run after all input events are emitted.
     Post,
@@ -48,34 +72,85 @@ pub enum InputSet {
 /// A plugin for handling events.
 ///
 /// This plugin adds the `KeyEvent` event, and a system that reads events from crossterm and sends
-/// them to the `KeyEvent` event.
-pub struct EventPlugin;
+/// them to the `KeyEvent` event. No events are read when the terminal is
+/// [headless](crate::terminal::HeadlessTerminal) or has not been set up, or while the startup
+/// [handshake](crate::handshake) is still waiting for the terminal to answer.
+///
+/// [`EventPlugin::default()`] reads events in the [`PreUpdate`] schedule. Use
+/// [`EventPlugin::in_schedule`] to read them in another schedule, e.g. [`First`] so that they are
+/// available to every system that runs after it. Input forwarded to bevy is always emitted in
+/// [`PreUpdate`], before bevy's [`InputSystem`](bevy::input::InputSystem), so that `ButtonInput`
+/// resources are up to date by the time `Update` runs.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_ratatui::event::EventPlugin;
+///
+/// let in_pre_update = EventPlugin::default();
+/// let in_first = EventPlugin::in_schedule(First);
+/// ```
+pub struct EventPlugin {
+    /// The schedule that terminal events are read in.
+    pub schedule: InternedScheduleLabel,
+}
+
+impl Default for EventPlugin {
+    fn default() -> Self {
+        Self {
+            schedule: PreUpdate.intern(),
+        }
+    }
+}
+
+impl EventPlugin {
+    /// Reads terminal events in the given schedule.
+    pub fn in_schedule(schedule: impl ScheduleLabel) -> Self {
+        Self {
+            schedule: schedule.intern(),
+        }
+    }
+}
 
 impl Plugin for EventPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<KeyEvent>()
+        let input_sets = || {
+            (
+                InputSet::Pre,
+                InputSet::EmitCrossterm,
+                InputSet::CheckEmulation,
+                InputSet::EmitBevy,
+                InputSet::Post,
+            )
+                .chain()
+        };
+        app.init_resource::<InterruptBehavior>()
+            .add_event::<KeyEvent>()
             .add_event::<MouseEvent>()
             .add_event::<FocusEvent>()
             .add_event::<ResizeEvent>()
+            .init_resource::<ResizeDebounce>()
             .add_event::<PasteEvent>()
             .add_event::<CrosstermEvent>()
-            .configure_sets(
-                Update,
+            .add_event::<InterruptRequested>()
+            .init_resource::<EventStats>()
+            .configure_sets(self.schedule, input_sets())
+            // Only matters in `First`, which is where events are updated.
+            .configure_sets(self.schedule, InputSet::Pre.after(EventUpdates))
+            .configure_sets(PreUpdate, input_sets())
+            .configure_sets(PreUpdate, InputSet::EmitBevy.before(InputSystem))
+            .add_systems(
+                self.schedule,
                 (
-                    InputSet::Pre,
-                    InputSet::EmitCrossterm,
-                    InputSet::CheckEmulation,
-                    InputSet::EmitBevy,
-                    InputSet::Post,
+                    crossterm_event_system
+                        .pipe(exit_on_error)
+                        .run_if(not(is_headless).and(resource_exists::<RatatuiContext>))
+                        .run_if(handshake_finished),
+                    interrupt_system,
                 )
-                    .chain(),
-            )
-            .add_systems(
-                PreUpdate,
-                crossterm_event_system
-                    .pipe(exit_on_error)
+                    .chain()
                     .in_set(InputSet::EmitCrossterm),
-            );
+            )
+            .add_systems(Last, update_queue_depth);
     }
 }
 
@@ -102,14 +177,89 @@ pub enum FocusEvent {
 #[derive(Debug, Clone, Copy, Event, PartialEq, Eq, Deref)]
 pub struct ResizeEvent(pub Size);
 
+/// How long the terminal size must stay the same before a [`ResizeEvent`] is sent.
+///
+/// Dragging a window corner makes the terminal report many sizes in quick succession. Only the last
+/// size read in a frame is sent, and the others are counted as
+/// [coalesced](EventStats::coalesced). With a non-zero debounce, the last size is also held back
+/// until no other size has been read for this long, so that the app is not laid out again for
+/// every step of the drag. Defaults to zero.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct ResizeDebounce(pub Duration);
+
 /// An event that is sent when text is pasted into the terminal.
+///
+/// With the [`PastePlugin`](crate::paste::PastePlugin), pastes are sent as
+/// [`PasteChunk`](crate::paste::PasteChunk)s instead.
 #[derive(Debug, Clone, Event, PartialEq, Eq, Deref)]
 pub struct PasteEvent(pub String);
 
+/// An event that is sent when `Ctrl+C` is pressed.
+///
+/// In raw mode the terminal does not turn `Ctrl+C` into a signal, so this event takes its place.
+/// It is sent once per press, alongside the [`KeyEvent`], and is the only place the crate reacts
+/// to `Ctrl+C`. What happens next is set by the [`InterruptBehavior`] resource.
+#[derive(Debug, Clone, Copy, Default, Event, PartialEq, Eq, Hash)]
+pub struct InterruptRequested;
+
+/// What the crate does when an [`InterruptRequested`] event is sent.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InterruptBehavior {
+    /// Exit the app with the [`ExitCodes::interrupt`] code. The [`AppExit`] event is sent once,
+    /// however many interrupts follow.
+    #[default]
+    Exit,
+    /// Do nothing, leaving the app to handle the [`InterruptRequested`] events, e.g. to ask
+    /// whether to save before exiting.
+    Ignore,
+}
+
+/// Counts of the terminal events read during the current frame.
+///
+/// This is reset at the start of every frame by [`crossterm_event_system`]. The
+/// [`EventDiagnosticsPlugin`](crate::diagnostics::EventDiagnosticsPlugin) publishes these counts
+/// as bevy diagnostics.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EventStats {
+    /// The number of events read from the terminal.
+    pub polled: usize,
+    /// The number of key events.
+    pub keys: usize,
+    /// The number of mouse events.
+    pub mouse: usize,
+    /// The total size in bytes of pasted text.
+    pub paste_bytes: usize,
+    /// The number of events that were merged into another event rather than being sent.
+    pub coalesced: usize,
+    /// The number of events waiting to be read at the end of the frame: those the [`InputThread`]
+    /// has read while the frame ran, and those held back by a [`SimulatedLatency`]. This is set in
+    /// [`Last`].
+    pub queue_depth: usize,
+}
+
+/// Counts the events that are left for the next frame.
+pub(crate) fn update_queue_depth(
+    mut stats: ResMut<EventStats>,
+    latency: Option<Res<SimulatedLatency>>,
+    input_thread: Option<Res<InputThread>>,
+) {
+    stats.queue_depth = latency.map_or(0, |latency| latency.queued())
+        + input_thread.map_or(0, |input_thread| input_thread.pending());
+}
+
 /// System that reads events from crossterm and sends them to the `KeyEvent` event.
 ///
 /// This system reads events from crossterm and sends them to the `KeyEvent` event. It also sends
-/// an `AppExit` event when `Ctrl+C` is pressed.
+/// an [`InterruptRequested`] event when `Ctrl+C` is pressed. Mouse events inside a
+/// [`MousePassthrough`](crate::mouse::MousePassthrough) region are dropped. Resizes are coalesced
+/// and debounced as described in [`ResizeDebounce`], and events are held back while there is a
+/// [`SimulatedLatency`]. While there is an [`InputThread`], the events it has read are taken
+/// instead of polling, and likewise the events termwiz has read with the `termwiz` feature's
+/// [`TermwizTerminalPlugin`](crate::termwiz::TermwizTerminalPlugin). With the `sigwinch` feature,
+/// the size read by the [`SigwinchPlugin`](crate::sigwinch::SigwinchPlugin) is sent as a resize.
+/// The keys that were pressed during the [startup handshake](crate::handshake) are sent first.
+/// Pastes are handed to the [`PastePlugin`](crate::paste::PastePlugin) if it is added.
+#[allow(clippy::too_many_arguments)]
 pub fn crossterm_event_system(
     mut events: EventWriter<CrosstermEvent>,
     mut keys: EventWriter<KeyEvent>,
@@ -117,19 +267,68 @@ pub fn crossterm_event_system(
     mut focus: EventWriter<FocusEvent>,
     mut paste: EventWriter<PasteEvent>,
     mut resize: EventWriter<ResizeEvent>,
-    mut exit: EventWriter<AppExit>,
+    mut interrupt: EventWriter<InterruptRequested>,
+    mut stats: ResMut<EventStats>,
+    passthrough: Option<Res<MousePassthroughAreas>>,
+    debounce: Res<ResizeDebounce>,
+    mut pending_resize: Local<Option<(Size, Instant)>>,
+    latency: Option<ResMut<SimulatedLatency>>,
+    // Grouped to stay within the number of parameters a system can have.
+    (input_thread, handshake_input): (Option<Res<InputThread>>, Option<ResMut<HandshakeInput>>),
+    mut pending_pastes: Option<ResMut<PendingPastes>>,
+    #[cfg(feature = "termwiz")] termwiz_input: Option<ResMut<TermwizInput>>,
+    #[cfg(all(unix, feature = "sigwinch"))] mut signalled_size: Local<Option<Size>>,
 ) -> Result<()> {
-    while event::poll(Duration::ZERO)? {
-        let event = event::read()?;
+    *stats = EventStats::default();
+    #[cfg(feature = "termwiz")]
+    let termwiz_input = termwiz_input.map(|mut input| std::mem::take(&mut input.0));
+    #[cfg(not(feature = "termwiz"))]
+    let termwiz_input: Option<Vec<event::Event>> = None;
+    let mut incoming = Vec::new();
+    if let Some(input_thread) = input_thread {
+        incoming = input_thread.drain()?;
+        stats.polled = incoming.len();
+    } else if let Some(termwiz_input) = termwiz_input {
+        incoming = termwiz_input;
+        stats.polled = incoming.len();
+    } else {
+        while event::poll(Duration::ZERO)? {
+            incoming.push(event::read()?);
+            stats.polled += 1;
+        }
+    }
+    if let Some(mut handshake_input) = handshake_input.filter(|input| !input.0.is_empty()) {
+        stats.polled += handshake_input.0.len();
+        incoming.splice(0..0, handshake_input.0.drain(..));
+    }
+    if let Some(mut latency) = latency {
+        incoming = latency.delay(incoming);
+    }
+    #[cfg(all(unix, feature = "sigwinch"))]
+    {
+        // A resize that was already sent when the signal arrived is not sent again.
+        let before = incoming.len();
+        incoming.retain(|event| {
+            !matches!(event, event::Event::Resize(columns, rows)
+                if *signalled_size == Some(Size::new(*columns, *rows)))
+        });
+        stats.coalesced += before - incoming.len();
+        if let Some(size) = crate::sigwinch::take_signalled_size() {
+            *signalled_size = Some(size);
+            incoming.push(event::Event::Resize(size.width, size.height));
+        }
+    }
+    for event in incoming {
         match event {
             Key(event) => {
                 if event.kind == KeyEventKind::Press
                     && event.modifiers == KeyModifiers::CONTROL
                     && event.code == KeyCode::Char('c')
                 {
-                    exit.send_default();
+                    interrupt.send_default();
                 }
 
+                stats.keys += 1;
                 keys.send(KeyEvent(event));
             }
             event::Event::FocusLost => {
@@ -139,16 +338,66 @@ pub fn crossterm_event_system(
                 focus.send(FocusEvent::Gained);
             }
             event::Event::Mouse(event) => {
+                let position = Position::new(event.column, event.row);
+                if passthrough
+                    .as_ref()
+                    .is_some_and(|areas| areas.contains(position))
+                {
+                    continue;
+                }
+                stats.mouse += 1;
                 mouse.send(MouseEvent(event));
             }
-            event::Event::Paste(ref s) => {
-                paste.send(PasteEvent(s.clone()));
+            event::Event::Paste(text) => {
+                stats.paste_bytes += text.len();
+                // The text is moved rather than copied, only to be sent in chunks.
+                if let Some(pending_pastes) = pending_pastes.as_mut() {
+                    pending_pastes.push(text);
+                    continue;
+                }
+                paste.send(PasteEvent(text.clone()));
+                events.send(CrosstermEvent(event::Event::Paste(text)));
+                continue;
             }
             event::Event::Resize(columns, rows) => {
-                resize.send(ResizeEvent(Size::new(columns, rows)));
+                let size = Size::new(columns, rows);
+                if pending_resize.replace((size, Instant::now())).is_some() {
+                    stats.coalesced += 1;
+                }
+                // The crossterm event is sent along with the resize event below.
+                continue;
             }
         }
         events.send(CrosstermEvent(event));
     }
+    if let Some((size, read_at)) = *pending_resize {
+        if read_at.elapsed() >= debounce.0 {
+            *pending_resize = None;
+            resize.send(ResizeEvent(size));
+            events.send(CrosstermEvent(event::Event::Resize(
+                size.width,
+                size.height,
+            )));
+        }
+    }
     Ok(())
 }
+
+/// Exits the app with the [`ExitCodes::interrupt`] code on the first [`InterruptRequested`] event
+/// if the [`InterruptBehavior`] is [`InterruptBehavior::Exit`].
+fn interrupt_system(
+    mut interrupts: EventReader<InterruptRequested>,
+    behavior: Res<InterruptBehavior>,
+    mut exiting: Local<bool>,
+    mut exit: EventWriter<AppExit>,
+    codes: Option<Res<ExitCodes>>,
+) {
+    if interrupts.is_empty() {
+        return;
+    }
+    interrupts.clear();
+    if *behavior == InterruptBehavior::Exit && !*exiting {
+        *exiting = true;
+        exit.send(ExitCodes::interrupt_exit(codes.as_deref()));
+    }
+}
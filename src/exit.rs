@@ -0,0 +1,227 @@
+//! Exit codes.
+//!
+//! [`App::run`] returns the [`AppExit`] that the app exited with, and returning it from `main` makes
+//! it the exit status of the process, so that a shell script that wraps the app can tell what
+//! happened. The ways the crate exits the app each use their own code:
+//!
+//! - An error passed to [`exit_on_error`](crate::error::exit_on_error), or one that the crate runs
+//!   into itself, exits with [`ExitCodes::error`], `1` by default.
+//! - `Ctrl+C` exits with [`ExitCodes::interrupt`], `130` by default, as a shell reports a process
+//!   that was interrupted.
+//! - On unix, `SIGTERM`, `SIGHUP` and `SIGINT` exit the app with the terminal restored, with `128`
+//!   plus the signal number, e.g. `143` for `SIGTERM`. A second signal of the same kind stops the
+//!   process right away, in case the app does not get to exit. The handlers are only installed
+//!   while the terminal is set up, see [`ExitSignalHandlers`], so a headless app, or a
+//!   [deferred](crate::terminal::TerminalState::Deferred) one that has not set up the terminal
+//!   yet, keeps the signal handling it had.
+//! - A panic exits with [`PANIC_EXIT_CODE`], as any panic out of `main` does, and the
+//!   [supervisor](crate::supervisor) uses it too once it gives up restarting the app.
+//! - Anything else, such as a [quit key](crate::quit), exits with the [`ExitStatus`], which is `0`
+//!   unless a system has set it.
+//!
+//! When an app is exited more than once in a frame, the first error wins.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{exit::ExitStatus, quit::QuitPlugin, RatatuiPlugins};
+//!
+//! fn main() -> AppExit {
+//!     App::new()
+//!         .add_plugins((RatatuiPlugins::default(), QuitPlugin))
+//!         .add_systems(Update, choose)
+//!         .run()
+//! }
+//!
+//! /// Exits with the number of the item that was chosen last when the app quits.
+//! fn choose(mut status: ResMut<ExitStatus>) {
+//!     status.set(2);
+//! }
+//! ```
+use std::num::NonZero;
+#[cfg(unix)]
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use bevy::{app::AppExit, ecs::event::EventUpdates, prelude::*};
+
+#[cfg(unix)]
+use crate::{
+    event::InputSet,
+    terminal::{is_headless, TerminalSet, TerminalStartup},
+};
+
+/// The exit code of a process that panicked.
+pub const PANIC_EXIT_CODE: u8 = 101;
+
+/// A plugin that sets the exit codes of the app.
+///
+/// This is part of [`RatatuiPlugins`](crate::RatatuiPlugins).
+pub struct ExitPlugin;
+
+impl Plugin for ExitPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExitCodes>()
+            .init_resource::<ExitStatus>()
+            .add_systems(Last, apply_exit_status);
+        #[cfg(unix)]
+        app.add_systems(
+            TerminalStartup,
+            install_signal_handlers
+                .run_if(not(is_headless))
+                .in_set(TerminalSet::Hooks),
+        )
+        .add_systems(
+            First,
+            exit_on_signal.after(EventUpdates).before(InputSet::Pre),
+        );
+    }
+}
+
+/// The exit codes for the ways the crate exits the app.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExitCodes {
+    /// The exit code after an error. Defaults to `1`.
+    pub error: NonZero<u8>,
+    /// The exit code after `Ctrl+C`. Defaults to `130`.
+    pub interrupt: NonZero<u8>,
+}
+
+impl Default for ExitCodes {
+    fn default() -> Self {
+        Self {
+            error: NonZero::<u8>::MIN,
+            interrupt: NonZero::new(130).unwrap(),
+        }
+    }
+}
+
+impl ExitCodes {
+    /// The exit after an error, with the codes in `codes` or the default ones.
+    pub(crate) fn error_exit(codes: Option<&Self>) -> AppExit {
+        AppExit::Error(codes.copied().unwrap_or_default().error)
+    }
+
+    /// The exit after `Ctrl+C`, with the codes in `codes` or the default ones.
+    pub(crate) fn interrupt_exit(codes: Option<&Self>) -> AppExit {
+        AppExit::Error(codes.copied().unwrap_or_default().interrupt)
+    }
+}
+
+/// The exit code when the app exits with [`AppExit::Success`], e.g. from a quit key. Defaults to
+/// `0`.
+///
+/// Setting this lets a system decide on the exit code of the app ahead of time, without exiting
+/// it. An app that exits with an error exits with the code of the error instead.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deref, DerefMut)]
+pub struct ExitStatus(pub u8);
+
+impl ExitStatus {
+    /// Sets the exit code.
+    pub fn set(&mut self, code: u8) {
+        self.0 = code;
+    }
+
+    /// The exit that a successful exit turns into.
+    pub fn exit(&self) -> AppExit {
+        AppExit::from_code(self.0)
+    }
+}
+
+/// Replaces successful exits with the [`ExitStatus`], unless the app also exited with an error.
+fn apply_exit_status(status: Res<ExitStatus>, mut exits: ResMut<Events<AppExit>>) {
+    if status.0 == 0 {
+        return;
+    }
+    let mut reader = exits.get_cursor();
+    let mut exited = false;
+    for exit in reader.read(&exits) {
+        if exit.is_error() {
+            return;
+        }
+        exited = true;
+    }
+    if exited {
+        exits.send(status.exit());
+    }
+}
+
+/// The signal that asked the app to exit, or zero.
+#[cfg(unix)]
+static EXIT_SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+#[cfg(unix)]
+extern "C" fn on_signal(signal: libc::c_int) {
+    // Only async-signal-safe work is allowed here, so the app exits in the next frame.
+    EXIT_SIGNAL.store(signal, Ordering::SeqCst);
+}
+
+/// The signals that exit the app.
+#[cfg(unix)]
+const EXIT_SIGNALS: [libc::c_int; 3] = [libc::SIGTERM, libc::SIGHUP, libc::SIGINT];
+
+/// Present while `SIGTERM`, `SIGHUP` and `SIGINT` exit the app. Removing it puts back the handlers
+/// that were installed before.
+///
+/// It is inserted when the terminal is set up, and removed when the terminal is restored as the
+/// app exits.
+#[cfg(unix)]
+#[derive(Resource)]
+pub struct ExitSignalHandlers {
+    previous: [libc::sigaction; 3],
+}
+
+#[cfg(unix)]
+impl ExitSignalHandlers {
+    /// Installs the handlers. Insert the returned resource to keep them installed.
+    pub fn install() -> Self {
+        let handler = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        let previous = EXIT_SIGNALS.map(|signal| {
+            // SAFETY: the actions are fully initialized, and the handler only stores to an atomic.
+            unsafe {
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = handler;
+                // The default action is restored once the signal is handled, so that a second
+                // signal stops an app that is stuck.
+                action.sa_flags = libc::SA_RESTART | libc::SA_RESETHAND;
+                libc::sigemptyset(&mut action.sa_mask);
+                let mut previous: libc::sigaction = std::mem::zeroed();
+                if libc::sigaction(signal, &action, &mut previous) != 0 {
+                    // Nothing was installed, so restoring the default action changes nothing.
+                    previous.sa_sigaction = libc::SIG_DFL;
+                }
+                previous
+            }
+        });
+        Self { previous }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for ExitSignalHandlers {
+    fn drop(&mut self) {
+        for (signal, previous) in EXIT_SIGNALS.iter().zip(&self.previous) {
+            // SAFETY: `previous` is the action that `sigaction` returned for this signal.
+            unsafe { libc::sigaction(*signal, previous, std::ptr::null_mut()) };
+        }
+    }
+}
+
+#[cfg(unix)]
+impl std::fmt::Debug for ExitSignalHandlers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExitSignalHandlers").finish_non_exhaustive()
+    }
+}
+
+#[cfg(unix)]
+fn install_signal_handlers(mut commands: Commands) {
+    commands.insert_resource(ExitSignalHandlers::install());
+}
+
+/// Exits the app with `128` plus the number of the signal that asked it to.
+#[cfg(unix)]
+fn exit_on_signal(mut exit: EventWriter<AppExit>) {
+    let signal = EXIT_SIGNAL.swap(0, Ordering::SeqCst);
+    if signal != 0 {
+        exit.send(AppExit::from_code((128 + signal) as u8));
+    }
+}
@@ -0,0 +1,187 @@
+//! Integrations with specific terminal emulators.
+//!
+//! Many terminals have features of their own beyond the common escape sequences, such as kitty's
+//! remote control, WezTerm's user vars or iTerm2's badges. Each integration is a
+//! [`TerminalExtension`], added with an [`ExtensionPlugin`]. The extension is only built when it
+//! supports the [`TerminalIdentity`] detected from the environment, so an app can add every
+//! integration it knows about and get the ones that work in the terminal it runs in. The
+//! [`TerminalExtensions`] resource records which extensions were added and which are active.
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::extension::{
+//!     ExtensionPlugin, TerminalExtension, TerminalExtensions, TerminalIdentity, TerminalProgram,
+//! };
+//!
+//! struct WezTermStatus;
+//!
+//! impl TerminalExtension for WezTermStatus {
+//!     fn name(&self) -> &'static str {
+//!         "wezterm-status"
+//!     }
+//!
+//!     fn supports(&self, identity: &TerminalIdentity) -> bool {
+//!         identity.program == TerminalProgram::WezTerm
+//!     }
+//!
+//!     fn build(&self, app: &mut App) {
+//!         // Add the systems that talk to WezTerm here.
+//!     }
+//! }
+//!
+//! let mut app = App::new();
+//! app.insert_resource(TerminalIdentity::from_vars(|name| {
+//!     (name == "TERM_PROGRAM").then(|| "WezTerm".to_string())
+//! }))
+//! .add_plugins(ExtensionPlugin(WezTermStatus));
+//! assert!(app.world().resource::<TerminalExtensions>().is_active("wezterm-status"));
+//! ```
+//!
+//! Extensions should leave a [headless](crate::terminal::HeadlessTerminal) terminal alone, e.g.
+//! with the [`is_headless`](crate::terminal::is_headless) run condition.
+use std::{env, fmt};
+
+use bevy::prelude::*;
+
+/// The terminal emulator the app runs in, as far as the environment tells.
+///
+/// This is read from the environment when the [`TerminalPlugin`](crate::terminal::TerminalPlugin)
+/// or the first [`ExtensionPlugin`] is added, unless the app has inserted one already. Inside a
+/// multiplexer such as tmux, the multiplexer is usually what is detected.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TerminalIdentity {
+    /// The terminal emulator.
+    pub program: TerminalProgram,
+    /// The version of the terminal emulator, from `TERM_PROGRAM_VERSION`.
+    pub version: Option<String>,
+    /// The value of `TERM`.
+    pub term: Option<String>,
+}
+
+/// A terminal emulator with extensions that this crate knows about.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub enum TerminalProgram {
+    Kitty,
+    WezTerm,
+    ITerm2,
+    Alacritty,
+    Ghostty,
+    /// Another terminal, named by `TERM_PROGRAM`.
+    Other(String),
+    /// The environment does not say.
+    #[default]
+    Unknown,
+}
+
+impl TerminalIdentity {
+    /// Detects the terminal from the environment variables of the process.
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| env::var(name).ok())
+    }
+
+    /// Detects the terminal from environment variables looked up with `var`.
+    pub fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let term = var("TERM");
+        let term_program = var("TERM_PROGRAM");
+        let is = |program: &str, term_name: &str, marker: &str| {
+            term_program.as_deref() == Some(program)
+                || term.as_deref() == Some(term_name)
+                || var(marker).is_some()
+        };
+        let program = if is("kitty", "xterm-kitty", "KITTY_WINDOW_ID") {
+            TerminalProgram::Kitty
+        } else if is("WezTerm", "wezterm", "WEZTERM_PANE") {
+            TerminalProgram::WezTerm
+        } else if is("iTerm.app", "iterm2", "ITERM_SESSION_ID") {
+            TerminalProgram::ITerm2
+        } else if is("alacritty", "alacritty", "ALACRITTY_WINDOW_ID") {
+            TerminalProgram::Alacritty
+        } else if is("ghostty", "xterm-ghostty", "GHOSTTY_RESOURCES_DIR") {
+            TerminalProgram::Ghostty
+        } else if let Some(name) = term_program.clone().filter(|name| !name.is_empty()) {
+            TerminalProgram::Other(name)
+        } else {
+            TerminalProgram::Unknown
+        };
+        Self {
+            program,
+            version: var("TERM_PROGRAM_VERSION").filter(|version| !version.is_empty()),
+            term,
+        }
+    }
+}
+
+/// An integration with the features of specific terminals.
+pub trait TerminalExtension: Send + Sync + 'static {
+    /// A short name for the extension, as listed in [`TerminalExtensions`].
+    fn name(&self) -> &'static str;
+
+    /// Whether the extension works in the identified terminal.
+    fn supports(&self, identity: &TerminalIdentity) -> bool;
+
+    /// Adds what the app needs to use the extension whether or not it is supported, such as its
+    /// events, so that systems using them work in any terminal. Does nothing by default.
+    fn register(&self, _app: &mut App) {}
+
+    /// Adds the resources and systems of the extension. Only called if it is supported.
+    fn build(&self, app: &mut App);
+}
+
+/// A plugin that builds a [`TerminalExtension`] if it supports the [`TerminalIdentity`].
+pub struct ExtensionPlugin<E: TerminalExtension>(pub E);
+
+impl<E: TerminalExtension> Plugin for ExtensionPlugin<E> {
+    fn build(&self, app: &mut App) {
+        self.0.register(app);
+        let world = app.world_mut();
+        let active = self
+            .0
+            .supports(&world.get_resource_or_insert_with(TerminalIdentity::from_env));
+        world
+            .get_resource_or_init::<TerminalExtensions>()
+            .extensions
+            .push((self.0.name(), active));
+        if active {
+            self.0.build(app);
+        }
+    }
+}
+
+/// The extensions that were added with an [`ExtensionPlugin`], and whether each is active in this
+/// terminal.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TerminalExtensions {
+    extensions: Vec<(&'static str, bool)>,
+}
+
+impl TerminalExtensions {
+    /// The names of the extensions in the order they were added, and whether each is active.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, bool)> + '_ {
+        self.extensions.iter().copied()
+    }
+
+    /// Whether the extension with this name was added and supports the terminal.
+    pub fn is_active(&self, name: &str) -> bool {
+        self.extensions
+            .iter()
+            .any(|(extension, active)| *extension == name && *active)
+    }
+}
+
+/// Writes `bytes` in base64, as many terminal escape sequences expect their arguments.
+pub(crate) fn write_base64(f: &mut impl fmt::Write, bytes: &[u8]) -> fmt::Result {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+            group | u32::from(*byte) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                f.write_char(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize].into())?;
+            } else {
+                f.write_char('=')?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,332 @@
+//! A file picker dialog.
+//!
+//! [`FilePickerPlugin`] adds a [`FilePicker`] resource that shows a directory browser in a popup
+//! over the app. Directories are read on the [`IoTaskPool`] so that large directories do not block
+//! the frame. When the user picks a file a [`FilePickerEvent::Selected`] event is sent.
+//!
+//! While the picker is open:
+//!
+//! - `Up`/`Down`, `PageUp`/`PageDown` and `Home`/`End` move the selection.
+//! - `Enter` opens the selected directory or picks the selected file.
+//! - `Left`, or `Backspace` with an empty filter, goes to the parent directory.
+//! - Typing filters the entries by name.
+//! - `Esc` closes the picker and sends [`FilePickerEvent::Cancelled`].
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     file_picker::{FilePicker, FilePickerEvent, FilePickerPlugin},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), FilePickerPlugin))
+//!     .add_systems(Startup, |mut picker: ResMut<FilePicker>| picker.open("."))
+//!     .add_systems(Update, open_file);
+//!
+//! fn open_file(mut events: EventReader<FilePickerEvent>) {
+//!     for event in events.read() {
+//!         if let FilePickerEvent::Selected(path) = event {
+//!             info!("opening {}", path.display());
+//!         }
+//!     }
+//! }
+//! ```
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, IoTaskPool, Task, TaskPool},
+};
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Clear, List, ListItem, ListState},
+};
+
+use crate::{
+    error::exit_on_error,
+    event::{InputSet, KeyEvent},
+    quit,
+    terminal::RatatuiContext,
+};
+
+/// A plugin that adds the [`FilePicker`] dialog.
+pub struct FilePickerPlugin;
+
+impl Plugin for FilePickerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FilePicker>()
+            .add_event::<FilePickerEvent>()
+            .add_systems(
+                PreUpdate,
+                (poll_entries, file_picker_input_system)
+                    .chain()
+                    .in_set(InputSet::Post)
+                    .after(quit::quit_system)
+                    .run_if(file_picker_open),
+            )
+            .add_systems(
+                Last,
+                draw_file_picker
+                    .pipe(exit_on_error)
+                    .run_if(file_picker_open.and(resource_exists::<RatatuiContext>)),
+            );
+    }
+}
+
+/// A run condition that is true while the file picker is open.
+pub fn file_picker_open(picker: Option<Res<FilePicker>>) -> bool {
+    picker.is_some_and(|picker| picker.is_open())
+}
+
+/// Sent when the file picker closes.
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub enum FilePickerEvent {
+    /// A file was picked.
+    Selected(PathBuf),
+    /// The picker was closed without picking a file.
+    Cancelled,
+}
+
+/// An entry in the directory shown by the file picker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    pub name: String,
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+/// The state of the file picker dialog.
+#[derive(Resource, Default)]
+pub struct FilePicker {
+    open: bool,
+    /// Whether entries starting with `.` are listed.
+    pub show_hidden: bool,
+    dir: PathBuf,
+    entries: Vec<FileEntry>,
+    filter: String,
+    list_state: ListState,
+    loading: Option<Task<io::Result<Vec<FileEntry>>>>,
+    error: Option<String>,
+}
+
+impl FilePicker {
+    /// Opens the picker in the given directory.
+    pub fn open(&mut self, dir: impl Into<PathBuf>) {
+        self.open = true;
+        self.navigate(dir.into());
+    }
+
+    /// Closes the picker without sending an event.
+    pub fn close(&mut self) {
+        self.open = false;
+        self.loading = None;
+    }
+
+    /// Returns true if the picker is open.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// The directory being shown.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns true while the directory is being read.
+    pub fn is_loading(&self) -> bool {
+        self.loading.is_some()
+    }
+
+    /// The text that entries are filtered by.
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    /// The entries of the directory that match the filter.
+    pub fn visible_entries(&self) -> impl Iterator<Item = &FileEntry> {
+        let filter = self.filter.to_lowercase();
+        self.entries.iter().filter(move |entry| {
+            (self.show_hidden || !entry.name.starts_with('.'))
+                && entry.name.to_lowercase().contains(&filter)
+        })
+    }
+
+    /// The selected entry, if any.
+    pub fn selected(&self) -> Option<&FileEntry> {
+        self.visible_entries().nth(self.list_state.selected()?)
+    }
+
+    /// Starts reading the directory in the background.
+    fn navigate(&mut self, dir: PathBuf) {
+        let pool = IoTaskPool::get_or_init(TaskPool::new);
+        let path = dir.clone();
+        self.loading = Some(pool.spawn(async move { read_entries(&path) }));
+        self.dir = dir;
+        self.entries.clear();
+        self.filter.clear();
+        self.error = None;
+        self.list_state.select(None);
+    }
+
+    fn parent(&mut self) {
+        let dir = fs::canonicalize(&self.dir).unwrap_or_else(|_| self.dir.clone());
+        if let Some(parent) = dir.parent() {
+            self.navigate(parent.to_path_buf());
+        }
+    }
+
+    fn reset_selection(&mut self) {
+        let selected = (self.visible_entries().count() > 0).then_some(0);
+        self.list_state.select(selected);
+    }
+
+    fn move_selection(&mut self, by: isize) {
+        let count = self.visible_entries().count();
+        if count == 0 {
+            return;
+        }
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state
+            .select(Some(selected.saturating_add_signed(by).min(count - 1)));
+    }
+
+    fn handle_key(&mut self, key: &crossterm::event::KeyEvent) -> Option<FilePickerEvent> {
+        match key.code {
+            KeyCode::Esc => {
+                self.close();
+                return Some(FilePickerEvent::Cancelled);
+            }
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::Home => self.move_selection(isize::MIN),
+            KeyCode::End => self.move_selection(isize::MAX),
+            KeyCode::Left => self.parent(),
+            KeyCode::Backspace if self.filter.is_empty() => self.parent(),
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.reset_selection();
+            }
+            KeyCode::Enter => {
+                let entry = self.selected()?.clone();
+                if entry.is_dir {
+                    self.navigate(entry.path);
+                } else {
+                    self.close();
+                    return Some(FilePickerEvent::Selected(entry.path));
+                }
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.filter.push(c);
+                self.reset_selection();
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+/// Reads a directory, listing the directories first, each sorted by name.
+fn read_entries(dir: &Path) -> io::Result<Vec<FileEntry>> {
+    let mut entries = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| FileEntry {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            is_dir: entry.path().is_dir(),
+            path: entry.path(),
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    Ok(entries)
+}
+
+fn poll_entries(mut picker: ResMut<FilePicker>) {
+    let Some(task) = picker.loading.as_mut() else {
+        return;
+    };
+    let Some(result) = block_on(future::poll_once(task)) else {
+        return;
+    };
+    picker.loading = None;
+    match result {
+        Ok(entries) => {
+            picker.entries = entries;
+            picker.reset_selection();
+        }
+        Err(err) => picker.error = Some(err.to_string()),
+    }
+}
+
+fn file_picker_input_system(
+    mut keys: EventReader<KeyEvent>,
+    mut picker: ResMut<FilePicker>,
+    mut events: EventWriter<FilePickerEvent>,
+) {
+    for key in keys.read().filter(|key| key.kind != KeyEventKind::Release) {
+        if !picker.is_open() {
+            break;
+        }
+        if let Some(event) = picker.handle_key(key) {
+            events.send(event);
+        }
+    }
+}
+
+/// Draws the file picker in a popup over the last frame.
+pub fn draw_file_picker(
+    mut context: ResMut<RatatuiContext>,
+    mut picker: ResMut<FilePicker>,
+) -> Result<()> {
+    let last_frame = context.last_frame().clone();
+    context.draw(|frame| {
+        let area = frame.area();
+        if last_frame.area == area {
+            *frame.buffer_mut() = last_frame;
+        }
+        let [popup] = Layout::horizontal([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [popup] = Layout::vertical([Constraint::Percentage(80)])
+            .flex(Flex::Center)
+            .areas(popup);
+        frame.render_widget(Clear, popup);
+
+        let block = Block::bordered()
+            .title(Line::from(format!(" {} ", picker.dir.display())))
+            .title_bottom(Line::from(format!(" filter: {} ", picker.filter)));
+        let inner = block.inner(popup);
+        frame.render_widget(block, popup);
+
+        let message = if picker.is_loading() {
+            Some("Loading...".to_string())
+        } else {
+            picker.error.clone()
+        };
+        if let Some(message) = message {
+            frame.render_widget(Line::from(message), inner);
+            return;
+        }
+        let items = picker
+            .visible_entries()
+            .map(|entry| {
+                let mut name = entry.name.clone();
+                if entry.is_dir {
+                    name.push('/');
+                }
+                ListItem::new(name)
+            })
+            .collect::<Vec<_>>();
+        let list = List::new(items).highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, inner, &mut picker.list_state);
+    })?;
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! Terminal input for systems that run in [`FixedUpdate`].
+//!
+//! Bevy events are read once per frame, while `FixedUpdate` may run zero, one or several times in
+//! a frame. Reading terminal events directly in a fixed system can therefore miss events (on frames
+//! where no fixed tick runs) or see them twice (when several ticks run in one frame).
+//!
+//! [`FixedInputPlugin`] collects the terminal events every frame into a [`FixedEvents`] resource,
+//! and hands each event to exactly one fixed tick: the first one that runs after the event was
+//! received.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{event::KeyEvent, fixed_input::FixedEvents};
+//!
+//! fn movement_system(keys: Res<FixedEvents<KeyEvent>>) {
+//!     for key in keys.read() {
+//!         // move the player one step
+//!     }
+//! }
+//! ```
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use crate::event::{FocusEvent, InputSet, KeyEvent, MouseEvent, PasteEvent, ResizeEvent};
+
+/// A plugin that makes all the terminal events available to fixed timestep systems through
+/// [`FixedEvents`].
+pub struct FixedInputPlugin;
+
+impl Plugin for FixedInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            FixedEventsPlugin::<KeyEvent>::default(),
+            FixedEventsPlugin::<MouseEvent>::default(),
+            FixedEventsPlugin::<FocusEvent>::default(),
+            FixedEventsPlugin::<ResizeEvent>::default(),
+            FixedEventsPlugin::<PasteEvent>::default(),
+        ));
+    }
+}
+
+/// A plugin that makes events of type `E` available to fixed timestep systems through
+/// [`FixedEvents<E>`].
+pub struct FixedEventsPlugin<E>(PhantomData<E>);
+
+impl<E> Default for FixedEventsPlugin<E> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<E: Event + Clone> Plugin for FixedEventsPlugin<E> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FixedEvents<E>>()
+            .add_systems(PreUpdate, collect_events::<E>.in_set(InputSet::Post))
+            .add_systems(FixedFirst, start_tick::<E>);
+    }
+}
+
+/// The events of type `E` that are available to the current fixed tick.
+///
+/// Events received since the previous fixed tick are collected every frame. When a fixed tick
+/// starts, they become available through [`FixedEvents::read`] for the duration of that tick only.
+#[derive(Resource, Debug)]
+pub struct FixedEvents<E> {
+    pending: Vec<E>,
+    current: Vec<E>,
+}
+
+impl<E> Default for FixedEvents<E> {
+    fn default() -> Self {
+        Self {
+            pending: Vec::new(),
+            current: Vec::new(),
+        }
+    }
+}
+
+impl<E> FixedEvents<E> {
+    /// Iterates over the events for the current fixed tick.
+    pub fn read(&self) -> impl Iterator<Item = &E> {
+        self.current.iter()
+    }
+
+    /// Returns true if there are no events for the current fixed tick.
+    pub fn is_empty(&self) -> bool {
+        self.current.is_empty()
+    }
+
+    /// The number of events for the current fixed tick.
+    pub fn len(&self) -> usize {
+        self.current.len()
+    }
+
+    /// The number of events waiting for the next fixed tick.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+fn collect_events<E: Event + Clone>(
+    mut events: EventReader<E>,
+    mut fixed_events: ResMut<FixedEvents<E>>,
+) {
+    fixed_events.pending.extend(events.read().cloned());
+}
+
+fn start_tick<E: Event>(mut fixed_events: ResMut<FixedEvents<E>>) {
+    let fixed_events = &mut *fixed_events;
+    fixed_events.current.clear();
+    std::mem::swap(&mut fixed_events.current, &mut fixed_events.pending);
+}
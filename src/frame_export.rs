@@ -0,0 +1,206 @@
+//! Streaming the drawn frames to another process or an embedding host.
+//!
+//! A GUI that shows the TUI in one of its panels, a recorder, or a compositor that mixes several
+//! apps needs the frames the app draws, and scraping them back out of a terminal loses the cells
+//! and styles. [`FrameExportPlugin`] sends every frame drawn to the [`RatatuiContext`] resource, as
+//! an [`ExportedFrame`] with the whole buffer and the areas that changed since the frame before,
+//! to each receiver returned by [`FrameExport::subscribe`]. The receivers can be moved to any
+//! thread.
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     frame_export::{FrameExport, FrameExportPlugin},
+//!     widget::RootWidget,
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::{layout::Rect, widgets::Paragraph};
+//!
+//! let mut app = App::new();
+//! app.add_plugins((
+//!     RatatuiPlugins {
+//!         headless: true,
+//!         ..default()
+//!     },
+//!     FrameExportPlugin,
+//! ))
+//! .insert_resource(RootWidget::new(Paragraph::new("hello")));
+//! let frames = app.world_mut().resource_mut::<FrameExport>().subscribe();
+//! app.update();
+//! let first = frames.try_recv().unwrap();
+//! assert_eq!(first.damage, [first.buffer.area]);
+//!
+//! app.insert_resource(RootWidget::new(Paragraph::new("help")));
+//! app.update();
+//! let second = frames.try_recv().unwrap();
+//! assert_eq!(second.damage, [Rect::new(3, 0, 2, 1)]);
+//! assert_eq!(second.buffer[(3, 0)].symbol(), "p");
+//! ```
+//!
+//! Each receiver holds at most [`FrameExport::capacity`] frames. Frames that a slow receiver has
+//! no room for are dropped rather than slowing down the app, and the next frame it does receive
+//! is marked as damaged everywhere, so that applying the damage always gives the full picture.
+//! Frames drawn to contexts spawned as components, for
+//! [other terminals](RatatuiContext#other-terminals), are not exported.
+use std::{
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc,
+    },
+    time::Instant,
+};
+
+use bevy::prelude::*;
+use ratatui::{buffer::Buffer, layout::Rect};
+
+use crate::{
+    render::RenderSet,
+    terminal::{RatatuiContext, TerminalSet},
+};
+
+/// A plugin that sends the drawn frames to the [`FrameExport`] subscribers.
+pub struct FrameExportPlugin;
+
+impl Plugin for FrameExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameExport>().add_systems(
+            PostUpdate,
+            export_frame
+                .run_if(resource_exists::<RatatuiContext>)
+                .after(RenderSet::Flush)
+                .before(TerminalSet::Cleanup),
+        );
+    }
+}
+
+/// A frame drawn to the terminal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportedFrame {
+    /// Counts the frames drawn since the plugin was added, starting at zero, including frames that
+    /// were dropped because the receiver was full.
+    pub number: u64,
+    /// When the frame was drawn.
+    pub drawn_at: Instant,
+    /// Every cell of the frame, shared between the subscribers.
+    pub buffer: Arc<Buffer>,
+    /// The areas that changed since the last frame this receiver got, each a run of cells in one
+    /// row, or the whole frame after a resize or a dropped frame. Empty if nothing changed.
+    pub damage: Vec<Rect>,
+}
+
+/// The subscribers to the drawn frames.
+#[derive(Resource, Debug)]
+pub struct FrameExport {
+    /// How many frames each receiver holds before new frames are dropped. Defaults to 4.
+    pub capacity: usize,
+    subscribers: Vec<Subscriber>,
+    previous: Option<Arc<Buffer>>,
+    last_draw: Option<Instant>,
+    number: u64,
+}
+
+impl Default for FrameExport {
+    fn default() -> Self {
+        Self {
+            capacity: 4,
+            subscribers: Vec::new(),
+            previous: None,
+            last_draw: None,
+            number: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Subscriber {
+    sender: SyncSender<ExportedFrame>,
+    missed_frame: bool,
+}
+
+impl FrameExport {
+    /// Returns a receiver for the frames drawn from now on. The first frame it receives is damaged
+    /// everywhere.
+    ///
+    /// The subscription ends when the receiver is dropped.
+    pub fn subscribe(&mut self) -> Receiver<ExportedFrame> {
+        let (sender, receiver) = mpsc::sync_channel(self.capacity.max(1));
+        self.subscribers.push(Subscriber {
+            sender,
+            missed_frame: true,
+        });
+        receiver
+    }
+
+    /// How many receivers have not been dropped yet, as of the last exported frame.
+    pub fn subscribers(&self) -> usize {
+        self.subscribers.len()
+    }
+
+    fn export(&mut self, buffer: &Buffer, drawn_at: Instant) {
+        let buffer = Arc::new(buffer.clone());
+        let full = vec![buffer.area];
+        let damage = match &self.previous {
+            Some(previous) if previous.area == buffer.area => damage(previous, &buffer),
+            _ => full.clone(),
+        };
+        let number = self.number;
+        self.subscribers.retain_mut(|subscriber| {
+            let frame = ExportedFrame {
+                number,
+                drawn_at,
+                buffer: Arc::clone(&buffer),
+                damage: if subscriber.missed_frame {
+                    full.clone()
+                } else {
+                    damage.clone()
+                },
+            };
+            match subscriber.sender.try_send(frame) {
+                Ok(()) => {
+                    subscriber.missed_frame = false;
+                    true
+                }
+                Err(TrySendError::Full(_)) => {
+                    subscriber.missed_frame = true;
+                    true
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            }
+        });
+        self.previous = Some(buffer);
+    }
+}
+
+/// The runs of cells in each row that differ between two buffers of the same area.
+fn damage(previous: &Buffer, current: &Buffer) -> Vec<Rect> {
+    let area = current.area;
+    let mut damage = Vec::new();
+    for y in area.top()..area.bottom() {
+        let mut run: Option<Rect> = None;
+        for x in area.left()..area.right() {
+            if previous[(x, y)] == current[(x, y)] {
+                damage.extend(run.take());
+            } else if let Some(run) = &mut run {
+                run.width += 1;
+            } else {
+                run = Some(Rect::new(x, y, 1, 1));
+            }
+        }
+        damage.extend(run);
+    }
+    damage
+}
+
+fn export_frame(context: Res<RatatuiContext>, mut export: ResMut<FrameExport>) {
+    let Some((drawn_at, _)) = context.last_draw() else {
+        return;
+    };
+    if export.last_draw == Some(drawn_at) {
+        return;
+    }
+    export.last_draw = Some(drawn_at);
+    if !export.subscribers.is_empty() {
+        export.export(context.last_frame(), drawn_at);
+    }
+    export.number += 1;
+}
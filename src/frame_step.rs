@@ -0,0 +1,280 @@
+//! Pausing the app and stepping it one frame at a time.
+//!
+//! [`FrameStepPlugin`] pauses the app when the pause key (`F9` by default) is pressed and runs a
+//! single frame each time the step key (`F10` by default) is pressed, showing a `PAUSED (frame N)`
+//! indicator in the top right corner. This is useful for debugging animation and layout logic.
+//!
+//! Input is always handled, so the keys keep working while paused. What else is paused depends on
+//! the [`FrameStepMode`]:
+//!
+//! - [`FrameStepMode::All`] pauses every schedule between [`PreUpdate`] and [`Last`], including
+//!   [`FixedUpdate`] and drawing. The last frame stays on screen.
+//! - [`FrameStepMode::UpdateOnly`] pauses only the [`Update`] schedule, so the app keeps drawing
+//!   while its state is frozen.
+//!
+//! While paused, [`Time<Virtual>`] is paused too, and each step advances it by
+//! [`FrameStep::step_delta`] so that time based logic moves by one frame.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     frame_step::{FrameStep, FrameStepMode, FrameStepPlugin},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), FrameStepPlugin))
+//!     .insert_resource(FrameStep::new(FrameStepMode::UpdateOnly));
+//! ```
+use std::time::Duration;
+
+use bevy::{
+    app::MainScheduleOrder,
+    ecs::{intern::Interned, schedule::ScheduleLabel},
+    prelude::*,
+};
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEventKind};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+};
+
+use crate::{
+    error::exit_on_error,
+    event::{InputSet, KeyEvent},
+    quit::{self, KeyBinding},
+    terminal::RatatuiContext,
+};
+
+/// A plugin that pauses and steps the app with the [`FrameStepKeys`].
+pub struct FrameStepPlugin;
+
+impl Plugin for FrameStepPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameStep>()
+            .init_resource::<FrameStepKeys>()
+            .init_schedule(SteppedMain)
+            .add_systems(SteppedMain, run_stepped_schedules)
+            .add_systems(
+                PreUpdate,
+                frame_step_input_system
+                    .in_set(InputSet::Post)
+                    .after(quit::quit_system),
+            )
+            .add_systems(
+                Last,
+                draw_paused_indicator
+                    .pipe(exit_on_error)
+                    .run_if(frame_step_paused.and(resource_exists::<RatatuiContext>)),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        // Done in finish so that schedules added to the main order by other plugins are included.
+        let world = app.world_mut();
+        let mut order = world.resource_mut::<MainScheduleOrder>();
+        let start = order
+            .labels
+            .iter()
+            .position(|label| *label == PreUpdate.intern());
+        let end = order
+            .labels
+            .iter()
+            .position(|label| *label == Last.intern());
+        let (Some(start), Some(end)) = (start, end) else {
+            warn!(
+                "frame stepping is disabled because PreUpdate or Last is not in the main schedule"
+            );
+            return;
+        };
+        let stepped: Vec<_> = order.labels.drain(start + 1..end).collect();
+        order.labels.insert(start + 1, SteppedMain.intern());
+        world.insert_resource(SteppedSchedules(stepped));
+    }
+}
+
+/// A run condition that is true while the app is paused.
+pub fn frame_step_paused(step: Option<Res<FrameStep>>) -> bool {
+    step.is_some_and(|step| step.is_paused())
+}
+
+/// What is paused by the [`FrameStepPlugin`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FrameStepMode {
+    /// Pause every schedule between [`PreUpdate`] and [`Last`], including drawing.
+    #[default]
+    All,
+    /// Pause only the [`Update`] schedule.
+    UpdateOnly,
+}
+
+/// Whether the app is paused, and the number of frames run.
+#[derive(Resource, Debug, Clone)]
+pub struct FrameStep {
+    pub mode: FrameStepMode,
+    /// How far [`Time<Virtual>`] advances on each step.
+    pub step_delta: Duration,
+    paused: bool,
+    steps: usize,
+    frame: u64,
+}
+
+impl Default for FrameStep {
+    fn default() -> Self {
+        Self::new(FrameStepMode::default())
+    }
+}
+
+impl FrameStep {
+    /// Creates a running app with the given mode, stepping by 1/60 of a second.
+    pub fn new(mode: FrameStepMode) -> Self {
+        Self {
+            mode,
+            step_delta: Duration::from_secs_f64(1. / 60.),
+            paused: false,
+            steps: 0,
+            frame: 0,
+        }
+    }
+
+    /// Returns true while the app is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// The number of frames in which the [`Update`] schedule has run.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resumes the app, dropping any steps that have not run yet.
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.steps = 0;
+    }
+
+    pub fn toggle(&mut self) {
+        if self.paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    /// Pauses the app and runs one more frame.
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.steps += 1;
+    }
+}
+
+/// The keys that pause and step the app.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameStepKeys {
+    /// Pauses and resumes the app. Defaults to `F9`.
+    pub pause: KeyBinding,
+    /// Runs a single frame. Defaults to `F10`.
+    pub step: KeyBinding,
+}
+
+impl Default for FrameStepKeys {
+    fn default() -> Self {
+        Self {
+            pause: KeyBinding::new(KeyCode::F(9)),
+            step: KeyBinding::new(KeyCode::F(10)),
+        }
+    }
+}
+
+/// The schedule that replaces the schedules between [`PreUpdate`] and [`Last`] in the main order.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct SteppedMain;
+
+/// The schedules run by [`SteppedMain`], in order.
+#[derive(Resource, Debug)]
+struct SteppedSchedules(Vec<Interned<dyn ScheduleLabel>>);
+
+fn frame_step_input_system(
+    mut keys: EventReader<KeyEvent>,
+    bindings: Res<FrameStepKeys>,
+    mut step: ResMut<FrameStep>,
+) {
+    for key in keys.read().filter(|key| key.kind != KeyEventKind::Release) {
+        if bindings.pause.matches(key) {
+            step.toggle();
+        } else if bindings.step.matches(key) {
+            step.step();
+        }
+    }
+}
+
+fn run_stepped_schedules(world: &mut World) {
+    let (paused, stepping, mode, delta) = {
+        let mut step = world.resource_mut::<FrameStep>();
+        let stepping = step.paused && step.steps > 0;
+        if stepping {
+            step.steps -= 1;
+        }
+        if !step.paused || stepping {
+            step.frame += 1;
+        }
+        (step.paused, stepping, step.mode, step.step_delta)
+    };
+
+    if let Some(mut virtual_time) = world.get_resource_mut::<Time<Virtual>>() {
+        match (paused, virtual_time.is_paused()) {
+            (true, false) => virtual_time.pause(),
+            (false, true) => virtual_time.unpause(),
+            _ => {}
+        }
+        if stepping {
+            virtual_time.advance_by(delta);
+            let time = virtual_time.as_generic();
+            world.insert_resource(time);
+        }
+    }
+
+    let Some(labels) = world
+        .get_resource::<SteppedSchedules>()
+        .map(|schedules| schedules.0.clone())
+    else {
+        return;
+    };
+    for label in labels {
+        let runs = match mode {
+            FrameStepMode::All => !paused || stepping,
+            FrameStepMode::UpdateOnly => label != Update.intern() || !paused || stepping,
+        };
+        if runs {
+            let _ = world.try_run_schedule(label);
+        }
+    }
+}
+
+/// Draws the `PAUSED (frame N)` indicator over the last frame.
+pub fn draw_paused_indicator(
+    mut context: ResMut<RatatuiContext>,
+    step: Res<FrameStep>,
+) -> Result<()> {
+    let last_frame = context.last_frame().clone();
+    context.draw(|frame| {
+        let area = frame.area();
+        if last_frame.area == area {
+            *frame.buffer_mut() = last_frame;
+        }
+        let text = format!(" PAUSED (frame {}) ", step.frame());
+        let width = (text.len() as u16).min(area.width);
+        let indicator = Rect::new(area.right() - width, area.y, width, 1.min(area.height));
+        frame.render_widget(
+            Line::from(text).style(Style::new().add_modifier(Modifier::REVERSED)),
+            indicator,
+        );
+    })?;
+    Ok(())
+}
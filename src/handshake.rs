@@ -0,0 +1,396 @@
+//! Startup capability queries that do not block the first frame.
+//!
+//! Some terminal features can only be detected by writing a query and waiting for the terminal to
+//! answer. Terminals that don't understand the query never answer, so waiting for it on the main
+//! thread can stall startup, and the queries read from stdin, so input can't be read while they
+//! run.
+//!
+//! [`HandshakePlugin`] runs the queries on a background thread when the terminal is set up. The
+//! app keeps running and drawing meanwhile, and terminal input is read once the queries are done.
+//! A [`TerminalReady`] event is sent with the answers as soon as they arrive, or with default
+//! [`TerminalCapabilities`] if they have not arrived within the [`HandshakeTimeout`]. Terminal
+//! input is read from then on, and answers that arrive after the timeout are ignored.
+//!
+//! The queries are the [kitty keyboard protocol](crate::kitty) query, the default foreground and
+//! background colors (OSC 10 and 11), the size of the window and of a cell in pixels (`CSI 14 t`
+//! and `CSI 16 t`) and, on unix with a fullscreen viewport, the cursor position that the shell is
+//! returned to on exit, see [`RatatuiContext::start_position`]. The cursor position query is
+//! written just before the alternate screen is entered, and its answer read here. Whatever a
+//! terminal does not answer is left at its default in the [`TerminalCapabilities`].
+//!
+//! Keys pressed while the queries run are read along with the answers. They are kept and sent as
+//! terminal events once the terminal is ready, in the order they were pressed.
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::handshake::TerminalReady;
+//!
+//! fn on_ready(mut ready: EventReader<TerminalReady>) {
+//!     for ready in ready.read() {
+//!         info!("terminal ready: {:?}", ready.capabilities);
+//!     }
+//! }
+//! ```
+#[cfg(unix)]
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Read, Write},
+    os::fd::AsRawFd,
+};
+use std::{
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use crossterm::event::Event;
+use ratatui::{
+    layout::{Position, Size},
+    style::Color,
+};
+
+use crate::{
+    event::InputSet,
+    remote_input::parse_input,
+    terminal::{
+        is_headless, output_target, take_start_position_request, OutputTarget, RatatuiContext,
+        TerminalSet, TerminalStartup,
+    },
+};
+
+/// Queries the terminal's capabilities in the background and sends [`TerminalReady`].
+///
+/// This is part of [`RatatuiPlugins`](crate::RatatuiPlugins). No queries are made when the
+/// terminal is [headless](crate::terminal::HeadlessTerminal), set up with termwiz, or drawn to
+/// through another stream than stdout, which may not be the terminal that the queries are written
+/// to. The event is then sent with the default capabilities on the first frame.
+pub struct HandshakePlugin;
+
+impl Plugin for HandshakePlugin {
+    fn build(&self, app: &mut App) {
+        let start = start_handshake
+            .run_if(not(is_headless))
+            .run_if(|| output_target() == OutputTarget::Stdout)
+            .in_set(TerminalSet::Features);
+        // Reading the answers would take termwiz's input.
+        #[cfg(feature = "termwiz")]
+        let start = start.run_if(not(crate::termwiz::is_termwiz));
+        app.init_resource::<HandshakeTimeout>()
+            .init_resource::<HandshakeInput>()
+            .add_event::<TerminalReady>()
+            .add_systems(TerminalStartup, start)
+            .add_systems(PreUpdate, poll_handshake.in_set(InputSet::Pre));
+    }
+}
+
+/// How long to wait for the terminal to answer the startup queries. Defaults to 200 milliseconds.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct HandshakeTimeout(pub Duration);
+
+impl Default for HandshakeTimeout {
+    fn default() -> Self {
+        Self(Duration::from_millis(200))
+    }
+}
+
+/// What the terminal reported during the startup handshake.
+///
+/// Inserted as a resource when [`TerminalReady`] is sent.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalCapabilities {
+    /// The terminal supports the kitty keyboard protocol.
+    pub keyboard_enhancement: bool,
+    /// Where the cursor was in the shell before the alternate screen was entered.
+    pub start_position: Option<Position>,
+    /// The terminal's default text color.
+    pub foreground: Option<Color>,
+    /// The terminal's default background color.
+    pub background: Option<Color>,
+    /// The size of the terminal window in pixels.
+    pub window_pixels: Option<Size>,
+    /// The size of a cell in pixels.
+    pub cell_pixels: Option<Size>,
+}
+
+/// Sent once when the startup handshake has finished or timed out.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalReady {
+    pub capabilities: TerminalCapabilities,
+    /// The terminal did not answer within the [`HandshakeTimeout`], so `capabilities` are the
+    /// defaults.
+    pub timed_out: bool,
+}
+
+/// Present while the startup queries are running.
+#[derive(Resource, Debug)]
+pub struct Handshake {
+    started: Instant,
+    thread: Option<JoinHandle<Answers>>,
+}
+
+/// The answers to the startup queries, and the input that was read along with them.
+type Answers = (TerminalCapabilities, Vec<u8>);
+
+/// The terminal events that were read while the startup queries ran, which are sent before any
+/// that are read afterwards.
+#[derive(Resource, Debug, Default)]
+pub struct HandshakeInput(pub(crate) Vec<Event>);
+
+/// A run condition that is true once the startup queries are no longer reading from stdin.
+pub fn handshake_finished(handshake: Option<Res<Handshake>>) -> bool {
+    handshake.is_none()
+}
+
+fn start_handshake(mut commands: Commands, timeout: Res<HandshakeTimeout>) {
+    let started = Instant::now();
+    let deadline = started + timeout.0;
+    let read_start_position = take_start_position_request();
+    let thread = thread::spawn(move || query_terminal(read_start_position, deadline));
+    commands.insert_resource(Handshake {
+        started,
+        thread: Some(thread),
+    });
+}
+
+/// Makes the queries and reads the answers from the terminal until `deadline`.
+///
+/// The answers are read here rather than by crossterm, which would keep its reader, and with it
+/// terminal input, to itself for up to two seconds if the terminal does not answer. The terminal
+/// is read a byte at a time, so that nothing after the answers is taken from crossterm. Anything
+/// read that is not an answer, such as keys pressed meanwhile, is returned along with them.
+#[cfg(unix)]
+fn query_terminal(read_start_position: bool, deadline: Instant) -> Answers {
+    let mut capabilities = TerminalCapabilities::default();
+    let mut input = Vec::new();
+    let Ok(mut tty) = OpenOptions::new().read(true).write(true).open("/dev/tty") else {
+        return (capabilities, input);
+    };
+    // `ESC [ ? u` asks for the kitty keyboard flags, which only terminals that support the
+    // protocol answer, `ESC ] 10 ; ?` and `ESC ] 11 ; ?` for the default colors, `ESC [ 14 t` and
+    // `ESC [ 16 t` for the window and cell sizes in pixels, and `ESC [ c` for the primary device
+    // attributes, which every terminal answers. Terminals answer in order, so once the device
+    // attributes arrive, the queries before them that are not answered never will be.
+    if tty
+        .write_all(b"\x1b[?u\x1b]10;?\x1b\\\x1b]11;?\x1b\\\x1b[14t\x1b[16t\x1b[c")
+        .and_then(|()| tty.flush())
+        .is_err()
+    {
+        return (capabilities, input);
+    }
+    // The cursor position query may be answered before or after these, depending on whether the
+    // setup was written to the terminal yet.
+    let mut awaiting_position = read_start_position;
+    let mut awaiting_attributes = true;
+    while awaiting_position || awaiting_attributes {
+        let Some(sequence) = read_answer(&mut tty, deadline, &mut input) else {
+            break;
+        };
+        match &sequence[1..] {
+            [b'[', b'?', .., b'u'] => capabilities.keyboard_enhancement = true,
+            [b'[', b'?', .., b'c'] => awaiting_attributes = false,
+            [b'[', b'4', b';', parameters @ .., b't'] => {
+                capabilities.window_pixels = parse_pixel_size(parameters);
+            }
+            [b'[', b'6', b';', parameters @ .., b't'] => {
+                capabilities.cell_pixels = parse_pixel_size(parameters);
+            }
+            [b'[', parameters @ .., b'R'] if awaiting_position => {
+                awaiting_position = false;
+                capabilities.start_position = parse_cursor_position(parameters);
+            }
+            [b']', b'1', b'0', b';', color @ ..] => capabilities.foreground = parse_color(color),
+            [b']', b'1', b'1', b';', color @ ..] => capabilities.background = parse_color(color),
+            // A key, such as an arrow key, that was pressed while waiting.
+            _ => input.extend(sequence),
+        }
+    }
+    (capabilities, input)
+}
+
+#[cfg(not(unix))]
+fn query_terminal(_read_start_position: bool, _deadline: Instant) -> Answers {
+    let capabilities = TerminalCapabilities {
+        keyboard_enhancement: crossterm::terminal::supports_keyboard_enhancement().unwrap_or(false),
+        ..default()
+    };
+    (capabilities, Vec::new())
+}
+
+/// Reads the next control sequence, `ESC [ ... final` or `ESC ] ... ST`, from the terminal, or
+/// `None` if none has arrived by `deadline`. The bytes before it are added to `input`.
+#[cfg(unix)]
+fn read_answer(tty: &mut File, deadline: Instant, input: &mut Vec<u8>) -> Option<Vec<u8>> {
+    let mut sequence = Vec::new();
+    loop {
+        let Some(timeout) = deadline.checked_duration_since(Instant::now()) else {
+            input.append(&mut sequence);
+            return None;
+        };
+        let mut fds = libc::pollfd {
+            fd: tty.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let timeout = libc::c_int::try_from(timeout.as_millis()).unwrap_or(libc::c_int::MAX);
+        // SAFETY: `fds` is a single valid pollfd, and the descriptor stays open while polling.
+        let polled = unsafe { libc::poll(&mut fds, 1, timeout) };
+        if polled < 0 && io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+            continue;
+        }
+        let mut byte = [0];
+        if polled <= 0 || !matches!(tty.read(&mut byte), Ok(1)) {
+            input.append(&mut sequence);
+            return None;
+        }
+        if read_byte(&mut sequence, input, byte[0]) {
+            return Some(sequence);
+        }
+    }
+}
+
+/// Adds a byte to the control sequence that is being read, and returns whether it is complete.
+/// Bytes that turn out not to be part of one are moved to `input`.
+#[cfg(unix)]
+fn read_byte(sequence: &mut Vec<u8>, input: &mut Vec<u8>, byte: u8) -> bool {
+    let escaped = sequence.last() == Some(&0x1b);
+    match (sequence.get(1), byte) {
+        // An OSC sequence ends in BEL or in ST, `ESC \`.
+        (Some(b']'), 0x07) => {
+            sequence.push(byte);
+            true
+        }
+        (Some(b']'), b'\\') if escaped => {
+            sequence.push(byte);
+            true
+        }
+        (Some(b']'), _) if escaped => {
+            // The escape started another sequence rather than ending this one.
+            sequence.pop();
+            input.append(sequence);
+            sequence.push(0x1b);
+            read_byte(sequence, input, byte)
+        }
+        (Some(b']'), _) | (Some(b'['), 0x20..=0x3f) => {
+            sequence.push(byte);
+            false
+        }
+        (Some(b'['), 0x40..=0x7e) => {
+            sequence.push(byte);
+            true
+        }
+        (None, b'[' | b']') if escaped => {
+            sequence.push(byte);
+            false
+        }
+        // Anything else ends what was read so far: a lone escape is the escape key, and an escape
+        // followed by another key is that key with Alt.
+        _ => {
+            input.append(sequence);
+            if byte == 0x1b {
+                sequence.push(byte);
+            } else {
+                input.push(byte);
+            }
+            false
+        }
+    }
+}
+
+/// Parses the `height ; width` of a pixel size report.
+#[cfg(unix)]
+fn parse_pixel_size(parameters: &[u8]) -> Option<Size> {
+    let parameters = std::str::from_utf8(parameters).ok()?;
+    let (height, width) = parameters.split_once(';')?;
+    Some(Size::new(width.parse().ok()?, height.parse().ok()?))
+}
+
+/// Parses the `rgb:RRRR/GGGG/BBBB` of a color report, which ends in BEL or ST. Each component has
+/// one to four hex digits.
+#[cfg(unix)]
+fn parse_color(color: &[u8]) -> Option<Color> {
+    let color = std::str::from_utf8(color).ok()?;
+    let color = color.trim_end_matches(['\x07', '\x1b', '\\']);
+    let mut components = color.strip_prefix("rgb:")?.split('/').map(|component| {
+        let value = u32::from_str_radix(component, 16).ok()?;
+        let max = 16u32.checked_pow(u32::try_from(component.len()).ok()?)? - 1;
+        u8::try_from(value * 255 / max).ok()
+    });
+    let (r, g, b) = (
+        components.next()??,
+        components.next()??,
+        components.next()??,
+    );
+    components.next().is_none().then_some(Color::Rgb(r, g, b))
+}
+
+/// Parses the `row ; column` of a cursor position report into a zero based position.
+#[cfg(unix)]
+fn parse_cursor_position(parameters: &[u8]) -> Option<Position> {
+    let parameters = std::str::from_utf8(parameters).ok()?;
+    let (row, column) = parameters.split_once(';')?;
+    let row = row.parse::<u16>().ok()?;
+    let column = column.parse::<u16>().ok()?;
+    Some(Position::new(
+        column.saturating_sub(1),
+        row.saturating_sub(1),
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn poll_handshake(
+    mut commands: Commands,
+    handshake: Option<ResMut<Handshake>>,
+    capabilities: Option<Res<TerminalCapabilities>>,
+    timeout: Res<HandshakeTimeout>,
+    mut ready: EventWriter<TerminalReady>,
+    context: Option<ResMut<RatatuiContext>>,
+    mut input: ResMut<HandshakeInput>,
+    mut late: Local<Option<JoinHandle<Answers>>>,
+) {
+    // The keys read by queries that timed out are still sent once the queries give up.
+    if late.as_ref().is_some_and(JoinHandle::is_finished) {
+        if let Some(Ok((_, bytes))) = late.take().map(JoinHandle::join) {
+            input.0.extend(parse_input(&bytes));
+        }
+    }
+    let already_ready = capabilities.is_some();
+    let (answer, timed_out) = match handshake {
+        None => (TerminalCapabilities::default(), false),
+        Some(mut handshake)
+            if handshake
+                .thread
+                .as_ref()
+                .is_some_and(JoinHandle::is_finished) =>
+        {
+            let answer = handshake.thread.take().map(JoinHandle::join);
+            commands.remove_resource::<Handshake>();
+            // A panicking query counts as no answer.
+            let (answer, bytes) = answer.and_then(Result::ok).unwrap_or_default();
+            input.0.extend(parse_input(&bytes));
+            (answer, false)
+        }
+        Some(mut handshake) => {
+            if handshake.started.elapsed() < timeout.0 {
+                return;
+            }
+            warn!("the terminal did not answer the capability queries in time, using defaults");
+            // The queries are left to give up on their own, without holding up terminal input
+            // any longer. While they hold crossterm's reader, polling for input finds nothing
+            // rather than taking their answers.
+            *late = handshake.thread.take();
+            commands.remove_resource::<Handshake>();
+            (TerminalCapabilities::default(), true)
+        }
+    };
+    if already_ready {
+        return;
+    }
+    if let (Some(position), Some(mut context)) = (answer.start_position, context) {
+        context.set_start_position(position);
+    }
+    commands.insert_resource(answer);
+    ready.send(TerminalReady {
+        capabilities: answer,
+        timed_out,
+    });
+}
@@ -0,0 +1,433 @@
+//! Heatmaps of 2D data.
+//!
+//! A [`Heatmap`] entity draws a grid of `f32` values from a resource into its [`AnchoredArea`],
+//! with each value colored by where it falls between the smallest and largest values, and a legend
+//! below showing the range. Each terminal cell shows two values, one above the other, with a half
+//! block character, and the grid is scaled to fit the area. The heatmap only asks for a redraw
+//! when the resource or the component changes, so with the
+//! [`RedrawOnDemandPlugin`](crate::redraw::RedrawOnDemandPlugin) a simulation that updates its
+//! grid ten times a second is drawn ten times a second.
+//!
+//! [`HeatmapGrid`] is a ready-made resource for the values. Other resources can be drawn by
+//! implementing [`HeatmapSource`] for them and adding a [`HeatmapPlugin`] for each.
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     anchor::AnchoredArea,
+//!     color::ColorLevel,
+//!     heatmap::{Heatmap, HeatmapGrid, HeatmapPlugin},
+//!     terminal::RatatuiContext,
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::layout::Rect;
+//!
+//! let mut app = App::new();
+//! app.add_plugins((
+//!     RatatuiPlugins {
+//!         headless: true,
+//!         ..default()
+//!     },
+//!     HeatmapPlugin::<HeatmapGrid>::default(),
+//! ))
+//! // Without colors, values are shaded with block characters.
+//! .insert_resource(ColorLevel::NoColor)
+//! .insert_resource(HeatmapGrid::from_values(UVec2::new(3, 1), vec![0.0, 0.5, 1.0]));
+//! app.world_mut().spawn((
+//!     Heatmap::<HeatmapGrid>::default().with_legend(false),
+//!     AnchoredArea(Rect::new(0, 0, 3, 1)),
+//! ));
+//! app.update();
+//! let context = app.world().resource::<RatatuiContext>();
+//! assert!(context.screen_lines()[0].starts_with(" ▒█"));
+//! ```
+//!
+//! Colors are interpolated in RGB between the stops of the [`HeatmapGradient`]. On terminals
+//! without true color, a [`ColorLevel`] resource converts them to the nearest colors of the
+//! palette, and at [`ColorLevel::NoColor`] the values are shaded with `░▒▓█` instead.
+use std::{marker::PhantomData, ops::RangeInclusive};
+
+use bevy::prelude::*;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::{
+    anchor::AnchoredArea,
+    color::{to_rgb, ColorLevel},
+    redraw::RedrawRequested,
+    render::{RenderBuffer, RenderPlugin, RenderSet},
+    widget::{draw_root_widget, WidgetVisibility},
+};
+
+/// The characters that shade values from low to high at [`ColorLevel::NoColor`].
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+/// A plugin that draws the [`Heatmap<R>`]s of the resource `R`.
+///
+/// This requires the [`RenderPlugin`], and adds it if it is missing.
+pub struct HeatmapPlugin<R>(PhantomData<R>);
+
+impl<R> Default for HeatmapPlugin<R> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<R: HeatmapSource> Plugin for HeatmapPlugin<R> {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RenderPlugin>() {
+            app.add_plugins(RenderPlugin);
+        }
+        app.add_systems(
+            PostUpdate,
+            (
+                request_heatmap_redraw::<R>.before(RenderSet::Background),
+                draw_heatmaps::<R>
+                    .after(draw_root_widget)
+                    .in_set(RenderSet::Widgets),
+            ),
+        );
+    }
+}
+
+/// A resource holding a 2D grid of values that a [`Heatmap`] can draw.
+pub trait HeatmapSource: Resource {
+    /// The width and height of the grid.
+    fn size(&self) -> UVec2;
+
+    /// The value at `cell`, which is within the size. NaN values are left blank.
+    fn value(&self, cell: UVec2) -> f32;
+}
+
+/// A grid of values stored row by row.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct HeatmapGrid {
+    size: UVec2,
+    values: Vec<f32>,
+}
+
+impl HeatmapGrid {
+    /// Creates a grid of `size` filled with zeros.
+    pub fn new(size: UVec2) -> Self {
+        Self::from_values(size, vec![0.0; (size.x * size.y) as usize])
+    }
+
+    /// Creates a grid of `size` from its values, row by row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are not `size.x * size.y` values.
+    pub fn from_values(size: UVec2, values: Vec<f32>) -> Self {
+        assert_eq!(
+            values.len(),
+            (size.x * size.y) as usize,
+            "a {}x{} grid needs {} values",
+            size.x,
+            size.y,
+            size.x * size.y
+        );
+        Self { size, values }
+    }
+
+    /// The value at `cell`, or `None` if it is outside the grid.
+    pub fn get(&self, cell: UVec2) -> Option<f32> {
+        self.index(cell).map(|index| self.values[index])
+    }
+
+    /// Sets the value at `cell`. Cells outside the grid are ignored.
+    pub fn set(&mut self, cell: UVec2, value: f32) {
+        if let Some(index) = self.index(cell) {
+            self.values[index] = value;
+        }
+    }
+
+    /// The values, row by row.
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+
+    /// The values, row by row, to change in place.
+    pub fn values_mut(&mut self) -> &mut [f32] {
+        &mut self.values
+    }
+
+    fn index(&self, cell: UVec2) -> Option<usize> {
+        (cell.x < self.size.x && cell.y < self.size.y)
+            .then(|| (cell.y * self.size.x + cell.x) as usize)
+    }
+}
+
+impl HeatmapSource for HeatmapGrid {
+    fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    fn value(&self, cell: UVec2) -> f32 {
+        self.get(cell).unwrap_or(f32::NAN)
+    }
+}
+
+/// The colors that values are mapped to, from the lowest to the highest value.
+///
+/// The default runs from dark blue through cyan, green and yellow to red.
+#[derive(Debug, Clone, PartialEq, Eq, Deref)]
+pub struct HeatmapGradient(Vec<Color>);
+
+impl Default for HeatmapGradient {
+    fn default() -> Self {
+        Self(vec![
+            Color::Rgb(0, 0, 128),
+            Color::Rgb(0, 192, 255),
+            Color::Rgb(0, 200, 0),
+            Color::Rgb(255, 220, 0),
+            Color::Rgb(220, 0, 0),
+        ])
+    }
+}
+
+impl HeatmapGradient {
+    /// Creates a gradient that runs through `stops`, spaced evenly. Colors without an RGB value,
+    /// such as [`Color::Reset`], are not blended.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no stops.
+    pub fn new(stops: impl IntoIterator<Item = Color>) -> Self {
+        let stops: Vec<_> = stops.into_iter().collect();
+        assert!(!stops.is_empty(), "a gradient needs at least one color");
+        Self(stops)
+    }
+
+    /// The color at `t`, between `0.0` for the first stop and `1.0` for the last.
+    ///
+    /// ```rust
+    /// use bevy_ratatui::heatmap::HeatmapGradient;
+    /// use ratatui::style::Color;
+    ///
+    /// let gradient = HeatmapGradient::new([Color::Rgb(0, 0, 0), Color::Rgb(200, 100, 0)]);
+    /// assert_eq!(gradient.color(0.5), Color::Rgb(100, 50, 0));
+    /// ```
+    pub fn color(&self, t: f32) -> Color {
+        let position = t.clamp(0.0, 1.0) * (self.0.len() - 1) as f32;
+        let index = (position.floor() as usize).min(self.0.len() - 1);
+        let (from, to) = (self.0[index], self.0[(index + 1).min(self.0.len() - 1)]);
+        let t = position - index as f32;
+        match (to_rgb(from), to_rgb(to)) {
+            (Some((r1, g1, b1)), Some((r2, g2, b2))) => {
+                let blend =
+                    |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+                Color::Rgb(blend(r1, r2), blend(g1, g2), blend(b1, b2))
+            }
+            _ if t < 0.5 => from,
+            _ => to,
+        }
+    }
+}
+
+/// A widget entity that draws the grid of the resource `R` as a heatmap in its [`AnchoredArea`].
+///
+/// Drawn by the [`HeatmapPlugin<R>`].
+#[derive(Component, Debug, Clone, PartialEq)]
+#[require(AnchoredArea)]
+pub struct Heatmap<R: HeatmapSource> {
+    /// The colors that values are mapped to.
+    pub gradient: HeatmapGradient,
+    /// The values mapped to the ends of the gradient, or `None` to use the smallest and largest
+    /// values in the grid. Values outside the range are clamped to it.
+    pub range: Option<RangeInclusive<f32>>,
+    /// Whether the bottom line of the area shows the range and the gradient.
+    pub legend: bool,
+    source: PhantomData<fn() -> R>,
+}
+
+impl<R: HeatmapSource> Default for Heatmap<R> {
+    fn default() -> Self {
+        Self {
+            gradient: HeatmapGradient::default(),
+            range: None,
+            legend: true,
+            source: PhantomData,
+        }
+    }
+}
+
+impl<R: HeatmapSource> Heatmap<R> {
+    /// Maps the values in `range` to the ends of the gradient, rather than the smallest and
+    /// largest values in the grid.
+    pub fn with_range(mut self, range: RangeInclusive<f32>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    pub fn with_gradient(mut self, gradient: HeatmapGradient) -> Self {
+        self.gradient = gradient;
+        self
+    }
+
+    /// Sets whether the bottom line of the area shows the range and the gradient.
+    pub fn with_legend(mut self, legend: bool) -> Self {
+        self.legend = legend;
+        self
+    }
+
+    fn render(&self, data: &R, area: Rect, buf: &mut Buffer, shaded: bool) {
+        let size = data.size();
+        if area.is_empty() || size.x == 0 || size.y == 0 {
+            return;
+        }
+        let range = self.range.clone().unwrap_or_else(|| value_range(data));
+        let scale = |value: f32| {
+            let span = range.end() - range.start();
+            if span > 0.0 {
+                (value - range.start()) / span
+            } else {
+                0.0
+            }
+        };
+        let (grid, legend) = if self.legend && area.height > 1 {
+            let grid = Rect {
+                height: area.height - 1,
+                ..area
+            };
+            (
+                grid,
+                Some(Rect {
+                    y: grid.bottom(),
+                    height: 1,
+                    ..area
+                }),
+            )
+        } else {
+            (area, None)
+        };
+        // Each cell shows two rows of values with a half block, or one when shaded.
+        let rows_per_cell = if shaded { 1 } else { 2 };
+        let rows = u32::from(grid.height) * rows_per_cell;
+        let sample = |column: u16, row: u32| {
+            let x = u32::from(column - grid.x) * size.x / u32::from(grid.width);
+            let y = row * size.y / rows;
+            data.value(UVec2::new(x, y))
+        };
+        for position in grid.positions() {
+            let row = u32::from(position.y - grid.y) * rows_per_cell;
+            let Some(cell) = buf.cell_mut(position) else {
+                continue;
+            };
+            if shaded {
+                let value = sample(position.x, row);
+                if !value.is_nan() {
+                    cell.set_char(shade(scale(value)));
+                }
+                continue;
+            }
+            let color = |value: f32| {
+                if value.is_nan() {
+                    Color::Reset
+                } else {
+                    self.gradient.color(scale(value))
+                }
+            };
+            let (top, bottom) = (sample(position.x, row), sample(position.x, row + 1));
+            cell.set_char('▀').set_fg(color(top)).set_bg(color(bottom));
+        }
+        if let Some(legend) = legend {
+            self.render_legend(&range, legend, buf, shaded);
+        }
+    }
+
+    /// Renders the lowest value, the gradient and the highest value on one line.
+    fn render_legend(
+        &self,
+        range: &RangeInclusive<f32>,
+        area: Rect,
+        buf: &mut Buffer,
+        shaded: bool,
+    ) {
+        let (low, high) = (format_value(*range.start()), format_value(*range.end()));
+        let labels = (low.len() + high.len() + 2) as u16;
+        let width = area.width.saturating_sub(labels);
+        let bar = (0..width).map(|i| {
+            let t = if width > 1 {
+                f32::from(i) / f32::from(width - 1)
+            } else {
+                0.5
+            };
+            if shaded {
+                Span::raw(shade(t).to_string())
+            } else {
+                Span::styled(" ", Style::new().bg(self.gradient.color(t)))
+            }
+        });
+        let spans = [Span::raw(format!("{low} "))]
+            .into_iter()
+            .chain(bar)
+            .chain([Span::raw(format!(" {high}"))]);
+        Line::from_iter(spans).render(area, buf);
+    }
+}
+
+/// The smallest and largest values in the grid, ignoring NaN values.
+fn value_range(data: &impl HeatmapSource) -> RangeInclusive<f32> {
+    let size = data.size();
+    let values = (0..size.y)
+        .flat_map(|y| (0..size.x).map(move |x| UVec2::new(x, y)))
+        .map(|cell| data.value(cell))
+        .filter(|value| !value.is_nan());
+    let (low, high) = values.fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), value| {
+        (low.min(value), high.max(value))
+    });
+    if low > high {
+        0.0..=0.0
+    } else {
+        low..=high
+    }
+}
+
+fn shade(t: f32) -> char {
+    SHADES[(t.clamp(0.0, 1.0) * (SHADES.len() - 1) as f32).round() as usize]
+}
+
+/// Formats a legend value with at most two decimals, e.g. `0.25` or `100`.
+fn format_value(value: f32) -> String {
+    let text = format!("{value:.2}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Requests a redraw when the resource or any of its heatmaps has changed.
+fn request_heatmap_redraw<R: HeatmapSource>(
+    data: Option<Res<R>>,
+    heatmaps: Query<Ref<Heatmap<R>>>,
+    redraw: Option<ResMut<RedrawRequested>>,
+) {
+    let Some(mut redraw) = redraw else {
+        return;
+    };
+    let data_changed = data.is_some_and(|data| data.is_changed());
+    if data_changed || heatmaps.iter().any(|heatmap| heatmap.is_changed()) {
+        redraw.request();
+    }
+}
+
+fn draw_heatmaps<R: HeatmapSource>(
+    mut buffer: ResMut<RenderBuffer>,
+    data: Option<Res<R>>,
+    heatmaps: Query<(&Heatmap<R>, &AnchoredArea, Option<&WidgetVisibility>)>,
+    color_level: Option<Res<ColorLevel>>,
+) {
+    let Some(data) = data else {
+        return;
+    };
+    let shaded = color_level.is_some_and(|level| *level == ColorLevel::NoColor);
+    let bounds = buffer.area;
+    for (heatmap, area, visibility) in &heatmaps {
+        if !visibility.is_none_or(|visibility| visibility.is_visible()) {
+            continue;
+        }
+        heatmap.render(&data, area.intersection(bounds), &mut buffer, shaded);
+    }
+}
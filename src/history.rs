@@ -0,0 +1,255 @@
+//! A history of recently drawn frames for debugging.
+//!
+//! [`BufferHistoryPlugin`] keeps the most recent frames drawn with
+//! [`RatatuiContext::draw`](crate::terminal::RatatuiContext::draw) in a [`BufferHistory`], skipping
+//! frames that are the same as the one before. Pressing the [`BufferHistoryKey`] (`F3` by default)
+//! opens a viewer that shows the recorded frames, so that a glitch that was only on screen for a
+//! moment can be inspected:
+//!
+//! - `Left`/`h` and `Right`/`l` show an older and a newer frame.
+//! - `Home` and `End` jump to the oldest and newest frames.
+//! - `Esc`, `q` or the toggle key close the viewer.
+//!
+//! While the viewer is open, no frames are recorded, the
+//! [`RootWidget`](crate::widget::RootWidget) is not drawn and the
+//! [`QuitPlugin`](crate::quit::QuitPlugin) does not quit. Add `not(history_open)` to your own draw
+//! systems to pause them too.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     history::{BufferHistory, BufferHistoryPlugin},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), BufferHistoryPlugin))
+//!     .insert_resource(BufferHistory::new(120));
+//! ```
+use std::collections::VecDeque;
+
+use bevy::{core::FrameCount, prelude::*};
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEventKind};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+};
+
+use crate::{
+    error::exit_on_error,
+    event::{InputSet, KeyEvent},
+    quit::{self, KeyBinding},
+    terminal::RatatuiContext,
+};
+
+/// A plugin that records the drawn frames in a [`BufferHistory`] and adds a viewer for them.
+pub struct BufferHistoryPlugin;
+
+impl Plugin for BufferHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BufferHistory>()
+            .init_resource::<BufferHistoryKey>()
+            .add_systems(
+                PreUpdate,
+                history_input_system
+                    .in_set(InputSet::Post)
+                    .after(quit::quit_system),
+            )
+            .add_systems(
+                Last,
+                (
+                    record_frame.run_if(not(history_open)),
+                    draw_history.pipe(exit_on_error).run_if(history_open),
+                )
+                    .chain()
+                    .run_if(resource_exists::<RatatuiContext>),
+            );
+    }
+}
+
+/// A run condition that is true while the history viewer is open.
+pub fn history_open(history: Option<Res<BufferHistory>>) -> bool {
+    history.is_some_and(|history| history.is_open())
+}
+
+/// The key that opens and closes the history viewer.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct BufferHistoryKey(pub KeyBinding);
+
+impl Default for BufferHistoryKey {
+    fn default() -> Self {
+        Self(KeyBinding::new(KeyCode::F(3)))
+    }
+}
+
+/// A frame recorded in the [`BufferHistory`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedFrame {
+    /// The [`FrameCount`] when the frame was recorded, or 0 without the `FrameCountPlugin`.
+    pub frame: u32,
+    pub buffer: Buffer,
+}
+
+/// The most recently drawn frames, oldest first.
+#[derive(Resource, Debug, Clone)]
+pub struct BufferHistory {
+    frames: VecDeque<RecordedFrame>,
+    capacity: usize,
+    /// The index of the frame shown in the viewer, if it is open.
+    viewing: Option<usize>,
+}
+
+impl Default for BufferHistory {
+    fn default() -> Self {
+        Self::new(60)
+    }
+}
+
+impl BufferHistory {
+    /// Creates an empty history that keeps up to `capacity` frames.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+            viewing: None,
+        }
+    }
+
+    /// The maximum number of frames kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The recorded frames, oldest first.
+    pub fn frames(&self) -> impl DoubleEndedIterator<Item = &RecordedFrame> {
+        self.frames.iter()
+    }
+
+    /// Records a frame, unless it is the same as the previous one.
+    ///
+    /// The oldest frame is dropped when the history is full.
+    pub fn record(&mut self, frame: u32, buffer: &Buffer) {
+        if self.capacity == 0
+            || self
+                .frames
+                .back()
+                .is_some_and(|last| last.buffer == *buffer)
+        {
+            return;
+        }
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(RecordedFrame {
+            frame,
+            buffer: buffer.clone(),
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.viewing = None;
+    }
+
+    /// Returns true while the viewer is open.
+    pub fn is_open(&self) -> bool {
+        self.viewing.is_some()
+    }
+
+    /// Opens the viewer at the newest frame. Does nothing if no frames have been recorded.
+    pub fn open(&mut self) {
+        self.viewing = self.frames.len().checked_sub(1);
+    }
+
+    pub fn close(&mut self) {
+        self.viewing = None;
+    }
+
+    /// The frame shown in the viewer and its index, if it is open.
+    pub fn viewed(&self) -> Option<(usize, &RecordedFrame)> {
+        let index = self.viewing?;
+        Some((index, self.frames.get(index)?))
+    }
+
+    /// Moves the viewer by `frames`, negative values going back in time.
+    pub fn step(&mut self, frames: isize) {
+        let last = self.frames.len().saturating_sub(1);
+        if let Some(index) = &mut self.viewing {
+            *index = index.saturating_add_signed(frames).min(last);
+        }
+    }
+
+    fn handle_key(&mut self, key: &crossterm::event::KeyEvent, toggle: KeyBinding) {
+        if toggle.matches(key) {
+            self.close();
+            return;
+        }
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') => self.step(-1),
+            KeyCode::Right | KeyCode::Char('l') => self.step(1),
+            KeyCode::Home => self.viewing = Some(0),
+            KeyCode::End => self.open(),
+            KeyCode::Esc | KeyCode::Char('q') => self.close(),
+            _ => {}
+        }
+    }
+}
+
+fn history_input_system(
+    mut keys: EventReader<KeyEvent>,
+    toggle: Res<BufferHistoryKey>,
+    mut history: ResMut<BufferHistory>,
+) {
+    for key in keys.read().filter(|key| key.kind != KeyEventKind::Release) {
+        if history.is_open() {
+            history.handle_key(key, **toggle);
+        } else if toggle.matches(key) {
+            history.open();
+        }
+    }
+}
+
+fn record_frame(
+    context: Res<RatatuiContext>,
+    frame: Option<Res<FrameCount>>,
+    mut history: ResMut<BufferHistory>,
+) {
+    let buffer = context.last_frame();
+    if buffer.area.is_empty() {
+        return;
+    }
+    history.record(frame.map_or(0, |frame| frame.0), buffer);
+}
+
+/// Draws the frame selected in the history viewer, with a status line at the bottom.
+pub fn draw_history(
+    mut context: ResMut<RatatuiContext>,
+    history: Res<BufferHistory>,
+) -> Result<()> {
+    let Some((index, recorded)) = history.viewed() else {
+        return Ok(());
+    };
+    context.draw(|frame| {
+        let area = frame.area();
+        let buffer = frame.buffer_mut();
+        let shown = recorded.buffer.area.intersection(area);
+        for position in shown.positions() {
+            buffer[position] = recorded.buffer[position].clone();
+        }
+        let status = format!(
+            " history {}/{} (frame {}) · ←/→ older/newer · Esc close ",
+            index + 1,
+            history.frames.len(),
+            recorded.frame
+        );
+        let row = Rect::new(area.x, area.bottom().saturating_sub(1), area.width, 1);
+        frame.render_widget(
+            Line::from(status).style(Style::new().add_modifier(Modifier::REVERSED)),
+            row.intersection(area),
+        );
+    })?;
+    Ok(())
+}
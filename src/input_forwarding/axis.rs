@@ -0,0 +1,140 @@
+//! A 2D axis from held keys
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use super::{hold::track_held_keys, HeldKeys, KeyHoldPlugin};
+use crate::event::InputSet;
+
+/// Turns held direction keys into a [KeyAxis] resource for game-like movement.
+///
+/// While a direction key is held its side of the axis ramps up over [KeyAxisSettings::ramp_up],
+/// using the hold durations of the [KeyHoldPlugin], which is added if it is not already. After
+/// the keys of an axis are released it decays back to zero over [KeyAxisSettings::decay].
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_ratatui::input_forwarding::*;
+/// # let mut app = App::new();
+/// app.add_plugins(KeyAxisPlugin);
+///
+/// fn movement(axis: Res<KeyAxis>, mut player: Single<&mut Transform>) {
+///     player.translation += axis.value.extend(0.0);
+/// }
+/// ```
+pub struct KeyAxisPlugin;
+
+impl Plugin for KeyAxisPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<KeyHoldPlugin>() {
+            app.add_plugins(KeyHoldPlugin);
+        }
+        app.init_resource::<KeyAxis>()
+            .init_resource::<KeyAxisSettings>()
+            .add_systems(
+                PreUpdate,
+                update_key_axis
+                    .in_set(InputSet::Post)
+                    .after(track_held_keys),
+            );
+    }
+}
+
+/// The keys of each direction and how the [KeyAxis] responds to them.
+///
+/// Defaults to the arrow keys and WASD, a quarter second ramp up, a tenth of a second decay and a
+/// dead zone of 0.1.
+#[derive(Debug, Clone, Resource)]
+pub struct KeyAxisSettings {
+    pub up: Vec<KeyCode>,
+    pub down: Vec<KeyCode>,
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+    /// How long a key must be held for its direction to reach full strength.
+    pub ramp_up: Duration,
+    /// How long a released axis takes to go from full strength back to zero.
+    pub decay: Duration,
+    /// Values shorter than this are reported as zero. Longer values are rescaled so that the
+    /// axis still starts from zero at the edge of the dead zone.
+    pub dead_zone: f32,
+}
+
+impl Default for KeyAxisSettings {
+    fn default() -> Self {
+        Self {
+            up: vec![KeyCode::ArrowUp, KeyCode::KeyW],
+            down: vec![KeyCode::ArrowDown, KeyCode::KeyS],
+            left: vec![KeyCode::ArrowLeft, KeyCode::KeyA],
+            right: vec![KeyCode::ArrowRight, KeyCode::KeyD],
+            ramp_up: Duration::from_millis(250),
+            decay: Duration::from_millis(100),
+            dead_zone: 0.1,
+        }
+    }
+}
+
+/// A 2D axis driven by the held direction keys.
+///
+/// `x` is positive to the right and `y` is positive downwards, like terminal cells. The length of
+/// the axis is at most 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Resource)]
+pub struct KeyAxis {
+    /// The axis after the dead zone is applied.
+    pub value: Vec2,
+    /// The axis before the dead zone is applied.
+    pub raw: Vec2,
+}
+
+fn update_key_axis(
+    held_keys: Res<HeldKeys>,
+    settings: Res<KeyAxisSettings>,
+    time: Res<Time<Real>>,
+    mut axis: ResMut<KeyAxis>,
+) {
+    let strength = |keys: &[KeyCode]| {
+        keys.iter()
+            .filter_map(|key| held_keys.duration(*key))
+            .map(|held| ramp(held, settings.ramp_up))
+            .fold(None, |max: Option<f32>, value| {
+                Some(max.map_or(value, |max| max.max(value)))
+            })
+    };
+    let decay_step = ramp(time.delta(), settings.decay);
+    let decay = |value: f32| value.signum() * (value.abs() - decay_step).max(0.0);
+    let axis_value =
+        |negative: Option<f32>, positive: Option<f32>, previous: f32| match (negative, positive) {
+            (None, None) => decay(previous),
+            (negative, positive) => positive.unwrap_or(0.0) - negative.unwrap_or(0.0),
+        };
+
+    let raw = Vec2::new(
+        axis_value(
+            strength(&settings.left),
+            strength(&settings.right),
+            axis.raw.x,
+        ),
+        axis_value(strength(&settings.up), strength(&settings.down), axis.raw.y),
+    )
+    .clamp_length_max(1.0);
+    let length = raw.length();
+    let dead_zone = settings.dead_zone.clamp(0.0, 0.99);
+    let value = if length <= dead_zone {
+        Vec2::ZERO
+    } else {
+        raw / length * ((length - dead_zone) / (1.0 - dead_zone))
+    };
+    let new_axis = KeyAxis { value, raw };
+    if *axis != new_axis {
+        *axis = new_axis;
+    }
+}
+
+/// The fraction of `full` that `elapsed` is, at most 1. A zero `full` is reached immediately.
+fn ramp(elapsed: Duration, full: Duration) -> f32 {
+    if full.is_zero() {
+        1.0
+    } else {
+        (elapsed.as_secs_f32() / full.as_secs_f32()).min(1.0)
+    }
+}
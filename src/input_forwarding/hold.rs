@@ -0,0 +1,147 @@
+//! Key hold durations
+
+use std::time::Duration;
+
+use bevy::{
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+    utils::HashMap,
+};
+
+use crate::event::InputSet;
+
+/// Tracks how long keys have been held down and emits [KeyHeld] and [KeyHoldThreshold] events.
+///
+/// This works on the bevy [KeyboardInput] events, so it requires the
+/// [KeyboardPlugin][crate::input_forwarding::KeyboardPlugin]. If the terminal does not report key
+/// releases, the emulated releases are used, so hold durations are only as accurate as the
+/// [ReleaseKey][crate::input_forwarding::ReleaseKey] setting allows.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use bevy::prelude::*;
+/// # use bevy_ratatui::input_forwarding::*;
+/// # let mut app = App::new();
+/// app.add_plugins(KeyHoldPlugin)
+///     .insert_resource(HoldThresholds(vec![Duration::from_millis(500)]));
+/// ```
+pub struct KeyHoldPlugin;
+
+impl Plugin for KeyHoldPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HeldKeys>()
+            .init_resource::<HoldThresholds>()
+            .add_event::<KeyHeld>()
+            .add_event::<KeyHoldThreshold>()
+            .add_systems(PreUpdate, track_held_keys.in_set(InputSet::Post));
+    }
+}
+
+/// Sent every frame for each key that is held down.
+#[derive(Debug, Clone, Copy, Event, PartialEq, Eq)]
+pub struct KeyHeld {
+    pub key: KeyCode,
+    /// How long the key has been held so far.
+    pub duration: Duration,
+}
+
+/// Sent once when a key has been held for at least one of the [HoldThresholds].
+#[derive(Debug, Clone, Copy, Event, PartialEq, Eq)]
+pub struct KeyHoldThreshold {
+    pub key: KeyCode,
+    pub threshold: Duration,
+}
+
+/// The hold durations that trigger a [KeyHoldThreshold] event.
+///
+/// Defaults to 500 milliseconds.
+#[derive(Debug, Clone, Resource, Deref, DerefMut)]
+pub struct HoldThresholds(pub Vec<Duration>);
+
+impl Default for HoldThresholds {
+    fn default() -> Self {
+        Self(vec![Duration::from_millis(500)])
+    }
+}
+
+/// The keys that are currently held down and since when.
+#[derive(Debug, Default, Resource)]
+pub struct HeldKeys {
+    /// When each held key was pressed, in [`Time<Real>`] elapsed time.
+    pressed_at: HashMap<KeyCode, Duration>,
+    /// How long each held key had been held as of the last update.
+    held: HashMap<KeyCode, Duration>,
+    /// Keys released this frame and the previous one, which are resumed rather than restarted if a
+    /// repeat press follows (repeats are forwarded as a release followed by a repeated press).
+    released: [HashMap<KeyCode, (Duration, Option<Duration>)>; 2],
+}
+
+impl HeldKeys {
+    /// How long the key has been held, or `None` if it is not held.
+    pub fn duration(&self, key: KeyCode) -> Option<Duration> {
+        self.held.get(&key).copied()
+    }
+
+    /// Iterates over the held keys and how long they have been held.
+    pub fn iter(&self) -> impl Iterator<Item = (KeyCode, Duration)> + '_ {
+        self.held.iter().map(|(key, duration)| (*key, *duration))
+    }
+}
+
+pub(super) fn track_held_keys(
+    mut keyboard_input: EventReader<KeyboardInput>,
+    mut held_keys: ResMut<HeldKeys>,
+    thresholds: Res<HoldThresholds>,
+    time: Res<Time<Real>>,
+    mut held_events: EventWriter<KeyHeld>,
+    mut threshold_events: EventWriter<KeyHoldThreshold>,
+) {
+    let now = time.elapsed();
+    let held_keys = &mut *held_keys;
+    held_keys.released.swap(0, 1);
+    held_keys.released[1].clear();
+    for event in keyboard_input.read() {
+        match event.state {
+            ButtonState::Pressed => {
+                let resumed = event
+                    .repeat
+                    .then(|| {
+                        held_keys.released[0]
+                            .remove(&event.key_code)
+                            .or_else(|| held_keys.released[1].remove(&event.key_code))
+                    })
+                    .flatten();
+                if let Some((pressed_at, held)) = resumed {
+                    held_keys.pressed_at.insert(event.key_code, pressed_at);
+                    if let Some(held) = held {
+                        held_keys.held.insert(event.key_code, held);
+                    }
+                } else {
+                    held_keys.pressed_at.entry(event.key_code).or_insert(now);
+                }
+            }
+            ButtonState::Released => {
+                let held = held_keys.held.remove(&event.key_code);
+                if let Some(pressed_at) = held_keys.pressed_at.remove(&event.key_code) {
+                    held_keys.released[1].insert(event.key_code, (pressed_at, held));
+                }
+            }
+        }
+    }
+    for (key, pressed_at) in &held_keys.pressed_at {
+        let duration = now.saturating_sub(*pressed_at);
+        let previous = held_keys.held.insert(*key, duration);
+        for threshold in thresholds.iter() {
+            if duration >= *threshold && previous.is_none_or(|previous| previous < *threshold) {
+                threshold_events.send(KeyHoldThreshold {
+                    key: *key,
+                    threshold: *threshold,
+                });
+            }
+        }
+        held_events.send(KeyHeld {
+            key: *key,
+            duration,
+        });
+    }
+}
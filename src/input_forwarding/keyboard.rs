@@ -12,7 +12,12 @@ use bevy::{
 };
 use crossterm::event::KeyModifiers;
 
-use crate::event::{InputSet, KeyEvent};
+use crate::{
+    event::{InputSet, KeyEvent},
+    terminal::TerminalSet,
+};
+
+use super::window::{spawn_terminal_window, TerminalWindow};
 
 bitflags::bitflags! {
     /// Crudely defines some capabilities of terminal. Useful for representing
@@ -88,7 +93,7 @@ impl Plugin for KeyboardPlugin {
             .init_resource::<Detected>()
             .init_resource::<EmulationPolicy>()
             .init_resource::<Emulate>()
-            .add_systems(Startup, setup_window)
+            .add_systems(Startup, spawn_terminal_window.in_set(TerminalSet::Features))
             .add_systems(
                 PreUpdate,
                 reset_emulation_check
@@ -108,6 +113,7 @@ impl Plugin for KeyboardPlugin {
                     send_key_events_with_emulation.run_if(resource_exists::<Emulate>),
                     send_key_events_no_emulation.run_if(not(resource_exists::<Emulate>)),
                 )
+                    .chain()
                     .in_set(InputSet::EmitBevy),
             );
     }
@@ -318,7 +324,7 @@ fn reset_emulation_check(mut commands: Commands) {
 #[allow(clippy::too_many_arguments)]
 fn send_key_events_with_emulation(
     mut keys: EventReader<KeyEvent>,
-    window: Query<Entity, With<DummyWindow>>,
+    window: Query<Entity, With<TerminalWindow>>,
     mut modifiers: Local<Modifiers>,
     mut last_pressed: Local<LastPress>,
     mut keyboard_input: EventWriter<KeyboardInput>,
@@ -411,7 +417,7 @@ fn send_key_events_with_emulation(
 /// when emulation is not involved.
 fn send_key_events_no_emulation(
     mut keys: EventReader<KeyEvent>,
-    window: Query<Entity, With<DummyWindow>>,
+    window: Query<Entity, With<TerminalWindow>>,
     mut keyboard_input: EventWriter<KeyboardInput>,
     mut key_repeat_queue: Local<Vec<KeyboardInput>>,
 ) {
@@ -433,15 +439,6 @@ fn send_key_events_no_emulation(
     }
 }
 
-#[derive(Debug, Component)]
-struct DummyWindow;
-
-/// This is a dummy window to satisfy the [KeyboardInput] struct.
-fn setup_window(mut commands: Commands) {
-    // Insert our window entity so that other parts of our app can use them.
-    commands.spawn(DummyWindow);
-}
-
 fn modifier_to_bevy(
     modifier: bevy::input::keyboard::Key,
     state: bevy::input::ButtonState,
@@ -492,8 +489,18 @@ fn key_event_to_bevy(
         }
         crossterm::event::KeyEventKind::Release => bevy::input::ButtonState::Released,
     };
-    let key_code = to_bevy_keycode(code);
     let logical_key = to_bevy_key(code);
+    let key_code = match (to_bevy_keycode(code), &logical_key) {
+        // The character is not on a US keyboard (e.g. `é` or `€`), so the physical key is
+        // unknown. Still forward it so that text input works.
+        (None, Some(bevy::input::keyboard::Key::Character(_))) => Some((
+            bevy::input::keyboard::KeyCode::Unidentified(
+                bevy::input::keyboard::NativeKeyCode::Unidentified,
+            ),
+            crossterm::event::KeyModifiers::empty(),
+        )),
+        (key_code, _) => key_code,
+    };
     key_code
         .zip(logical_key)
         .map(|((key_code, mods), logical_key)| {
@@ -846,6 +853,14 @@ fn to_bevy_keycode(
     .map(|key_code| (key_code, mods))
 }
 
+/// Converts a crossterm key code to a bevy logical key.
+///
+/// The logical key is the character reported by the terminal rather than one reconstructed from
+/// the physical key. When the kitty protocol reports alternate keys, crossterm replaces a shifted
+/// key with the character the layout produces (e.g. `@`).
+///
+/// Note: crossterm does not expose the kitty "associated text" field, so text that the layout
+/// produces in other ways, such as with AltGr or a dead key, is not reported.
 fn to_bevy_key(key_code: &crossterm::event::KeyCode) -> Option<bevy::input::keyboard::Key> {
     use bevy::input::keyboard::Key as b;
     use crossterm::event::KeyCode as c;
@@ -97,11 +97,53 @@
 //! There are other policies one can choose by configuring [ReleaseKey]. See its
 //! documentation for more details.
 //!
+//! ## Hold Durations
+//!
+//! Add the [KeyHoldPlugin] to track how long keys have been held. It sends
+//! [KeyHeld] events every frame a key is held and [KeyHoldThreshold] events
+//! when a key has been held for long enough to count as a long press.
+//!
+//! ## Analog Movement
+//!
+//! Add the [KeyAxisPlugin] to turn held arrow keys and WASD into a [KeyAxis],
+//! a 2D axis that ramps up while keys are held and decays after they are
+//! released, like a gamepad stick.
+//!
+//! ## Mouse
+//!
+//! Add the [MouseForwardingPlugin] to use `ButtonInput<MouseButton>` and the
+//! bevy [`MouseButtonInput`][bevy::input::mouse::MouseButtonInput] events for
+//! mouse buttons. It also turns terminal mouse moves into bevy
+//! [`CursorMoved`][bevy::window::CursorMoved] events and keep the
+//! [TerminalCursorPosition] resource up to date. Scrolling is sent as
+//! [`MouseWheel`][bevy::input::mouse::MouseWheel] events scaled by [WheelScale]. It is added by
+//! [RatatuiPlugins][crate::RatatuiPlugins] when both input forwarding and mouse
+//! capture are enabled.
+//!
+//! ## Window
+//!
+//! The [WindowForwardingPlugin] sends terminal focus changes as bevy
+//! [`WindowFocused`][bevy::window::WindowFocused] events, so that systems that
+//! pause when the window loses focus work in the terminal, and resizes as
+//! [`WindowResized`][bevy::window::WindowResized] events. All forwarded input is
+//! sent for the terminal window entity, which has a [TerminalWindow] with the
+//! terminal's size, pixel size, title and focus, and a
+//! [`Window`][bevy::window::Window] sized in terminal cells. It is added by
+//! [RatatuiPlugins][crate::RatatuiPlugins] with input forwarding.
+//!
 //! # Terminal Choice
 //!
 //! For the best experience, it is recommended to enable the kitty protocol on
 //! your terminal. [See
 //! here](https://sw.kovidgoyal.net/kitty/keyboard-protocol/) for a list of
 //! terminals implementing this protocol.
+mod axis;
+mod hold;
 mod keyboard;
+mod mouse;
+mod window;
+pub use axis::*;
+pub use hold::*;
 pub use keyboard::*;
+pub use mouse::*;
+pub use window::*;
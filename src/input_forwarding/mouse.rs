@@ -0,0 +1,189 @@
+//! Input forwarding for the mouse
+
+use bevy::{
+    input::{
+        mouse::{MouseButtonInput, MouseScrollUnit, MouseWheel},
+        ButtonState,
+    },
+    prelude::*,
+    utils::HashSet,
+    window::CursorMoved,
+};
+use crossterm::event::MouseEventKind;
+
+use super::window::TerminalWindow;
+use crate::event::{InputSet, MouseEvent};
+
+/// Forwards terminal mouse events to bevy: button presses as [MouseButtonInput] events, moves as
+/// [CursorMoved] events, and scrolling as [MouseWheel] events.
+///
+/// The button events update `ButtonInput<MouseButton>` before `Update` runs. A drag with a button
+/// that was not seen going down, e.g. because it was pressed before mouse capture was enabled,
+/// counts as a press.
+///
+/// Moves and drags are both reported, since the cursor moves in either case. Positions are
+/// measured in terminal cells, with the column as `x` and the row as `y`. The events are sent for
+/// the same window entity as the forwarded [KeyboardInput][bevy::input::keyboard::KeyboardInput]
+/// events, so this requires the [KeyboardPlugin][crate::input_forwarding::KeyboardPlugin] and adds
+/// it if it is missing. Mouse capture must be enabled for the terminal to report moves at all.
+///
+/// Each scroll step the terminal reports is sent as one [MouseWheel] event, scaled by the
+/// [WheelScale] resource.
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_ratatui::input_forwarding::*;
+/// # let mut app = App::new();
+/// app.add_plugins(MouseForwardingPlugin)
+///     .insert_resource(WheelScale::lines(3.0));
+/// ```
+pub struct MouseForwardingPlugin;
+
+impl Plugin for MouseForwardingPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<super::KeyboardPlugin>() {
+            app.add_plugins(super::KeyboardPlugin);
+        }
+        app.init_resource::<TerminalCursorPosition>()
+            .init_resource::<WheelScale>()
+            .add_event::<CursorMoved>()
+            .add_systems(
+                PreUpdate,
+                (send_mouse_buttons, send_cursor_moved, send_mouse_wheel)
+                    .in_set(InputSet::EmitBevy),
+            );
+    }
+}
+
+/// The last known position of the mouse cursor, in terminal cells.
+///
+/// This is `None` until the terminal reports the first mouse move.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Resource, Deref)]
+pub struct TerminalCursorPosition(pub Option<Vec2>);
+
+/// How far one terminal scroll step moves in the forwarded [MouseWheel] events.
+///
+/// Defaults to one line per step.
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+pub struct WheelScale {
+    /// The unit of the forwarded events.
+    pub unit: MouseScrollUnit,
+    /// The distance in `unit`s of one step.
+    pub step: f32,
+}
+
+impl Default for WheelScale {
+    fn default() -> Self {
+        Self::lines(1.0)
+    }
+}
+
+impl WheelScale {
+    /// Scrolls `step` lines per terminal scroll step.
+    pub fn lines(step: f32) -> Self {
+        Self {
+            unit: MouseScrollUnit::Line,
+            step,
+        }
+    }
+
+    /// Scrolls `step` pixels per terminal scroll step.
+    pub fn pixels(step: f32) -> Self {
+        Self {
+            unit: MouseScrollUnit::Pixel,
+            step,
+        }
+    }
+}
+
+fn send_mouse_buttons(
+    mut mouse: EventReader<MouseEvent>,
+    mut buttons: EventWriter<MouseButtonInput>,
+    mut pressed: Local<HashSet<MouseButton>>,
+    window: Query<Entity, With<TerminalWindow>>,
+) {
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    for event in mouse.read() {
+        let (button, state) = match event.kind {
+            MouseEventKind::Down(button) => (button, ButtonState::Pressed),
+            MouseEventKind::Drag(button) if !pressed.contains(&mouse_button_to_bevy(button)) => {
+                (button, ButtonState::Pressed)
+            }
+            MouseEventKind::Up(button) => (button, ButtonState::Released),
+            _ => continue,
+        };
+        let button = mouse_button_to_bevy(button);
+        match state {
+            ButtonState::Pressed => pressed.insert(button),
+            ButtonState::Released => pressed.remove(&button),
+        };
+        buttons.send(MouseButtonInput {
+            button,
+            state,
+            window,
+        });
+    }
+}
+
+fn mouse_button_to_bevy(button: crossterm::event::MouseButton) -> MouseButton {
+    match button {
+        crossterm::event::MouseButton::Left => MouseButton::Left,
+        crossterm::event::MouseButton::Right => MouseButton::Right,
+        crossterm::event::MouseButton::Middle => MouseButton::Middle,
+    }
+}
+
+fn send_cursor_moved(
+    mut mouse: EventReader<MouseEvent>,
+    mut cursor_moved: EventWriter<CursorMoved>,
+    mut position: ResMut<TerminalCursorPosition>,
+    window: Query<Entity, With<TerminalWindow>>,
+) {
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    for event in mouse.read() {
+        if !matches!(event.kind, MouseEventKind::Moved | MouseEventKind::Drag(_)) {
+            continue;
+        }
+        let new = Vec2::new(event.column as f32, event.row as f32);
+        if position.0 == Some(new) {
+            continue;
+        }
+        cursor_moved.send(CursorMoved {
+            window,
+            position: new,
+            delta: position.0.map(|old| new - old),
+        });
+        position.0 = Some(new);
+    }
+}
+
+fn send_mouse_wheel(
+    mut mouse: EventReader<MouseEvent>,
+    mut wheel: EventWriter<MouseWheel>,
+    scale: Res<WheelScale>,
+    window: Query<Entity, With<TerminalWindow>>,
+) {
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    for event in mouse.read() {
+        // Like bevy, positive values scroll the content towards the top left.
+        let (x, y) = match event.kind {
+            MouseEventKind::ScrollUp => (0.0, scale.step),
+            MouseEventKind::ScrollDown => (0.0, -scale.step),
+            MouseEventKind::ScrollLeft => (scale.step, 0.0),
+            MouseEventKind::ScrollRight => (-scale.step, 0.0),
+            _ => continue,
+        };
+        wheel.send(MouseWheel {
+            unit: scale.unit,
+            x,
+            y,
+            window,
+        });
+    }
+}
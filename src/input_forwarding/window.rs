@@ -0,0 +1,167 @@
+//! Input forwarding for the terminal window
+
+use bevy::{
+    prelude::*,
+    window::{WindowFocused, WindowResized, WindowResolution},
+};
+use crossterm::{terminal::SetTitle, ExecutableCommand};
+use ratatui::layout::Size;
+
+use crate::{
+    event::{FocusEvent, InputSet, ResizeEvent},
+    terminal::{is_headless, output, HeadlessTerminal, TerminalInfo, TerminalSet},
+};
+
+/// Forwards terminal window events to bevy: focus changes as [WindowFocused] events, and resizes
+/// as [WindowResized] events.
+///
+/// The [TerminalWindow] and the [Window] of the terminal window entity are kept up to date too.
+/// The logical size of the [Window] is the size of the terminal in cells, with a scale factor of
+/// one. Setting [TerminalWindow::title] sets the title of the terminal.
+///
+/// The terminal window entity is spawned by the
+/// [KeyboardPlugin][crate::input_forwarding::KeyboardPlugin], which this requires and adds if it
+/// is missing. Terminals only report focus changes once they have been asked to with crossterm's
+/// [`EnableFocusChange`](crossterm::event::EnableFocusChange).
+///
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_ratatui::input_forwarding::*;
+/// # let mut app = App::new();
+/// app.add_plugins(WindowForwardingPlugin);
+/// ```
+pub struct WindowForwardingPlugin;
+
+impl Plugin for WindowForwardingPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<super::KeyboardPlugin>() {
+            app.add_plugins(super::KeyboardPlugin);
+        }
+        app.add_event::<WindowFocused>()
+            .add_event::<WindowResized>()
+            .add_systems(
+                PreUpdate,
+                (send_window_focused, send_window_resized)
+                    .chain()
+                    .in_set(InputSet::EmitBevy),
+            )
+            .add_systems(
+                PostUpdate,
+                apply_window_title
+                    .run_if(not(is_headless))
+                    .before(TerminalSet::Cleanup),
+            );
+    }
+}
+
+/// The terminal, as the window that all forwarded input is sent for.
+///
+/// One entity with this component and a bevy [Window] is spawned at startup. Query it to find out
+/// about the terminal without accessing the [RatatuiContext][crate::terminal::RatatuiContext].
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct TerminalWindow {
+    /// The size of the terminal in cells.
+    pub size: Size,
+    /// The size of the terminal in pixels, if the terminal reports it.
+    pub pixel_size: Option<Size>,
+    /// The title of the terminal, if the app has set one.
+    pub title: Option<String>,
+    /// Whether the terminal has focus.
+    pub focused: bool,
+}
+
+/// Spawns the terminal window entity.
+///
+/// The entity is needed for the window field of the forwarded bevy input events.
+pub(super) fn spawn_terminal_window(
+    mut commands: Commands,
+    terminal: Option<Res<TerminalInfo>>,
+    headless: Option<Res<HeadlessTerminal>>,
+) {
+    let size = terminal.map(|terminal| terminal.size).unwrap_or_default();
+    commands.spawn((
+        TerminalWindow {
+            size,
+            pixel_size: pixel_size(headless.is_some()),
+            title: None,
+            focused: true,
+        },
+        Window {
+            resolution: WindowResolution::new(size.width.into(), size.height.into()),
+            ..default()
+        },
+    ));
+}
+
+/// The size of the terminal in pixels, or `None` if it is not reported.
+fn pixel_size(headless: bool) -> Option<Size> {
+    if headless {
+        return None;
+    }
+    crossterm::terminal::window_size()
+        .ok()
+        .filter(|size| size.width > 0 && size.height > 0)
+        .map(|size| Size::new(size.width, size.height))
+}
+
+fn send_window_focused(
+    mut focus: EventReader<FocusEvent>,
+    mut focused: EventWriter<WindowFocused>,
+    mut window: Query<(Entity, &mut TerminalWindow, &mut Window)>,
+) {
+    let Ok((entity, mut terminal, mut window)) = window.get_single_mut() else {
+        return;
+    };
+    for event in focus.read() {
+        terminal.focused = *event == FocusEvent::Gained;
+        window.focused = terminal.focused;
+        focused.send(WindowFocused {
+            window: entity,
+            focused: terminal.focused,
+        });
+    }
+}
+
+fn send_window_resized(
+    mut resize: EventReader<ResizeEvent>,
+    mut resized: EventWriter<WindowResized>,
+    mut window: Query<(Entity, &mut TerminalWindow, &mut Window)>,
+    headless: Option<Res<HeadlessTerminal>>,
+) {
+    let Ok((entity, mut terminal, mut window)) = window.get_single_mut() else {
+        return;
+    };
+    // Only the last size matters, and the terminal sends at most one resize per frame anyway.
+    let Some(size) = resize.read().last() else {
+        return;
+    };
+    terminal.size = **size;
+    terminal.pixel_size = pixel_size(headless.is_some());
+    let (width, height) = (f32::from(size.width), f32::from(size.height));
+    window.resolution.set(width, height);
+    resized.send(WindowResized {
+        window: entity,
+        width,
+        height,
+    });
+}
+
+/// Sets the title of the terminal when [TerminalWindow::title] changes.
+fn apply_window_title(
+    mut window: Query<(&TerminalWindow, &mut Window), Changed<TerminalWindow>>,
+    mut applied: Local<Option<String>>,
+) {
+    let Ok((terminal, mut window)) = window.get_single_mut() else {
+        return;
+    };
+    if terminal.title == *applied {
+        return;
+    }
+    if let Some(title) = &terminal.title {
+        if let Err(err) = output().execute(SetTitle(title)) {
+            warn!("Failed to set the terminal title: {err}");
+        }
+        window.title.clone_from(title);
+    }
+    applied.clone_from(&terminal.title);
+}
@@ -0,0 +1,161 @@
+//! A summary of the terminal input of each frame.
+//!
+//! [`InputSnapshotPlugin`] collects the terminal events of a frame into an [`InputSnapshot`]
+//! resource of plain data: the keys pressed and released, the text typed, how far the mouse moved
+//! and scrolled, and whether the terminal was resized. It is handy for simulations that need the
+//! input of a frame as a single value, e.g. to send it over the network, to roll back and replay
+//! it, or to record it as a compact replay. See the [rollback](crate::rollback) module for
+//! encoding snapshots and feeding them back in.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::input_snapshot::InputSnapshot;
+//!
+//! fn record(input: Res<InputSnapshot>, mut replay: Local<Vec<InputSnapshot>>) {
+//!     replay.push(input.clone());
+//! }
+//! ```
+use bevy::prelude::*;
+use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEventKind};
+use ratatui::layout::{Position, Size};
+
+use crate::{
+    event::{FocusEvent, InputSet, KeyEvent, MouseEvent, PasteEvent, ResizeEvent},
+    paste::PasteChunk,
+    quit::KeyBinding,
+    rollback::InputFeed,
+};
+
+/// A plugin that updates the [`InputSnapshot`] every frame.
+pub struct InputSnapshotPlugin;
+
+impl Plugin for InputSnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        // Sent instead of paste events while the `PastePlugin` is added.
+        app.add_event::<PasteChunk>()
+            .init_resource::<InputSnapshot>()
+            .add_systems(PreUpdate, update_input_snapshot.in_set(InputSet::Post));
+    }
+}
+
+/// The terminal input received this frame.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct InputSnapshot {
+    /// The keys pressed, in order.
+    pub pressed: Vec<KeyBinding>,
+    /// The keys that repeated while held, in order.
+    pub repeated: Vec<KeyBinding>,
+    /// The keys released, in order. Only terminals with the kitty protocol report releases.
+    pub released: Vec<KeyBinding>,
+    /// The text typed with pressed and repeated keys, without any pasted text.
+    pub text: String,
+    /// The text pasted. With the [`PastePlugin`](crate::paste::PastePlugin), this is the text of
+    /// the [`PasteChunk`]s sent this frame, so a large paste is spread over several frames.
+    pub pasted: String,
+    /// The mouse buttons pressed and where.
+    pub mouse_pressed: Vec<(MouseButton, Position)>,
+    /// The mouse buttons released and where.
+    pub mouse_released: Vec<(MouseButton, Position)>,
+    /// How many cells the mouse moved across and down since the last frame it was reported in.
+    pub mouse_delta: (i32, i32),
+    /// The last reported mouse position, kept from earlier frames.
+    pub mouse_position: Option<Position>,
+    /// The scroll steps to the right and down.
+    pub scroll: (i32, i32),
+    /// The new size if the terminal was resized.
+    pub resized: Option<Size>,
+    /// Whether the terminal gained (`true`) or lost (`false`) focus last.
+    pub focused: Option<bool>,
+}
+
+impl InputSnapshot {
+    /// Whether nothing was received this frame.
+    pub fn is_empty(&self) -> bool {
+        let previous_position = self.mouse_position;
+        *self
+            == Self {
+                mouse_position: previous_position,
+                ..default()
+            }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_input_snapshot(
+    mut keys: EventReader<KeyEvent>,
+    mut mouse: EventReader<MouseEvent>,
+    mut pastes: EventReader<PasteEvent>,
+    mut paste_chunks: EventReader<PasteChunk>,
+    mut resizes: EventReader<ResizeEvent>,
+    mut focus: EventReader<FocusEvent>,
+    feed: Option<ResMut<InputFeed>>,
+    mut snapshot: ResMut<InputSnapshot>,
+) {
+    if let Some(mut feed) = feed {
+        keys.clear();
+        mouse.clear();
+        pastes.clear();
+        paste_chunks.clear();
+        resizes.clear();
+        focus.clear();
+        *snapshot = feed.next_snapshot();
+        return;
+    }
+    let mut next = InputSnapshot {
+        mouse_position: snapshot.mouse_position,
+        ..default()
+    };
+    for key in keys.read() {
+        let binding = KeyBinding {
+            code: key.code,
+            modifiers: key.modifiers,
+        };
+        match key.kind {
+            KeyEventKind::Press => next.pressed.push(binding),
+            KeyEventKind::Repeat => next.repeated.push(binding),
+            KeyEventKind::Release => {
+                next.released.push(binding);
+                continue;
+            }
+        }
+        if let KeyCode::Char(c) = key.code {
+            if !key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SUPER)
+            {
+                next.text.push(c);
+            }
+        }
+    }
+    for event in mouse.read() {
+        let position = Position::new(event.column, event.row);
+        if let Some(previous) = next.mouse_position {
+            next.mouse_delta.0 += i32::from(position.x) - i32::from(previous.x);
+            next.mouse_delta.1 += i32::from(position.y) - i32::from(previous.y);
+        }
+        next.mouse_position = Some(position);
+        match event.kind {
+            MouseEventKind::Down(button) => next.mouse_pressed.push((button, position)),
+            MouseEventKind::Up(button) => next.mouse_released.push((button, position)),
+            MouseEventKind::ScrollDown => next.scroll.1 += 1,
+            MouseEventKind::ScrollUp => next.scroll.1 -= 1,
+            MouseEventKind::ScrollRight => next.scroll.0 += 1,
+            MouseEventKind::ScrollLeft => next.scroll.0 -= 1,
+            MouseEventKind::Drag(_) | MouseEventKind::Moved => {}
+        }
+    }
+    for paste in pastes.read() {
+        next.pasted.push_str(paste);
+    }
+    for chunk in paste_chunks.read() {
+        next.pasted.push_str(chunk);
+    }
+    next.resized = resizes.read().last().map(|resize| resize.0);
+    next.focused = focus
+        .read()
+        .last()
+        .map(|event| *event == FocusEvent::Gained);
+    if *snapshot != next {
+        *snapshot = next;
+    }
+}
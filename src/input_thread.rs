@@ -0,0 +1,197 @@
+//! Reading terminal events on a background thread.
+//!
+//! By default terminal events are polled for at the start of every frame, which costs a system
+//! call per frame even when there is no input. With [`InputThreadPlugin`], a background thread
+//! waits for terminal events instead and hands them over through the [`InputThread`] resource.
+//! The frames only drain what the thread has read, and
+//! [`InputWakeRunnerPlugin`](crate::runner::InputWakeRunnerPlugin) is woken up by the thread as
+//! soon as an event arrives.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{input_thread::InputThreadPlugin, RatatuiPlugins};
+//!
+//! App::new().add_plugins((RatatuiPlugins::default(), InputThreadPlugin));
+//! ```
+//!
+//! The thread starts once the startup [handshake](crate::handshake) has finished, since the
+//! handshake reads the terminal too. [`run_external`](crate::terminal::run_external) pauses the
+//! thread so that the child process gets the input.
+use std::{
+    collections::VecDeque,
+    io,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use crossterm::event::{self, Event};
+
+use crate::{
+    event::InputSet,
+    handshake::handshake_finished,
+    terminal::{is_headless, RatatuiContext},
+};
+
+/// A plugin that reads terminal events on a background thread.
+pub struct InputThreadPlugin;
+
+impl Plugin for InputThreadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            start_input_thread
+                .run_if(
+                    not(resource_exists::<InputThread>)
+                        .and(resource_exists::<RatatuiContext>)
+                        .and(not(is_headless))
+                        .and(handshake_finished),
+                )
+                .in_set(InputSet::Pre),
+        );
+    }
+}
+
+/// How long the thread waits for an event before checking whether it should pause or stop.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The background thread that reads terminal events, and the events it has read.
+///
+/// While this resource exists, the events are taken from it rather than polled for. The thread is
+/// stopped when the resource is dropped.
+#[derive(Resource, Debug)]
+pub struct InputThread {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+    events: Mutex<VecDeque<io::Result<Event>>>,
+    /// The length of `events`, which can be read without taking the lock. It is only changed while
+    /// the lock is held.
+    pending: AtomicUsize,
+    ready: Condvar,
+    paused: AtomicBool,
+    idle: AtomicBool,
+    stop: AtomicBool,
+}
+
+impl InputThread {
+    /// Starts reading terminal events on a new thread.
+    pub fn spawn() -> io::Result<Self> {
+        let shared = Arc::new(Shared::default());
+        let thread = thread::Builder::new()
+            .name("bevy_ratatui input".into())
+            .spawn({
+                let shared = Arc::clone(&shared);
+                move || read_events(&shared)
+            })?;
+        Ok(Self {
+            shared,
+            thread: Some(thread),
+        })
+    }
+
+    /// Takes the events read since the last call, oldest first.
+    ///
+    /// Stops at the first read error, which is returned after the events before it.
+    pub fn drain(&self) -> io::Result<Vec<Event>> {
+        let mut events = self.events();
+        let mut drained = Vec::with_capacity(events.len());
+        while let Some(event) = events.pop_front() {
+            self.shared.pending.fetch_sub(1, Ordering::SeqCst);
+            drained.push(event?);
+        }
+        Ok(drained)
+    }
+
+    /// The number of events read by the thread that have not been drained yet.
+    pub fn pending(&self) -> usize {
+        self.shared.pending.load(Ordering::SeqCst)
+    }
+
+    /// Waits until there are events to drain, or until the timeout has passed. Returns whether
+    /// there are events.
+    pub fn wait(&self, timeout: Duration) -> bool {
+        let events = self.events();
+        let (events, _) = self
+            .shared
+            .ready
+            .wait_timeout_while(events, timeout, |events| events.is_empty())
+            .unwrap_or_else(|err| err.into_inner());
+        !events.is_empty()
+    }
+
+    /// Stops reading events, waiting until the thread no longer touches the terminal.
+    pub fn pause(&self) {
+        self.shared.paused.store(true, Ordering::SeqCst);
+        while !self.shared.idle.load(Ordering::SeqCst) && !self.is_finished() {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Reads events again after [`InputThread::pause`].
+    pub fn resume(&self) {
+        self.shared.paused.store(false, Ordering::SeqCst);
+    }
+
+    fn is_finished(&self) -> bool {
+        self.thread.as_ref().is_none_or(JoinHandle::is_finished)
+    }
+
+    fn events(&self) -> MutexGuard<'_, VecDeque<io::Result<Event>>> {
+        // The queue is always left in a consistent state, so a panic while it was locked does not
+        // matter.
+        self.shared
+            .events
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Drop for InputThread {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn read_events(shared: &Shared) {
+    while !shared.stop.load(Ordering::SeqCst) {
+        if shared.paused.load(Ordering::SeqCst) {
+            shared.idle.store(true, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(1));
+            continue;
+        }
+        shared.idle.store(false, Ordering::SeqCst);
+        let event = match event::poll(POLL_INTERVAL) {
+            Ok(false) => continue,
+            Ok(true) => event::read(),
+            Err(err) => Err(err),
+        };
+        let failed = event.is_err();
+        let mut events = shared.events.lock().unwrap_or_else(|err| err.into_inner());
+        events.push_back(event);
+        shared.pending.fetch_add(1, Ordering::SeqCst);
+        drop(events);
+        shared.ready.notify_all();
+        if failed {
+            break;
+        }
+    }
+    shared.idle.store(true, Ordering::SeqCst);
+}
+
+fn start_input_thread(mut commands: Commands) {
+    match InputThread::spawn() {
+        Ok(thread) => commands.insert_resource(thread),
+        Err(err) => warn!("Failed to start the input thread, polling for input instead: {err}"),
+    }
+}
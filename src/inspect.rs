@@ -0,0 +1,230 @@
+//! Tree views of reflected values.
+//!
+//! [`reflect_tree`] turns any [`PartialReflect`] value, such as a resource or component, into a
+//! [`TreeView`] with a node for each field, colored by the kind of value. With the `json` feature,
+//! [`json_tree`] does the same for a `serde_json::Value`. Render the tree with
+//! [`TreeViewWidget`](crate::tree::TreeViewWidget).
+//!
+//! The trees are built in the same order each time, so a tree rebuilt from a value that changed can
+//! keep the nodes the user expanded with [`TreeView::keep_state_from`].
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::inspect::reflect_tree;
+//!
+//! #[derive(Reflect)]
+//! struct Settings {
+//!     name: String,
+//!     volume: f32,
+//! }
+//!
+//! let settings = Settings {
+//!     name: "default".into(),
+//!     volume: 0.5,
+//! };
+//! let tree = reflect_tree("settings", &settings);
+//! assert_eq!(tree.visible_rows().len(), 3);
+//! ```
+use bevy::reflect::{PartialReflect, ReflectRef, VariantType};
+use ratatui::{
+    style::{Color, Style},
+    text::{Line, Span},
+};
+
+use crate::tree::{NodeKind, TreeNodeId, TreeView};
+
+const STRING: Style = Style::new().fg(Color::Green);
+const NUMBER: Style = Style::new().fg(Color::Cyan);
+const BOOL: Style = Style::new().fg(Color::Yellow);
+const NULL: Style = Style::new().fg(Color::DarkGray);
+const TYPE: Style = Style::new().fg(Color::Magenta);
+
+/// Builds an expandable tree of a reflected value, with the root expanded.
+pub fn reflect_tree(label: impl Into<String>, value: &dyn PartialReflect) -> TreeView {
+    let mut builder = TreeBuilder::default();
+    let root = builder.add_reflect(None, label.into(), value);
+    builder.tree.expand(root);
+    builder.tree
+}
+
+/// Builds an expandable tree of a JSON value, with the root expanded.
+#[cfg(feature = "json")]
+pub fn json_tree(label: impl Into<String>, value: &serde_json::Value) -> TreeView {
+    let mut builder = TreeBuilder::default();
+    let root = builder.add_json(None, label.into(), value);
+    builder.tree.expand(root);
+    builder.tree
+}
+
+struct TreeBuilder {
+    tree: TreeView,
+    next_id: u64,
+}
+
+impl Default for TreeBuilder {
+    fn default() -> Self {
+        Self {
+            tree: TreeView::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl TreeBuilder {
+    fn add(&mut self, parent: Option<TreeNodeId>, key: String, value: Span<'static>) -> TreeNodeId {
+        let id = TreeNodeId(self.next_id);
+        self.next_id += 1;
+        let label = Line::from(vec![Span::raw(format!("{key}: ")), value]);
+        match parent {
+            Some(parent) => self.tree.add_child(parent, id, label, NodeKind::Leaf),
+            None => self.tree.add_root(id, label, NodeKind::Leaf),
+        }
+        id
+    }
+
+    fn add_reflect(
+        &mut self,
+        parent: Option<TreeNodeId>,
+        key: String,
+        value: &dyn PartialReflect,
+    ) -> TreeNodeId {
+        let type_name = Span::styled(value.reflect_short_type_path().to_string(), TYPE);
+        match value.reflect_ref() {
+            ReflectRef::Struct(value) => {
+                let id = self.add(parent, key, type_name);
+                for index in 0..value.field_len() {
+                    let name = value.name_at(index).unwrap_or_default().to_string();
+                    if let Some(field) = value.field_at(index) {
+                        self.add_reflect(Some(id), name, field);
+                    }
+                }
+                id
+            }
+            ReflectRef::TupleStruct(value) => {
+                let id = self.add(parent, key, type_name);
+                for (index, field) in value.iter_fields().enumerate() {
+                    self.add_reflect(Some(id), index.to_string(), field);
+                }
+                id
+            }
+            ReflectRef::Tuple(value) => {
+                let id = self.add(parent, key, type_name);
+                for (index, field) in value.iter_fields().enumerate() {
+                    self.add_reflect(Some(id), index.to_string(), field);
+                }
+                id
+            }
+            ReflectRef::List(value) => {
+                let id = self.add(parent, key, type_name);
+                for (index, item) in value.iter().enumerate() {
+                    self.add_reflect(Some(id), index.to_string(), item);
+                }
+                id
+            }
+            ReflectRef::Array(value) => {
+                let id = self.add(parent, key, type_name);
+                for (index, item) in value.iter().enumerate() {
+                    self.add_reflect(Some(id), index.to_string(), item);
+                }
+                id
+            }
+            ReflectRef::Set(value) => {
+                let id = self.add(parent, key, type_name);
+                for (index, item) in value.iter().enumerate() {
+                    self.add_reflect(Some(id), index.to_string(), item);
+                }
+                id
+            }
+            ReflectRef::Map(value) => {
+                let id = self.add(parent, key, type_name);
+                for (key, item) in value.iter() {
+                    self.add_reflect(Some(id), format!("{key:?}"), item);
+                }
+                id
+            }
+            ReflectRef::Enum(value) => {
+                let variant = value.variant_name().to_string();
+                let style = if variant == "None" { NULL } else { TYPE };
+                let id = self.add(parent, key, Span::styled(variant, style));
+                for (index, field) in value.iter_fields().enumerate() {
+                    let name = match value.variant_type() {
+                        VariantType::Struct => field.name().unwrap_or_default().to_string(),
+                        _ => index.to_string(),
+                    };
+                    self.add_reflect(Some(id), name, field.value());
+                }
+                id
+            }
+            _ => self.add(parent, key, opaque_span(value)),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    fn add_json(
+        &mut self,
+        parent: Option<TreeNodeId>,
+        key: String,
+        value: &serde_json::Value,
+    ) -> TreeNodeId {
+        use serde_json::Value;
+        match value {
+            Value::Null => self.add(parent, key, Span::styled("null", NULL)),
+            Value::Bool(value) => self.add(parent, key, Span::styled(value.to_string(), BOOL)),
+            Value::Number(value) => self.add(parent, key, Span::styled(value.to_string(), NUMBER)),
+            Value::String(value) => {
+                self.add(parent, key, Span::styled(format!("{value:?}"), STRING))
+            }
+            Value::Array(items) => {
+                let summary = Span::styled(format!("[{}]", items.len()), TYPE);
+                let id = self.add(parent, key, summary);
+                for (index, item) in items.iter().enumerate() {
+                    self.add_json(Some(id), index.to_string(), item);
+                }
+                id
+            }
+            Value::Object(fields) => {
+                let summary = Span::styled(format!("{{{}}}", fields.len()), TYPE);
+                let id = self.add(parent, key, summary);
+                for (name, field) in fields {
+                    self.add_json(Some(id), name.clone(), field);
+                }
+                id
+            }
+        }
+    }
+}
+
+/// Formats a value without fields, colored by its type.
+fn opaque_span(value: &dyn PartialReflect) -> Span<'static> {
+    let text = format!("{value:?}");
+    let Some(value) = value.try_as_reflect() else {
+        return Span::raw(text);
+    };
+    let style = if value.is::<String>() || value.is::<&'static str>() || value.is::<char>() {
+        STRING
+    } else if value.is::<bool>() {
+        BOOL
+    } else if [
+        value.is::<i8>(),
+        value.is::<i16>(),
+        value.is::<i32>(),
+        value.is::<i64>(),
+        value.is::<i128>(),
+        value.is::<isize>(),
+        value.is::<u8>(),
+        value.is::<u16>(),
+        value.is::<u32>(),
+        value.is::<u64>(),
+        value.is::<u128>(),
+        value.is::<usize>(),
+        value.is::<f32>(),
+        value.is::<f64>(),
+    ]
+    .contains(&true)
+    {
+        NUMBER
+    } else {
+        Style::new()
+    };
+    Span::styled(text, style)
+}
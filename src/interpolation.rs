@@ -0,0 +1,181 @@
+//! Smooth motion between fixed timestep ticks.
+//!
+//! Games that move entities in [`FixedUpdate`] look choppy when the terminal redraws at a rate
+//! that does not match the tick rate: some frames show the same tick twice and others skip one.
+//! [`InterpolationPlugin`] keeps the value of an interpolated component from the previous tick, and
+//! after the fixed timestep loop of every frame stores a blend of the previous and current values
+//! in [`Interpolated`], weighted by how far the frame is between the two ticks. Simulate with the
+//! component in [`FixedUpdate`] and draw with [`Interpolated`].
+//!
+//! [`CellPosition`] and [`CellColor`] are interpolated by [`InterpolationPlugin`]. Other components
+//! that implement [`Interpolate`] can be added with [`InterpolateComponentPlugin`].
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     interpolation::{CellPosition, Interpolated, InterpolationPlugin},
+//!     terminal::RatatuiContext,
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), InterpolationPlugin))
+//!     .add_systems(Startup, |mut commands: Commands| {
+//!         commands.spawn(CellPosition(Vec2::ZERO));
+//!     })
+//!     .add_systems(FixedUpdate, |mut positions: Query<&mut CellPosition>| {
+//!         for mut position in &mut positions {
+//!             position.x += 1.0;
+//!         }
+//!     })
+//!     .add_systems(Update, draw);
+//!
+//! fn draw(mut context: ResMut<RatatuiContext>, balls: Query<&Interpolated<CellPosition>>) {
+//!     let _ = context.draw(|frame| {
+//!         for ball in &balls {
+//!             if let Some(cell) = frame.buffer_mut().cell_mut(ball.cell()) {
+//!                 cell.set_char('o');
+//!             }
+//!         }
+//!     });
+//! }
+//! ```
+use std::marker::PhantomData;
+
+use bevy::{app::RunFixedMainLoopSystem, prelude::*};
+use ratatui::{layout::Position, style::Color};
+
+use crate::color::to_rgb;
+
+/// A plugin that interpolates [`CellPosition`] and [`CellColor`].
+pub struct InterpolationPlugin;
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            InterpolateComponentPlugin::<CellPosition>::default(),
+            InterpolateComponentPlugin::<CellColor>::default(),
+        ));
+    }
+}
+
+/// A plugin that interpolates the component `C` into [`Interpolated<C>`].
+pub struct InterpolateComponentPlugin<C>(PhantomData<C>);
+
+impl<C> Default for InterpolateComponentPlugin<C> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C: Component + Interpolate> Plugin for InterpolateComponentPlugin<C> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedFirst, store_previous::<C>)
+            .add_systems(
+                RunFixedMainLoop,
+                interpolate::<C>.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+            );
+    }
+}
+
+/// A value that can be blended with another value of the same type.
+pub trait Interpolate: Clone + Send + Sync + 'static {
+    /// Blends `self` towards `other`, where `t` is between `0.0` (`self`) and `1.0` (`other`).
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+}
+
+/// The position of an entity in cells, with fractional cells allowed so that it can move
+/// smoothly.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Deref, DerefMut)]
+pub struct CellPosition(pub Vec2);
+
+impl CellPosition {
+    /// The cell the position is in, clamped to the range of cell coordinates.
+    pub fn cell(&self) -> Position {
+        let clamp = |value: f32| value.round().clamp(0.0, f32::from(u16::MAX)) as u16;
+        Position::new(clamp(self.x), clamp(self.y))
+    }
+}
+
+impl Interpolate for CellPosition {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        Self(self.0.lerp(other.0, t))
+    }
+}
+
+/// The color of an entity.
+///
+/// Colors that have an RGB value are blended into an RGB color. Others, such as
+/// [`Color::Reset`], switch halfway between the ticks.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct CellColor(pub Color);
+
+impl Interpolate for CellColor {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        if self.0 == other.0 {
+            return *self;
+        }
+        match (to_rgb(self.0), to_rgb(other.0)) {
+            (Some((r1, g1, b1)), Some((r2, g2, b2))) => {
+                let blend = |a: u8, b: u8| {
+                    (f32::from(a) + (f32::from(b) - f32::from(a)) * t.clamp(0.0, 1.0)).round() as u8
+                };
+                Self(Color::Rgb(blend(r1, r2), blend(g1, g2), blend(b1, b2)))
+            }
+            _ if t < 0.5 => *self,
+            _ => *other,
+        }
+    }
+}
+
+/// The value of the component `C` of the previous fixed timestep tick.
+#[derive(Component, Debug, Clone, Deref)]
+pub struct PreviousTick<C>(pub C);
+
+/// The value of the component `C` blended between the previous and the current fixed timestep
+/// tick, to be used for drawing.
+#[derive(Component, Debug, Clone, Deref)]
+pub struct Interpolated<C>(pub C);
+
+fn store_previous<C: Component + Interpolate>(
+    mut commands: Commands,
+    mut components: Query<(Entity, &C, Option<&mut PreviousTick<C>>)>,
+) {
+    for (entity, component, previous) in &mut components {
+        match previous {
+            Some(mut previous) => previous.0 = component.clone(),
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(PreviousTick(component.clone()));
+            }
+        }
+    }
+}
+
+type InterpolateQuery<'a, C> = (
+    Entity,
+    &'a C,
+    Option<&'a PreviousTick<C>>,
+    Option<&'a mut Interpolated<C>>,
+);
+
+fn interpolate<C: Component + Interpolate>(
+    mut commands: Commands,
+    time: Res<Time<Fixed>>,
+    mut components: Query<InterpolateQuery<C>>,
+) {
+    let t = time.overstep_fraction();
+    for (entity, current, previous, interpolated) in &mut components {
+        let value = match previous {
+            Some(previous) => previous.0.interpolate(current, t),
+            None => current.clone(),
+        };
+        match interpolated {
+            Some(mut interpolated) => interpolated.0 = value,
+            None => {
+                commands.entity(entity).insert(Interpolated(value));
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Surfacing status in iTerm2 while the window is in the background.
+//!
+//! iTerm2 can show a badge, a short string drawn large and faint in the top right of the session,
+//! and can ask for attention by bouncing the dock icon. [`ITerm2Integration`] is a
+//! [terminal extension](crate::extension) that sets the badge from [`ITerm2Badge`] events and
+//! asks for attention on [`RequestAttention`] events. In other terminals the events do nothing, so
+//! an app can send them unconditionally.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     extension::ExtensionPlugin,
+//!     iterm2::{ITerm2Badge, ITerm2Integration, RequestAttention},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), ExtensionPlugin(ITerm2Integration)))
+//!     .add_systems(Update, build_finished);
+//!
+//! fn build_finished(
+//!     mut badge: EventWriter<ITerm2Badge>,
+//!     mut attention: EventWriter<RequestAttention>,
+//! ) {
+//!     badge.send(ITerm2Badge::from("build ok"));
+//!     attention.send(RequestAttention::Once);
+//! }
+//! ```
+use std::{fmt, io::Write};
+
+use bevy::prelude::*;
+
+use crate::{
+    extension::{self, TerminalExtension, TerminalIdentity, TerminalProgram},
+    terminal::{is_headless, output, TerminalSet},
+};
+
+/// A [`TerminalExtension`] that sets badges and requests attention in iTerm2.
+pub struct ITerm2Integration;
+
+impl TerminalExtension for ITerm2Integration {
+    fn name(&self) -> &'static str {
+        "iterm2"
+    }
+
+    fn supports(&self, identity: &TerminalIdentity) -> bool {
+        identity.program == TerminalProgram::ITerm2
+    }
+
+    fn register(&self, app: &mut App) {
+        app.add_event::<ITerm2Badge>()
+            .add_event::<RequestAttention>();
+    }
+
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            write_iterm2_requests
+                .run_if(not(is_headless))
+                .before(TerminalSet::Cleanup),
+        );
+    }
+}
+
+/// Sets the badge of the iTerm2 session. An empty string removes it.
+///
+/// The badge is an iTerm2 format string, so `\(session.name)` and similar are replaced by iTerm2.
+#[derive(Event, Debug, Clone, Default, PartialEq, Eq, Deref)]
+pub struct ITerm2Badge(pub String);
+
+impl<T: Into<String>> From<T> for ITerm2Badge {
+    fn from(badge: T) -> Self {
+        Self(badge.into())
+    }
+}
+
+/// Asks iTerm2 to draw the user's attention to the app.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestAttention {
+    /// Bounces the dock icon once.
+    Once,
+    /// Bounces the dock icon until the app is activated.
+    UntilActivated,
+    /// Stops bouncing.
+    Cancel,
+    /// Shows fireworks at the cursor.
+    Fireworks,
+}
+
+/// A crossterm command that sets the iTerm2 badge, with `OSC 1337 ; SetBadgeFormat=format BEL`.
+///
+/// ```rust
+/// use bevy_ratatui::iterm2::SetBadgeFormat;
+/// use crossterm::Command;
+///
+/// let mut ansi = String::new();
+/// SetBadgeFormat("ok").write_ansi(&mut ansi).unwrap();
+/// assert_eq!(ansi, "\x1b]1337;SetBadgeFormat=b2s=\x07");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetBadgeFormat<'a>(pub &'a str);
+
+impl crossterm::Command for SetBadgeFormat<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1b]1337;SetBadgeFormat=")?;
+        extension::write_base64(f, self.0.as_bytes())?;
+        f.write_char('\x07')
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl crossterm::Command for RequestAttention {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let value = match self {
+            RequestAttention::Once => "once",
+            RequestAttention::UntilActivated => "yes",
+            RequestAttention::Cancel => "no",
+            RequestAttention::Fireworks => "fireworks",
+        };
+        write!(f, "\x1b]1337;RequestAttention={value}\x07")
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn write_iterm2_requests(
+    mut badges: EventReader<ITerm2Badge>,
+    mut attention: EventReader<RequestAttention>,
+) {
+    if badges.is_empty() && attention.is_empty() {
+        return;
+    }
+    let mut output = output();
+    // Only the last badge of the frame would be visible anyway.
+    if let Some(badge) = badges.read().last() {
+        if let Err(err) = crossterm::queue!(output, SetBadgeFormat(badge)) {
+            warn!("Failed to set the iTerm2 badge: {err}");
+        }
+    }
+    for request in attention.read() {
+        if let Err(err) = crossterm::queue!(output, *request) {
+            warn!("Failed to request attention from iTerm2: {err}");
+        }
+    }
+    if let Err(err) = output.flush() {
+        warn!("Failed to write iTerm2 requests: {err}");
+    }
+}
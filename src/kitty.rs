@@ -1,35 +1,255 @@
 //! Enhanced kitty keyboard protocol.
-use std::io::{self, stdout};
+//!
+//! The terminal keeps a stack of keyboard enhancement flags which may be shared with other
+//! libraries or child processes. The [`KeyboardEnhancementStack`] resource keeps track of the flags
+//! pushed by this crate, so that exactly as many entries are popped on exit as were pushed, and so
+//! that the flags can be popped before handing the terminal to another program and pushed again
+//! afterwards.
+//!
+//! [`KittyPlugin`] pushes the flags in the [`KittyFlags`] resource, which defaults to all of them,
+//! once the startup [handshake](crate::handshake) reports that the terminal supports the protocol.
+//! Some terminals misbehave with some of the flags, so insert the resource to request fewer:
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{kitty::KittyFlags, RatatuiPlugins};
+//! use crossterm::event::KeyboardEnhancementFlags;
+//!
+//! App::new()
+//!     .insert_resource(KittyFlags(
+//!         KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+//!             | KeyboardEnhancementFlags::REPORT_EVENT_TYPES,
+//!     ))
+//!     .add_plugins(RatatuiPlugins::default());
+//! ```
+//!
+//! The protocol can be turned off and on while the app runs by sending [`SetKittyProtocol`]
+//! events, and changing [`KittyFlags`] while it is on pushes the new flags in place of the old.
+//! [`run_external`](crate::terminal::run_external) already pops the flags while a child process
+//! has the terminal.
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::kitty::SetKittyProtocol;
+//!
+//! fn enter_text_mode(mut kitty: EventWriter<SetKittyProtocol>) {
+//!     kitty.send(SetKittyProtocol::Disable);
+//! }
+//! ```
+use std::io::{self, Write};
 
 use bevy::prelude::*;
 use crossterm::{
     event::{KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
     terminal::supports_keyboard_enhancement,
-    ExecutableCommand,
+    ExecutableCommand, QueueableCommand,
 };
 
-use crate::terminal;
+use crate::{
+    error::exit_on_error,
+    event::InputSet,
+    handshake::{poll_handshake, HandshakePlugin, TerminalCapabilities, TerminalReady},
+    terminal::{output, output_target, OutputTarget, TerminalSet},
+};
 
 pub struct KittyPlugin;
 
 impl Plugin for KittyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup.after(terminal::setup));
+        if !app.is_plugin_added::<HandshakePlugin>() {
+            app.add_plugins(HandshakePlugin);
+        }
+        app.init_resource::<KeyboardEnhancementStack>()
+            .init_resource::<KittyFlags>()
+            .add_event::<SetKittyProtocol>()
+            .add_systems(
+                PreUpdate,
+                (
+                    enable_when_ready,
+                    set_kitty_protocol.pipe(exit_on_error),
+                    update_kitty_flags.pipe(exit_on_error),
+                )
+                    .chain()
+                    .after(poll_handshake)
+                    .in_set(InputSet::Pre)
+                    .in_set(TerminalSet::Features)
+                    .ambiguous_with(TerminalSet::Features),
+            );
+    }
+}
+
+/// The keyboard enhancement flags that [`KittyPlugin`] requests. Defaults to all of them.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct KittyFlags(pub KeyboardEnhancementFlags);
+
+impl Default for KittyFlags {
+    fn default() -> Self {
+        Self(KeyboardEnhancementFlags::all())
     }
 }
 
-fn setup(mut commands: Commands) {
-    if enable_kitty_protocol().is_ok() {
-        commands.insert_resource(KittyEnabled);
+fn enable_when_ready(
+    mut commands: Commands,
+    mut ready: EventReader<TerminalReady>,
+    mut stack: ResMut<KeyboardEnhancementStack>,
+    flags: Res<KittyFlags>,
+) {
+    let Some(ready) = ready.read().last() else {
+        return;
+    };
+    if ready.capabilities.keyboard_enhancement && stack.push(flags.0).is_ok() {
+        commands.insert_resource(KittyEnabled { flags: flags.0 });
+    }
+}
+
+/// Turns the kitty keyboard protocol on or off at the start of the next frame.
+#[derive(Debug, Clone, Copy, Event, PartialEq, Eq, Hash)]
+pub enum SetKittyProtocol {
+    /// Pushes the [`KittyFlags`] if the terminal supports the protocol and it is not on already.
+    ///
+    /// This does nothing until the startup [handshake](crate::handshake) has finished.
+    Enable,
+    /// Pops the flags if the protocol is on.
+    Disable,
+}
+
+fn set_kitty_protocol(
+    mut commands: Commands,
+    mut events: EventReader<SetKittyProtocol>,
+    mut stack: ResMut<KeyboardEnhancementStack>,
+    flags: Res<KittyFlags>,
+    enabled: Option<Res<KittyEnabled>>,
+    capabilities: Option<Res<TerminalCapabilities>>,
+) -> color_eyre::Result<()> {
+    // Only the last request counts, so that toggling several times in a frame is cheap.
+    let Some(event) = events.read().last() else {
+        return Ok(());
+    };
+    match (event, enabled) {
+        (SetKittyProtocol::Enable, None)
+            if capabilities.is_some_and(|capabilities| capabilities.keyboard_enhancement) =>
+        {
+            stack.push(flags.0)?;
+            commands.insert_resource(KittyEnabled { flags: flags.0 });
+        }
+        (SetKittyProtocol::Disable, Some(_)) => {
+            stack.pop()?;
+            commands.remove_resource::<KittyEnabled>();
+        }
+        _ => {}
     }
+    Ok(())
+}
+
+/// Replaces the pushed flags when [`KittyFlags`] changes while the protocol is on.
+fn update_kitty_flags(
+    mut commands: Commands,
+    mut stack: ResMut<KeyboardEnhancementStack>,
+    flags: Res<KittyFlags>,
+    enabled: Option<Res<KittyEnabled>>,
+) -> color_eyre::Result<()> {
+    let Some(enabled) = enabled else {
+        return Ok(());
+    };
+    if flags.is_changed() && enabled.flags != flags.0 {
+        stack.pop()?;
+        stack.push(flags.0)?;
+        commands.insert_resource(KittyEnabled { flags: flags.0 });
+    }
+    Ok(())
+}
+
+/// Present when the kitty keyboard protocol has been enabled.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KittyEnabled {
+    /// The flags that were pushed. The terminal does not report which of them it supports.
+    pub flags: KeyboardEnhancementFlags,
+}
+
+/// The keyboard enhancement flags pushed onto the terminal's stack by this crate.
+///
+/// Use [`KeyboardEnhancementStack::push`] and [`KeyboardEnhancementStack::pop`] rather than
+/// writing the escape codes directly so that the depth stays balanced. All the flags pushed
+/// through this resource are popped when it is dropped.
+#[derive(Resource, Debug, Default)]
+pub struct KeyboardEnhancementStack {
+    pushed: Vec<KeyboardEnhancementFlags>,
+    suspended: bool,
 }
 
-#[derive(Resource)]
-pub struct KittyEnabled;
+impl KeyboardEnhancementStack {
+    /// Pushes flags onto the terminal's stack.
+    ///
+    /// While suspended, the flags are only recorded, and are pushed when the stack is resumed.
+    pub fn push(&mut self, flags: KeyboardEnhancementFlags) -> io::Result<()> {
+        if !self.suspended {
+            output().execute(PushKeyboardEnhancementFlags(flags))?;
+        }
+        self.pushed.push(flags);
+        Ok(())
+    }
+
+    /// Pops the flags most recently pushed by this crate, if any.
+    pub fn pop(&mut self) -> io::Result<Option<KeyboardEnhancementFlags>> {
+        let flags = self.pushed.pop();
+        if flags.is_some() && !self.suspended {
+            output().execute(PopKeyboardEnhancementFlags)?;
+        }
+        Ok(flags)
+    }
+
+    /// The number of entries pushed by this crate.
+    pub fn depth(&self) -> usize {
+        self.pushed.len()
+    }
 
-impl Drop for KittyEnabled {
+    /// The flags that were pushed last, which are the ones in effect unless something else has
+    /// pushed flags since.
+    pub fn current(&self) -> Option<KeyboardEnhancementFlags> {
+        self.pushed.last().copied()
+    }
+
+    /// Returns true if the flags have been popped by [`KeyboardEnhancementStack::suspend`].
+    pub fn is_suspended(&self) -> bool {
+        self.suspended
+    }
+
+    /// Pops all the flags pushed by this crate from the terminal, remembering them so that
+    /// [`KeyboardEnhancementStack::resume`] can push them again.
+    ///
+    /// Use this before handing the terminal to another program, such as `$EDITOR`.
+    pub fn suspend(&mut self) -> io::Result<()> {
+        if self.suspended {
+            return Ok(());
+        }
+        let mut output = output();
+        for _ in &self.pushed {
+            output.queue(PopKeyboardEnhancementFlags)?;
+        }
+        output.flush()?;
+        self.suspended = true;
+        Ok(())
+    }
+
+    /// Pushes all the flags again after [`KeyboardEnhancementStack::suspend`].
+    pub fn resume(&mut self) -> io::Result<()> {
+        if !self.suspended {
+            return Ok(());
+        }
+        let mut output = output();
+        for flags in &self.pushed {
+            output.queue(PushKeyboardEnhancementFlags(*flags))?;
+        }
+        output.flush()?;
+        self.suspended = false;
+        Ok(())
+    }
+}
+
+/// Pops everything that was pushed through this stack.
+impl Drop for KeyboardEnhancementStack {
     fn drop(&mut self) {
-        let _ = disable_kitty_protocol();
+        let _ = self.suspend();
     }
 }
 
@@ -42,10 +262,25 @@ impl Drop for KittyEnabled {
 /// a guarantee that all features are supported: you should have fallbacks that you use until you
 /// detect the event type you are looking for.
 ///
+/// This pushes all the flags without recording them in the [`KeyboardEnhancementStack`]. Use
+/// [`enable_kitty_protocol_flags`] to push only some of them. The protocol is reported as not
+/// supported when the terminal is drawn to through another [`OutputTarget`] than stdout, as the
+/// terminal can only be asked whether it supports the protocol through stdout.
+///
 /// [kitty keyboard protocol]: https://sw.kovidgoyal.net/kitty/keyboard-protocol/
 pub fn enable_kitty_protocol() -> io::Result<()> {
-    if supports_keyboard_enhancement()? {
-        stdout().execute(PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::all()))?;
+    enable_kitty_protocol_flags(KeyboardEnhancementFlags::all())
+}
+
+/// Enables support for the [kitty keyboard protocol] with the given flags.
+///
+/// See [`enable_kitty_protocol`].
+///
+/// [kitty keyboard protocol]: https://sw.kovidgoyal.net/kitty/keyboard-protocol/
+pub fn enable_kitty_protocol_flags(flags: KeyboardEnhancementFlags) -> io::Result<()> {
+    // Crossterm asks whether the protocol is supported through stdout.
+    if output_target() == OutputTarget::Stdout && supports_keyboard_enhancement()? {
+        output().execute(PushKeyboardEnhancementFlags(flags))?;
         return Ok(());
     }
     Err(io::Error::new(
@@ -58,6 +293,6 @@ pub fn enable_kitty_protocol() -> io::Result<()> {
 ///
 /// [kitty keyboard protocol]: https://sw.kovidgoyal.net/kitty/keyboard-protocol/
 pub fn disable_kitty_protocol() -> io::Result<()> {
-    stdout().execute(PopKeyboardEnhancementFlags)?;
+    output().execute(PopKeyboardEnhancementFlags)?;
     Ok(())
 }
@@ -0,0 +1,206 @@
+//! Controlling the kitty window the app runs in.
+//!
+//! kitty can be controlled remotely, e.g. to set the window title or colors, open windows and
+//! tabs, or resize. [`KittyRemoteControl`] is a [terminal extension](crate::extension) that runs
+//! each [`KittyCommand`] event with `kitten @`, and sends the outcome back as a
+//! [`KittyCommandResult`] event. Commands run on a background thread, so a slow answer does not
+//! hold up the app.
+//!
+//! kitty has to be listening for remote control on a socket, i.e. run with `--listen-on` or the
+//! `listen_on` option, so that `KITTY_LISTEN_ON` is set. The extension is inactive otherwise, and
+//! in any other terminal, and the events are ignored.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     extension::ExtensionPlugin,
+//!     kitty_remote::{KittyCommand, KittyCommandResult, KittyRemoteControl},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), ExtensionPlugin(KittyRemoteControl)))
+//!     .add_systems(Startup, |mut commands: EventWriter<KittyCommand>| {
+//!         commands.send(KittyCommand::SetTitle("my app".into()));
+//!     })
+//!     .add_systems(Update, |mut results: EventReader<KittyCommandResult>| {
+//!         for result in results.read() {
+//!             if let Err(err) = &result.output {
+//!                 warn!("kitty {:?} failed: {err}", result.command);
+//!             }
+//!         }
+//!     });
+//! ```
+use std::{
+    env,
+    process::Command,
+    thread::{self, JoinHandle},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    extension::{TerminalExtension, TerminalIdentity, TerminalProgram},
+    terminal::{is_headless, TerminalSet},
+};
+
+/// A [`TerminalExtension`] that runs [`KittyCommand`]s in kitty.
+pub struct KittyRemoteControl;
+
+impl TerminalExtension for KittyRemoteControl {
+    fn name(&self) -> &'static str {
+        "kitty-remote-control"
+    }
+
+    fn supports(&self, identity: &TerminalIdentity) -> bool {
+        identity.program == TerminalProgram::Kitty
+            && env::var_os("KITTY_LISTEN_ON").is_some_and(|socket| !socket.is_empty())
+    }
+
+    fn register(&self, app: &mut App) {
+        app.add_event::<KittyCommand>()
+            .add_event::<KittyCommandResult>();
+    }
+
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunningKittyCommands>().add_systems(
+            PostUpdate,
+            (run_kitty_commands, finish_kitty_commands)
+                .chain()
+                .run_if(not(is_headless))
+                .before(TerminalSet::Cleanup),
+        );
+    }
+}
+
+/// A remote control command for the kitty window the app runs in.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub enum KittyCommand {
+    /// Sets the title of the window.
+    SetTitle(String),
+    /// Sets a color of the window, e.g. `background` to `#1e1e2e`.
+    SetColor { name: String, value: String },
+    /// Opens a new window, tab or OS window running `args`, or a shell if they are empty.
+    Launch {
+        kind: KittyLaunch,
+        args: Vec<String>,
+    },
+    /// Makes the window wider by that many cells, or narrower if negative.
+    ResizeWidth(i32),
+    /// Makes the window taller by that many cells, or shorter if negative.
+    ResizeHeight(i32),
+    /// Resizes the OS window to the given size in cells.
+    ResizeOsWindow { width: u16, height: u16 },
+    /// Any other command, as the arguments to `kitten @`.
+    Other(Vec<String>),
+}
+
+/// Where [`KittyCommand::Launch`] opens the new program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KittyLaunch {
+    Window,
+    Tab,
+    OsWindow,
+}
+
+impl KittyCommand {
+    /// The arguments to `kitten @` for this command.
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            KittyCommand::SetTitle(title) => {
+                vec!["set-window-title".into(), "--self".into(), title.clone()]
+            }
+            KittyCommand::SetColor { name, value } => {
+                vec![
+                    "set-colors".into(),
+                    "--self".into(),
+                    format!("{name}={value}"),
+                ]
+            }
+            KittyCommand::Launch { kind, args } => {
+                let kind = match kind {
+                    KittyLaunch::Window => "window",
+                    KittyLaunch::Tab => "tab",
+                    KittyLaunch::OsWindow => "os-window",
+                };
+                let mut launch = vec!["launch".into(), format!("--type={kind}")];
+                launch.extend(args.iter().cloned());
+                launch
+            }
+            KittyCommand::ResizeWidth(increment) => resize_window("horizontal", *increment),
+            KittyCommand::ResizeHeight(increment) => resize_window("vertical", *increment),
+            KittyCommand::ResizeOsWindow { width, height } => vec![
+                "resize-os-window".into(),
+                "--self".into(),
+                "--unit=cells".into(),
+                format!("--width={width}"),
+                format!("--height={height}"),
+            ],
+            KittyCommand::Other(args) => args.clone(),
+        }
+    }
+}
+
+fn resize_window(axis: &str, increment: i32) -> Vec<String> {
+    vec![
+        "resize-window".into(),
+        "--self".into(),
+        format!("--axis={axis}"),
+        format!("--increment={increment}"),
+    ]
+}
+
+/// Sent when a [`KittyCommand`] has finished.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct KittyCommandResult {
+    pub command: KittyCommand,
+    /// What kitty printed, or why the command failed.
+    pub output: Result<String, String>,
+}
+
+/// The commands that are still running.
+#[derive(Resource, Debug, Default)]
+struct RunningKittyCommands(Vec<(KittyCommand, JoinHandle<Result<String, String>>)>);
+
+fn run_kitty_commands(
+    mut commands: EventReader<KittyCommand>,
+    mut running: ResMut<RunningKittyCommands>,
+) {
+    for command in commands.read() {
+        let args = command.args();
+        let thread = thread::spawn(move || {
+            let output = Command::new("kitten")
+                .arg("@")
+                .args(&args)
+                .output()
+                .map_err(|err| format!("could not run kitten: {err}"))?;
+            if output.status.success() {
+                Ok(String::from_utf8_lossy(&output.stdout)
+                    .trim_end()
+                    .to_string())
+            } else {
+                Err(String::from_utf8_lossy(&output.stderr)
+                    .trim_end()
+                    .to_string())
+            }
+        });
+        running.0.push((command.clone(), thread));
+    }
+}
+
+fn finish_kitty_commands(
+    mut running: ResMut<RunningKittyCommands>,
+    mut results: EventWriter<KittyCommandResult>,
+) {
+    let (finished, still_running) = running
+        .0
+        .drain(..)
+        .partition(|(_, thread)| thread.is_finished());
+    running.0 = still_running;
+    for (command, thread) in finished {
+        let output = thread
+            .join()
+            .unwrap_or_else(|_| Err("the command panicked".to_string()));
+        results.send(KittyCommandResult { command, output });
+    }
+}
@@ -0,0 +1,134 @@
+//! Simulated latency for trying out an app over a slow connection.
+//!
+//! Over SSH, every key press takes a round trip before its effect is on screen, and a slow link
+//! makes writes to the terminal block. Insert a [`SimulatedLatency`] resource to feel this
+//! locally: terminal events are held back by a delay plus random jitter before the app sees them,
+//! and each write to the terminal is followed by an output delay. The events keep their order, as
+//! they would over a real connection.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//!
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{latency::SimulatedLatency, RatatuiPlugins};
+//!
+//! App::new()
+//!     .add_plugins(RatatuiPlugins::default())
+//!     .insert_resource(
+//!         SimulatedLatency::new(Duration::from_millis(80))
+//!             .with_jitter(Duration::from_millis(40))
+//!             .with_output_delay(Duration::from_millis(20)),
+//!     );
+//! ```
+//!
+//! The jitter comes from a fixed seed, so a run with the same input is delayed the same way each
+//! time. Events that are still held back are counted in
+//! [`EventStats::queue_depth`](crate::event::EventStats::queue_depth).
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use bevy::{ecs::event::EventUpdates, prelude::*};
+use crossterm::event::Event;
+
+use crate::{event::InputSet, terminal};
+
+/// A plugin that applies the output delay of the [`SimulatedLatency`] resource, if there is one.
+///
+/// This is part of [`RatatuiPlugins`](crate::RatatuiPlugins). The input delay is applied where the
+/// terminal events are read.
+pub struct LatencySimulationPlugin;
+
+impl Plugin for LatencySimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            First,
+            sync_output_delay.after(EventUpdates).before(InputSet::Pre),
+        );
+    }
+}
+
+/// Delays terminal input and output by the given amounts while present.
+#[derive(Resource, Debug, Clone)]
+pub struct SimulatedLatency {
+    /// How long each terminal event is held back.
+    pub input_delay: Duration,
+    /// Up to how much longer each event is held back, chosen at random per event.
+    pub input_jitter: Duration,
+    /// How long each write to the terminal blocks for.
+    pub output_delay: Duration,
+    queue: VecDeque<(Instant, Event)>,
+    rng: u64,
+}
+
+impl Default for SimulatedLatency {
+    fn default() -> Self {
+        Self::new(Duration::ZERO)
+    }
+}
+
+impl SimulatedLatency {
+    /// Delays terminal events by `input_delay`.
+    pub fn new(input_delay: Duration) -> Self {
+        Self {
+            input_delay,
+            input_jitter: Duration::ZERO,
+            output_delay: Duration::ZERO,
+            queue: VecDeque::new(),
+            rng: 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    /// Delays each event by up to `input_jitter` more.
+    pub fn with_jitter(mut self, input_jitter: Duration) -> Self {
+        self.input_jitter = input_jitter;
+        self
+    }
+
+    /// Makes each write to the terminal block for `output_delay`.
+    pub fn with_output_delay(mut self, output_delay: Duration) -> Self {
+        self.output_delay = output_delay;
+        self
+    }
+
+    /// The number of events that are held back.
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Holds back events that were just read, and returns the events that are due, oldest first.
+    pub fn delay(&mut self, events: impl IntoIterator<Item = Event>) -> Vec<Event> {
+        let now = Instant::now();
+        for event in events {
+            let mut due = now + self.input_delay + self.jitter();
+            // Later events never overtake earlier ones.
+            if let Some((last_due, _)) = self.queue.back() {
+                due = due.max(*last_due);
+            }
+            self.queue.push_back((due, event));
+        }
+        let ready = self.queue.iter().take_while(|(due, _)| *due <= now).count();
+        self.queue.drain(..ready).map(|(_, event)| event).collect()
+    }
+
+    /// A random duration up to the jitter, from an xorshift generator.
+    fn jitter(&mut self) -> Duration {
+        if self.input_jitter.is_zero() {
+            return Duration::ZERO;
+        }
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        let fraction = (self.rng >> 11) as f64 / (1u64 << 53) as f64;
+        self.input_jitter.mul_f64(fraction)
+    }
+}
+
+fn sync_output_delay(latency: Option<Res<SimulatedLatency>>, mut applied: Local<Duration>) {
+    let output_delay = latency.map_or(Duration::ZERO, |latency| latency.output_delay);
+    if output_delay != *applied {
+        *applied = output_delay;
+        terminal::set_output_delay(output_delay);
+    }
+}
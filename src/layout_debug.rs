@@ -0,0 +1,178 @@
+//! Outlines of layout rects for debugging.
+//!
+//! Layouts are computed inside draw systems, so the crate cannot see them by itself. Record the
+//! areas a draw system computes in the [`LayoutDebug`] resource, and [`LayoutDebugPlugin`] outlines
+//! each of them with its label and constraint on top of the frame while the debug mode is on. The
+//! [`LayoutDebugKey`] (`F6` by default) toggles it. This helps to find out why a pane ends up the
+//! wrong size.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     layout_debug::{LayoutDebug, LayoutDebugPlugin},
+//!     terminal::RatatuiContext,
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::layout::{Constraint, Layout};
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), LayoutDebugPlugin))
+//!     .add_systems(Update, draw);
+//!
+//! fn draw(mut context: ResMut<RatatuiContext>, mut debug: ResMut<LayoutDebug>) {
+//!     let _ = context.draw(|frame| {
+//!         let constraints = [Constraint::Length(20), Constraint::Fill(1)];
+//!         let areas = Layout::horizontal(constraints).split(frame.area());
+//!         debug.record_split(["sidebar", "main"], &constraints, &areas);
+//!     });
+//! }
+//! ```
+use bevy::prelude::*;
+use color_eyre::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Style},
+    widgets::Block,
+};
+
+use crate::{
+    error::exit_on_error,
+    event::{InputSet, KeyEvent},
+    quit::KeyBinding,
+    terminal::RatatuiContext,
+};
+
+/// A plugin that outlines the rects recorded in [`LayoutDebug`] while the debug mode is on.
+pub struct LayoutDebugPlugin;
+
+impl Plugin for LayoutDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LayoutDebug>()
+            .init_resource::<LayoutDebugKey>()
+            .add_systems(First, clear_rects)
+            .add_systems(PreUpdate, layout_debug_input_system.in_set(InputSet::Post))
+            .add_systems(
+                Last,
+                draw_layout_debug
+                    .pipe(exit_on_error)
+                    .run_if(layout_debug_enabled.and(resource_exists::<RatatuiContext>)),
+            );
+    }
+}
+
+/// A run condition that is true while the layout debug mode is on.
+pub fn layout_debug_enabled(debug: Option<Res<LayoutDebug>>) -> bool {
+    debug.is_some_and(|debug| debug.enabled)
+}
+
+/// The key that toggles the layout debug mode.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct LayoutDebugKey(pub KeyBinding);
+
+impl Default for LayoutDebugKey {
+    fn default() -> Self {
+        Self(KeyBinding::new(KeyCode::F(6)))
+    }
+}
+
+/// A rect recorded for the layout debug overlay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugRect {
+    pub label: String,
+    pub area: Rect,
+    /// The constraint the area was computed from, if known.
+    pub constraint: Option<Constraint>,
+}
+
+/// The layout rects computed this frame.
+///
+/// The rects are cleared at the start of every frame.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct LayoutDebug {
+    /// Whether the rects are outlined.
+    pub enabled: bool,
+    rects: Vec<DebugRect>,
+}
+
+impl LayoutDebug {
+    /// Records an area.
+    pub fn record(&mut self, label: impl Into<String>, area: Rect) {
+        self.rects.push(DebugRect {
+            label: label.into(),
+            area,
+            constraint: None,
+        });
+    }
+
+    /// Records the areas of a split along with the constraints they were computed from.
+    pub fn record_split<L: Into<String>>(
+        &mut self,
+        labels: impl IntoIterator<Item = L>,
+        constraints: &[Constraint],
+        areas: &[Rect],
+    ) {
+        for ((label, constraint), area) in labels.into_iter().zip(constraints).zip(areas) {
+            self.rects.push(DebugRect {
+                label: label.into(),
+                area: *area,
+                constraint: Some(*constraint),
+            });
+        }
+    }
+
+    /// The rects recorded this frame, in the order they were recorded.
+    pub fn rects(&self) -> &[DebugRect] {
+        &self.rects
+    }
+}
+
+fn clear_rects(mut debug: ResMut<LayoutDebug>) {
+    debug.rects.clear();
+}
+
+fn layout_debug_input_system(
+    mut keys: EventReader<KeyEvent>,
+    toggle: Res<LayoutDebugKey>,
+    mut debug: ResMut<LayoutDebug>,
+) {
+    for key in keys.read() {
+        if toggle.matches(key) {
+            debug.enabled = !debug.enabled;
+        }
+    }
+}
+
+const COLORS: [Color; 6] = [
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+];
+
+/// Outlines the recorded rects over the last frame, each with its label, size and constraint.
+pub fn draw_layout_debug(
+    mut context: ResMut<RatatuiContext>,
+    debug: Res<LayoutDebug>,
+) -> Result<()> {
+    let last_frame = context.last_frame().clone();
+    context.draw(|frame| {
+        let area = frame.area();
+        if last_frame.area == area {
+            *frame.buffer_mut() = last_frame;
+        }
+        for (rect, color) in debug.rects.iter().zip(COLORS.iter().cycle()) {
+            let mut title = format!("{} {}x{}", rect.label, rect.area.width, rect.area.height);
+            if let Some(constraint) = rect.constraint {
+                title.push_str(&format!(" {constraint}"));
+            }
+            let block = Block::bordered()
+                .border_style(Style::new().fg(*color))
+                .title(title);
+            frame.render_widget(block, rect.area.intersection(area));
+        }
+    })?;
+    Ok(())
+}
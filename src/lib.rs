@@ -3,20 +3,17 @@
 //! # Example
 //!
 //! ```rust,no_run
-//! use bevy::{
-//!     app::{AppExit, ScheduleRunnerPlugin},
-//!     prelude::*,
-//! };
+//! use bevy::{app::ScheduleRunnerPlugin, prelude::*};
 //! use bevy_ratatui::{
-//!     error::exit_on_error, event::KeyEvent, terminal::RatatuiContext, RatatuiPlugins,
+//!     error::exit_on_error, quit::QuitPlugin, terminal::RatatuiContext, RatatuiPlugins,
 //! };
 //!
 //! fn main() {
 //!     let wait_duration = std::time::Duration::from_secs_f64(1. / 60.); // 60 FPS
 //!     App::new()
 //!         .add_plugins(RatatuiPlugins::default())
+//!         .add_plugins(QuitPlugin)
 //!         .add_plugins(ScheduleRunnerPlugin::run_loop(wait_duration))
-//!         .add_systems(PreUpdate, keyboard_input_system)
 //!         .add_systems(Update, hello_world.pipe(exit_on_error))
 //!         .run();
 //! }
@@ -28,21 +25,11 @@
 //!     })?;
 //!     Ok(())
 //! }
-//!
-//! fn keyboard_input_system(mut events: EventReader<KeyEvent>, mut exit: EventWriter<AppExit>) {
-//!     use crossterm::event::KeyCode; // beware bevy prelude also has a KeyCode enum
-//!     for event in events.read() {
-//!         match event.code {
-//!             KeyCode::Char('q') | KeyCode::Esc => {
-//!                 exit.send_default();
-//!             }
-//!             _ => {}
-//!         }
-//!     }
-//! }
 //! ```
 //!
-//! See the [examples] directory for more examples.
+//! See the [examples] directory for more examples. For apps where keypress-to-screen latency
+//! matters, such as editors, use the [runner::InputWakeRunnerPlugin] in place of the
+//! `ScheduleRunnerPlugin`.
 //!
 //! # Input Forwarding
 //!
@@ -53,12 +40,87 @@
 //! [Ratatui]: https://ratatui.rs
 //! [examples]: https://github.com/joshka/bevy_ratatui/tree/main/examples
 
+pub mod action;
+pub mod anchor;
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod buffer;
+pub mod cell_metrics;
+#[cfg(feature = "clock")]
+pub mod clock;
+pub mod collision;
+pub mod color;
+pub mod condition;
+pub mod diagnostics;
+pub mod draw_queue;
+pub mod env;
 pub mod error;
 pub mod event;
+pub mod exit;
+pub mod extension;
+pub mod file_picker;
+pub mod fixed_input;
+pub mod frame_export;
+pub mod frame_step;
+pub mod handshake;
+pub mod heatmap;
+pub mod history;
 pub mod input_forwarding;
+pub mod input_snapshot;
+pub mod input_thread;
+pub mod inspect;
+pub mod interpolation;
+pub mod iterm2;
 pub mod kitty;
+#[cfg(feature = "kitty-remote")]
+pub mod kitty_remote;
+pub mod latency;
+pub mod layout_debug;
+pub mod loading;
 pub mod mouse;
+pub mod output_pane;
+pub mod pager;
+pub mod paste;
+pub mod profiling;
+#[cfg(feature = "pty")]
+pub mod pty;
+pub mod quit;
 mod ratatui;
+pub mod redraw;
+pub mod refresh;
+mod remote_input;
+pub mod render;
+pub mod render_app;
+pub mod rollback;
+pub mod routing;
+pub mod runner;
+pub mod screensaver;
+pub mod search;
+#[cfg(feature = "sigwinch")]
+pub mod sigwinch;
+pub mod snapshot;
+#[cfg(feature = "ssh")]
+pub mod ssh;
+pub mod stdio_guard;
+pub mod supervisor;
+pub mod suspend;
+#[cfg(feature = "syntax-highlighting")]
+pub mod syntax;
+#[cfg(feature = "sysinfo")]
+pub mod system_metrics;
+pub mod table;
+pub mod tail_file;
 pub mod terminal;
+#[cfg(feature = "termwiz")]
+pub mod termwiz;
+pub mod text_buffer;
+pub mod throttle;
+pub mod timeline;
+pub mod tree;
+pub mod virtual_list;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+pub mod wezterm;
+pub mod widget;
 
 pub use ratatui::RatatuiPlugins;
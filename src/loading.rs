@@ -0,0 +1,270 @@
+//! A splash screen shown while the app loads.
+//!
+//! [`LoadingPlugin`] draws a [`SplashScreen`] with a progress gauge until everything registered in
+//! [`LoadingProgress`] has finished and the splash has been shown for at least
+//! [`SplashScreen::min_duration`]. It then sends a [`LoadingFinished`] event and stops drawing.
+//!
+//! Work is registered either as background tasks with [`LoadingProgress::spawn`], which run on the
+//! [`IoTaskPool`] and are counted as done when they finish, or as steps with
+//! [`LoadingProgress::add_steps`] that the app marks done with [`LoadingProgress::complete_steps`].
+//! With the `asset` feature, assets loaded by the [`AssetServer`](bevy::asset::AssetServer) can be
+//! registered with `LoadingProgress::track`, and are counted as done once they and their
+//! dependencies have loaded, or failed to.
+//!
+//! Gate the systems that draw the main screen with the [`loading_finished`] run condition. The
+//! [`RootWidget`](crate::widget::RootWidget) is not drawn until loading has finished. Apps that use
+//! states can switch to the main state when [`LoadingFinished`] is sent.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     loading::{loading_finished, LoadingPlugin, LoadingProgress},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), LoadingPlugin))
+//!     .add_systems(Startup, |mut progress: ResMut<LoadingProgress>| {
+//!         progress.spawn("config", async {
+//!             let _config = std::fs::read_to_string("config.toml");
+//!         });
+//!     })
+//!     .add_systems(Update, draw_main_screen.run_if(loading_finished));
+//!
+//! fn draw_main_screen() {}
+//! ```
+use std::{future::Future, time::Duration};
+
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, IoTaskPool, Task, TaskPool},
+};
+use color_eyre::Result;
+use ratatui::{
+    layout::{Constraint, Flex, Layout},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::Gauge,
+};
+
+#[cfg(feature = "asset")]
+use bevy::asset::{LoadState, RecursiveDependencyLoadState, UntypedHandle};
+
+use crate::{
+    error::exit_on_error,
+    terminal::{RatatuiContext, TerminalSet},
+};
+
+/// A plugin that shows a [`SplashScreen`] until the [`LoadingProgress`] has finished.
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SplashScreen>()
+            .init_resource::<LoadingProgress>()
+            .add_event::<LoadingFinished>()
+            .add_systems(First, update_loading.run_if(not(loading_finished)))
+            .add_systems(
+                PostUpdate,
+                draw_splash_screen
+                    .pipe(exit_on_error)
+                    .run_if(not(loading_finished).and(resource_exists::<RatatuiContext>))
+                    .before(TerminalSet::Cleanup),
+            );
+    }
+}
+
+/// A run condition that is true once loading has finished, or if there is nothing to load.
+pub fn loading_finished(progress: Option<Res<LoadingProgress>>) -> bool {
+    progress.is_none_or(|progress| progress.is_finished())
+}
+
+/// Sent once when loading has finished.
+#[derive(Debug, Clone, Copy, Default, Event)]
+pub struct LoadingFinished;
+
+/// What the splash screen shows.
+#[derive(Resource, Debug, Clone)]
+pub struct SplashScreen {
+    pub title: String,
+    /// The shortest time the splash is shown for, so that it does not flash by.
+    pub min_duration: Duration,
+}
+
+impl Default for SplashScreen {
+    fn default() -> Self {
+        Self {
+            title: "Loading".into(),
+            min_duration: Duration::from_millis(500),
+        }
+    }
+}
+
+/// The work to finish before the splash screen is replaced by the main screen.
+#[derive(Resource, Default)]
+pub struct LoadingProgress {
+    total: usize,
+    done: usize,
+    tasks: Vec<(String, Task<()>)>,
+    #[cfg(feature = "asset")]
+    assets: Vec<UntypedHandle>,
+    message: Option<String>,
+    finished: bool,
+}
+
+impl LoadingProgress {
+    /// Runs a future on the [`IoTaskPool`] as a loading step, which is done when it finishes.
+    pub fn spawn(
+        &mut self,
+        label: impl Into<String>,
+        future: impl Future<Output = ()> + Send + 'static,
+    ) {
+        let task = IoTaskPool::get_or_init(TaskPool::new).spawn(future);
+        self.tasks.push((label.into(), task));
+        self.total += 1;
+    }
+
+    /// Adds an asset as a loading step, which is done once the asset and its dependencies have
+    /// loaded, or failed to. This requires the `asset` feature.
+    ///
+    /// The handle is let go of once the asset has loaded, so keep one of your own for the asset to
+    /// stay loaded.
+    ///
+    /// ```rust
+    /// use bevy::prelude::*;
+    /// use bevy_ratatui::loading::LoadingProgress;
+    ///
+    /// #[derive(Asset, TypePath)]
+    /// struct Map(String);
+    ///
+    /// #[derive(Resource)]
+    /// struct CurrentMap(Handle<Map>);
+    ///
+    /// fn load_map(
+    ///     mut commands: Commands,
+    ///     asset_server: Res<AssetServer>,
+    ///     mut progress: ResMut<LoadingProgress>,
+    /// ) {
+    ///     let map = asset_server.load("maps/start.map");
+    ///     progress.track(map.clone());
+    ///     commands.insert_resource(CurrentMap(map));
+    /// }
+    /// ```
+    #[cfg(feature = "asset")]
+    pub fn track(&mut self, handle: impl Into<UntypedHandle>) {
+        self.assets.push(handle.into());
+        self.total += 1;
+    }
+
+    /// Adds steps that the app marks as done with [`LoadingProgress::complete_steps`].
+    pub fn add_steps(&mut self, steps: usize) {
+        self.total += steps;
+    }
+
+    /// Marks steps as done.
+    pub fn complete_steps(&mut self, steps: usize) {
+        self.done = (self.done + steps).min(self.total);
+    }
+
+    /// Sets the message shown under the gauge, e.g. what is loading.
+    pub fn set_message(&mut self, message: impl Into<String>) {
+        self.message = Some(message.into());
+    }
+
+    /// The fraction of the steps that are done, from 0 to 1.
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        self.done as f64 / self.total as f64
+    }
+
+    /// Returns true once loading has finished and the splash screen has been shown long enough.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+fn update_loading(
+    mut progress: ResMut<LoadingProgress>,
+    splash: Res<SplashScreen>,
+    time: Res<Time<Real>>,
+    mut finished: EventWriter<LoadingFinished>,
+    #[cfg(feature = "asset")] asset_server: Option<Res<AssetServer>>,
+) {
+    let progress = &mut *progress;
+    let before = progress.tasks.len();
+    progress
+        .tasks
+        .retain_mut(|(_, task)| block_on(future::poll_once(task)).is_none());
+    progress.complete_steps(before - progress.tasks.len());
+    #[cfg(feature = "asset")]
+    if let Some(asset_server) = asset_server {
+        let before = progress.assets.len();
+        progress
+            .assets
+            .retain(|handle| !asset_finished(&asset_server, handle));
+        progress.complete_steps(before - progress.assets.len());
+    }
+    let label = progress.tasks.first().map(|(label, _)| label.clone());
+    #[cfg(feature = "asset")]
+    let label = label.or_else(|| {
+        let path = progress.assets.iter().find_map(UntypedHandle::path)?;
+        Some(path.to_string())
+    });
+    if let Some(label) = label {
+        progress.message = Some(format!("Loading {label}..."));
+    }
+    if progress.done == progress.total && time.elapsed() >= splash.min_duration {
+        progress.finished = true;
+        finished.send_default();
+    }
+}
+
+/// Whether the asset and its dependencies have loaded, or failed to, which is logged.
+#[cfg(feature = "asset")]
+fn asset_finished(asset_server: &AssetServer, handle: &UntypedHandle) -> bool {
+    if let LoadState::Failed(err) = asset_server.load_state(handle.id()) {
+        warn!("Failed to load an asset: {err}");
+        return true;
+    }
+    match asset_server.recursive_dependency_load_state(handle.id()) {
+        RecursiveDependencyLoadState::Loaded => true,
+        RecursiveDependencyLoadState::Failed(err) => {
+            warn!("Failed to load a dependency of an asset: {err}");
+            true
+        }
+        RecursiveDependencyLoadState::NotLoaded | RecursiveDependencyLoadState::Loading => false,
+    }
+}
+
+/// Draws the splash screen: the title and a progress gauge in the middle of the terminal.
+pub fn draw_splash_screen(
+    mut context: ResMut<RatatuiContext>,
+    splash: Res<SplashScreen>,
+    progress: Res<LoadingProgress>,
+) -> Result<()> {
+    context.draw(|frame| {
+        let [area] = Layout::horizontal([Constraint::Max(60)])
+            .flex(Flex::Center)
+            .areas(frame.area());
+        let [title, gauge, message] = Layout::vertical([Constraint::Length(1); 3])
+            .spacing(1)
+            .flex(Flex::Center)
+            .areas(area);
+        frame.render_widget(
+            Line::from(splash.title.as_str())
+                .style(Style::new().add_modifier(Modifier::BOLD))
+                .centered(),
+            title,
+        );
+        frame.render_widget(
+            Gauge::default().ratio(progress.ratio()).use_unicode(true),
+            gauge,
+        );
+        if let Some(text) = &progress.message {
+            frame.render_widget(Line::from(text.as_str()).centered(), message);
+        }
+    })?;
+    Ok(())
+}
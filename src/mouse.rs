@@ -1,33 +1,181 @@
 //! Mouse support.
-use std::io::stdout;
+//!
+//! [`MousePlugin`] captures the mouse on startup. Send [`SetMouseCapture`] events to release and
+//! capture it again while the app runs, e.g. so that users can select text natively in a pause
+//! mode. Removing and inserting the [`MouseCaptureEnabled`] resource does the same.
+//!
+//! Capturing the mouse stops the terminal from selecting text with it. To keep parts of the screen
+//! copy-friendly, spawn entities with a [`MousePassthrough`] component. Mouse events inside their
+//! regions are dropped instead of being sent as [`MouseEvent`](crate::event::MouseEvent)s.
+//!
+//! Terminals only report the mouse as a whole, so the capture cannot be lifted for part of the
+//! screen. Most terminals still select text natively when `Shift` is held while dragging, which
+//! is what a passthrough region is meant to be used with.
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::mouse::MousePassthrough;
+//!
+//! fn spawn_log_pane(mut commands: Commands) {
+//!     // The bottom third of the screen shows logs that users want to copy.
+//!     commands.spawn(MousePassthrough::Percent {
+//!         x: 0,
+//!         y: 67,
+//!         width: 100,
+//!         height: 33,
+//!     });
+//! }
+//! ```
+use std::io;
 
 use bevy::prelude::*;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     ExecutableCommand,
 };
+use ratatui::layout::{Position, Rect, Size};
 
-use crate::error::exit_on_error;
+use crate::{
+    error::exit_on_error,
+    event::InputSet,
+    terminal::{output, RatatuiContext, TerminalSet, TerminalStartup},
+};
 
 pub struct MousePlugin;
 
 impl Plugin for MousePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup.pipe(exit_on_error));
+        app.init_resource::<MousePassthroughAreas>()
+            .add_systems(
+                TerminalStartup,
+                setup.pipe(exit_on_error).in_set(TerminalSet::Features),
+            )
+            // The areas are used when the next frame's events are read, whichever schedule that is
+            // in.
+            .add_systems(Last, update_passthrough_areas)
+            .add_event::<SetMouseCapture>()
+            .add_systems(
+                PreUpdate,
+                set_mouse_capture
+                    .pipe(exit_on_error)
+                    .in_set(InputSet::Pre)
+                    .in_set(TerminalSet::Features)
+                    .ambiguous_with(TerminalSet::Features),
+            );
     }
 }
 
-#[derive(Resource, Default)]
-pub struct MouseCaptureEnabled;
+/// Present while the mouse is captured. Removing it releases the mouse.
+#[derive(Resource, Debug)]
+pub struct MouseCaptureEnabled(());
+
+impl MouseCaptureEnabled {
+    /// Captures the mouse, writing to the stream set by the
+    /// [`OutputTarget`](crate::terminal::OutputTarget). Insert the returned resource to keep it
+    /// captured.
+    pub fn enable() -> io::Result<Self> {
+        output().execute(EnableMouseCapture)?;
+        Ok(Self(()))
+    }
+}
 
 fn setup(mut commands: Commands) -> color_eyre::Result<()> {
-    stdout().execute(EnableMouseCapture)?;
-    commands.insert_resource(MouseCaptureEnabled);
+    commands.insert_resource(MouseCaptureEnabled::enable()?);
+    Ok(())
+}
+
+/// Captures or releases the mouse at the start of the next frame.
+#[derive(Debug, Clone, Copy, Event, PartialEq, Eq, Hash)]
+pub enum SetMouseCapture {
+    Enable,
+    Disable,
+}
+
+fn set_mouse_capture(
+    mut commands: Commands,
+    mut events: EventReader<SetMouseCapture>,
+    enabled: Option<Res<MouseCaptureEnabled>>,
+) -> color_eyre::Result<()> {
+    match (events.read().last(), enabled) {
+        (Some(SetMouseCapture::Enable), None) => {
+            commands.insert_resource(MouseCaptureEnabled::enable()?);
+        }
+        (Some(SetMouseCapture::Disable), Some(_)) => {
+            commands.remove_resource::<MouseCaptureEnabled>();
+        }
+        _ => {}
+    }
     Ok(())
 }
 
 impl Drop for MouseCaptureEnabled {
     fn drop(&mut self) {
-        let _ = stdout().execute(DisableMouseCapture);
+        let _ = output().execute(DisableMouseCapture);
+    }
+}
+
+/// A region of the screen where mouse events are ignored.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MousePassthrough {
+    /// A fixed area of the screen.
+    Area(Rect),
+    /// An area given in percent of the terminal size, which follows resizes.
+    Percent {
+        x: u16,
+        y: u16,
+        width: u16,
+        height: u16,
+    },
+}
+
+impl MousePassthrough {
+    /// The area of the region on a terminal of the given size.
+    pub fn area(&self, size: Size) -> Rect {
+        let screen = Rect::from((Position::ORIGIN, size));
+        match *self {
+            Self::Area(area) => area.intersection(screen),
+            Self::Percent {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                let percent = |value: u16, total: u16| {
+                    (u32::from(value.min(100)) * u32::from(total) / 100) as u16
+                };
+                let area = Rect::new(
+                    percent(x, size.width),
+                    percent(y, size.height),
+                    percent(width, size.width),
+                    percent(height, size.height),
+                );
+                area.intersection(screen)
+            }
+        }
+    }
+}
+
+/// The areas of all [`MousePassthrough`] regions, updated at the end of every frame.
+#[derive(Resource, Debug, Clone, Default, PartialEq, Eq)]
+pub struct MousePassthroughAreas(pub Vec<Rect>);
+
+impl MousePassthroughAreas {
+    /// Whether the cell at the given position is in a passthrough region.
+    pub fn contains(&self, position: Position) -> bool {
+        self.0.iter().any(|area| area.contains(position))
+    }
+}
+
+fn update_passthrough_areas(
+    context: Option<Res<RatatuiContext>>,
+    regions: Query<&MousePassthrough>,
+    mut areas: ResMut<MousePassthroughAreas>,
+) {
+    let Some(Ok(size)) = context.map(|context| context.size()) else {
+        return;
+    };
+    let new_areas: Vec<Rect> = regions.iter().map(|region| region.area(size)).collect();
+    if areas.0 != new_areas {
+        areas.0 = new_areas;
     }
 }
@@ -0,0 +1,518 @@
+//! Showing the output of build tools, tests and other non-interactive commands.
+//!
+//! An [`OutputPane`] runs a command with its stdout and stderr piped, reads both on background
+//! threads, and keeps the lines with their ANSI colors and styles. [`OutputPanePlugin`] draws each
+//! pane into its [`AnchoredArea`], following the end of the output as it grows, and scrolls it with
+//! the keys and mouse wheel [routed](crate::routing) to it. Programs that need a terminal, such as
+//! shells and editors, belong in a [`PtyPane`](crate::pty::PtyPane) instead.
+//!
+//! ```rust,no_run
+//! use std::process::Command;
+//!
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     anchor::AnchoredArea,
+//!     output_pane::{OutputExited, OutputPane, OutputPanePlugin},
+//!     routing::FocusedPane,
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::layout::Rect;
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), OutputPanePlugin))
+//!     .add_systems(Startup, build)
+//!     .add_systems(Update, report)
+//!     .run();
+//!
+//! fn build(mut commands: Commands, mut focused: ResMut<FocusedPane>) {
+//!     let mut cargo = Command::new("cargo");
+//!     cargo.args(["build", "--color=always"]);
+//!     match OutputPane::spawn(&mut cargo) {
+//!         Ok(pane) => {
+//!             let area = AnchoredArea(Rect::new(0, 0, 80, 24));
+//!             focused.0 = Some(commands.spawn((pane, area)).id());
+//!         }
+//!         Err(err) => error!("Failed to run cargo: {err}"),
+//!     }
+//! }
+//!
+//! fn report(mut exited: EventReader<OutputExited>) {
+//!     for exited in exited.read() {
+//!         info!("cargo exited with {}", exited.status);
+//!     }
+//! }
+//! ```
+//!
+//! While focused, `Up`/`k` and `Down`/`j` scroll by a line, `PageUp` and `PageDown` by a page, and
+//! `Home`/`g` jumps to the start. Scrolling up stops following the output, and `End`/`G` follows it
+//! again. `Tab` switches the [`OutputView`] between both streams, stdout only and stderr only.
+//! Despawning the pane kills the command if it is still running.
+use std::{
+    collections::VecDeque,
+    io::{self, BufRead, BufReader, Read},
+    mem,
+    process::{Child, Command, ExitStatus, Stdio},
+    sync::{
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Mutex, PoisonError,
+    },
+    thread,
+};
+
+use bevy::prelude::*;
+use crossterm::event::{KeyCode, KeyEventKind, MouseEventKind};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::{
+    anchor::AnchoredArea,
+    event::InputSet,
+    redraw::RedrawRequested,
+    render::{RenderBuffer, RenderPlugin, RenderSet},
+    routing::{InputRoutingPlugin, InputTarget, RoutedKeyEvent, RoutedMouseEvent},
+    widget::{draw_root_widget, is_interactive, Interactive, WidgetVisibility},
+};
+
+/// How many lines the mouse wheel scrolls by.
+const WHEEL_LINES: isize = 3;
+
+/// A plugin that reads, draws and scrolls [`OutputPane`]s.
+///
+/// This requires the [`RenderPlugin`] and the [`InputRoutingPlugin`], and adds them if they are
+/// missing.
+pub struct OutputPanePlugin;
+
+impl Plugin for OutputPanePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RenderPlugin>() {
+            app.add_plugins(RenderPlugin);
+        }
+        if !app.is_plugin_added::<InputRoutingPlugin>() {
+            app.add_plugins(InputRoutingPlugin);
+        }
+        app.add_event::<OutputExited>()
+            .add_systems(
+                PreUpdate,
+                (scroll_output_panes, read_command_output)
+                    .chain()
+                    .after(InputSet::Post),
+            )
+            .add_systems(
+                PostUpdate,
+                draw_output_panes
+                    .after(draw_root_widget)
+                    .in_set(RenderSet::Widgets),
+            );
+    }
+}
+
+/// The stream a line of output was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// Which lines an [`OutputPane`] shows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputView {
+    /// The lines of both streams, in the order they were read.
+    #[default]
+    Merged,
+    /// Only the lines written to stdout.
+    Stdout,
+    /// Only the lines written to stderr.
+    Stderr,
+}
+
+impl OutputView {
+    /// Whether the view shows lines written to `stream`.
+    pub fn shows(self, stream: OutputStream) -> bool {
+        match self {
+            OutputView::Merged => true,
+            OutputView::Stdout => stream == OutputStream::Stdout,
+            OutputView::Stderr => stream == OutputStream::Stderr,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            OutputView::Merged => OutputView::Stdout,
+            OutputView::Stdout => OutputView::Stderr,
+            OutputView::Stderr => OutputView::Merged,
+        }
+    }
+}
+
+/// A command whose output is shown in a pane.
+///
+/// The pane is drawn in its [`AnchoredArea`], and takes the keys and mouse events routed to it, as
+/// an [`InputTarget`], unless it is [`Disabled`](crate::widget::Disabled) or hidden.
+#[derive(Component, Debug)]
+#[require(InputTarget, AnchoredArea)]
+pub struct OutputPane {
+    /// Which lines the pane shows. Defaults to [`OutputView::Merged`].
+    pub view: OutputView,
+    lines: VecDeque<(OutputStream, Line<'static>)>,
+    capacity: usize,
+    /// The index of the first line on screen among the lines of the view, or `None` to follow the
+    /// end of the output.
+    offset: Option<usize>,
+    /// The number of lines that fit in the pane, as of the last draw.
+    page_height: usize,
+    child: Child,
+    output: Mutex<Receiver<(OutputStream, Vec<u8>)>>,
+    exit_status: Option<ExitStatus>,
+}
+
+impl OutputPane {
+    /// Runs `command` with stdout and stderr piped to the pane, and stdin closed. The pane keeps
+    /// the last 10,000 lines.
+    pub fn spawn(command: &mut Command) -> io::Result<Self> {
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let (sender, output) = mpsc::channel();
+        if let Some(stdout) = child.stdout.take() {
+            read_lines(stdout, OutputStream::Stdout, sender.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            read_lines(stderr, OutputStream::Stderr, sender);
+        }
+        Ok(Self {
+            view: OutputView::default(),
+            lines: VecDeque::new(),
+            capacity: 10_000,
+            offset: None,
+            page_height: 0,
+            child,
+            output: Mutex::new(output),
+            exit_status: None,
+        })
+    }
+
+    /// Keeps at most `capacity` lines, dropping the oldest first.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// The lines read so far with the stream they were written to, oldest first.
+    pub fn lines(&self) -> impl ExactSizeIterator<Item = (OutputStream, &Line<'static>)> {
+        self.lines.iter().map(|(stream, line)| (*stream, line))
+    }
+
+    /// How the command exited, or `None` while it runs.
+    pub fn exit_status(&self) -> Option<ExitStatus> {
+        self.exit_status
+    }
+
+    /// Whether the pane follows the end of the output.
+    pub fn is_following(&self) -> bool {
+        self.offset.is_none()
+    }
+
+    /// Follows the end of the output, showing new lines as they are read.
+    pub fn follow(&mut self) {
+        self.offset = None;
+    }
+
+    /// Scrolls by `lines`, up if negative. Scrolling stops following the output unless it reaches
+    /// the end.
+    pub fn scroll_by(&mut self, lines: isize) {
+        let max_offset = self.max_offset();
+        let offset = self
+            .offset
+            .unwrap_or(max_offset)
+            .min(max_offset)
+            .saturating_add_signed(lines)
+            .min(max_offset);
+        self.offset = (offset < max_offset).then_some(offset);
+    }
+
+    /// Scrolls to the first line.
+    pub fn scroll_to_top(&mut self) {
+        self.offset = (self.max_offset() > 0).then_some(0);
+    }
+
+    fn visible_len(&self) -> usize {
+        match self.view {
+            OutputView::Merged => self.lines.len(),
+            view => self
+                .lines
+                .iter()
+                .filter(|(stream, _)| view.shows(*stream))
+                .count(),
+        }
+    }
+
+    fn max_offset(&self) -> usize {
+        self.visible_len().saturating_sub(self.page_height)
+    }
+
+    fn push(&mut self, stream: OutputStream, line: &[u8]) {
+        if self.lines.len() == self.capacity {
+            if let Some((dropped, _)) = self.lines.pop_front() {
+                // Keep the same lines on screen while scrolled back.
+                if self.view.shows(dropped) {
+                    self.offset = self.offset.map(|offset| offset.saturating_sub(1));
+                }
+            }
+        }
+        self.lines.push_back((stream, parse_line(line)));
+    }
+
+    /// Takes the lines the command has written since the last call. Returns whether there were
+    /// any, and whether both streams have ended.
+    fn read_output(&mut self) -> (bool, bool) {
+        let mut read = false;
+        loop {
+            let next = self
+                .output
+                .get_mut()
+                .unwrap_or_else(PoisonError::into_inner)
+                .try_recv();
+            match next {
+                Ok((stream, line)) => {
+                    self.push(stream, &line);
+                    read = true;
+                }
+                Err(TryRecvError::Empty) => return (read, false),
+                Err(TryRecvError::Disconnected) => return (read, true),
+            }
+        }
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        let page = self.page_height.max(1) as isize;
+        match code {
+            KeyCode::Char('k') | KeyCode::Up => self.scroll_by(-1),
+            KeyCode::Char('j') | KeyCode::Down => self.scroll_by(1),
+            KeyCode::PageUp => self.scroll_by(-page),
+            KeyCode::PageDown => self.scroll_by(page),
+            KeyCode::Char('g') | KeyCode::Home => self.scroll_to_top(),
+            KeyCode::Char('G') | KeyCode::End => self.follow(),
+            KeyCode::Tab => {
+                self.view = self.view.next();
+                self.follow();
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Drop for OutputPane {
+    fn drop(&mut self) {
+        if self.exit_status.is_none() {
+            let _ = self.child.kill();
+        }
+    }
+}
+
+impl Widget for &OutputPane {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let lines = self
+            .lines
+            .iter()
+            .filter(|(stream, _)| self.view.shows(*stream))
+            .map(|(_, line)| line)
+            .skip(self.offset.unwrap_or(usize::MAX).min(self.max_offset()));
+        for (y, line) in (area.top()..area.bottom()).zip(lines) {
+            line.render(Rect::new(area.x, y, area.width, 1), buf);
+        }
+    }
+}
+
+/// Sent when the command of an [`OutputPane`] has exited, once all of its output has been read.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputExited {
+    /// The [`OutputPane`] entity.
+    pub pane: Entity,
+    pub status: ExitStatus,
+}
+
+/// Reads `reader` line by line on a new thread until it ends.
+fn read_lines(
+    reader: impl Read + Send + 'static,
+    stream: OutputStream,
+    sender: Sender<(OutputStream, Vec<u8>)>,
+) {
+    thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut line = Vec::new();
+        while let Ok(1..) = reader.read_until(b'\n', &mut line) {
+            if sender.send((stream, mem::take(&mut line))).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Parses a line of output, with or without its line ending, into styled spans.
+///
+/// A carriage return starts the line over, as progress bars do, so only the text after the last
+/// one is kept.
+pub(crate) fn parse_line(bytes: &[u8]) -> Line<'static> {
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim_end_matches(['\n', '\r']);
+    ansi_line(text.rsplit('\r').next().unwrap_or_default())
+}
+
+/// Parses the SGR escape sequences in `text` into styled spans, and drops other escape sequences.
+fn ansi_line(text: &str) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut style = Style::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('\x1b') {
+        if start > 0 {
+            spans.push(Span::styled(rest[..start].to_string(), style));
+        }
+        rest = &rest[start + 1..];
+        if !rest.starts_with('[') {
+            // Only CSI sequences are understood; a lone escape is dropped.
+            rest = rest.get(1..).unwrap_or_default();
+            continue;
+        }
+        let Some(end) = rest[1..].find(|c: char| ('\x40'..='\x7e').contains(&c)) else {
+            return Line::from(spans);
+        };
+        let (params, last) = (&rest[1..=end], rest.as_bytes()[end + 1]);
+        if last == b'm' {
+            style = apply_sgr(style, params);
+        }
+        rest = &rest[end + 2..];
+    }
+    if !rest.is_empty() {
+        spans.push(Span::styled(rest.to_string(), style));
+    }
+    Line::from(spans)
+}
+
+/// Applies the parameters of an SGR sequence, `ESC [ params m`, to `style`.
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    let mut params = params
+        .split([';', ':'])
+        .map(|param| param.parse::<u8>().unwrap_or(0));
+    while let Some(param) = params.next() {
+        style = match param {
+            0 => Style::new(),
+            1 => style.add_modifier(Modifier::BOLD),
+            2 => style.add_modifier(Modifier::DIM),
+            3 => style.add_modifier(Modifier::ITALIC),
+            4 => style.add_modifier(Modifier::UNDERLINED),
+            5 => style.add_modifier(Modifier::SLOW_BLINK),
+            7 => style.add_modifier(Modifier::REVERSED),
+            8 => style.add_modifier(Modifier::HIDDEN),
+            9 => style.add_modifier(Modifier::CROSSED_OUT),
+            22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+            23 => style.remove_modifier(Modifier::ITALIC),
+            24 => style.remove_modifier(Modifier::UNDERLINED),
+            25 => style.remove_modifier(Modifier::SLOW_BLINK),
+            27 => style.remove_modifier(Modifier::REVERSED),
+            28 => style.remove_modifier(Modifier::HIDDEN),
+            29 => style.remove_modifier(Modifier::CROSSED_OUT),
+            30..=37 => style.fg(Color::Indexed(param - 30)),
+            38 => style.fg(extended_color(&mut params).unwrap_or(Color::Reset)),
+            39 => style.fg(Color::Reset),
+            40..=47 => style.bg(Color::Indexed(param - 40)),
+            48 => style.bg(extended_color(&mut params).unwrap_or(Color::Reset)),
+            49 => style.bg(Color::Reset),
+            90..=97 => style.fg(Color::Indexed(param - 90 + 8)),
+            100..=107 => style.bg(Color::Indexed(param - 100 + 8)),
+            _ => style,
+        };
+    }
+    style
+}
+
+/// Parses the rest of `38;5;n` and `38;2;r;g;b`, and the same for backgrounds.
+fn extended_color(params: &mut impl Iterator<Item = u8>) -> Option<Color> {
+    match params.next()? {
+        5 => Some(Color::Indexed(params.next()?)),
+        2 => Some(Color::Rgb(params.next()?, params.next()?, params.next()?)),
+        _ => None,
+    }
+}
+
+fn scroll_output_panes(
+    mut keys: EventReader<RoutedKeyEvent>,
+    mut mouse: EventReader<RoutedMouseEvent>,
+    mut panes: Query<Interactive<&mut OutputPane>>,
+) {
+    for key in keys.read() {
+        if key.event.kind == KeyEventKind::Release {
+            continue;
+        }
+        if let Ok((mut pane, visibility, disabled)) = panes.get_mut(key.target) {
+            if is_interactive(visibility, disabled) {
+                pane.handle_key(key.event.code);
+            }
+        }
+    }
+    for event in mouse.read() {
+        let lines = match event.event.kind {
+            MouseEventKind::ScrollUp => -WHEEL_LINES,
+            MouseEventKind::ScrollDown => WHEEL_LINES,
+            _ => continue,
+        };
+        if let Ok((mut pane, visibility, disabled)) = panes.get_mut(event.target) {
+            if is_interactive(visibility, disabled) {
+                pane.scroll_by(lines);
+            }
+        }
+    }
+}
+
+fn read_command_output(
+    mut panes: Query<(Entity, &mut OutputPane)>,
+    mut exited: EventWriter<OutputExited>,
+    redraw: Option<ResMut<RedrawRequested>>,
+) {
+    let mut changed = false;
+    for (entity, mut pane) in &mut panes {
+        // Bypassing change detection, as a pane whose command is quiet has not changed.
+        let pane = pane.bypass_change_detection();
+        let (read, ended) = pane.read_output();
+        changed |= read;
+        // Waiting for the streams to end first, so that the exit follows all of the output.
+        if !ended || pane.exit_status.is_some() {
+            continue;
+        }
+        match pane.child.try_wait() {
+            Ok(Some(status)) => {
+                pane.exit_status = Some(status);
+                exited.send(OutputExited {
+                    pane: entity,
+                    status,
+                });
+            }
+            Ok(None) => {}
+            Err(err) => warn!("Failed to check on the command of an output pane: {err}"),
+        }
+    }
+    if let (true, Some(mut redraw)) = (changed, redraw) {
+        redraw.request();
+    }
+}
+
+fn draw_output_panes(
+    mut buffer: ResMut<RenderBuffer>,
+    mut panes: Query<(&mut OutputPane, &AnchoredArea, Option<&WidgetVisibility>)>,
+) {
+    let bounds = buffer.area;
+    for (mut pane, area, visibility) in &mut panes {
+        if !visibility.is_none_or(|visibility| visibility.is_visible()) {
+            continue;
+        }
+        let area = area.intersection(bounds);
+        pane.page_height = usize::from(area.height);
+        pane.render(area, &mut buffer);
+    }
+}
@@ -0,0 +1,341 @@
+//! A full-screen pager over a scrollback buffer.
+//!
+//! [`PagerPlugin`] keeps a [`Scrollback`] buffer of text lines, such as logs or command output, and
+//! shows it in a full-screen pager when the [`PagerToggleKey`] (`F2` by default) is pressed. The
+//! pager supports scrolling and searching, similar to `less`:
+//!
+//! - `j`/`Down` and `k`/`Up` scroll by a line, `Space`/`PageDown` and `b`/`PageUp` by a page.
+//! - `g`/`Home` and `G`/`End` jump to the start and end.
+//! - `/` starts an incremental [search](crate::search), `n` and `N` jump to the next and previous
+//!   match.
+//! - `q`, `Esc` or the toggle key close the pager.
+//!
+//! While the pager is open, the [`RootWidget`](crate::widget::RootWidget) is not drawn and the
+//! [`QuitPlugin`](crate::quit::QuitPlugin) does not quit. Add the [`pager_closed`] run condition to
+//! your own draw and input systems to pause them too.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     pager::{pager_closed, PagerPlugin, Scrollback},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), PagerPlugin))
+//!     .add_systems(Update, log_frame.run_if(pager_closed));
+//!
+//! fn log_frame(mut scrollback: ResMut<Scrollback>, time: Res<Time>) {
+//!     scrollback.push(format!("elapsed: {:?}", time.elapsed()));
+//! }
+//! ```
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEventKind};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+    widgets::Widget,
+};
+
+use crate::{
+    error::exit_on_error,
+    event::{InputSet, KeyEvent},
+    quit::{self, KeyBinding},
+    search::{highlight_matches, SearchEvent, SearchOverlay, SearchState},
+    terminal::{RatatuiContext, TerminalSet},
+};
+
+/// A plugin that adds the [`Scrollback`] buffer and a pager to view it.
+pub struct PagerPlugin;
+
+impl Plugin for PagerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Scrollback>()
+            .init_resource::<PagerToggleKey>()
+            .init_resource::<Pager>()
+            .add_systems(
+                PreUpdate,
+                pager_input_system
+                    .in_set(InputSet::Post)
+                    .after(quit::quit_system),
+            )
+            .add_systems(
+                PostUpdate,
+                draw_pager
+                    .pipe(exit_on_error)
+                    .run_if(pager_open.and(resource_exists::<RatatuiContext>))
+                    .before(TerminalSet::Cleanup),
+            );
+    }
+}
+
+/// A run condition that is true while the pager is open.
+pub fn pager_open(pager: Option<Res<Pager>>) -> bool {
+    pager.is_some_and(|pager| pager.is_open())
+}
+
+/// A run condition that is true unless the pager is open.
+pub fn pager_closed(pager: Option<Res<Pager>>) -> bool {
+    !pager_open(pager)
+}
+
+/// The key that opens and closes the pager. Defaults to `F2`.
+#[derive(Resource, Debug, Clone, Copy, Deref, DerefMut)]
+pub struct PagerToggleKey(pub KeyBinding);
+
+impl Default for PagerToggleKey {
+    fn default() -> Self {
+        Self(KeyBinding::new(KeyCode::F(2)))
+    }
+}
+
+/// A buffer of text lines shown by the pager.
+///
+/// Only the most recent [`Scrollback::capacity`] lines are kept.
+#[derive(Resource, Debug, Clone)]
+pub struct Scrollback {
+    lines: VecDeque<String>,
+    capacity: usize,
+}
+
+impl Default for Scrollback {
+    /// Keeps 10,000 lines.
+    fn default() -> Self {
+        Self::with_capacity(10_000)
+    }
+}
+
+impl Scrollback {
+    /// Creates an empty scrollback buffer that keeps at most `capacity` lines.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Appends text to the buffer, one line for each line of the text.
+    pub fn push(&mut self, text: impl AsRef<str>) {
+        for line in text.as_ref().lines() {
+            if self.lines.len() == self.capacity {
+                self.lines.pop_front();
+            }
+            self.lines.push_back(line.to_string());
+        }
+    }
+
+    /// The lines in the buffer, oldest first.
+    pub fn lines(&self) -> impl ExactSizeIterator<Item = &str> {
+        self.lines.iter().map(String::as_str)
+    }
+
+    /// The number of lines in the buffer.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns true if the buffer has no lines.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// The maximum number of lines kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Removes all the lines.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+/// The state of the pager.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct Pager {
+    open: bool,
+    /// The index of the first line on screen. Clamped when drawn, so `usize::MAX` shows the end.
+    offset: usize,
+    /// The number of lines that fit on screen, as of the last draw.
+    page_height: usize,
+    search: SearchState,
+    /// The offset when the search was started, which incremental matches are searched from.
+    search_origin: usize,
+}
+
+impl Pager {
+    /// Returns true if the pager is open.
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Opens the pager at the end of the scrollback.
+    pub fn open(&mut self) {
+        self.open = true;
+        self.offset = usize::MAX;
+    }
+
+    /// Closes the pager.
+    pub fn close(&mut self) {
+        self.open = false;
+        self.search.cancel();
+    }
+
+    /// The search in the pager.
+    pub fn search(&self) -> &SearchState {
+        &self.search
+    }
+
+    fn max_offset(&self, scrollback: &Scrollback) -> usize {
+        scrollback.len().saturating_sub(self.page_height)
+    }
+
+    fn scroll_by(&mut self, lines: isize, scrollback: &Scrollback) {
+        let max_offset = self.max_offset(scrollback);
+        self.offset = self
+            .offset
+            .min(max_offset)
+            .saturating_add_signed(lines)
+            .min(max_offset);
+    }
+
+    /// Moves to the first line matching the query at or after `from`, or if searching backwards,
+    /// the last one before it.
+    fn find(&mut self, scrollback: &Scrollback, from: usize, forward: bool) {
+        let Some(query) = self.search.highlighted() else {
+            return;
+        };
+        let lines = scrollback.lines().enumerate();
+        let found = if forward {
+            lines.skip(from).find(|(_, line)| line.contains(query))
+        } else {
+            lines
+                .take(from)
+                .filter(|(_, line)| line.contains(query))
+                .last()
+        };
+        if let Some((index, _)) = found {
+            self.offset = index;
+        }
+    }
+
+    fn handle_key(
+        &mut self,
+        key: &crossterm::event::KeyEvent,
+        toggle: KeyBinding,
+        scrollback: &Scrollback,
+    ) {
+        let current = self.offset.min(self.max_offset(scrollback));
+        if let Some(event) = self.search.handle_key(key) {
+            match event {
+                SearchEvent::Changed(_) => {
+                    self.offset = self.search_origin;
+                    self.find(scrollback, self.search_origin, true);
+                }
+                SearchEvent::Next => self.find(scrollback, current + 1, true),
+                SearchEvent::Previous => self.find(scrollback, current, false),
+                SearchEvent::Submitted(_) => {}
+                SearchEvent::Cancelled => self.offset = self.search_origin,
+            }
+            return;
+        }
+        if self.search.is_active() {
+            return;
+        }
+        let page = self.page_height.max(1) as isize;
+        match key.code {
+            _ if toggle.matches(key) => self.close(),
+            KeyCode::Char('q') | KeyCode::Esc => self.close(),
+            KeyCode::Char('j') | KeyCode::Down | KeyCode::Enter => self.scroll_by(1, scrollback),
+            KeyCode::Char('k') | KeyCode::Up => self.scroll_by(-1, scrollback),
+            KeyCode::Char(' ') | KeyCode::Char('f') | KeyCode::PageDown => {
+                self.scroll_by(page, scrollback)
+            }
+            KeyCode::Char('b') | KeyCode::PageUp => self.scroll_by(-page, scrollback),
+            KeyCode::Char('g') | KeyCode::Home => self.offset = 0,
+            KeyCode::Char('G') | KeyCode::End => self.offset = usize::MAX,
+            KeyCode::Char('/') => {
+                self.search_origin = current;
+                self.search.start();
+            }
+            KeyCode::Char('n') => self.find(scrollback, current + 1, true),
+            KeyCode::Char('N') => self.find(scrollback, current, false),
+            _ => {}
+        }
+    }
+}
+
+fn pager_input_system(
+    mut keys: EventReader<KeyEvent>,
+    toggle: Res<PagerToggleKey>,
+    scrollback: Res<Scrollback>,
+    mut pager: ResMut<Pager>,
+) {
+    for key in keys.read().filter(|key| key.kind != KeyEventKind::Release) {
+        if pager.is_open() {
+            pager.handle_key(key, **toggle, &scrollback);
+        } else if toggle.matches(key) {
+            pager.open();
+        }
+    }
+}
+
+/// Draws the pager over the whole terminal.
+pub fn draw_pager(
+    mut context: ResMut<RatatuiContext>,
+    mut pager: ResMut<Pager>,
+    scrollback: Res<Scrollback>,
+) -> Result<()> {
+    context.draw(|frame| {
+        let area = frame.area();
+        pager.page_height = area.height.saturating_sub(1) as usize;
+        pager.offset = pager.offset.min(pager.max_offset(&scrollback));
+        PagerWidget {
+            pager: &pager,
+            scrollback: &scrollback,
+        }
+        .render(area, frame.buffer_mut());
+    })?;
+    Ok(())
+}
+
+struct PagerWidget<'a> {
+    pager: &'a Pager,
+    scrollback: &'a Scrollback,
+}
+
+impl Widget for PagerWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let query = self.pager.search.highlighted();
+        let match_style = Style::new().add_modifier(Modifier::REVERSED);
+        let lines = self.scrollback.lines().skip(self.pager.offset);
+        for (y, line) in (area.top()..area.bottom().saturating_sub(1)).zip(lines) {
+            let row = Rect::new(area.x, y, area.width, 1);
+            highlight_matches(line, query, match_style).render(row, buf);
+        }
+
+        let Some(y) = area.bottom().checked_sub(1).filter(|y| *y >= area.top()) else {
+            return;
+        };
+        let status_area = Rect::new(area.x, y, area.width, 1);
+        if self.pager.search.is_active() {
+            SearchOverlay::new(&self.pager.search).render(status_area, buf);
+            return;
+        }
+        let first = (self.pager.offset + 1).min(self.scrollback.len());
+        let last = (self.pager.offset + self.pager.page_height).min(self.scrollback.len());
+        let mut status = format!("lines {first}-{last}/{}", self.scrollback.len());
+        if let Some(query) = query {
+            status.push_str(&format!("  /{query}"));
+        }
+        status.push_str("  (q: close, /: search, n/N: next/previous)");
+        Line::from(status)
+            .style(Style::new().add_modifier(Modifier::REVERSED))
+            .render(status_area, buf);
+    }
+}
@@ -0,0 +1,180 @@
+//! Bracketed paste.
+//!
+//! With bracketed paste enabled, the terminal marks pasted text so that it arrives as a single
+//! [`PasteEvent`] rather than as a key press for each character. [`PastePlugin`] enables it on
+//! startup, and it can be turned off and on at runtime by removing and inserting the
+//! [`BracketedPasteEnabled`] resource.
+//!
+//! Very large pastes can take a while to process. [`PastePlugin`] sends each paste as ordered
+//! [`PasteChunk`] events of at most [`PasteChunking::chunk_bytes`], spread over as many frames as
+//! needed to stay within [`PasteChunking::bytes_per_frame`], followed by a [`PasteEnd`]. Apps can
+//! stream the chunks into a buffer instead of handling the whole paste in one frame. The pasted
+//! text is handed over as read rather than copied, so no [`PasteEvent`] or paste
+//! [`CrosstermEvent`] is sent while the plugin is added.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     paste::{PasteChunk, PasteEnd},
+//!     text_buffer::TextBuffer,
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins(RatatuiPlugins {
+//!         enable_bracketed_paste: true,
+//!         ..default()
+//!     })
+//!     .init_resource::<TextBuffer>()
+//!     .add_systems(Update, stream_paste);
+//!
+//! fn stream_paste(
+//!     mut chunks: EventReader<PasteChunk>,
+//!     mut ends: EventReader<PasteEnd>,
+//!     mut buffer: ResMut<TextBuffer>,
+//! ) {
+//!     for chunk in chunks.read() {
+//!         let end = buffer.len_chars();
+//!         buffer.insert(end, chunk);
+//!     }
+//!     for end in ends.read() {
+//!         info!("pasted {} bytes", end.bytes);
+//!     }
+//! }
+//! ```
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use crossterm::{
+    event::{DisableBracketedPaste, EnableBracketedPaste},
+    ExecutableCommand,
+};
+
+#[cfg(doc)]
+use crate::event::{CrosstermEvent, PasteEvent};
+use crate::{
+    error::exit_on_error,
+    event::InputSet,
+    terminal::{output, TerminalSet, TerminalStartup},
+};
+
+/// A plugin that enables bracketed paste and sends pastes as [`PasteChunk`]s.
+pub struct PastePlugin;
+
+impl Plugin for PastePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PasteChunking>()
+            .init_resource::<PendingPastes>()
+            .add_event::<PasteChunk>()
+            .add_event::<PasteEnd>()
+            .add_systems(
+                TerminalStartup,
+                // The terminal features are independent of each other, they only share the error
+                // handling.
+                setup
+                    .pipe(exit_on_error)
+                    .in_set(TerminalSet::Features)
+                    .ambiguous_with(TerminalSet::Features),
+            )
+            .add_systems(PreUpdate, send_paste_chunks.in_set(InputSet::EmitBevy));
+    }
+}
+
+/// Present while bracketed paste is enabled. Removing it disables bracketed paste.
+#[derive(Resource, Debug)]
+pub struct BracketedPasteEnabled(());
+
+impl BracketedPasteEnabled {
+    /// Enables bracketed paste. Insert the returned resource to keep it enabled.
+    pub fn enable() -> std::io::Result<Self> {
+        output().execute(EnableBracketedPaste)?;
+        Ok(Self(()))
+    }
+}
+
+impl Drop for BracketedPasteEnabled {
+    fn drop(&mut self) {
+        let _ = output().execute(DisableBracketedPaste);
+    }
+}
+
+fn setup(mut commands: Commands) -> color_eyre::Result<()> {
+    commands.insert_resource(BracketedPasteEnabled::enable()?);
+    Ok(())
+}
+
+/// A part of a pasted text. The chunks of a paste are sent in order and followed by a
+/// [`PasteEnd`].
+#[derive(Debug, Clone, Event, PartialEq, Eq, Deref)]
+pub struct PasteChunk(pub String);
+
+/// Sent after the last [`PasteChunk`] of a paste.
+#[derive(Debug, Clone, Copy, Event, PartialEq, Eq)]
+pub struct PasteEnd {
+    /// The size of the whole paste in bytes.
+    pub bytes: usize,
+}
+
+/// How pastes are split into [`PasteChunk`]s.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasteChunking {
+    /// The largest chunk in bytes. Chunks always end on a character boundary. Defaults to 64 KiB.
+    pub chunk_bytes: usize,
+    /// The most bytes sent in a single frame. The rest is sent in the following frames. Defaults
+    /// to 1 MiB.
+    pub bytes_per_frame: usize,
+}
+
+impl Default for PasteChunking {
+    fn default() -> Self {
+        Self {
+            chunk_bytes: 64 * 1024,
+            bytes_per_frame: 1024 * 1024,
+        }
+    }
+}
+
+/// The pastes that have not been sent as chunks yet, and how far the first one has been sent.
+#[derive(Resource, Debug, Default)]
+pub struct PendingPastes {
+    pastes: VecDeque<String>,
+    sent: usize,
+}
+
+impl PendingPastes {
+    /// Queues a paste read from the terminal.
+    pub(crate) fn push(&mut self, text: String) {
+        self.pastes.push_back(text);
+    }
+}
+
+fn send_paste_chunks(
+    chunking: Res<PasteChunking>,
+    mut pending: ResMut<PendingPastes>,
+    mut chunks: EventWriter<PasteChunk>,
+    mut ends: EventWriter<PasteEnd>,
+) {
+    let chunk_bytes = chunking.chunk_bytes.max(1);
+    let mut budget = chunking.bytes_per_frame.max(1);
+    let pending = &mut *pending;
+    while let Some(paste) = pending.pastes.front() {
+        if budget == 0 {
+            break;
+        }
+        let rest = &paste[pending.sent..];
+        if !rest.is_empty() {
+            let mut end = chunk_bytes.min(budget).min(rest.len());
+            while !rest.is_char_boundary(end) {
+                end += 1;
+            }
+            chunks.send(PasteChunk(rest[..end].to_string()));
+            pending.sent += end;
+            budget = budget.saturating_sub(end);
+        }
+        if pending.sent == paste.len() {
+            ends.send(PasteEnd { bytes: paste.len() });
+            pending.pastes.pop_front();
+            pending.sent = 0;
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Render timings of individual widgets.
+//!
+//! When a frame takes too long to draw, the frame time alone does not say which widget is to
+//! blame. Render widgets through the [`WidgetProfiler`] resource and [`WidgetProfilerPlugin`]
+//! records how long each of them took. While profiling is on, the slowest widgets are listed in an
+//! overlay and every widget's render time is published as a bevy diagnostic under
+//! `bevy_ratatui/widget/<label>`. The [`WidgetProfilerKey`] (`F7` by default) toggles profiling,
+//! and widgets are rendered without being timed while it is off.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     profiling::{WidgetProfiler, WidgetProfilerPlugin},
+//!     terminal::RatatuiContext,
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::widgets::Paragraph;
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), WidgetProfilerPlugin))
+//!     .add_systems(Update, draw);
+//!
+//! fn draw(mut context: ResMut<RatatuiContext>, mut profiler: ResMut<WidgetProfiler>) {
+//!     let _ = context.draw(|frame| {
+//!         let area = frame.area();
+//!         profiler.render("log", Paragraph::new("..."), area, frame.buffer_mut());
+//!     });
+//! }
+//! ```
+//!
+//! The [`RootWidget`](crate::widget::RootWidget) is timed as `root` when the plugin is added.
+use std::time::{Duration, Instant};
+
+use bevy::{
+    diagnostic::{
+        Diagnostic, DiagnosticMeasurement, DiagnosticPath, DiagnosticsStore, RegisterDiagnostic,
+    },
+    prelude::*,
+    utils::HashMap,
+};
+use color_eyre::Result;
+use crossterm::event::KeyCode;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Style, Stylize},
+    widgets::{Block, Clear, Row, Table, Widget},
+};
+
+use crate::{
+    error::exit_on_error,
+    event::{InputSet, KeyEvent},
+    quit::KeyBinding,
+    terminal::RatatuiContext,
+};
+
+/// A plugin that collects the timings recorded in [`WidgetProfiler`] and shows them while
+/// profiling is on.
+pub struct WidgetProfilerPlugin;
+
+impl Plugin for WidgetProfilerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WidgetProfiler>()
+            .init_resource::<WidgetProfilerKey>()
+            .register_diagnostic(Diagnostic::new(WidgetProfiler::RENDER_TIME).with_suffix("ms"))
+            .add_systems(First, finish_frame.run_if(widget_profiling_enabled))
+            .add_systems(PreUpdate, profiler_input_system.in_set(InputSet::Post))
+            .add_systems(
+                Last,
+                draw_widget_profile
+                    .pipe(exit_on_error)
+                    .run_if(widget_profiling_enabled.and(resource_exists::<RatatuiContext>)),
+            );
+    }
+}
+
+/// A run condition that is true while widget render times are recorded.
+pub fn widget_profiling_enabled(profiler: Option<Res<WidgetProfiler>>) -> bool {
+    profiler.is_some_and(|profiler| profiler.enabled)
+}
+
+/// The key that toggles widget profiling.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct WidgetProfilerKey(pub KeyBinding);
+
+impl Default for WidgetProfilerKey {
+    fn default() -> Self {
+        Self(KeyBinding::new(KeyCode::F(7)))
+    }
+}
+
+/// How long a widget took to render.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WidgetTiming {
+    /// The time spent in the last frame, summed over every time it was rendered.
+    pub last: Duration,
+    /// A moving average of `last` over recent frames.
+    pub average: Duration,
+    /// The longest `last` seen since profiling was turned on.
+    pub max: Duration,
+}
+
+/// Records how long widgets take to render.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct WidgetProfiler {
+    /// Whether render times are recorded and shown.
+    pub enabled: bool,
+    frame: HashMap<String, Duration>,
+    timings: HashMap<String, WidgetTiming>,
+}
+
+/// How much of each new frame's time goes into [`WidgetTiming::average`].
+const SMOOTHING: f64 = 0.1;
+
+/// The number of widgets listed in the overlay.
+const OVERLAY_ROWS: usize = 10;
+
+impl WidgetProfiler {
+    /// The total time spent rendering profiled widgets each frame.
+    pub const RENDER_TIME: DiagnosticPath = DiagnosticPath::const_new("bevy_ratatui/widget");
+
+    /// Runs `render`, adding the time it takes to the widget's time for this frame.
+    ///
+    /// Use a label that identifies the widget, such as a name or an entity, so that a widget
+    /// that is rendered more than once per frame is added up rather than listed repeatedly.
+    pub fn time<R>(&mut self, label: impl Into<String>, render: impl FnOnce() -> R) -> R {
+        if !self.enabled {
+            return render();
+        }
+        let start = Instant::now();
+        let result = render();
+        *self.frame.entry(label.into()).or_default() += start.elapsed();
+        result
+    }
+
+    /// Renders a widget, timing it under `label`.
+    pub fn render(
+        &mut self,
+        label: impl Into<String>,
+        widget: impl Widget,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        self.time(label, || widget.render(area, buf));
+    }
+
+    /// The timings of each widget.
+    pub fn timings(&self) -> impl Iterator<Item = (&str, &WidgetTiming)> {
+        self.timings
+            .iter()
+            .map(|(label, timing)| (label.as_str(), timing))
+    }
+
+    /// The `n` widgets with the longest average render times, slowest first.
+    pub fn slowest(&self, n: usize) -> Vec<(&str, &WidgetTiming)> {
+        let mut timings: Vec<_> = self.timings().collect();
+        timings.sort_by(|a, b| b.1.average.cmp(&a.1.average).then(a.0.cmp(b.0)));
+        timings.truncate(n);
+        timings
+    }
+
+    /// Folds the times recorded this frame into the timings and starts the next frame.
+    fn finish_frame(&mut self) {
+        for (label, timing) in &mut self.timings {
+            timing.last = self.frame.remove(label).unwrap_or_default();
+        }
+        for (label, last) in self.frame.drain() {
+            self.timings.insert(
+                label,
+                WidgetTiming {
+                    last,
+                    average: last,
+                    max: Duration::ZERO,
+                },
+            );
+        }
+        for timing in self.timings.values_mut() {
+            let average = timing.average.as_secs_f64();
+            let last = timing.last.as_secs_f64();
+            timing.average = Duration::from_secs_f64(average + (last - average) * SMOOTHING);
+            timing.max = timing.max.max(timing.last);
+        }
+        // Forget widgets that are no longer drawn once their average has decayed.
+        self.timings
+            .retain(|_, timing| timing.average >= Duration::from_micros(1));
+    }
+}
+
+fn finish_frame(mut profiler: ResMut<WidgetProfiler>, store: Option<ResMut<DiagnosticsStore>>) {
+    profiler.finish_frame();
+    let Some(mut store) = store else {
+        return;
+    };
+    let time = Instant::now();
+    let mut measure = |path: DiagnosticPath, duration: Duration| {
+        if store.get(&path).is_none() {
+            store.add(Diagnostic::new(path.clone()).with_suffix("ms"));
+        }
+        if let Some(diagnostic) = store.get_mut(&path) {
+            diagnostic.add_measurement(DiagnosticMeasurement {
+                time,
+                value: duration.as_secs_f64() * 1000.0,
+            });
+        }
+    };
+    let mut total = Duration::ZERO;
+    for (label, timing) in profiler.timings() {
+        total += timing.last;
+        measure(diagnostic_path(label), timing.last);
+    }
+    measure(WidgetProfiler::RENDER_TIME, total);
+}
+
+/// The diagnostic path of a widget, with the characters that are not allowed in a path component
+/// replaced.
+fn diagnostic_path(label: &str) -> DiagnosticPath {
+    let label = if label.is_empty() {
+        "_".to_string()
+    } else {
+        label.replace('/', "_")
+    };
+    DiagnosticPath::from_components(["bevy_ratatui", "widget", &label])
+}
+
+fn profiler_input_system(
+    mut keys: EventReader<KeyEvent>,
+    toggle: Res<WidgetProfilerKey>,
+    mut profiler: ResMut<WidgetProfiler>,
+) {
+    for key in keys.read() {
+        if toggle.matches(key) {
+            profiler.enabled = !profiler.enabled;
+            if !profiler.enabled {
+                *profiler = WidgetProfiler::default();
+            }
+        }
+    }
+}
+
+/// Lists the slowest widgets in the top right corner of the last frame.
+pub fn draw_widget_profile(
+    mut context: ResMut<RatatuiContext>,
+    profiler: Res<WidgetProfiler>,
+) -> Result<()> {
+    let last_frame = context.last_frame().clone();
+    let slowest = profiler.slowest(OVERLAY_ROWS);
+    context.draw(|frame| {
+        let area = frame.area();
+        if last_frame.area == area {
+            *frame.buffer_mut() = last_frame;
+        }
+        let rows = slowest.iter().map(|(label, timing)| {
+            Row::new([
+                label.to_string(),
+                format_duration(timing.average),
+                format_duration(timing.max),
+            ])
+        });
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(9),
+            Constraint::Length(9),
+        ];
+        let table = Table::new(rows, widths)
+            .header(Row::new(["widget", "avg", "max"]).bold())
+            .block(Block::bordered().title("widget render times"))
+            .style(Style::new().reversed());
+        let height = u16::try_from(slowest.len()).unwrap_or(u16::MAX) + 3;
+        let width = 44.min(area.width);
+        let overlay =
+            Rect::new(area.right().saturating_sub(width), area.y, width, height).intersection(area);
+        frame.render_widget(Clear, overlay);
+        frame.render_widget(table, overlay);
+    })?;
+    Ok(())
+}
+
+fn format_duration(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,500 @@
+//! Embedding shells, editors and other terminal programs as panes.
+//!
+//! A [`PtyPane`] runs a program on a pseudo terminal, reads its output on a background thread and
+//! feeds it to a terminal emulator, so that the pane always holds the program's current screen.
+//! [`PtyPanePlugin`] draws each pane into its [`AnchoredArea`], resizing the pseudo terminal along
+//! with the area, and forwards the keys [routed](crate::routing) to the pane while it is
+//! [focused](crate::routing::FocusedPane). When the program exits, a [`PtyExited`] event is sent
+//! and the pane keeps showing its last screen until it is despawned. To draw a pane somewhere else,
+//! such as inside a bordered block, render its [`PtyScreen`] widget.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     anchor::AnchoredArea,
+//!     pty::{CommandBuilder, PtyExited, PtyPane, PtyPanePlugin},
+//!     routing::FocusedPane,
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::layout::{Rect, Size};
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), PtyPanePlugin))
+//!     .add_systems(Startup, spawn_shell)
+//!     .add_systems(Update, exit_with_shell)
+//!     .run();
+//!
+//! fn spawn_shell(mut commands: Commands, mut focused: ResMut<FocusedPane>) {
+//!     let shell = CommandBuilder::new_default_prog();
+//!     let area = Rect::new(0, 0, 80, 24);
+//!     match PtyPane::spawn(shell, area.as_size()) {
+//!         Ok(pane) => focused.0 = Some(commands.spawn((pane, AnchoredArea(area))).id()),
+//!         Err(err) => error!("Failed to start the shell: {err}"),
+//!     }
+//! }
+//!
+//! fn exit_with_shell(mut exited: EventReader<PtyExited>, mut exit: EventWriter<AppExit>) {
+//!     if exited.read().next().is_some() {
+//!         exit.send(AppExit::Success);
+//!     }
+//! }
+//! ```
+//!
+//! Despawning the pane kills the program if it is still running. This module requires the `pty`
+//! feature.
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    sync::{
+        mpsc::{self, Receiver},
+        Mutex, PoisonError,
+    },
+    thread,
+};
+
+use bevy::prelude::*;
+use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+use portable_pty::{Child, MasterPty, PtySize};
+pub use portable_pty::{CommandBuilder, ExitStatus};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Position, Rect, Size},
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+
+use crate::{
+    anchor::AnchoredArea,
+    event::{InputSet, KeyEvent},
+    redraw::RedrawRequested,
+    render::{RenderBuffer, RenderPlugin, RenderSet},
+    routing::{FocusedPane, InputRoutingPlugin, InputTarget, RoutedKeyEvent},
+    widget::{draw_root_widget, is_interactive, Interactive, WidgetVisibility},
+};
+
+/// How many lines of scrollback the terminal emulator of a pane keeps.
+const SCROLLBACK: usize = 1000;
+
+/// A plugin that runs, draws and forwards input to [`PtyPane`]s.
+///
+/// This requires the [`RenderPlugin`] and the [`InputRoutingPlugin`], and adds them if they are
+/// missing.
+pub struct PtyPanePlugin;
+
+impl Plugin for PtyPanePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RenderPlugin>() {
+            app.add_plugins(RenderPlugin);
+        }
+        if !app.is_plugin_added::<InputRoutingPlugin>() {
+            app.add_plugins(InputRoutingPlugin);
+        }
+        app.add_event::<PtyExited>()
+            .add_systems(
+                PreUpdate,
+                (forward_pty_input, read_pty_output)
+                    .chain()
+                    .after(InputSet::Post),
+            )
+            .add_systems(
+                PostUpdate,
+                draw_pty_panes
+                    .after(draw_root_widget)
+                    .in_set(RenderSet::Widgets),
+            );
+    }
+}
+
+/// A program running on a pseudo terminal, and its screen.
+///
+/// The pane is drawn in its [`AnchoredArea`], and takes the keys routed to it, as an
+/// [`InputTarget`], unless it is [`Disabled`](crate::widget::Disabled) or hidden.
+#[derive(Component)]
+#[require(InputTarget, AnchoredArea)]
+pub struct PtyPane {
+    parser: vt100::Parser,
+    master: Mutex<Box<dyn MasterPty + Send>>,
+    writer: Mutex<Box<dyn Write + Send>>,
+    child: Box<dyn Child + Send + Sync>,
+    output: Mutex<Receiver<Vec<u8>>>,
+    exit_status: Option<ExitStatus>,
+}
+
+impl PtyPane {
+    /// Starts `command` on a new pseudo terminal of the given size.
+    pub fn spawn(command: CommandBuilder, size: Size) -> io::Result<Self> {
+        let pair = portable_pty::native_pty_system()
+            .openpty(pty_size(size))
+            .map_err(io::Error::other)?;
+        let child = pair
+            .slave
+            .spawn_command(command)
+            .map_err(io::Error::other)?;
+        let mut reader = pair.master.try_clone_reader().map_err(io::Error::other)?;
+        let writer = pair.master.take_writer().map_err(io::Error::other)?;
+        let (sender, output) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buf = [0; 4096];
+            // The read fails rather than ending on some platforms once the program has exited.
+            while let Ok(len @ 1..) = reader.read(&mut buf) {
+                if sender.send(buf[..len].to_vec()).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(Self {
+            parser: vt100::Parser::new(size.height, size.width, SCROLLBACK),
+            master: Mutex::new(pair.master),
+            writer: Mutex::new(writer),
+            child,
+            output: Mutex::new(output),
+            exit_status: None,
+        })
+    }
+
+    /// The screen of the program, as the terminal emulator last saw it.
+    pub fn screen(&self) -> &vt100::Screen {
+        self.parser.screen()
+    }
+
+    /// The title the program has set for its window.
+    pub fn title(&self) -> &str {
+        self.parser.screen().title()
+    }
+
+    /// How the program exited, or `None` while it runs.
+    pub fn exit_status(&self) -> Option<&ExitStatus> {
+        self.exit_status.as_ref()
+    }
+
+    /// Writes `bytes` to the program as if they were typed, e.g. to run a command in a shell.
+    pub fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let writer = self
+            .writer
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        writer.write_all(bytes)?;
+        writer.flush()
+    }
+
+    /// Resizes the pseudo terminal and the screen, which tells the program about the new size.
+    pub fn resize(&mut self, size: Size) -> io::Result<()> {
+        if self.parser.screen().size() == (size.height, size.width) {
+            return Ok(());
+        }
+        self.parser.set_size(size.height, size.width);
+        self.master
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .resize(pty_size(size))
+            .map_err(io::Error::other)
+    }
+
+    /// Feeds the output the program has written since the last call to the terminal emulator.
+    /// Returns whether there was any.
+    fn process_output(&mut self) -> bool {
+        let output = self
+            .output
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut processed = false;
+        while let Ok(bytes) = output.try_recv() {
+            self.parser.process(&bytes);
+            processed = true;
+        }
+        processed
+    }
+
+    /// A widget that renders the screen of the program.
+    pub fn widget(&self) -> PtyScreen<'_> {
+        PtyScreen::new(self)
+    }
+}
+
+/// Renders the screen of a [`PtyPane`], e.g. inside a block of an app's own layout.
+///
+/// The screen is drawn from its top left corner, and clipped to the area. Resize the pane with
+/// [`PtyPane::resize`] to fit the program to the area.
+///
+/// ```rust
+/// use bevy_ratatui::pty::{PtyPane, PtyScreen};
+/// use ratatui::{
+///     widgets::{Block, Widget},
+///     Frame,
+/// };
+///
+/// fn draw_build_output(frame: &mut Frame, pane: &PtyPane) {
+///     let block = Block::bordered().title(pane.title().to_owned());
+///     let inner = block.inner(frame.area());
+///     frame.render_widget(block, frame.area());
+///     frame.render_widget(PtyScreen::new(pane).cursor(false), inner);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct PtyScreen<'a> {
+    pane: &'a PtyPane,
+    cursor: bool,
+}
+
+impl<'a> PtyScreen<'a> {
+    /// Creates a widget for the screen of `pane`, with the cursor shown.
+    pub fn new(pane: &'a PtyPane) -> Self {
+        Self { pane, cursor: true }
+    }
+
+    /// Sets whether the cursor is shown, unless the program has hidden it.
+    pub fn cursor(mut self, cursor: bool) -> Self {
+        self.cursor = cursor;
+        self
+    }
+}
+
+impl Widget for PtyScreen<'_> {
+    fn render(self, area: Rect, buffer: &mut Buffer) {
+        let area = area.intersection(buffer.area);
+        let screen = self.pane.parser.screen();
+        for y in 0..area.height {
+            for x in 0..area.width {
+                let Some(cell) = screen.cell(y, x) else {
+                    continue;
+                };
+                if cell.is_wide_continuation() {
+                    continue;
+                }
+                let Some(target) = buffer.cell_mut((area.x + x, area.y + y)) else {
+                    continue;
+                };
+                if cell.has_contents() {
+                    target.set_symbol(&cell.contents());
+                } else {
+                    target.set_symbol(" ");
+                }
+                target.set_style(cell_style(cell));
+            }
+        }
+        let (row, column) = screen.cursor_position();
+        if self.cursor && !screen.hide_cursor() && row < area.height && column < area.width {
+            let position = Position::new(area.x + column, area.y + row);
+            if let Some(target) = buffer.cell_mut(position) {
+                target.modifier.toggle(Modifier::REVERSED);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for PtyPane {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PtyPane")
+            .field("child", &self.child)
+            .field("exit_status", &self.exit_status)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for PtyPane {
+    fn drop(&mut self) {
+        if self.exit_status.is_none() {
+            let _ = self.child.kill();
+        }
+    }
+}
+
+/// Sent when the program of a [`PtyPane`] has exited.
+#[derive(Event, Debug, Clone)]
+pub struct PtyExited {
+    /// The [`PtyPane`] entity.
+    pub pane: Entity,
+    pub status: ExitStatus,
+}
+
+fn pty_size(size: Size) -> PtySize {
+    PtySize {
+        rows: size.height,
+        cols: size.width,
+        pixel_width: 0,
+        pixel_height: 0,
+    }
+}
+
+fn cell_style(cell: &vt100::Cell) -> Style {
+    let mut style = Style::new()
+        .fg(color(cell.fgcolor()))
+        .bg(color(cell.bgcolor()));
+    if cell.bold() {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+    if cell.italic() {
+        style = style.add_modifier(Modifier::ITALIC);
+    }
+    if cell.underline() {
+        style = style.add_modifier(Modifier::UNDERLINED);
+    }
+    if cell.inverse() {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    style
+}
+
+fn color(color: vt100::Color) -> Color {
+    match color {
+        vt100::Color::Default => Color::Reset,
+        vt100::Color::Idx(index) => Color::Indexed(index),
+        vt100::Color::Rgb(r, g, b) => Color::Rgb(r, g, b),
+    }
+}
+
+/// The bytes a terminal sends for a key, or `None` for keys that it doesn't send.
+///
+/// `application_cursor` is whether the program has asked for the cursor keys to be sent as
+/// `ESC O A` rather than `ESC [ A`, which full screen programs such as editors do.
+fn encode_key(key: &crossterm::event::KeyEvent, application_cursor: bool) -> Option<Vec<u8>> {
+    let modifiers = key.modifiers;
+    let alt = modifiers.contains(KeyModifiers::ALT);
+    // The modifier parameter of CSI sequences, e.g. 5 in `ESC [ 1 ; 5 A` for Ctrl+Up.
+    let parameter = 1
+        + u8::from(modifiers.contains(KeyModifiers::SHIFT))
+        + 2 * u8::from(alt)
+        + 4 * u8::from(modifiers.contains(KeyModifiers::CONTROL));
+    let cursor = |c: char| {
+        if parameter > 1 {
+            format!("\x1b[1;{parameter}{c}")
+        } else if application_cursor {
+            format!("\x1bO{c}")
+        } else {
+            format!("\x1b[{c}")
+        }
+    };
+    let tilde = |n: u8| {
+        if parameter > 1 {
+            format!("\x1b[{n};{parameter}~")
+        } else {
+            format!("\x1b[{n}~")
+        }
+    };
+    let sequence = match key.code {
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+            let byte = match c.to_ascii_lowercase() {
+                c @ 'a'..='z' => c as u8 - b'a' + 1,
+                ' ' | '@' | '2' => 0,
+                '[' | '3' => 0x1b,
+                '\\' | '4' => 0x1c,
+                ']' | '5' => 0x1d,
+                '^' | '6' => 0x1e,
+                '_' | '7' | '/' => 0x1f,
+                '8' | '?' => 0x7f,
+                _ => return None,
+            };
+            return Some(with_alt(alt, vec![byte]));
+        }
+        KeyCode::Char(c) => return Some(with_alt(alt, c.to_string().into_bytes())),
+        KeyCode::Enter => return Some(with_alt(alt, vec![b'\r'])),
+        KeyCode::Tab => return Some(with_alt(alt, vec![b'\t'])),
+        KeyCode::Backspace => return Some(with_alt(alt, vec![0x7f])),
+        KeyCode::Esc => return Some(with_alt(alt, vec![0x1b])),
+        KeyCode::BackTab => "\x1b[Z".to_string(),
+        KeyCode::Up => cursor('A'),
+        KeyCode::Down => cursor('B'),
+        KeyCode::Right => cursor('C'),
+        KeyCode::Left => cursor('D'),
+        KeyCode::Home => cursor('H'),
+        KeyCode::End => cursor('F'),
+        KeyCode::Insert => tilde(2),
+        KeyCode::Delete => tilde(3),
+        KeyCode::PageUp => tilde(5),
+        KeyCode::PageDown => tilde(6),
+        KeyCode::F(n @ 1..=4) if parameter == 1 => format!("\x1bO{}", char::from(b'O' + n)),
+        KeyCode::F(n @ 1..=4) => format!("\x1b[1;{parameter}{}", char::from(b'O' + n)),
+        KeyCode::F(n @ 5) => tilde(n + 10),
+        KeyCode::F(n @ 6..=10) => tilde(n + 11),
+        KeyCode::F(n @ 11..=12) => tilde(n + 12),
+        _ => return None,
+    };
+    Some(sequence.into_bytes())
+}
+
+fn with_alt(alt: bool, mut bytes: Vec<u8>) -> Vec<u8> {
+    if alt {
+        bytes.insert(0, 0x1b);
+    }
+    bytes
+}
+
+fn forward_pty_input(
+    mut keys: EventReader<RoutedKeyEvent>,
+    mut panes: Query<Interactive<&mut PtyPane>>,
+) {
+    for key in keys.read() {
+        if key.event.kind == KeyEventKind::Release {
+            continue;
+        }
+        let Ok((mut pane, visibility, disabled)) = panes.get_mut(key.target) else {
+            continue;
+        };
+        if !is_interactive(visibility, disabled) || pane.exit_status.is_some() {
+            continue;
+        }
+        let KeyEvent(event) = &key.event;
+        let Some(bytes) = encode_key(event, pane.screen().application_cursor()) else {
+            continue;
+        };
+        if let Err(err) = pane.write(&bytes) {
+            warn!("Failed to write to a pty pane: {err}");
+        }
+    }
+}
+
+fn read_pty_output(
+    mut panes: Query<(Entity, &mut PtyPane)>,
+    mut exited: EventWriter<PtyExited>,
+    redraw: Option<ResMut<RedrawRequested>>,
+) {
+    let mut changed = false;
+    for (entity, mut pane) in &mut panes {
+        // Bypassing change detection, as a pane whose program is quiet has not changed.
+        let pane = pane.bypass_change_detection();
+        changed |= pane.process_output();
+        if pane.exit_status.is_some() {
+            continue;
+        }
+        match pane.child.try_wait() {
+            Ok(Some(status)) => {
+                // Output written just before the exit may only have arrived now.
+                changed |= pane.process_output();
+                pane.exit_status = Some(status.clone());
+                exited.send(PtyExited {
+                    pane: entity,
+                    status,
+                });
+            }
+            Ok(None) => {}
+            Err(err) => warn!("Failed to check on the program of a pty pane: {err}"),
+        }
+    }
+    if let (true, Some(mut redraw)) = (changed, redraw) {
+        redraw.request();
+    }
+}
+
+fn draw_pty_panes(
+    mut buffer: ResMut<RenderBuffer>,
+    mut panes: Query<(
+        Entity,
+        &mut PtyPane,
+        &AnchoredArea,
+        Option<&WidgetVisibility>,
+    )>,
+    focused: Option<Res<FocusedPane>>,
+) {
+    let focused = focused.and_then(|focused| focused.0);
+    for (entity, mut pane, area, visibility) in &mut panes {
+        if !visibility.is_none_or(|visibility| visibility.is_visible()) {
+            continue;
+        }
+        if let Err(err) = pane.resize(area.as_size()) {
+            warn!("Failed to resize a pty pane: {err}");
+        }
+        PtyScreen::new(&pane)
+            .cursor(focused == Some(entity))
+            .render(area.0, &mut buffer);
+    }
+}
@@ -0,0 +1,175 @@
+//! Quitting the app from the keyboard.
+//!
+//! [`QuitPlugin`] exits the app when one of the [`QuitKeys`] is pressed. By default these are `q`
+//! and `Esc`. The [`QuitBehavior`] resource can require the key to be pressed twice, or ask for
+//! confirmation first. `Ctrl+C` is handled separately, as an
+//! [`InterruptRequested`](crate::event::InterruptRequested) event.
+//!
+//! The quit keys are ignored while the [pager](crate::pager), [search](crate::search),
+//! [file picker](crate::file_picker), a [table cell editor](crate::table) or the
+//! [history viewer](crate::history) is taking the key presses.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//!
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     quit::{QuitBehavior, QuitPlugin},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), QuitPlugin))
+//!     .insert_resource(QuitBehavior::DoublePress(Duration::from_millis(500)));
+//! ```
+use std::time::{Duration, Instant};
+
+use bevy::{app::AppExit, prelude::*};
+use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+
+use crate::{
+    event::{InputSet, KeyEvent},
+    file_picker::file_picker_open,
+    history::history_open,
+    pager::pager_closed,
+    search::search_active,
+    table::table_editing,
+};
+
+/// A plugin that exits the app when a quit key is pressed.
+pub struct QuitPlugin;
+
+impl Plugin for QuitPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QuitKeys>()
+            .init_resource::<QuitBehavior>()
+            .add_systems(
+                PreUpdate,
+                quit_system.in_set(InputSet::Post).run_if(
+                    pager_closed
+                        .and(not(search_active))
+                        .and(not(file_picker_open))
+                        .and(not(table_editing))
+                        .and(not(history_open)),
+                ),
+            );
+    }
+}
+
+/// A key together with the modifiers that must be held.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// A binding for the key without any modifiers.
+    pub const fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    /// A binding for the key with the control modifier held.
+    pub const fn ctrl(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    /// Returns true if the key event is a press of this binding.
+    pub fn matches(&self, event: &crossterm::event::KeyEvent) -> bool {
+        event.kind == KeyEventKind::Press
+            && event.code == self.code
+            && event.modifiers == self.modifiers
+    }
+}
+
+impl From<KeyCode> for KeyBinding {
+    fn from(code: KeyCode) -> Self {
+        Self::new(code)
+    }
+}
+
+/// The keys that quit the app.
+#[derive(Resource, Debug, Clone, Deref, DerefMut)]
+pub struct QuitKeys(pub Vec<KeyBinding>);
+
+impl Default for QuitKeys {
+    /// `q` and `Esc`.
+    fn default() -> Self {
+        Self(vec![
+            KeyBinding::new(KeyCode::Char('q')),
+            KeyBinding::new(KeyCode::Esc),
+        ])
+    }
+}
+
+impl QuitKeys {
+    fn matches(&self, event: &crossterm::event::KeyEvent) -> bool {
+        self.0.iter().any(|binding| binding.matches(event))
+    }
+}
+
+/// How the app responds to a quit key.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum QuitBehavior {
+    /// Quit as soon as a quit key is pressed.
+    #[default]
+    Immediate,
+    /// Quit when a quit key is pressed twice within the given duration.
+    DoublePress(Duration),
+    /// Ask for confirmation after a quit key is pressed. Pressing a quit key again, `y` or `Enter`
+    /// quits, while any other key cancels.
+    Confirm,
+}
+
+/// Present while the app is waiting for a second quit key press or a confirmation.
+///
+/// Apps can check for this resource to show a hint such as "Press q again to quit".
+#[derive(Resource, Debug, Clone, Copy, Deref)]
+pub struct QuitPending(pub Instant);
+
+pub(crate) fn quit_system(
+    mut keys: EventReader<KeyEvent>,
+    quit_keys: Res<QuitKeys>,
+    behavior: Res<QuitBehavior>,
+    quit_pending: Option<Res<QuitPending>>,
+    mut commands: Commands,
+    mut exit: EventWriter<AppExit>,
+) {
+    let was_pending = quit_pending.map(|pending| pending.0);
+    let mut pending = was_pending;
+    if let (QuitBehavior::DoublePress(window), Some(since)) = (*behavior, pending) {
+        if since.elapsed() > window {
+            pending = None;
+        }
+    }
+    for key in keys.read().filter(|key| key.kind == KeyEventKind::Press) {
+        let is_quit_key = quit_keys.matches(key);
+        let quit = match *behavior {
+            QuitBehavior::Immediate => is_quit_key,
+            QuitBehavior::DoublePress(_) => is_quit_key && pending.is_some(),
+            QuitBehavior::Confirm => {
+                pending.is_some()
+                    && (is_quit_key || matches!(key.code, KeyCode::Char('y') | KeyCode::Enter))
+            }
+        };
+        if quit {
+            exit.send_default();
+            pending = None;
+        } else if is_quit_key && pending.is_none() && *behavior != QuitBehavior::Immediate {
+            pending = Some(Instant::now());
+        } else {
+            pending = None;
+        }
+    }
+    match pending {
+        Some(since) if was_pending != Some(since) => commands.insert_resource(QuitPending(since)),
+        None if was_pending.is_some() => commands.remove_resource::<QuitPending>(),
+        _ => {}
+    }
+}
@@ -1,9 +1,19 @@
-use bevy::{app::PluginGroupBuilder, prelude::*};
+use bevy::{
+    app::PluginGroupBuilder,
+    ecs::schedule::{InternedScheduleLabel, ScheduleLabel},
+    prelude::*,
+};
 
-use crate::{error, event, input_forwarding, kitty, mouse, terminal};
+use crate::{
+    cell_metrics, draw_queue, env::EnvOverrides, error, event, exit, handshake, input_forwarding,
+    kitty, latency, mouse, paste, render, suspend, terminal, throttle, widget,
+};
 
 /// A plugin group that includes all the plugins in the Ratatui crate.
 ///
+/// The options can be overridden at runtime with `BEVY_RATATUI_*` environment variables. See the
+/// [env][crate::env] module for details.
+///
 /// # Example
 ///
 /// ```rust
@@ -19,6 +29,14 @@ pub struct RatatuiPlugins {
     pub enable_mouse_capture: bool,
     /// Forwards terminal input events to the bevy input system if enabled.
     pub enable_input_forwarding: bool,
+    /// Enables bracketed paste and sends pastes as [`PasteChunk`](paste::PasteChunk)s if enabled.
+    pub enable_bracketed_paste: bool,
+    /// The schedule that terminal events are read in. Defaults to [`PreUpdate`].
+    pub event_schedule: InternedScheduleLabel,
+    /// Draws to a [`TestBackend`](ratatui::backend::TestBackend) instead of the terminal if
+    /// enabled. The kitty protocol, mouse capture and bracketed paste are not enabled in this
+    /// mode. See [`HeadlessTerminal`](terminal::HeadlessTerminal).
+    pub headless: bool,
 }
 
 impl Default for RatatuiPlugins {
@@ -27,6 +45,9 @@ impl Default for RatatuiPlugins {
             enable_kitty_protocol: true,
             enable_mouse_capture: false,
             enable_input_forwarding: false,
+            enable_bracketed_paste: false,
+            event_schedule: PreUpdate.intern(),
+            headless: false,
         }
     }
 }
@@ -42,11 +63,41 @@ impl Default for RatatuiPlugins {
 /// App::new().add_plugins(RatatuiPlugins::default());
 /// ```
 impl PluginGroup for RatatuiPlugins {
-    fn build(self) -> PluginGroupBuilder {
+    fn build(mut self) -> PluginGroupBuilder {
+        let overrides = EnvOverrides::from_env();
+        if overrides.disable_kitty {
+            self.enable_kitty_protocol = false;
+        }
+        if overrides.disable_mouse {
+            self.enable_mouse_capture = false;
+        }
         let mut builder = PluginGroupBuilder::start::<Self>()
+            .add(crate::env::EnvPlugin(overrides))
             .add(error::ErrorPlugin)
+            .add(exit::ExitPlugin)
             .add(terminal::TerminalPlugin)
-            .add(event::EventPlugin);
+            .add(event::EventPlugin {
+                schedule: self.event_schedule,
+            })
+            .add(handshake::HandshakePlugin)
+            .add(latency::LatencySimulationPlugin)
+            .add(throttle::DrawThrottlePlugin)
+            .add(cell_metrics::CellMetricsPlugin)
+            .add(render::RenderPlugin)
+            .add(draw_queue::DrawQueuePlugin)
+            .add(widget::RootWidgetPlugin);
+        if self.headless {
+            builder = builder.add(terminal::HeadlessTerminalPlugin);
+            self.enable_kitty_protocol = false;
+            self.enable_mouse_capture = false;
+            self.enable_bracketed_paste = false;
+        } else {
+            builder = builder.add(suspend::SuspendPlugin);
+            #[cfg(feature = "sigwinch")]
+            {
+                builder = builder.add(crate::sigwinch::SigwinchPlugin);
+            }
+        }
         if self.enable_kitty_protocol {
             builder = builder.add(kitty::KittyPlugin);
         }
@@ -54,7 +105,15 @@ impl PluginGroup for RatatuiPlugins {
             builder = builder.add(mouse::MousePlugin);
         }
         if self.enable_input_forwarding {
-            builder = builder.add(input_forwarding::KeyboardPlugin);
+            builder = builder
+                .add(input_forwarding::KeyboardPlugin)
+                .add(input_forwarding::WindowForwardingPlugin);
+            if self.enable_mouse_capture {
+                builder = builder.add(input_forwarding::MouseForwardingPlugin);
+            }
+        }
+        if self.enable_bracketed_paste {
+            builder = builder.add(paste::PastePlugin);
         }
         builder
     }
@@ -0,0 +1,117 @@
+//! Drawing only when something has changed.
+//!
+//! Most TUIs sit idle most of the time, yet drawing every frame still renders every widget and
+//! diffs the whole buffer. With [`RedrawOnDemandPlugin`], the [`draw_due`] run condition is only
+//! true once a redraw has been requested through the [`RedrawRequested`] resource. Terminal events,
+//! including resizes, and changes to the [`RootWidget`] request a redraw, and so do systems that
+//! change what is on screen:
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     redraw::{RedrawOnDemandPlugin, RedrawRequested},
+//!     terminal::RatatuiContext,
+//!     throttle::draw_due,
+//!     RatatuiPlugins,
+//! };
+//!
+//! #[derive(Resource, Default)]
+//! struct Downloads(usize);
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), RedrawOnDemandPlugin))
+//!     .init_resource::<Downloads>()
+//!     .add_systems(Update, download)
+//!     .add_systems(PostUpdate, draw.run_if(draw_due));
+//!
+//! fn download(mut downloads: ResMut<Downloads>, mut redraw: ResMut<RedrawRequested>) {
+//!     downloads.0 += 1;
+//!     redraw.request();
+//! }
+//!
+//! fn draw(mut context: ResMut<RatatuiContext>, downloads: Res<Downloads>) {
+//!     let _ = context.draw(|frame| frame.render_widget(format!("{}", downloads.0), frame.area()));
+//! }
+//! ```
+//!
+//! A request stays pending until a draw has happened after it, so requests made while draws are
+//! [throttled](crate::throttle) are not lost.
+use std::time::Instant;
+
+use bevy::{ecs::event::EventUpdates, prelude::*};
+
+use crate::{
+    event::{CrosstermEvent, InputSet},
+    terminal::{sync_color_level, RatatuiContext},
+    widget::RootWidget,
+};
+
+/// A plugin that makes [`draw_due`] wait for a [`RedrawRequested`].
+///
+/// [`draw_due`]: crate::throttle::draw_due
+pub struct RedrawOnDemandPlugin;
+
+impl Plugin for RedrawOnDemandPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RedrawRequested>()
+            .add_systems(
+                First,
+                clear_redraw_request
+                    .after(EventUpdates)
+                    .after(sync_color_level),
+            )
+            .add_systems(PreUpdate, request_redraw_on_change.after(InputSet::Post));
+    }
+}
+
+/// Whether the screen needs to be drawn again.
+///
+/// A redraw is requested when this resource is created, so the first frame is always drawn.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedrawRequested {
+    requested_at: Option<Instant>,
+}
+
+impl Default for RedrawRequested {
+    fn default() -> Self {
+        Self {
+            requested_at: Some(Instant::now()),
+        }
+    }
+}
+
+impl RedrawRequested {
+    /// Requests that the screen is drawn again.
+    pub fn request(&mut self) {
+        self.requested_at.get_or_insert_with(Instant::now);
+    }
+
+    /// Whether a redraw has been requested since the last draw.
+    pub fn is_requested(&self) -> bool {
+        self.requested_at.is_some()
+    }
+}
+
+fn clear_redraw_request(context: Option<Res<RatatuiContext>>, mut redraw: ResMut<RedrawRequested>) {
+    let Some((finished, _)) = context.and_then(|context| context.last_draw()) else {
+        return;
+    };
+    if redraw
+        .requested_at
+        .is_some_and(|requested_at| requested_at <= finished)
+    {
+        redraw.requested_at = None;
+    }
+}
+
+fn request_redraw_on_change(
+    mut events: EventReader<CrosstermEvent>,
+    root: Option<Res<RootWidget>>,
+    mut redraw: ResMut<RedrawRequested>,
+) {
+    let has_events = !events.is_empty();
+    events.clear();
+    if has_events || root.is_some_and(|root| root.is_changed()) {
+        redraw.request();
+    }
+}
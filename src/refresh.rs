@@ -0,0 +1,98 @@
+//! Refreshing parts of the screen at their own rates.
+//!
+//! In a dashboard, a clock only changes once a second and a chart a few times a second, while the
+//! input line should follow every key press. Give each widget entity a [`RefreshRate`] and render
+//! it through [`RefreshRate::render`]: the widget is only rendered again once its interval has
+//! passed, and in between the cells it rendered last are copied into the frame. This saves the
+//! cost of rendering slow changing widgets every frame, and since the copied cells are unchanged,
+//! ratatui has nothing to write for them either.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{refresh::RefreshRate, terminal::RatatuiContext};
+//! use ratatui::{layout::Rect, widgets::Widget};
+//!
+//! #[derive(Component)]
+//! struct Clock;
+//!
+//! fn spawn_clock(mut commands: Commands) {
+//!     commands.spawn((Clock, RefreshRate::hz(1.0)));
+//! }
+//!
+//! fn draw(mut context: ResMut<RatatuiContext>, mut clock: Single<&mut RefreshRate, With<Clock>>) {
+//!     let _ = context.draw(|frame| {
+//!         let top = Rect { height: 1, ..frame.area() };
+//!         clock.render(top, frame.buffer_mut(), |area, buf| {
+//!             format!("{:?}", std::time::SystemTime::now()).render(area, buf)
+//!         });
+//!     });
+//! }
+//! ```
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use ratatui::{buffer::Buffer, layout::Rect};
+
+/// How often a widget entity is rendered, along with the cells it rendered last.
+#[derive(Component, Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefreshRate {
+    interval: Duration,
+    last_refresh: Option<Instant>,
+    cache: Buffer,
+}
+
+impl RefreshRate {
+    /// Renders at most once per `interval`.
+    pub fn every(interval: Duration) -> Self {
+        Self {
+            interval,
+            ..default()
+        }
+    }
+
+    /// Renders at most `rate` times per second.
+    ///
+    /// A rate that is not positive, such as 0 or NaN, only renders the widget again when its area
+    /// changes or after [`RefreshRate::invalidate`].
+    pub fn hz(rate: f64) -> Self {
+        Self::every(Duration::try_from_secs_f64(1.0 / rate).unwrap_or(Duration::MAX))
+    }
+
+    /// Renders every frame, without caching.
+    pub fn full() -> Self {
+        Self::default()
+    }
+
+    /// The shortest time between renders.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Whether the widget is rendered again by the next [`RefreshRate::render`].
+    pub fn is_due(&self) -> bool {
+        self.last_refresh
+            .is_none_or(|last_refresh| last_refresh.elapsed() >= self.interval)
+    }
+
+    /// Makes the next [`RefreshRate::render`] render the widget, e.g. after its content changed.
+    pub fn invalidate(&mut self) {
+        self.last_refresh = None;
+    }
+
+    /// Renders the widget into `area` if it is due or the area has changed, and otherwise copies
+    /// the cells it rendered last.
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer, render: impl FnOnce(Rect, &mut Buffer)) {
+        if self.interval.is_zero() {
+            render(area, buf);
+            return;
+        }
+        if self.is_due() || self.cache.area != area {
+            self.cache = Buffer::empty(area);
+            render(area, &mut self.cache);
+            self.last_refresh = Some(Instant::now());
+        }
+        for position in area.intersection(buf.area).positions() {
+            buf[position] = self.cache[position].clone();
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Parsing the input that a remote terminal sends, for the servers that draw to other terminals.
+//!
+//! Crossterm only reads the app's own terminal, so the bytes that arrive from an SSH client or a
+//! browser terminal are parsed here instead, as are the keys that the
+//! [startup handshake](crate::handshake) read from the app's own terminal.
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+
+/// Parses the bytes a terminal sent into key presses and mouse events.
+///
+/// This understands printable text, control characters, the escape sequences that terminals send
+/// for cursor, editing and function keys, with modifiers, and SGR mouse reports. Other escape
+/// sequences are dropped.
+pub(crate) fn parse_input(mut bytes: &[u8]) -> Vec<Event> {
+    let mut events = Vec::new();
+    while let Some(&byte) = bytes.first() {
+        let (event, len) = match byte {
+            0x1b => parse_escape(bytes),
+            _ => {
+                let (key, len) = parse_char(bytes);
+                (key.map(Event::Key), len)
+            }
+        };
+        events.extend(event);
+        bytes = &bytes[len.max(1)..];
+    }
+    events
+}
+
+fn parse_char(bytes: &[u8]) -> (Option<KeyEvent>, usize) {
+    let ctrl = |c: char| Some(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+    let key = match bytes[0] {
+        b'\r' | b'\n' => Some(KeyEvent::from(KeyCode::Enter)),
+        b'\t' => Some(KeyEvent::from(KeyCode::Tab)),
+        0x7f | 0x08 => Some(KeyEvent::from(KeyCode::Backspace)),
+        0x00 => ctrl(' '),
+        byte @ 0x01..=0x1a => ctrl(char::from(b'a' + byte - 1)),
+        byte @ 0x1c..=0x1f => ctrl(char::from(b'4' + byte - 0x1c)),
+        byte => {
+            let len = match byte {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 1,
+            };
+            let Some(c) = bytes
+                .get(..len)
+                .and_then(|bytes| std::str::from_utf8(bytes).ok())
+                .and_then(|s| s.chars().next())
+            else {
+                return (None, 1);
+            };
+            return (Some(KeyEvent::from(KeyCode::Char(c))), len);
+        }
+    };
+    (key, 1)
+}
+
+fn parse_escape(bytes: &[u8]) -> (Option<Event>, usize) {
+    match bytes.get(1) {
+        None | Some(0x1b) => (Some(Event::Key(KeyEvent::from(KeyCode::Esc))), 1),
+        Some(b'[') => parse_csi(bytes),
+        Some(b'O') => {
+            let code = bytes.get(2).and_then(|&byte| final_key(byte));
+            (code.map(|code| Event::Key(code.into())), 3.min(bytes.len()))
+        }
+        Some(_) => {
+            let (key, len) = parse_char(&bytes[1..]);
+            let key = key.map(|mut key| {
+                key.modifiers |= KeyModifiers::ALT;
+                Event::Key(key)
+            });
+            (key, len + 1)
+        }
+    }
+}
+
+/// Parses `ESC [ params final`, e.g. `ESC [ 1 ; 5 A` for Ctrl+Up, or `ESC [ < params M` for a
+/// mouse event.
+fn parse_csi(bytes: &[u8]) -> (Option<Event>, usize) {
+    let Some(end) = bytes[2..]
+        .iter()
+        .position(|byte| (0x40..=0x7e).contains(byte))
+    else {
+        return (None, bytes.len());
+    };
+    let len = end + 3;
+    let mouse = bytes[2] == b'<';
+    let params: Vec<u16> = std::str::from_utf8(&bytes[2 + usize::from(mouse)..len - 1])
+        .unwrap_or_default()
+        .split(';')
+        .map(|param| param.parse().unwrap_or(1))
+        .collect();
+    let last = bytes[len - 1];
+    if mouse {
+        return (parse_mouse(&params, last == b'M').map(Event::Mouse), len);
+    }
+    let code = match last {
+        b'~' => match params.first() {
+            Some(1 | 7) => Some(KeyCode::Home),
+            Some(2) => Some(KeyCode::Insert),
+            Some(3) => Some(KeyCode::Delete),
+            Some(4 | 8) => Some(KeyCode::End),
+            Some(5) => Some(KeyCode::PageUp),
+            Some(6) => Some(KeyCode::PageDown),
+            Some(&n @ 11..=15) => Some(KeyCode::F((n - 10) as u8)),
+            Some(&n @ 17..=21) => Some(KeyCode::F((n - 11) as u8)),
+            Some(&n @ 23..=24) => Some(KeyCode::F((n - 12) as u8)),
+            _ => None,
+        },
+        b'Z' => Some(KeyCode::BackTab),
+        byte => final_key(byte),
+    };
+    let modifiers = params
+        .get(1)
+        .map(|&param| {
+            let bits = param.saturating_sub(1);
+            let mut modifiers = KeyModifiers::NONE;
+            modifiers.set(KeyModifiers::SHIFT, bits & 1 != 0);
+            modifiers.set(KeyModifiers::ALT, bits & 2 != 0);
+            modifiers.set(KeyModifiers::CONTROL, bits & 4 != 0);
+            modifiers
+        })
+        .unwrap_or(KeyModifiers::NONE);
+    let modifiers = if code == Some(KeyCode::BackTab) {
+        modifiers | KeyModifiers::SHIFT
+    } else {
+        modifiers
+    };
+    (
+        code.map(|code| Event::Key(KeyEvent::new(code, modifiers))),
+        len,
+    )
+}
+
+/// Parses the parameters of an SGR mouse report, `ESC [ < button ; column ; row M`, which ends in
+/// `m` instead when a button is released.
+fn parse_mouse(params: &[u16], pressed: bool) -> Option<MouseEvent> {
+    let &[flags, column, row] = params else {
+        return None;
+    };
+    let button = match flags & 0b11 {
+        0 => Some(MouseButton::Left),
+        1 => Some(MouseButton::Middle),
+        2 => Some(MouseButton::Right),
+        _ => None,
+    };
+    let kind = if flags & 64 != 0 {
+        match flags & 0b11 {
+            0 => MouseEventKind::ScrollUp,
+            1 => MouseEventKind::ScrollDown,
+            2 => MouseEventKind::ScrollLeft,
+            _ => MouseEventKind::ScrollRight,
+        }
+    } else if flags & 32 != 0 {
+        button.map_or(MouseEventKind::Moved, MouseEventKind::Drag)
+    } else if pressed {
+        MouseEventKind::Down(button?)
+    } else {
+        MouseEventKind::Up(button?)
+    };
+    let mut modifiers = KeyModifiers::NONE;
+    modifiers.set(KeyModifiers::SHIFT, flags & 4 != 0);
+    modifiers.set(KeyModifiers::ALT, flags & 8 != 0);
+    modifiers.set(KeyModifiers::CONTROL, flags & 16 != 0);
+    Some(MouseEvent {
+        kind,
+        column: column.saturating_sub(1),
+        row: row.saturating_sub(1),
+        modifiers,
+    })
+}
+
+/// The key of the final byte of `ESC [ ... final` and `ESC O final`.
+fn final_key(byte: u8) -> Option<KeyCode> {
+    match byte {
+        b'A' => Some(KeyCode::Up),
+        b'B' => Some(KeyCode::Down),
+        b'C' => Some(KeyCode::Right),
+        b'D' => Some(KeyCode::Left),
+        b'H' => Some(KeyCode::Home),
+        b'F' => Some(KeyCode::End),
+        b'P' => Some(KeyCode::F(1)),
+        b'Q' => Some(KeyCode::F(2)),
+        b'R' => Some(KeyCode::F(3)),
+        b'S' => Some(KeyCode::F(4)),
+        _ => None,
+    }
+}
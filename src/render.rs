@@ -0,0 +1,173 @@
+//! Ordered drawing from several systems into one frame.
+//!
+//! Each [`RatatuiContext::draw`] writes a whole frame, so two systems that both draw overwrite
+//! each other. [`RenderPlugin`] instead gives every system the same [`RenderBuffer`] to render
+//! into, in the [`RenderSet`]s in [`PostUpdate`]: backgrounds first, then widgets, then overlays
+//! on top. The buffer is drawn to the terminal once, in [`RenderSet::Flush`], if any system
+//! rendered into it and a draw is [due](crate::throttle::draw_due).
+//!
+//! The plugin is part of [`RatatuiPlugins`](crate::RatatuiPlugins), and the
+//! [`RootWidget`](crate::widget::RootWidget) is rendered in [`RenderSet::Widgets`].
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     render::{RenderBuffer, RenderSet},
+//!     terminal::RatatuiContext,
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::{
+//!     style::{Style, Stylize},
+//!     widgets::Widget,
+//! };
+//!
+//! fn background(mut buffer: ResMut<RenderBuffer>) {
+//!     let area = buffer.area;
+//!     buffer.set_style(area, Style::new().on_blue());
+//! }
+//!
+//! fn status_line(mut buffer: ResMut<RenderBuffer>) {
+//!     let mut area = buffer.area;
+//!     area.height = area.height.min(1);
+//!     "ready".render(area, &mut buffer);
+//! }
+//!
+//! let mut app = App::new();
+//! app.add_plugins(RatatuiPlugins {
+//!     headless: true,
+//!     ..default()
+//! })
+//! .add_systems(PostUpdate, background.in_set(RenderSet::Background))
+//! .add_systems(PostUpdate, status_line.in_set(RenderSet::Overlay));
+//! app.update();
+//! let context = app.world().resource::<RatatuiContext>();
+//! assert!(context.screen_lines()[0].starts_with("ready"));
+//! ```
+//!
+//! Systems within one set are not ordered against each other, so two systems that render into the
+//! same cells should be in different sets or ordered explicitly.
+//!
+//! Contexts spawned as components, for [other terminals](RatatuiContext#other-terminals), have a
+//! [`RenderBuffer`] component of their own, which is cleared and drawn along with the resource.
+use std::io;
+
+use bevy::{
+    ecs::{component::Tick, system::SystemChangeTick},
+    prelude::*,
+};
+use ratatui::buffer::Buffer;
+
+use crate::{
+    error::exit_on_error,
+    terminal::{RatatuiContext, TerminalSet},
+    throttle::draw_due,
+};
+
+/// A plugin that sets up the [`RenderSet`]s and draws the [`RenderBuffer`].
+pub struct RenderPlugin;
+
+impl Plugin for RenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RenderBuffer>()
+            .configure_sets(
+                PostUpdate,
+                (
+                    RenderSet::Background,
+                    RenderSet::Widgets,
+                    RenderSet::Overlay,
+                    RenderSet::Flush,
+                )
+                    .chain()
+                    .run_if(resource_exists::<RatatuiContext>.and(draw_due))
+                    .before(TerminalSet::Cleanup),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    clear_render_buffer
+                        .run_if(resource_exists::<RatatuiContext>.and(draw_due))
+                        .before(RenderSet::Background),
+                    flush_render_buffer
+                        .pipe(exit_on_error)
+                        .in_set(RenderSet::Flush),
+                ),
+            );
+    }
+}
+
+/// The order that systems render into the [`RenderBuffer`] in, in [`PostUpdate`].
+///
+/// The sets are chained, and only run while there is a [`RatatuiContext`] and a draw is due.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone, Copy)]
+pub enum RenderSet {
+    /// Fill the background.
+    Background,
+    /// Render the main widgets.
+    Widgets,
+    /// Render popups, status lines and other things on top of the widgets.
+    Overlay,
+    /// Draw the buffer to the terminal.
+    Flush,
+}
+
+/// The frame that the [`RenderSet`] systems render into.
+///
+/// It is cleared and resized to the terminal before [`RenderSet::Background`], and drawn in
+/// [`RenderSet::Flush`] if any system has accessed it mutably. The resource is drawn to the
+/// [`RatatuiContext`] resource, and the component of an entity to the entity's context.
+#[derive(Resource, Component, Debug, Default, Clone, PartialEq, Eq, Deref, DerefMut)]
+pub struct RenderBuffer {
+    #[deref]
+    buffer: Buffer,
+    cleared_at: Tick,
+}
+
+fn clear_render_buffer(
+    mut context: ResMut<RatatuiContext>,
+    mut buffer: ResMut<RenderBuffer>,
+    mut others: Query<(&mut RatatuiContext, &mut RenderBuffer)>,
+    tick: SystemChangeTick,
+) {
+    clear(&mut context, buffer.reborrow(), tick.this_run());
+    for (mut context, buffer) in &mut others {
+        clear(&mut context, buffer, tick.this_run());
+    }
+}
+
+fn clear(context: &mut RatatuiContext, mut buffer: Mut<RenderBuffer>, now: Tick) {
+    let area = context.get_frame().area();
+    // Clearing is not rendering, so it must not make the buffer count as changed.
+    let buffer = buffer.bypass_change_detection();
+    if area == buffer.area {
+        buffer.reset();
+    } else {
+        buffer.buffer = Buffer::empty(area);
+    }
+    buffer.cleared_at = now;
+}
+
+fn flush_render_buffer(
+    mut context: ResMut<RatatuiContext>,
+    buffer: Res<RenderBuffer>,
+    mut others: Query<(&mut RatatuiContext, Ref<RenderBuffer>)>,
+    tick: SystemChangeTick,
+) -> color_eyre::Result<()> {
+    flush(&mut context, buffer.into(), tick.this_run())?;
+    for (mut context, buffer) in &mut others {
+        flush(&mut context, buffer, tick.this_run())?;
+    }
+    Ok(())
+}
+
+fn flush(context: &mut RatatuiContext, buffer: Ref<RenderBuffer>, now: Tick) -> io::Result<()> {
+    if !buffer.last_changed().is_newer_than(buffer.cleared_at, now) {
+        return Ok(());
+    }
+    context.draw(|frame| {
+        let area = frame.area().intersection(buffer.area);
+        for position in area.positions() {
+            frame.buffer_mut()[position] = buffer[position].clone();
+        }
+    })?;
+    Ok(())
+}
@@ -0,0 +1,165 @@
+//! Terminal rendering in a sub-app.
+//!
+//! [`RenderAppPlugin`] adds a [`RatatuiRenderApp`] sub-app with its own world. After each update
+//! of the main app in which a redraw is due, it runs two schedules in the sub-app:
+//!
+//! - [`TerminalExtract`], where systems copy what they need to draw from the [`MainWorld`] into
+//!   the render world. [`ExtractResourcePlugin`] does this for a single resource.
+//! - [`TerminalRender`], where systems draw to the [`RatatuiContext`], which is moved into the
+//!   render world for the duration of the schedule.
+//!
+//! Draw systems in the render world never contend with simulation systems, and the redraw rate is
+//! set independently of the simulation's tick rate with [`RenderAppPlugin::redraw_interval`].
+//! Draws in the render app come after any draws in the main app. [`AppExit`] events sent in the
+//! render world, e.g. by [`exit_on_error`](crate::error::exit_on_error), are forwarded to the main
+//! app.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//!
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     error::exit_on_error,
+//!     render_app::{ExtractResourcePlugin, RatatuiRenderApp, RenderAppPlugin, TerminalRender},
+//!     terminal::RatatuiContext,
+//!     RatatuiPlugins,
+//! };
+//!
+//! #[derive(Resource, Clone, Default)]
+//! struct Score(u32);
+//!
+//! let mut app = App::new();
+//! app.add_plugins((
+//!     RatatuiPlugins::default(),
+//!     RenderAppPlugin {
+//!         redraw_interval: Some(Duration::from_secs_f64(1.0 / 30.0)),
+//!     },
+//!     ExtractResourcePlugin::<Score>::default(),
+//! ))
+//! .init_resource::<Score>();
+//! app.sub_app_mut(RatatuiRenderApp)
+//!     .add_systems(TerminalRender, draw.pipe(exit_on_error));
+//!
+//! fn draw(mut context: ResMut<RatatuiContext>, score: Res<Score>) -> color_eyre::Result<()> {
+//!     context.draw(|frame| frame.render_widget(format!("Score: {}", score.0), frame.area()))?;
+//!     Ok(())
+//! }
+//! ```
+use std::{
+    marker::PhantomData,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    app::{AppExit, AppLabel, SubApp},
+    ecs::schedule::ScheduleLabel,
+    prelude::*,
+};
+
+use crate::terminal::RatatuiContext;
+
+/// The label of the terminal render sub-app.
+#[derive(AppLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RatatuiRenderApp;
+
+/// The schedule of the render app that copies data from the [`MainWorld`].
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TerminalExtract;
+
+/// The schedule of the render app that draws to the [`RatatuiContext`].
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TerminalRender;
+
+/// The main app's world, available as a resource of the render world while [`TerminalExtract`]
+/// runs.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct MainWorld(World);
+
+/// A plugin that adds the [`RatatuiRenderApp`] sub-app.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RenderAppPlugin {
+    /// The least time between two redraws. `None` redraws after every update of the main app.
+    pub redraw_interval: Option<Duration>,
+}
+
+impl Plugin for RenderAppPlugin {
+    fn build(&self, app: &mut App) {
+        let mut render_app = SubApp::new();
+        render_app
+            .init_schedule(TerminalExtract)
+            .init_schedule(TerminalRender)
+            .init_resource::<Events<AppExit>>()
+            .insert_resource(RedrawTimer {
+                interval: self.redraw_interval,
+                last: None,
+            })
+            .set_extract(extract_and_render);
+        app.insert_sub_app(RatatuiRenderApp, render_app);
+    }
+}
+
+#[derive(Resource, Debug)]
+struct RedrawTimer {
+    interval: Option<Duration>,
+    last: Option<Instant>,
+}
+
+/// Runs the render schedules if a redraw is due. The sub-app has no update schedule, since the
+/// schedules need the main world.
+fn extract_and_render(main_world: &mut World, render_world: &mut World) {
+    let now = Instant::now();
+    let mut timer = render_world.resource_mut::<RedrawTimer>();
+    if let (Some(interval), Some(last)) = (timer.interval, timer.last) {
+        if now.duration_since(last) < interval {
+            return;
+        }
+    }
+    timer.last = Some(now);
+
+    let Some(context) = main_world.remove_resource::<RatatuiContext>() else {
+        return;
+    };
+    render_world.insert_resource(MainWorld(std::mem::take(main_world)));
+    render_world.run_schedule(TerminalExtract);
+    let main = render_world
+        .remove_resource::<MainWorld>()
+        .expect("the main world is removed only here");
+    *main_world = main.0;
+
+    render_world.insert_resource(context);
+    render_world.run_schedule(TerminalRender);
+    if let Some(context) = render_world.remove_resource::<RatatuiContext>() {
+        main_world.insert_resource(context);
+    }
+
+    let exits: Vec<AppExit> = render_world
+        .resource_mut::<Events<AppExit>>()
+        .drain()
+        .collect();
+    main_world.send_event_batch(exits);
+    render_world.clear_trackers();
+}
+
+/// A plugin that copies the resource `R` of the main world into the render world in
+/// [`TerminalExtract`]. Add it after [`RenderAppPlugin`].
+pub struct ExtractResourcePlugin<R>(PhantomData<R>);
+
+impl<R> Default for ExtractResourcePlugin<R> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<R: Resource + Clone> Plugin for ExtractResourcePlugin<R> {
+    fn build(&self, app: &mut App) {
+        app.sub_app_mut(RatatuiRenderApp)
+            .add_systems(TerminalExtract, extract_resource::<R>);
+    }
+}
+
+fn extract_resource<R: Resource + Clone>(mut commands: Commands, main_world: Res<MainWorld>) {
+    match main_world.get_resource::<R>() {
+        Some(resource) => commands.insert_resource(resource.clone()),
+        None => commands.remove_resource::<R>(),
+    }
+}
@@ -0,0 +1,410 @@
+//! Deterministic input for rollback and networked games.
+//!
+//! Rollback and lockstep games exchange the input of every frame and re-simulate frames from it,
+//! so the input must encode to the same bytes on every machine and feed the simulation the same
+//! way whether it came from the terminal or from a peer.
+//!
+//! [`InputSnapshot::encode`] writes a frame's [`InputSnapshot`] in a stable, versioned binary
+//! format, and [`InputSnapshot::decode`] reads it back. Inserting an [`InputFeed`] resource makes
+//! [`InputSnapshotPlugin`](crate::input_snapshot::InputSnapshotPlugin) take each frame's snapshot
+//! from the feed instead of the terminal, so that confirmed or predicted inputs from the rollback
+//! system drive the simulation. Systems that read only the [`InputSnapshot`] then behave the same
+//! either way.
+//!
+//! ```rust
+//! use bevy_ratatui::{input_snapshot::InputSnapshot, quit::KeyBinding};
+//! use crossterm::event::KeyCode;
+//!
+//! let input = InputSnapshot {
+//!     pressed: vec![KeyBinding::new(KeyCode::Char(' ')), KeyBinding::ctrl(KeyCode::F(5))],
+//!     text: " ".into(),
+//!     mouse_delta: (-3, 200),
+//!     ..Default::default()
+//! };
+//! let bytes = input.encode();
+//! assert_eq!(InputSnapshot::decode(&bytes), Ok(input));
+//! ```
+use std::{collections::VecDeque, error::Error, fmt};
+
+use bevy::prelude::*;
+use crossterm::event::{KeyCode, KeyModifiers, MediaKeyCode, ModifierKeyCode, MouseButton};
+use ratatui::layout::{Position, Size};
+
+use crate::{input_snapshot::InputSnapshot, quit::KeyBinding};
+
+/// Snapshots to use instead of the terminal input, one per frame.
+///
+/// While this resource exists, the [`InputSnapshot`] of each frame is the next snapshot in the
+/// feed, or an empty snapshot if the feed has run dry, and the terminal input of the frame is left
+/// out of it. Remove the resource to go back to the terminal.
+///
+/// Only the snapshot is replaced. Terminal input is still read as usual, so systems that read the
+/// [`KeyEvent`](crate::event::KeyEvent), [`MouseEvent`](crate::event::MouseEvent) and other
+/// [terminal events](crate::event), or the Bevy input that
+/// [input forwarding](crate::input_forwarding) derives from them, still see the live terminal.
+/// A deterministic simulation must read its input from the [`InputSnapshot`] alone.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct InputFeed {
+    snapshots: VecDeque<InputSnapshot>,
+}
+
+impl InputFeed {
+    /// Queues the snapshot of a future frame.
+    pub fn push(&mut self, snapshot: InputSnapshot) {
+        self.snapshots.push_back(snapshot);
+    }
+
+    /// Takes the snapshot of the current frame.
+    pub fn next_snapshot(&mut self) -> InputSnapshot {
+        self.snapshots.pop_front().unwrap_or_default()
+    }
+
+    /// The number of queued snapshots.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
+
+/// The version of the encoding written by [`InputSnapshot::encode`].
+pub const ENCODING_VERSION: u8 = 1;
+
+/// Why an encoded [`InputSnapshot`] could not be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The data was written by a version of the encoding that this version cannot read.
+    UnsupportedVersion(u8),
+    /// The data ended in the middle of a snapshot.
+    UnexpectedEnd,
+    /// The data contains a value that no snapshot encodes to.
+    Invalid(&'static str),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported input encoding version {version}")
+            }
+            DecodeError::UnexpectedEnd => write!(f, "input data ended unexpectedly"),
+            DecodeError::Invalid(what) => write!(f, "invalid {what} in input data"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+impl InputSnapshot {
+    /// Encodes the snapshot in version [`ENCODING_VERSION`] of the binary encoding.
+    ///
+    /// The encoding depends only on the contents of the snapshot, so equal snapshots always
+    /// encode to the same bytes. Numbers are written as LEB128 variable length integers, so an
+    /// empty snapshot takes a few bytes.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = vec![ENCODING_VERSION];
+        write_keys(&mut out, &self.pressed);
+        write_keys(&mut out, &self.repeated);
+        write_keys(&mut out, &self.released);
+        write_str(&mut out, &self.text);
+        write_str(&mut out, &self.pasted);
+        write_buttons(&mut out, &self.mouse_pressed);
+        write_buttons(&mut out, &self.mouse_released);
+        write_i32(&mut out, self.mouse_delta.0);
+        write_i32(&mut out, self.mouse_delta.1);
+        match self.mouse_position {
+            Some(position) => {
+                out.push(1);
+                write_position(&mut out, position);
+            }
+            None => out.push(0),
+        }
+        write_i32(&mut out, self.scroll.0);
+        write_i32(&mut out, self.scroll.1);
+        match self.resized {
+            Some(size) => {
+                out.push(1);
+                write_u32(&mut out, size.width.into());
+                write_u32(&mut out, size.height.into());
+            }
+            None => out.push(0),
+        }
+        out.push(match self.focused {
+            None => 0,
+            Some(false) => 1,
+            Some(true) => 2,
+        });
+        out
+    }
+
+    /// Decodes a snapshot written by [`InputSnapshot::encode`].
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut reader = Reader { bytes };
+        let version = reader.u8()?;
+        if version != ENCODING_VERSION {
+            return Err(DecodeError::UnsupportedVersion(version));
+        }
+        let snapshot = InputSnapshot {
+            pressed: reader.keys()?,
+            repeated: reader.keys()?,
+            released: reader.keys()?,
+            text: reader.string()?,
+            pasted: reader.string()?,
+            mouse_pressed: reader.buttons()?,
+            mouse_released: reader.buttons()?,
+            mouse_delta: (reader.i32()?, reader.i32()?),
+            mouse_position: match reader.u8()? {
+                0 => None,
+                1 => Some(reader.position()?),
+                _ => return Err(DecodeError::Invalid("mouse position")),
+            },
+            scroll: (reader.i32()?, reader.i32()?),
+            resized: match reader.u8()? {
+                0 => None,
+                1 => Some(Size::new(reader.u16()?, reader.u16()?)),
+                _ => return Err(DecodeError::Invalid("size")),
+            },
+            focused: match reader.u8()? {
+                0 => None,
+                1 => Some(false),
+                2 => Some(true),
+                _ => return Err(DecodeError::Invalid("focus")),
+            },
+        };
+        if !reader.bytes.is_empty() {
+            return Err(DecodeError::Invalid("trailing data"));
+        }
+        Ok(snapshot)
+    }
+}
+
+const MEDIA_KEYS: [MediaKeyCode; 13] = [
+    MediaKeyCode::Play,
+    MediaKeyCode::Pause,
+    MediaKeyCode::PlayPause,
+    MediaKeyCode::Reverse,
+    MediaKeyCode::Stop,
+    MediaKeyCode::FastForward,
+    MediaKeyCode::Rewind,
+    MediaKeyCode::TrackNext,
+    MediaKeyCode::TrackPrevious,
+    MediaKeyCode::Record,
+    MediaKeyCode::LowerVolume,
+    MediaKeyCode::RaiseVolume,
+    MediaKeyCode::MuteVolume,
+];
+
+const MODIFIER_KEYS: [ModifierKeyCode; 14] = [
+    ModifierKeyCode::LeftShift,
+    ModifierKeyCode::LeftControl,
+    ModifierKeyCode::LeftAlt,
+    ModifierKeyCode::LeftSuper,
+    ModifierKeyCode::LeftHyper,
+    ModifierKeyCode::LeftMeta,
+    ModifierKeyCode::RightShift,
+    ModifierKeyCode::RightControl,
+    ModifierKeyCode::RightAlt,
+    ModifierKeyCode::RightSuper,
+    ModifierKeyCode::RightHyper,
+    ModifierKeyCode::RightMeta,
+    ModifierKeyCode::IsoLevel3Shift,
+    ModifierKeyCode::IsoLevel5Shift,
+];
+
+/// The keys without a payload, indexed by their tag. The tags of the keys with a payload follow.
+const SIMPLE_KEYS: [KeyCode; 22] = [
+    KeyCode::Backspace,
+    KeyCode::Enter,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Home,
+    KeyCode::End,
+    KeyCode::PageUp,
+    KeyCode::PageDown,
+    KeyCode::Tab,
+    KeyCode::BackTab,
+    KeyCode::Delete,
+    KeyCode::Insert,
+    KeyCode::Null,
+    KeyCode::Esc,
+    KeyCode::CapsLock,
+    KeyCode::ScrollLock,
+    KeyCode::NumLock,
+    KeyCode::PrintScreen,
+    KeyCode::Pause,
+    KeyCode::Menu,
+];
+const TAG_KEYPAD_BEGIN: u8 = 22;
+const TAG_F: u8 = 23;
+const TAG_CHAR: u8 = 24;
+const TAG_MEDIA: u8 = 25;
+const TAG_MODIFIER: u8 = 26;
+
+const MOUSE_BUTTONS: [MouseButton; 3] =
+    [MouseButton::Left, MouseButton::Right, MouseButton::Middle];
+
+fn index_of<T: PartialEq>(values: &[T], value: &T) -> u8 {
+    values
+        .iter()
+        .position(|candidate| candidate == value)
+        .expect("every value is listed") as u8
+}
+
+fn write_u32(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Zigzag encodes the value so that small negative numbers stay short.
+fn write_i32(out: &mut Vec<u8>, value: i32) {
+    write_u32(out, ((value << 1) ^ (value >> 31)) as u32);
+}
+
+fn write_str(out: &mut Vec<u8>, value: &str) {
+    write_u32(out, value.len() as u32);
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn write_position(out: &mut Vec<u8>, position: Position) {
+    write_u32(out, position.x.into());
+    write_u32(out, position.y.into());
+}
+
+fn write_keys(out: &mut Vec<u8>, keys: &[KeyBinding]) {
+    write_u32(out, keys.len() as u32);
+    for key in keys {
+        match key.code {
+            KeyCode::KeypadBegin => out.push(TAG_KEYPAD_BEGIN),
+            KeyCode::F(n) => out.extend([TAG_F, n]),
+            KeyCode::Char(c) => {
+                out.push(TAG_CHAR);
+                write_u32(out, c.into());
+            }
+            KeyCode::Media(media) => out.extend([TAG_MEDIA, index_of(&MEDIA_KEYS, &media)]),
+            KeyCode::Modifier(modifier) => {
+                out.extend([TAG_MODIFIER, index_of(&MODIFIER_KEYS, &modifier)]);
+            }
+            code => out.push(index_of(&SIMPLE_KEYS, &code)),
+        }
+        out.push(key.modifiers.bits());
+    }
+}
+
+fn write_buttons(out: &mut Vec<u8>, buttons: &[(MouseButton, Position)]) {
+    write_u32(out, buttons.len() as u32);
+    for (button, position) in buttons {
+        out.push(index_of(&MOUSE_BUTTONS, button));
+        write_position(out, *position);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        let (first, rest) = self.bytes.split_first().ok_or(DecodeError::UnexpectedEnd)?;
+        self.bytes = rest;
+        Ok(*first)
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        let mut value = 0u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.u8()?;
+            let bits = u32::from(byte & 0x7f);
+            // The fifth byte only has room for the top four bits.
+            if (bits << shift) >> shift != bits {
+                return Err(DecodeError::Invalid("integer"));
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::Invalid("integer"))
+    }
+
+    fn u16(&mut self) -> Result<u16, DecodeError> {
+        u16::try_from(self.u32()?).map_err(|_| DecodeError::Invalid("integer"))
+    }
+
+    fn i32(&mut self) -> Result<i32, DecodeError> {
+        let value = self.u32()?;
+        Ok((value >> 1) as i32 ^ -((value & 1) as i32))
+    }
+
+    fn len(&mut self) -> Result<usize, DecodeError> {
+        let len = self.u32()? as usize;
+        // Every element takes at least a byte, which stops a corrupt length from allocating.
+        if len > self.bytes.len() {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        Ok(len)
+    }
+
+    fn string(&mut self) -> Result<String, DecodeError> {
+        let len = self.len()?;
+        let (text, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        String::from_utf8(text.to_vec()).map_err(|_| DecodeError::Invalid("text"))
+    }
+
+    fn position(&mut self) -> Result<Position, DecodeError> {
+        Ok(Position::new(self.u16()?, self.u16()?))
+    }
+
+    fn listed<T: Copy>(&mut self, values: &[T], what: &'static str) -> Result<T, DecodeError> {
+        values
+            .get(usize::from(self.u8()?))
+            .copied()
+            .ok_or(DecodeError::Invalid(what))
+    }
+
+    fn keys(&mut self) -> Result<Vec<KeyBinding>, DecodeError> {
+        let len = self.len()?;
+        let mut keys = Vec::with_capacity(len);
+        for _ in 0..len {
+            let code = match self.u8()? {
+                TAG_KEYPAD_BEGIN => KeyCode::KeypadBegin,
+                TAG_F => KeyCode::F(self.u8()?),
+                TAG_CHAR => KeyCode::Char(
+                    char::from_u32(self.u32()?).ok_or(DecodeError::Invalid("character"))?,
+                ),
+                TAG_MEDIA => KeyCode::Media(self.listed(&MEDIA_KEYS, "media key")?),
+                TAG_MODIFIER => KeyCode::Modifier(self.listed(&MODIFIER_KEYS, "modifier key")?),
+                tag => *SIMPLE_KEYS
+                    .get(usize::from(tag))
+                    .ok_or(DecodeError::Invalid("key"))?,
+            };
+            let modifiers =
+                KeyModifiers::from_bits(self.u8()?).ok_or(DecodeError::Invalid("modifiers"))?;
+            keys.push(KeyBinding { code, modifiers });
+        }
+        Ok(keys)
+    }
+
+    fn buttons(&mut self) -> Result<Vec<(MouseButton, Position)>, DecodeError> {
+        let len = self.len()?;
+        let mut buttons = Vec::with_capacity(len);
+        for _ in 0..len {
+            buttons.push((
+                self.listed(&MOUSE_BUTTONS, "mouse button")?,
+                self.position()?,
+            ));
+        }
+        Ok(buttons)
+    }
+}
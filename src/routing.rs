@@ -0,0 +1,164 @@
+//! Routing input to panes, e.g. for several players on one keyboard.
+//!
+//! Entities marked with [`InputTarget`], such as the panes of a split screen game, each receive
+//! their own share of the input. [`InputRoutingPlugin`] resends key and mouse events as
+//! [`RoutedKeyEvent`]s and [`RoutedMouseEvent`]s that carry the entity they are meant for:
+//!
+//! - keys with a rule in [`InputRoutes`] go to the rule's target, whichever pane is focused, so
+//!   that e.g. WASD always moves the first player and the arrow keys the second,
+//! - mouse events go to the smallest target whose [`AnchoredArea`] contains the mouse, and a click
+//!   focuses it,
+//! - everything else goes to the [`FocusedPane`].
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::routing::{InputRoutes, InputRoutingPlugin, InputTarget, RoutedKeyEvent};
+//! use crossterm::event::KeyCode;
+//!
+//! #[derive(Component)]
+//! struct Player(u8);
+//!
+//! App::new()
+//!     .add_plugins(InputRoutingPlugin)
+//!     .add_systems(Startup, spawn_players)
+//!     .add_systems(Update, move_players);
+//!
+//! fn spawn_players(mut commands: Commands, mut routes: ResMut<InputRoutes>) {
+//!     let one = commands.spawn((Player(1), InputTarget)).id();
+//!     let two = commands.spawn((Player(2), InputTarget)).id();
+//!     routes.route(['w', 'a', 's', 'd'].map(KeyCode::Char), one);
+//!     routes.route([KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right], two);
+//! }
+//!
+//! fn move_players(mut keys: EventReader<RoutedKeyEvent>, players: Query<&Player>) {
+//!     for key in keys.read() {
+//!         if let Ok(player) = players.get(key.target) {
+//!             info!("player {} pressed {:?}", player.0, key.event.code);
+//!         }
+//!     }
+//! }
+//! ```
+use bevy::{prelude::*, utils::HashMap};
+use crossterm::event::{KeyCode, MouseEventKind};
+use ratatui::layout::Position;
+
+use crate::{
+    anchor::AnchoredArea,
+    event::{InputSet, KeyEvent, MouseEvent},
+};
+
+/// A plugin that routes key and mouse events to [`InputTarget`] entities.
+pub struct InputRoutingPlugin;
+
+impl Plugin for InputRoutingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InputRoutes>()
+            .init_resource::<FocusedPane>()
+            .add_event::<RoutedKeyEvent>()
+            .add_event::<RoutedMouseEvent>()
+            .add_systems(
+                PreUpdate,
+                (route_mouse_events, route_key_events)
+                    .chain()
+                    .in_set(InputSet::Post),
+            );
+    }
+}
+
+/// Marks an entity that receives routed input, such as a pane or a player.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InputTarget;
+
+/// The target that receives the input that no rule routes elsewhere.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Deref, DerefMut)]
+pub struct FocusedPane(pub Option<Entity>);
+
+/// Rules that send keys to a target regardless of which pane is focused.
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub struct InputRoutes {
+    keys: HashMap<KeyCode, Entity>,
+}
+
+impl InputRoutes {
+    /// Sends the keys to the target. A key can only have one target, so this replaces any earlier
+    /// rule for the same key.
+    pub fn route(&mut self, keys: impl IntoIterator<Item = KeyCode>, target: Entity) {
+        self.keys.extend(keys.into_iter().map(|key| (key, target)));
+    }
+
+    /// Removes the rules of a target, e.g. when a player leaves.
+    pub fn remove_target(&mut self, target: Entity) {
+        self.keys.retain(|_, routed| *routed != target);
+    }
+
+    /// The target of a key, if it has a rule.
+    pub fn target(&self, key: KeyCode) -> Option<Entity> {
+        self.keys.get(&key).copied()
+    }
+}
+
+/// A key event and the target it was routed to.
+#[derive(Debug, Clone, Event, PartialEq, Eq, Hash)]
+pub struct RoutedKeyEvent {
+    pub target: Entity,
+    pub event: KeyEvent,
+}
+
+/// A mouse event and the target it was routed to.
+#[derive(Debug, Clone, Copy, Event, PartialEq, Eq)]
+pub struct RoutedMouseEvent {
+    pub target: Entity,
+    pub event: MouseEvent,
+}
+
+fn route_key_events(
+    mut keys: EventReader<KeyEvent>,
+    routes: Res<InputRoutes>,
+    focused: Res<FocusedPane>,
+    targets: Query<(), With<InputTarget>>,
+    mut routed: EventWriter<RoutedKeyEvent>,
+) {
+    for key in keys.read() {
+        let target = routes
+            .target(key.code)
+            .or(focused.0)
+            .filter(|target| targets.contains(*target));
+        if let Some(target) = target {
+            routed.send(RoutedKeyEvent {
+                target,
+                event: key.clone(),
+            });
+        }
+    }
+}
+
+fn route_mouse_events(
+    mut mouse: EventReader<MouseEvent>,
+    mut focused: ResMut<FocusedPane>,
+    targets: Query<(Entity, Option<&AnchoredArea>), With<InputTarget>>,
+    mut routed: EventWriter<RoutedMouseEvent>,
+) {
+    for event in mouse.read() {
+        let position = Position::new(event.column, event.row);
+        let under_mouse = targets
+            .iter()
+            .filter_map(|(entity, area)| Some((entity, area?.0)))
+            .filter(|(_, area)| area.contains(position))
+            .min_by_key(|(_, area)| area.area())
+            .map(|(entity, _)| entity);
+        if let (Some(target), MouseEventKind::Down(_)) = (under_mouse, event.kind) {
+            if focused.0 != Some(target) {
+                focused.0 = Some(target);
+            }
+        }
+        let target = under_mouse
+            .or(focused.0)
+            .filter(|target| targets.contains(*target));
+        if let Some(target) = target {
+            routed.send(RoutedMouseEvent {
+                target,
+                event: *event,
+            });
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! An app runner that updates as soon as terminal input arrives.
+//!
+//! With bevy's [`ScheduleRunnerPlugin`](bevy::app::ScheduleRunnerPlugin), a key pressed just
+//! after a frame is only drawn at the end of the next frame, so keypress-to-screen latency can be
+//! up to a whole frame period. [`InputWakeRunnerPlugin`] waits for terminal input instead of
+//! sleeping between frames, and runs the next update as soon as input arrives, so the latency is
+//! about one poll. Without input, the app still updates once per frame period. With an
+//! [`InputThread`](crate::input_thread::InputThread), the runner is woken up by the thread rather
+//! than polling itself.
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//!
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{runner::InputWakeRunnerPlugin, RatatuiPlugins};
+//!
+//! App::new()
+//!     .add_plugins(RatatuiPlugins::default())
+//!     .add_plugins(InputWakeRunnerPlugin::new(Duration::from_secs_f64(1.0 / 30.0)))
+//!     .run();
+//! ```
+//!
+//! This replaces the app runner, so it should not be combined with another runner plugin. The
+//! `BEVY_RATATUI_FRAME_RATE` [environment variable](crate::env) replaces it in turn.
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy::{app::PluginsState, prelude::*, tasks::tick_global_task_pools_on_main_thread};
+use crossterm::event;
+
+use crate::{handshake::Handshake, input_thread::InputThread, terminal::RatatuiContext};
+
+/// Runs the app once per frame period, and immediately whenever terminal input arrives.
+pub struct InputWakeRunnerPlugin {
+    /// The longest time between updates when there is no input.
+    pub frame_period: Duration,
+}
+
+impl InputWakeRunnerPlugin {
+    pub fn new(frame_period: Duration) -> Self {
+        Self { frame_period }
+    }
+}
+
+impl Plugin for InputWakeRunnerPlugin {
+    fn build(&self, app: &mut App) {
+        let frame_period = self.frame_period;
+        app.set_runner(move |app| run(app, frame_period));
+    }
+}
+
+fn run(mut app: App, frame_period: Duration) -> AppExit {
+    if app.plugins_state() != PluginsState::Cleaned {
+        while app.plugins_state() == PluginsState::Adding {
+            tick_global_task_pools_on_main_thread();
+        }
+        app.finish();
+        app.cleanup();
+    }
+    loop {
+        let start = Instant::now();
+        app.update();
+        if let Some(exit) = app.should_exit() {
+            return exit;
+        }
+        let remaining = frame_period.saturating_sub(start.elapsed());
+        if remaining.is_zero() {
+            continue;
+        }
+        if can_wait_for_input(app.world()) {
+            // The event is left in the queue for the next update to read.
+            if let Some(input_thread) = app.world().get_resource::<InputThread>() {
+                input_thread.wait(remaining);
+                continue;
+            }
+            if event::poll(remaining).is_ok() {
+                continue;
+            }
+        }
+        thread::sleep(remaining);
+    }
+}
+
+/// Whether terminal input can be polled without getting in the way of the startup handshake, and
+/// whether crossterm reads the input at all. Headless, termwiz and writer contexts get their input
+/// elsewhere, so the runner sleeps instead.
+fn can_wait_for_input(world: &World) -> bool {
+    world
+        .get_resource::<RatatuiContext>()
+        .is_some_and(RatatuiContext::is_terminal)
+        && !world.contains_resource::<Handshake>()
+}
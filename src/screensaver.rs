@@ -0,0 +1,194 @@
+//! A screensaver for dashboards that are left running.
+//!
+//! [`ScreensaverPlugin`] starts the screensaver once there has been no terminal input for the
+//! [`Screensaver::timeout`], and stops it on the next key press, mouse event, paste or focus
+//! change. While it runs, the [`ScreensaverWidget`] is drawn over the whole screen if the app has
+//! inserted one, and otherwise the UI is dimmed. A [`ScreensaverEvent`] is sent when it starts and
+//! stops, and the [`screensaver_active`] run condition can pause work that nobody is looking at.
+//!
+//! ```rust
+//! use std::time::Duration;
+//!
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     screensaver::{Screensaver, ScreensaverPlugin, ScreensaverWidget},
+//!     terminal::RatatuiContext,
+//!     widget::RootWidget,
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::widgets::Paragraph;
+//!
+//! let mut app = App::new();
+//! app.add_plugins((
+//!     RatatuiPlugins {
+//!         headless: true,
+//!         ..default()
+//!     },
+//!     ScreensaverPlugin,
+//! ))
+//! .insert_resource(Screensaver {
+//!     timeout: Duration::ZERO,
+//! })
+//! .insert_resource(ScreensaverWidget::new(Paragraph::new("zzz")))
+//! .insert_resource(RootWidget::new(Paragraph::new("dashboard")));
+//! app.update();
+//! let context = app.world().resource::<RatatuiContext>();
+//! assert!(context.screen_lines()[0].starts_with("zzz"));
+//! ```
+//!
+//! The input that stops the screensaver is still sent to the app as usual.
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use crossterm::event::Event;
+use ratatui::{
+    style::{Modifier, Style},
+    widgets::WidgetRef,
+};
+
+use crate::{
+    event::{CrosstermEvent, InputSet},
+    redraw::RedrawRequested,
+    render::{RenderBuffer, RenderPlugin, RenderSet},
+    terminal::RatatuiContext,
+    throttle::draw_due,
+};
+
+/// A plugin that starts a screensaver after the [`Screensaver::timeout`] without input.
+///
+/// This requires the [`RenderPlugin`], and adds it if it is missing.
+pub struct ScreensaverPlugin;
+
+impl Plugin for ScreensaverPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RenderPlugin>() {
+            app.add_plugins(RenderPlugin);
+        }
+        app.init_resource::<Screensaver>()
+            .init_resource::<ScreensaverState>()
+            .add_event::<ScreensaverEvent>()
+            .add_systems(PreUpdate, update_screensaver.in_set(InputSet::Post))
+            .add_systems(
+                PostUpdate,
+                draw_screensaver
+                    .run_if(
+                        screensaver_active
+                            .and(resource_exists::<RatatuiContext>)
+                            .and(draw_due),
+                    )
+                    .after(RenderSet::Overlay)
+                    .before(RenderSet::Flush),
+            );
+    }
+}
+
+/// How long the app waits for input before starting the screensaver. Defaults to five minutes.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Screensaver {
+    pub timeout: Duration,
+}
+
+impl Default for Screensaver {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5 * 60),
+        }
+    }
+}
+
+/// What is drawn while the screensaver runs, instead of dimming the UI.
+#[derive(Resource, Deref, DerefMut)]
+pub struct ScreensaverWidget(pub Box<dyn WidgetRef + Send + Sync>);
+
+impl ScreensaverWidget {
+    /// Creates a screensaver from any widget that can be rendered by reference.
+    pub fn new<W: WidgetRef + Send + Sync + 'static>(widget: W) -> Self {
+        Self(Box::new(widget))
+    }
+}
+
+/// Whether the screensaver runs, and when the last input was.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreensaverState {
+    active: bool,
+    last_input: Instant,
+}
+
+impl Default for ScreensaverState {
+    fn default() -> Self {
+        Self {
+            active: false,
+            last_input: Instant::now(),
+        }
+    }
+}
+
+impl ScreensaverState {
+    /// Whether the screensaver runs.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// How long there has been no input for.
+    pub fn idle_for(&self) -> Duration {
+        self.last_input.elapsed()
+    }
+
+    /// Counts as input, which stops the screensaver and restarts the timeout, e.g. when an alert
+    /// should be seen.
+    pub fn wake(&mut self) {
+        self.last_input = Instant::now();
+    }
+}
+
+/// Sent when the screensaver starts and stops.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScreensaverEvent {
+    Started,
+    Stopped,
+}
+
+/// A run condition that is true while the screensaver runs.
+pub fn screensaver_active(state: Option<Res<ScreensaverState>>) -> bool {
+    state.is_some_and(|state| state.active)
+}
+
+fn update_screensaver(
+    mut events: EventReader<CrosstermEvent>,
+    mut state: ResMut<ScreensaverState>,
+    mut screensaver_events: EventWriter<ScreensaverEvent>,
+    screensaver: Res<Screensaver>,
+    redraw: Option<ResMut<RedrawRequested>>,
+) {
+    // Resizes come from the terminal rather than the user, so they don't count as input.
+    if events
+        .read()
+        .any(|event| !matches!(**event, Event::Resize(..)))
+    {
+        state.wake();
+    }
+    let active = state.idle_for() >= screensaver.timeout;
+    if active == state.active {
+        return;
+    }
+    state.active = active;
+    screensaver_events.send(if active {
+        ScreensaverEvent::Started
+    } else {
+        ScreensaverEvent::Stopped
+    });
+    if let Some(mut redraw) = redraw {
+        redraw.request();
+    }
+}
+
+fn draw_screensaver(mut buffer: ResMut<RenderBuffer>, widget: Option<Res<ScreensaverWidget>>) {
+    let area = buffer.area;
+    match widget {
+        Some(widget) => {
+            buffer.reset();
+            widget.render_ref(area, &mut buffer);
+        }
+        None => buffer.set_style(area, Style::new().add_modifier(Modifier::DIM)),
+    }
+}
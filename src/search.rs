@@ -0,0 +1,285 @@
+//! Incremental search.
+//!
+//! [`SearchState`] handles the keys for typing a search query and moving between matches, and
+//! [`SearchOverlay`] draws the input line. Components such as lists and the
+//! [pager](crate::pager) embed a [`SearchState`] and act on the [`SearchEvent`]s it returns, and use
+//! [`highlight_matches`] to style the matches in their content.
+//!
+//! For a single app-wide search, add the [`SearchPlugin`]. Pressing the [`SearchKey`] (`/` by
+//! default) starts a search in the [`Search`] resource, and the changes are sent as
+//! [`SearchEvent`]s:
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     search::{SearchEvent, SearchPlugin},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), SearchPlugin))
+//!     .add_systems(Update, filter_items);
+//!
+//! fn filter_items(mut events: EventReader<SearchEvent>) {
+//!     for event in events.read() {
+//!         if let SearchEvent::Changed(query) = event {
+//!             info!("filtering by {query}");
+//!         }
+//!     }
+//! }
+//! ```
+use bevy::prelude::*;
+use color_eyre::Result;
+use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Clear, Widget},
+};
+
+use crate::{
+    error::exit_on_error,
+    event::{InputSet, KeyEvent},
+    pager::pager_closed,
+    quit::{self, KeyBinding},
+    terminal::RatatuiContext,
+};
+
+/// A plugin that adds an app-wide incremental search.
+///
+/// While the search is active it takes all the key presses, and the
+/// [`QuitPlugin`](crate::quit::QuitPlugin) does not quit. The input line is drawn over the bottom
+/// row of the terminal after the app has drawn its frame.
+pub struct SearchPlugin;
+
+impl Plugin for SearchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Search>()
+            .init_resource::<SearchKey>()
+            .add_event::<SearchEvent>()
+            .add_systems(
+                PreUpdate,
+                search_input_system
+                    .in_set(InputSet::Post)
+                    .after(quit::quit_system)
+                    .run_if(pager_closed),
+            )
+            .add_systems(
+                Last,
+                draw_search_overlay
+                    .pipe(exit_on_error)
+                    .run_if(search_active.and(resource_exists::<RatatuiContext>)),
+            );
+    }
+}
+
+/// A run condition that is true while the app-wide search is active.
+pub fn search_active(search: Option<Res<Search>>) -> bool {
+    search.is_some_and(|search| search.is_active())
+}
+
+/// The key that starts the app-wide search. Defaults to `/`.
+#[derive(Resource, Debug, Clone, Copy, Deref, DerefMut)]
+pub struct SearchKey(pub KeyBinding);
+
+impl Default for SearchKey {
+    fn default() -> Self {
+        Self(KeyBinding::new(KeyCode::Char('/')))
+    }
+}
+
+/// The app-wide search used by the [`SearchPlugin`].
+#[derive(Resource, Debug, Default, Clone, Deref, DerefMut)]
+pub struct Search(pub SearchState);
+
+/// A change to a search, returned by [`SearchState::handle_key`] and sent by the [`SearchPlugin`].
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub enum SearchEvent {
+    /// The query was edited. Sent with an empty query when the search starts.
+    Changed(String),
+    /// Move to the next match.
+    Next,
+    /// Move to the previous match.
+    Previous,
+    /// The query was confirmed with `Enter`.
+    Submitted(String),
+    /// The search was cancelled with `Esc`.
+    Cancelled,
+}
+
+/// The state of an incremental search.
+///
+/// While active, typed characters edit the query, `Down`/`Ctrl+N` and `Up`/`Ctrl+P` move between
+/// matches, `Enter` confirms the query and `Esc` cancels it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SearchState {
+    /// The query being typed, if the search is active.
+    input: Option<String>,
+    /// The last confirmed query.
+    query: Option<String>,
+}
+
+impl SearchState {
+    /// Starts a new search with an empty query.
+    pub fn start(&mut self) -> SearchEvent {
+        self.input = Some(String::new());
+        SearchEvent::Changed(String::new())
+    }
+
+    /// Stops the search without changing the last confirmed query.
+    pub fn cancel(&mut self) {
+        self.input = None;
+    }
+
+    /// Returns true while the query is being typed.
+    pub fn is_active(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /// The query being typed, if the search is active.
+    pub fn input(&self) -> Option<&str> {
+        self.input.as_deref()
+    }
+
+    /// The last confirmed query, if any.
+    pub fn query(&self) -> Option<&str> {
+        self.query.as_deref()
+    }
+
+    /// The query to highlight: the one being typed if the search is active, otherwise the last
+    /// confirmed one. Empty queries are ignored.
+    pub fn highlighted(&self) -> Option<&str> {
+        self.input
+            .as_deref()
+            .or(self.query.as_deref())
+            .filter(|query| !query.is_empty())
+    }
+
+    /// Handles a key event while the search is active.
+    ///
+    /// Returns `None` if the search is not active or the key does nothing. All key presses are
+    /// taken while the search is active, so callers should not handle them further.
+    pub fn handle_key(&mut self, key: &crossterm::event::KeyEvent) -> Option<SearchEvent> {
+        let input = self.input.as_mut()?;
+        if key.kind == KeyEventKind::Release {
+            return None;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('n') if ctrl => Some(SearchEvent::Next),
+            KeyCode::Char('p') if ctrl => Some(SearchEvent::Previous),
+            KeyCode::Down => Some(SearchEvent::Next),
+            KeyCode::Up => Some(SearchEvent::Previous),
+            KeyCode::Char(c) if !ctrl => {
+                input.push(c);
+                Some(SearchEvent::Changed(input.clone()))
+            }
+            KeyCode::Backspace => {
+                input.pop();
+                Some(SearchEvent::Changed(input.clone()))
+            }
+            KeyCode::Enter => {
+                self.query = self.input.take();
+                self.query.clone().map(SearchEvent::Submitted)
+            }
+            KeyCode::Esc => {
+                self.cancel();
+                Some(SearchEvent::Cancelled)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Draws the search input line, e.g. `/query`, with a block cursor at the end.
+///
+/// Render it over the row where the input should appear, typically the bottom row of the screen.
+/// Nothing is drawn when the search is not active.
+pub struct SearchOverlay<'a> {
+    state: &'a SearchState,
+    style: Style,
+}
+
+impl<'a> SearchOverlay<'a> {
+    pub fn new(state: &'a SearchState) -> Self {
+        Self {
+            state,
+            style: Style::new(),
+        }
+    }
+
+    /// Sets the style of the input line.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+impl Widget for SearchOverlay<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Some(input) = self.state.input() else {
+            return;
+        };
+        Clear.render(area, buf);
+        buf.set_style(area, self.style);
+        Line::from(vec![
+            Span::raw("/"),
+            Span::raw(input),
+            Span::styled(" ", Style::new().add_modifier(Modifier::REVERSED)),
+        ])
+        .render(area, buf);
+    }
+}
+
+/// Splits the line into spans, with the matches of the query styled with `style`.
+pub fn highlight_matches<'a>(line: &'a str, query: Option<&str>, style: Style) -> Line<'a> {
+    let Some(query) = query.filter(|query| !query.is_empty()) else {
+        return Line::from(line);
+    };
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for (index, matched) in line.match_indices(query) {
+        if index > start {
+            spans.push(Span::raw(&line[start..index]));
+        }
+        spans.push(Span::styled(matched, style));
+        start = index + matched.len();
+    }
+    if start < line.len() {
+        spans.push(Span::raw(&line[start..]));
+    }
+    Line::from(spans)
+}
+
+fn search_input_system(
+    mut keys: EventReader<KeyEvent>,
+    search_key: Res<SearchKey>,
+    mut search: ResMut<Search>,
+    mut events: EventWriter<SearchEvent>,
+) {
+    for key in keys.read() {
+        if search.is_active() {
+            if let Some(event) = search.handle_key(key) {
+                events.send(event);
+            }
+        } else if search_key.matches(key) {
+            events.send(search.start());
+        }
+    }
+}
+
+/// Draws the [`SearchOverlay`] for the app-wide search over the bottom row of the last frame.
+pub fn draw_search_overlay(mut context: ResMut<RatatuiContext>, search: Res<Search>) -> Result<()> {
+    let last_frame = context.last_frame().clone();
+    context.draw(|frame| {
+        let area = frame.area();
+        if last_frame.area == area {
+            *frame.buffer_mut() = last_frame;
+        }
+        let row = Rect::new(area.x, area.bottom().saturating_sub(1), area.width, 1);
+        frame.render_widget(SearchOverlay::new(&search), row.intersection(area));
+    })?;
+    Ok(())
+}
@@ -0,0 +1,144 @@
+//! Resizing on `SIGWINCH`.
+//!
+//! crossterm reports a resize as an event that is only read when the terminal is next polled, and
+//! the terminal size it carries is the one at that time. With the `sigwinch` feature,
+//! [`SigwinchPlugin`] handles the `SIGWINCH` signal that the terminal sends when it is resized,
+//! asking the terminal for its new size right in the signal handler. The next frame sends a
+//! [`ResizeEvent`](crate::event::ResizeEvent) with that size even if crossterm has not read its own
+//! resize event yet, e.g. because an [`InputThread`](crate::input_thread::InputThread) is still
+//! waiting to be woken up, so an app with a low frame rate is laid out for the new size in the
+//! first frame after the resize. A resize event that crossterm reads later for the same size is
+//! dropped, so each resize is sent once, and resizes are still coalesced and debounced as described
+//! in [`ResizeDebounce`](crate::event::ResizeDebounce).
+//!
+//! The plugin is part of the [`RatatuiPlugins`](crate::RatatuiPlugins) when the feature is enabled
+//! and the terminal is not headless. Any `SIGWINCH` handler installed before it, such as
+//! crossterm's, is still called. It does nothing on platforms other than unix.
+//!
+//! ```toml
+//! [dependencies]
+//! bevy_ratatui = { version = "*", features = ["sigwinch"] }
+//! ```
+#[cfg(unix)]
+use std::{
+    fs::File,
+    os::fd::IntoRawFd,
+    sync::{
+        atomic::{AtomicI32, AtomicU64, Ordering},
+        OnceLock,
+    },
+};
+
+use bevy::prelude::*;
+#[cfg(unix)]
+use ratatui::layout::Size;
+
+#[cfg(unix)]
+use crate::terminal::{TerminalSet, TerminalStartup};
+
+/// A plugin that reads the terminal size as soon as the terminal sends `SIGWINCH`.
+pub struct SigwinchPlugin;
+
+impl Plugin for SigwinchPlugin {
+    fn build(&self, app: &mut App) {
+        #[cfg(unix)]
+        app.add_systems(
+            TerminalStartup,
+            install_signal_handler.in_set(TerminalSet::Features),
+        );
+        #[cfg(not(unix))]
+        let _ = app;
+    }
+}
+
+/// The controlling terminal, which the signal handler asks for its size, or `-1` before the
+/// handler is installed.
+#[cfg(unix)]
+static TTY: AtomicI32 = AtomicI32::new(-1);
+
+/// The size read by the signal handler and not yet taken, as `1 << 32 | columns << 16 | rows`, or
+/// zero.
+#[cfg(unix)]
+static SIGNALLED_SIZE: AtomicU64 = AtomicU64::new(0);
+
+/// The `SIGWINCH` action that was installed before ours.
+#[cfg(unix)]
+static PREVIOUS_ACTION: OnceLock<libc::sigaction> = OnceLock::new();
+
+#[cfg(unix)]
+extern "C" fn on_sigwinch(
+    signal: libc::c_int,
+    info: *mut libc::siginfo_t,
+    context: *mut libc::c_void,
+) {
+    // Only async-signal-safe work is allowed here, which includes `ioctl`.
+    let tty = TTY.load(Ordering::SeqCst);
+    // SAFETY: `winsize` is plain data, and `TIOCGWINSZ` only writes to it.
+    unsafe {
+        let mut size: libc::winsize = std::mem::zeroed();
+        if tty >= 0 && libc::ioctl(tty, libc::TIOCGWINSZ, &mut size) == 0 {
+            let packed = 1 << 32 | u64::from(size.ws_col) << 16 | u64::from(size.ws_row);
+            SIGNALLED_SIZE.store(packed, Ordering::SeqCst);
+        }
+    }
+    let Some(previous) = PREVIOUS_ACTION.get() else {
+        return;
+    };
+    let handler = previous.sa_sigaction;
+    if handler == libc::SIG_DFL || handler == libc::SIG_IGN {
+        return;
+    }
+    // SAFETY: the previous handler was installed for this signal with these flags, so it has the
+    // matching signature.
+    unsafe {
+        if previous.sa_flags & libc::SA_SIGINFO != 0 {
+            let handler = std::mem::transmute::<
+                libc::sighandler_t,
+                extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void),
+            >(handler);
+            handler(signal, info, context);
+        } else {
+            let handler =
+                std::mem::transmute::<libc::sighandler_t, extern "C" fn(libc::c_int)>(handler);
+            handler(signal);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn install_signal_handler() {
+    if TTY.load(Ordering::SeqCst) >= 0 {
+        return;
+    }
+    let tty = match File::open("/dev/tty") {
+        Ok(tty) => tty.into_raw_fd(),
+        Err(err) => {
+            warn!("Failed to open the terminal, not handling SIGWINCH: {err}");
+            return;
+        }
+    };
+    TTY.store(tty, Ordering::SeqCst);
+    let handler = on_sigwinch as extern "C" fn(libc::c_int, *mut libc::siginfo_t, *mut libc::c_void)
+        as libc::sighandler_t;
+    // SAFETY: the actions are fully initialized, and the handler only does async-signal-safe work.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler;
+        action.sa_flags = libc::SA_RESTART | libc::SA_SIGINFO;
+        libc::sigemptyset(&mut action.sa_mask);
+        let mut previous: libc::sigaction = std::mem::zeroed();
+        // The previous action is stored before the new one is installed, so that the handler
+        // never misses it.
+        libc::sigaction(libc::SIGWINCH, std::ptr::null(), &mut previous);
+        let _ = PREVIOUS_ACTION.set(previous);
+        libc::sigaction(libc::SIGWINCH, &action, std::ptr::null_mut());
+    }
+}
+
+/// Takes the terminal size read when the last `SIGWINCH` arrived, if one arrived since the last
+/// call.
+#[cfg(unix)]
+pub(crate) fn take_signalled_size() -> Option<Size> {
+    let packed = SIGNALLED_SIZE.swap(0, Ordering::SeqCst);
+    (packed != 0).then(|| Size::new((packed >> 16) as u16, packed as u16))
+}
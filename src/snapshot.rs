@@ -0,0 +1,161 @@
+//! Render-only copies of resources and components for draw systems.
+//!
+//! A draw system that reads a resource shares access to it with every simulation system that
+//! writes it, so the two cannot run in parallel and the draw system sees whatever state the frame
+//! happens to be in. Like bevy's render extraction, [`SnapshotPlugin`] copies a resource into a
+//! [`Snapshot`] once per frame in [`SnapshotSet`], and [`ComponentSnapshotPlugin`] does the same for
+//! a component into a [`ComponentSnapshot`]. Draw systems that read only the snapshots can run
+//! after [`SnapshotSet`] without contending with the simulation, and always see a consistent state.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     snapshot::{Snapshot, SnapshotPlugin, SnapshotSet},
+//!     terminal::{RatatuiContext, TerminalSet},
+//!     RatatuiPlugins,
+//! };
+//!
+//! #[derive(Resource, Clone, Default)]
+//! struct Score(u32);
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), SnapshotPlugin::<Score>::default()))
+//!     .init_resource::<Score>()
+//!     .add_systems(
+//!         PostUpdate,
+//!         draw.after(SnapshotSet).before(TerminalSet::Cleanup),
+//!     );
+//!
+//! fn draw(mut context: ResMut<RatatuiContext>, score: Res<Snapshot<Score>>) {
+//!     let _ = context.draw(|frame| {
+//!         if let Some(score) = score.get() {
+//!             frame.render_widget(format!("Score: {}", score.0), frame.area());
+//!         }
+//!     });
+//! }
+//! ```
+use std::marker::PhantomData;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::terminal::TerminalSet;
+
+/// The set in [`PostUpdate`] that copies resources and components into their snapshots. It runs
+/// before [`TerminalSet::Cleanup`].
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SnapshotSet;
+
+/// A plugin that copies the resource `R` into a [`Snapshot<R>`] every frame.
+pub struct SnapshotPlugin<R>(PhantomData<R>);
+
+impl<R> Default for SnapshotPlugin<R> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<R: Resource + Clone> Plugin for SnapshotPlugin<R> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Snapshot<R>>()
+            .configure_sets(PostUpdate, SnapshotSet.before(TerminalSet::Cleanup))
+            .add_systems(PostUpdate, snapshot_resource::<R>.in_set(SnapshotSet));
+    }
+}
+
+/// A copy of the resource `R` as it was in the last [`SnapshotSet`].
+#[derive(Resource, Debug, Clone)]
+pub struct Snapshot<R>(Option<R>);
+
+impl<R> Default for Snapshot<R> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<R> Snapshot<R> {
+    /// The copied resource, or `None` if the resource did not exist.
+    pub fn get(&self) -> Option<&R> {
+        self.0.as_ref()
+    }
+}
+
+fn snapshot_resource<R: Resource + Clone>(
+    resource: Option<Res<R>>,
+    mut snapshot: ResMut<Snapshot<R>>,
+) {
+    match resource {
+        Some(resource) if resource.is_changed() || snapshot.0.is_none() => {
+            snapshot.0 = Some(resource.clone());
+        }
+        Some(_) => {}
+        None => {
+            if snapshot.0.is_some() {
+                snapshot.0 = None;
+            }
+        }
+    }
+}
+
+/// A plugin that copies every component `C` into a [`ComponentSnapshot<C>`] every frame.
+pub struct ComponentSnapshotPlugin<C>(PhantomData<C>);
+
+impl<C> Default for ComponentSnapshotPlugin<C> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<C: Component + Clone> Plugin for ComponentSnapshotPlugin<C> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ComponentSnapshot<C>>()
+            .configure_sets(PostUpdate, SnapshotSet.before(TerminalSet::Cleanup))
+            .add_systems(PostUpdate, snapshot_components::<C>.in_set(SnapshotSet));
+    }
+}
+
+/// Copies of the components `C` of every entity as they were in the last [`SnapshotSet`].
+#[derive(Resource, Debug, Clone)]
+pub struct ComponentSnapshot<C>(HashMap<Entity, C>);
+
+impl<C> Default for ComponentSnapshot<C> {
+    fn default() -> Self {
+        Self(HashMap::default())
+    }
+}
+
+impl<C> ComponentSnapshot<C> {
+    /// The copied component of an entity.
+    pub fn get(&self, entity: Entity) -> Option<&C> {
+        self.0.get(&entity)
+    }
+
+    /// The entities and their copied components, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &C)> {
+        self.0
+            .iter()
+            .map(|(entity, component)| (*entity, component))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+fn snapshot_components<C: Component + Clone>(
+    components: Query<(Entity, Ref<C>)>,
+    mut removed: RemovedComponents<C>,
+    mut snapshot: ResMut<ComponentSnapshot<C>>,
+) {
+    for entity in removed.read() {
+        snapshot.0.remove(&entity);
+    }
+    for (entity, component) in &components {
+        if component.is_changed() || !snapshot.0.contains_key(&entity) {
+            snapshot.0.insert(entity, component.clone());
+        }
+    }
+}
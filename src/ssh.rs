@@ -0,0 +1,578 @@
+//! Serving the app to remote players over SSH.
+//!
+//! [`SshServerPlugin`] listens for SSH connections on a background thread. Every client that opens
+//! a session gets an entity with an [`SshClient`] and a [`RatatuiContext`] that draws to the
+//! client's terminal, so systems render into the entity's
+//! [`RenderBuffer`](crate::render::RenderBuffer) like they do for
+//! [other terminals](crate::terminal::RatatuiContext#other-terminals). The keys the client presses
+//! are sent as [`SshKeyEvent`]s, and the context is resized when the client's window is.
+//! Despawning the entity disconnects the client.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     render::{RenderBuffer, RenderSet},
+//!     ssh::{SshClient, SshKeyEvent, SshServerPlugin},
+//!     RatatuiPlugins,
+//! };
+//! use crossterm::event::KeyCode;
+//! use ratatui::widgets::Widget;
+//!
+//! App::new()
+//!     .add_plugins((
+//!         RatatuiPlugins {
+//!             headless: true,
+//!             ..default()
+//!         },
+//!         SshServerPlugin,
+//!     ))
+//!     .add_systems(Update, quit_on_q)
+//!     .add_systems(PostUpdate, greet.in_set(RenderSet::Widgets))
+//!     .run();
+//!
+//! fn quit_on_q(mut keys: EventReader<SshKeyEvent>, mut commands: Commands) {
+//!     for key in keys.read() {
+//!         if key.code == KeyCode::Char('q') {
+//!             commands.entity(key.client).despawn();
+//!         }
+//!     }
+//! }
+//!
+//! fn greet(mut clients: Query<(&SshClient, &mut RenderBuffer)>) {
+//!     for (client, mut buffer) in &mut clients {
+//!         let area = buffer.area;
+//!         format!("hello {}, press q to leave", client.user).render(area, &mut buffer);
+//!     }
+//! }
+//! ```
+//!
+//! A server without a terminal of its own runs [headless](crate::terminal::HeadlessTerminal),
+//! which still draws the clients. The [`SshServer`] resource sets the address to listen on, the
+//! host key, and who may connect. By default the server only listens on the local machine and
+//! only lets in the keys in an `authorized_keys` file. Serving a public game to anyone is an
+//! explicit choice:
+//!
+//! ```rust
+//! use bevy_ratatui::ssh::{AuthorizedKeys, SshServer};
+//!
+//! let server = SshServer {
+//!     address: "0.0.0.0:2222".into(),
+//!     authorized_keys: AuthorizedKeys::Anyone,
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! This module requires the `ssh` feature.
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Write},
+    net::SocketAddr,
+    path::PathBuf,
+    process::Command,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
+
+use bevy::prelude::*;
+use crossterm::{
+    cursor,
+    event::{Event, KeyEvent},
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+    QueueableCommand,
+};
+use ratatui::layout::Size;
+use russh::{
+    keys::{PrivateKey, PublicKey},
+    server::{Auth, ChannelOpenHandle, Config, Handle, Handler, Msg, Server, Session},
+    Channel, ChannelId, Pty,
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::{event::InputSet, remote_input::parse_input, terminal::RatatuiContext};
+
+/// A plugin that serves the app to SSH clients. See the [module docs](self).
+pub struct SshServerPlugin;
+
+impl Plugin for SshServerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SshServer>()
+            .add_event::<SshKeyEvent>()
+            .add_event::<SshClientConnected>()
+            .add_event::<SshClientDisconnected>()
+            .add_systems(Startup, start_ssh_server)
+            .add_systems(
+                PreUpdate,
+                receive_ssh_messages.in_set(InputSet::EmitCrossterm),
+            );
+    }
+}
+
+/// Where and how the [`SshServerPlugin`] listens.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct SshServer {
+    /// The address to listen on. Defaults to `127.0.0.1:2222`, which only takes connections from
+    /// the local machine.
+    pub address: String,
+    /// The OpenSSH private key that identifies the server. It is created with `ssh-keygen` if it
+    /// does not exist, so that clients see the same key every time the server starts. Defaults to
+    /// `ssh_host_ed25519_key`.
+    pub host_key: PathBuf,
+    /// Who may connect. Defaults to the keys in an `authorized_keys` file, which must exist.
+    pub authorized_keys: AuthorizedKeys,
+}
+
+impl Default for SshServer {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:2222".into(),
+            host_key: "ssh_host_ed25519_key".into(),
+            authorized_keys: AuthorizedKeys::default(),
+        }
+    }
+}
+
+/// Who may connect to the [`SshServer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthorizedKeys {
+    /// Clients with one of the public keys in this `authorized_keys` file. Defaults to
+    /// `authorized_keys`.
+    File(PathBuf),
+    /// Anyone, without authentication, as public game servers do.
+    Anyone,
+}
+
+impl Default for AuthorizedKeys {
+    fn default() -> Self {
+        Self::File("authorized_keys".into())
+    }
+}
+
+/// A connected SSH client. The entity also has a [`RatatuiContext`] that draws to the client.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct SshClient {
+    /// The user name the client logged in with.
+    pub user: String,
+    /// The address the client connected from.
+    pub peer: Option<SocketAddr>,
+    /// The `TERM` of the client's terminal, once it has asked for a PTY.
+    pub term: Option<String>,
+}
+
+/// A key pressed by an SSH client.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct SshKeyEvent {
+    /// The [`SshClient`] entity.
+    pub client: Entity,
+    #[deref]
+    pub key: KeyEvent,
+}
+
+/// Sent when an SSH client has opened a session.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct SshClientConnected(pub Entity);
+
+/// Sent when an SSH client has disconnected. The entity has been despawned by then.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct SshClientDisconnected(pub Entity);
+
+/// What the server thread tells the app.
+enum SshMessage {
+    Opened {
+        id: u64,
+        user: String,
+        peer: Option<SocketAddr>,
+        output: SshOutput,
+    },
+    Pty {
+        id: u64,
+        term: String,
+        size: Size,
+    },
+    Resized {
+        id: u64,
+        size: Size,
+    },
+    Input {
+        id: u64,
+        bytes: Vec<u8>,
+    },
+    Closed {
+        id: u64,
+    },
+}
+
+/// The running server, and the clients it has connected.
+#[derive(Resource)]
+struct SshConnections {
+    messages: UnboundedReceiver<SshMessage>,
+    thread: Option<JoinHandle<io::Result<()>>>,
+    clients: HashMap<u64, Entity>,
+}
+
+fn start_ssh_server(mut commands: Commands, settings: Res<SshServer>) {
+    let settings = settings.clone();
+    let (sender, messages) = unbounded_channel();
+    let thread = thread::spawn(move || {
+        let config = Config {
+            keys: vec![load_host_key(&settings)?],
+            nodelay: true,
+            ..default()
+        };
+        let authorized_keys = match &settings.authorized_keys {
+            AuthorizedKeys::File(path) => Some(load_authorized_keys(path)?),
+            AuthorizedKeys::Anyone => None,
+        };
+        let mut server = SshAcceptor {
+            messages: sender,
+            authorized_keys: authorized_keys.map(Arc::new),
+            next_id: Arc::new(AtomicU64::new(0)),
+        };
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(server.run_on_address(Arc::new(config), settings.address.as_str()))
+    });
+    commands.insert_resource(SshConnections {
+        messages,
+        thread: Some(thread),
+        clients: HashMap::new(),
+    });
+}
+
+fn load_host_key(settings: &SshServer) -> io::Result<PrivateKey> {
+    if !settings.host_key.exists() {
+        let status = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+            .arg(&settings.host_key)
+            .status()?;
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "ssh-keygen could not create {}",
+                settings.host_key.display()
+            )));
+        }
+    }
+    russh::keys::load_secret_key(&settings.host_key, None).map_err(io::Error::other)
+}
+
+fn load_authorized_keys(path: &PathBuf) -> io::Result<Vec<PublicKey>> {
+    let keys = fs::read_to_string(path)
+        .map_err(|err| io::Error::new(err.kind(), format!("{}: {err}", path.display())))?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| match PublicKey::from_openssh(line) {
+            Ok(key) => Some(key),
+            Err(err) => {
+                warn!("Skipping a key in {}: {err}", path.display());
+                None
+            }
+        })
+        .collect();
+    Ok(keys)
+}
+
+fn receive_ssh_messages(
+    mut commands: Commands,
+    mut connections: ResMut<SshConnections>,
+    mut clients: Query<(&mut SshClient, &mut RatatuiContext)>,
+    mut keys: EventWriter<SshKeyEvent>,
+    mut connected: EventWriter<SshClientConnected>,
+    mut disconnected: EventWriter<SshClientDisconnected>,
+) {
+    if connections
+        .thread
+        .as_ref()
+        .is_some_and(JoinHandle::is_finished)
+    {
+        match connections.thread.take().map(JoinHandle::join) {
+            Some(Ok(Err(err))) => error!("The SSH server stopped: {err}"),
+            Some(Err(_)) => error!("The SSH server panicked"),
+            _ => error!("The SSH server stopped"),
+        }
+    }
+    while let Ok(message) = connections.messages.try_recv() {
+        match message {
+            SshMessage::Opened {
+                id,
+                user,
+                peer,
+                output,
+            } => {
+                let context = match client_context(output) {
+                    Ok(context) => context,
+                    Err(err) => {
+                        warn!("Failed to set up the terminal of {user}: {err}");
+                        continue;
+                    }
+                };
+                let client = SshClient {
+                    user,
+                    peer,
+                    term: None,
+                };
+                let entity = commands.spawn((client, context)).id();
+                connections.clients.insert(id, entity);
+                connected.send(SshClientConnected(entity));
+            }
+            SshMessage::Pty { id, term, size } => {
+                let Some(&entity) = connections.clients.get(&id) else {
+                    continue;
+                };
+                if let Ok((mut client, mut context)) = clients.get_mut(entity) {
+                    client.term = Some(term);
+                    if let Err(err) = context.resize_to(size) {
+                        warn!("Failed to resize the terminal of {}: {err}", client.user);
+                    }
+                }
+            }
+            SshMessage::Resized { id, size } => {
+                let Some(&entity) = connections.clients.get(&id) else {
+                    continue;
+                };
+                if let Ok((client, mut context)) = clients.get_mut(entity) {
+                    if let Err(err) = context.resize_to(size) {
+                        warn!("Failed to resize the terminal of {}: {err}", client.user);
+                    }
+                }
+            }
+            SshMessage::Input { id, bytes } => {
+                let Some(&client) = connections.clients.get(&id) else {
+                    continue;
+                };
+                for event in parse_input(&bytes) {
+                    if let Event::Key(key) = event {
+                        keys.send(SshKeyEvent { client, key });
+                    }
+                }
+            }
+            SshMessage::Closed { id } => {
+                let Some(entity) = connections.clients.remove(&id) else {
+                    continue;
+                };
+                if let Some(mut entity) = commands.get_entity(entity) {
+                    entity.despawn();
+                }
+                disconnected.send(SshClientDisconnected(entity));
+            }
+        }
+    }
+}
+
+/// A context that draws to the client, with the size a client gets before it asks for a PTY.
+fn client_context(mut output: SshOutput) -> io::Result<RatatuiContext> {
+    output.queue(EnterAlternateScreen)?.queue(cursor::Hide)?;
+    RatatuiContext::with_writer(output, Size::new(80, 24))
+}
+
+/// Writes to an SSH channel. The bytes are sent when the writer is flushed, and the channel is
+/// closed when the writer is dropped.
+struct SshOutput {
+    sender: UnboundedSender<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+impl SshOutput {
+    fn start(handle: Handle, channel: ChannelId) -> Self {
+        let (sender, mut receiver) = unbounded_channel::<Vec<u8>>();
+        tokio::spawn(async move {
+            while let Some(data) = receiver.recv().await {
+                if handle.data(channel, data).await.is_err() {
+                    return;
+                }
+            }
+            // The app has despawned the client, so give the client its terminal back.
+            let mut restore = Vec::new();
+            let _ = restore
+                .queue(cursor::Show)
+                .and_then(|restore| restore.queue(LeaveAlternateScreen));
+            let _ = handle.data(channel, restore).await;
+            let _ = handle.exit_status_request(channel, 0).await;
+            let _ = handle.close(channel).await;
+        });
+        Self {
+            sender,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl Write for SshOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        self.sender
+            .send(std::mem::take(&mut self.pending))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+}
+
+/// Accepts connections on the server thread.
+#[derive(Clone)]
+struct SshAcceptor {
+    messages: UnboundedSender<SshMessage>,
+    /// The keys that may connect, or `None` to let anyone connect.
+    authorized_keys: Option<Arc<Vec<PublicKey>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Server for SshAcceptor {
+    type Handler = SshConnection;
+
+    fn new_client(&mut self, peer: Option<SocketAddr>) -> SshConnection {
+        SshConnection {
+            acceptor: self.clone(),
+            peer,
+            user: String::new(),
+            channels: HashMap::new(),
+        }
+    }
+}
+
+/// One connection, which may open several sessions.
+struct SshConnection {
+    acceptor: SshAcceptor,
+    peer: Option<SocketAddr>,
+    user: String,
+    channels: HashMap<ChannelId, u64>,
+}
+
+impl SshConnection {
+    fn send(&self, channel: ChannelId, message: impl FnOnce(u64) -> SshMessage) {
+        if let Some(&id) = self.channels.get(&channel) {
+            let _ = self.acceptor.messages.send(message(id));
+        }
+    }
+}
+
+impl Drop for SshConnection {
+    fn drop(&mut self) {
+        for &id in self.channels.values() {
+            let _ = self.acceptor.messages.send(SshMessage::Closed { id });
+        }
+    }
+}
+
+fn size(columns: u32, rows: u32) -> Size {
+    let clamp = |value: u32| u16::try_from(value).unwrap_or(u16::MAX);
+    Size::new(clamp(columns), clamp(rows))
+}
+
+impl Handler for SshConnection {
+    type Error = russh::Error;
+
+    async fn auth_none(&mut self, user: &str) -> Result<Auth, Self::Error> {
+        if self.acceptor.authorized_keys.is_some() {
+            return Ok(Auth::reject());
+        }
+        self.user = user.to_string();
+        Ok(Auth::Accept)
+    }
+
+    async fn auth_publickey(&mut self, user: &str, key: &PublicKey) -> Result<Auth, Self::Error> {
+        let authorized = self.acceptor.authorized_keys.as_ref().is_none_or(|keys| {
+            keys.iter()
+                .any(|authorized| authorized.key_data() == key.key_data())
+        });
+        if !authorized {
+            return Ok(Auth::reject());
+        }
+        self.user = user.to_string();
+        Ok(Auth::Accept)
+    }
+
+    async fn channel_open_session(
+        &mut self,
+        channel: Channel<Msg>,
+        reply: ChannelOpenHandle,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let id = self.acceptor.next_id.fetch_add(1, Ordering::SeqCst);
+        self.channels.insert(channel.id(), id);
+        let output = SshOutput::start(session.handle(), channel.id());
+        let _ = self.acceptor.messages.send(SshMessage::Opened {
+            id,
+            user: self.user.clone(),
+            peer: self.peer,
+            output,
+        });
+        reply.accept().await;
+        Ok(())
+    }
+
+    async fn pty_request(
+        &mut self,
+        channel: ChannelId,
+        term: &str,
+        columns: u32,
+        rows: u32,
+        _: u32,
+        _: u32,
+        _: &[(Pty, u32)],
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let term = term.to_string();
+        self.send(channel, |id| SshMessage::Pty {
+            id,
+            term,
+            size: size(columns, rows),
+        });
+        session.channel_success(channel)
+    }
+
+    async fn shell_request(
+        &mut self,
+        channel: ChannelId,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        session.channel_success(channel)
+    }
+
+    async fn window_change_request(
+        &mut self,
+        channel: ChannelId,
+        columns: u32,
+        rows: u32,
+        _: u32,
+        _: u32,
+        _: &mut Session,
+    ) -> Result<(), Self::Error> {
+        self.send(channel, |id| SshMessage::Resized {
+            id,
+            size: size(columns, rows),
+        });
+        Ok(())
+    }
+
+    async fn data(
+        &mut self,
+        channel: ChannelId,
+        data: &[u8],
+        _: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let bytes = data.to_vec();
+        self.send(channel, |id| SshMessage::Input { id, bytes });
+        Ok(())
+    }
+
+    async fn channel_close(
+        &mut self,
+        channel: ChannelId,
+        _: &mut Session,
+    ) -> Result<(), Self::Error> {
+        if let Some(id) = self.channels.remove(&channel) {
+            let _ = self.acceptor.messages.send(SshMessage::Closed { id });
+        }
+        Ok(())
+    }
+}
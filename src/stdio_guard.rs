@@ -0,0 +1,339 @@
+//! Keeping stray prints from drawing over the app.
+//!
+//! A `println!` or `eprintln!` from a dependency, or a logger that writes to stderr, writes over
+//! the alternate screen, and the text stays there until the next full redraw. With
+//! [`StdioGuardPlugin`], stdout and stderr are redirected into pipes while the alternate screen is
+//! active, and what is written to them ends up in the [`CapturedOutput`] resource instead, and in
+//! a file if [`StdioGuard::log_file`] is set. This crate keeps writing to the terminal itself.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     stdio_guard::{CapturedOutput, StdioGuard, StdioGuardPlugin},
+//!     terminal::RatatuiContext,
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::widgets::Paragraph;
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), StdioGuardPlugin))
+//!     .insert_resource(StdioGuard {
+//!         log_file: Some("output.log".into()),
+//!         ..default()
+//!     })
+//!     .add_systems(Update, draw_log_pane);
+//!
+//! fn draw_log_pane(mut context: ResMut<RatatuiContext>, output: Res<CapturedOutput>) {
+//!     let lines: Vec<_> = output.lines().map(|line| line.text.as_str()).collect();
+//!     let pane = Paragraph::new(lines.join("\n"));
+//!     let _ = context.draw(|frame| frame.render_widget(pane, frame.area()));
+//! }
+//! ```
+//!
+//! The redirection is undone whenever the terminal is restored, so panics, errors and the output
+//! of [`run_external`](crate::terminal::run_external) commands are shown as usual. It is only
+//! available on Unix, and only applies to a [fullscreen](crate::terminal::TerminalViewport)
+//! viewport.
+use std::{
+    collections::VecDeque,
+    fs::File,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{ecs::event::EventUpdates, prelude::*};
+
+use crate::terminal::{is_headless, TerminalSet, TerminalStartup};
+
+/// A plugin that redirects stdout and stderr while the alternate screen is active.
+pub struct StdioGuardPlugin;
+
+impl Plugin for StdioGuardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StdioGuard>()
+            .init_resource::<CapturedOutput>()
+            .add_systems(
+                TerminalStartup,
+                install_guard
+                    .run_if(not(is_headless))
+                    .in_set(TerminalSet::Features),
+            )
+            .add_systems(First, collect_captured_output.after(EventUpdates));
+    }
+}
+
+/// Settings for the [`StdioGuardPlugin`], read when the guard is installed at startup.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct StdioGuard {
+    /// A file that captured output is appended to, as it arrives.
+    pub log_file: Option<PathBuf>,
+    /// How many lines [`CapturedOutput`] keeps. Defaults to 1000.
+    pub capacity: usize,
+}
+
+impl Default for StdioGuard {
+    fn default() -> Self {
+        Self {
+            log_file: None,
+            capacity: 1000,
+        }
+    }
+}
+
+/// Which stream a [`CapturedLine`] was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CapturedStream {
+    Stdout,
+    Stderr,
+}
+
+/// A line that was written to stdout or stderr while the alternate screen was active.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedLine {
+    pub stream: CapturedStream,
+    pub text: String,
+}
+
+/// The most recent lines written to stdout and stderr while the [`StdioGuardPlugin`] had them
+/// redirected, oldest first.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct CapturedOutput {
+    lines: VecDeque<CapturedLine>,
+}
+
+impl CapturedOutput {
+    /// The captured lines, oldest first.
+    pub fn lines(&self) -> impl DoubleEndedIterator<Item = &CapturedLine> {
+        self.lines.iter()
+    }
+
+    /// The number of captured lines.
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Whether no lines have been captured.
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Forgets the captured lines.
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+/// Lines read by the reader threads that have not been moved into [`CapturedOutput`] yet.
+static PENDING: Mutex<Vec<CapturedLine>> = Mutex::new(Vec::new());
+
+fn install_guard(settings: Res<StdioGuard>) {
+    let log_file = match &settings.log_file {
+        Some(path) => match File::options().create(true).append(true).open(path) {
+            Ok(file) => Some(Arc::new(Mutex::new(file))),
+            Err(err) => {
+                warn!(
+                    "Could not write captured output to {}: {err}",
+                    path.display()
+                );
+                None
+            }
+        },
+        None => None,
+    };
+    if let Err(err) = redirect::install(log_file) {
+        warn!("Could not redirect stdout and stderr: {err}");
+    }
+}
+
+fn collect_captured_output(settings: Res<StdioGuard>, mut output: ResMut<CapturedOutput>) {
+    let mut pending = PENDING.lock().unwrap_or_else(|err| err.into_inner());
+    if pending.is_empty() {
+        return;
+    }
+    output.lines.extend(pending.drain(..));
+    let excess = output.lines.len().saturating_sub(settings.capacity);
+    output.lines.drain(..excess);
+}
+
+/// The file descriptor juggling, which only Unix supports.
+#[cfg(unix)]
+pub(crate) mod redirect {
+    use std::{
+        fs::File,
+        io::{self, stderr, stdout, BufRead, BufReader, Write},
+        mem::ManuallyDrop,
+        os::fd::{AsFd, AsRawFd, FromRawFd, OwnedFd, RawFd},
+        sync::{
+            atomic::{AtomicI32, Ordering},
+            Arc, Mutex,
+        },
+        thread,
+    };
+
+    use super::{CapturedLine, CapturedStream, PENDING};
+    use crate::terminal::in_alternate_screen;
+
+    /// Copies of the original stdout and stderr, which the terminal is written to while they are
+    /// redirected, or -1 when the guard is not installed.
+    ///
+    /// Once installed, the guard stays installed, so the descriptors are never closed.
+    static TERMINAL_FDS: [AtomicI32; 2] = [AtomicI32::new(-1), AtomicI32::new(-1)];
+
+    static GUARD: Mutex<Option<Guard>> = Mutex::new(None);
+
+    #[derive(Debug)]
+    struct Guard {
+        /// Copies of the original stdout and stderr.
+        original: [OwnedFd; 2],
+        /// The write ends of the pipes that replace them.
+        pipes: [OwnedFd; 2],
+        redirected: bool,
+    }
+
+    const STDIO: [RawFd; 2] = [libc::STDOUT_FILENO, libc::STDERR_FILENO];
+
+    pub(crate) fn install(log_file: Option<Arc<Mutex<File>>>) -> io::Result<()> {
+        let mut guard = GUARD.lock().unwrap_or_else(|err| err.into_inner());
+        if guard.is_some() {
+            return Ok(());
+        }
+        let original = [
+            stdout().as_fd().try_clone_to_owned()?,
+            stderr().as_fd().try_clone_to_owned()?,
+        ];
+        let (stdout_reader, stdout_writer) = pipe()?;
+        let (stderr_reader, stderr_writer) = pipe()?;
+        spawn_reader(stdout_reader, CapturedStream::Stdout, log_file.clone())?;
+        spawn_reader(stderr_reader, CapturedStream::Stderr, log_file)?;
+        for (terminal_fd, original) in TERMINAL_FDS.iter().zip(&original) {
+            terminal_fd.store(original.as_raw_fd(), Ordering::SeqCst);
+        }
+        *guard = Some(Guard {
+            original,
+            pipes: [stdout_writer, stderr_writer],
+            redirected: false,
+        });
+        drop(guard);
+        if in_alternate_screen() {
+            resume();
+        }
+        Ok(())
+    }
+
+    /// Creates a pipe, returning its read end and its write end.
+    fn pipe() -> io::Result<(File, OwnedFd)> {
+        let mut fds = [0; 2];
+        // SAFETY: `fds` has room for the two descriptors that `pipe` writes.
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: `pipe` succeeded, so both descriptors are open and owned by nothing else.
+        let (reader, writer) =
+            unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+        for fd in [&reader, &writer] {
+            // Child processes must not keep the pipes open.
+            // SAFETY: the descriptor is open, and only its flags are changed.
+            if unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok((File::from(reader), writer))
+    }
+
+    /// Points stdout and stderr back at the terminal, if they are redirected.
+    pub(crate) fn suspend() {
+        swap(false);
+    }
+
+    /// Redirects stdout and stderr again after [`suspend`], if the guard is installed.
+    pub(crate) fn resume() {
+        swap(true);
+    }
+
+    fn swap(redirect: bool) {
+        let mut guard = GUARD.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(guard) = guard.as_mut().filter(|guard| guard.redirected != redirect) else {
+            return;
+        };
+        let _ = stdout().flush();
+        let _ = stderr().flush();
+        let targets = if redirect {
+            &guard.pipes
+        } else {
+            &guard.original
+        };
+        for (target, fd) in targets.iter().zip(STDIO) {
+            // SAFETY: both descriptors are open, and `dup2` only replaces `fd`.
+            unsafe { libc::dup2(target.as_raw_fd(), fd) };
+        }
+        guard.redirected = redirect;
+    }
+
+    /// What `stream` was before it was redirected, if the guard is installed.
+    pub(crate) fn terminal(stream: CapturedStream) -> Option<ManuallyDrop<File>> {
+        let index = match stream {
+            CapturedStream::Stdout => 0,
+            CapturedStream::Stderr => 1,
+        };
+        let fd = TERMINAL_FDS[index].load(Ordering::SeqCst);
+        // SAFETY: the descriptor stays open once set, and is not closed here.
+        (fd >= 0).then(|| ManuallyDrop::new(unsafe { File::from_raw_fd(fd) }))
+    }
+
+    fn spawn_reader(
+        reader: File,
+        stream: CapturedStream,
+        log_file: Option<Arc<Mutex<File>>>,
+    ) -> io::Result<()> {
+        thread::Builder::new()
+            .name(format!("bevy_ratatui {stream:?} guard").to_lowercase())
+            .spawn(move || {
+                let mut reader = BufReader::new(reader);
+                let mut line = Vec::new();
+                while reader
+                    .read_until(b'\n', &mut line)
+                    .is_ok_and(|read| read > 0)
+                {
+                    if let Some(file) = &log_file {
+                        let mut file = file.lock().unwrap_or_else(|err| err.into_inner());
+                        let _ = file.write_all(&line);
+                    }
+                    let text = String::from_utf8_lossy(&line);
+                    PENDING
+                        .lock()
+                        .unwrap_or_else(|err| err.into_inner())
+                        .push(CapturedLine {
+                            stream,
+                            text: text.trim_end_matches(['\r', '\n']).to_string(),
+                        });
+                    line.clear();
+                }
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) mod redirect {
+    use std::{
+        fs::File,
+        io,
+        mem::ManuallyDrop,
+        sync::{Arc, Mutex},
+    };
+
+    pub(crate) fn install(_log_file: Option<Arc<Mutex<File>>>) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Redirecting stdout and stderr is only supported on Unix.",
+        ))
+    }
+
+    pub(crate) fn suspend() {}
+
+    pub(crate) fn resume() {}
+
+    pub(crate) fn terminal(_stream: super::CapturedStream) -> Option<ManuallyDrop<File>> {
+        None
+    }
+}
@@ -0,0 +1,148 @@
+//! Keeping unattended apps running after a panic.
+//!
+//! A dashboard on a wall has nobody around to start it again when it crashes. [`Supervisor::run`]
+//! builds and runs the app, and when it panics, lets the [panic hook](crate::error) restore the
+//! terminal and print the report, appends the panic to the [`Supervisor::log_file`] if there is
+//! one, and then builds and runs a fresh app. Apps that keep crashing are restarted after a delay
+//! that doubles with every crash, from [`Supervisor::min_backoff`] up to
+//! [`Supervisor::max_backoff`], and that is reset once an app has run for
+//! [`Supervisor::reset_after`].
+//!
+//! ```rust
+//! use std::{
+//!     sync::atomic::{AtomicU32, Ordering},
+//!     time::Duration,
+//! };
+//!
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{supervisor::Supervisor, RatatuiPlugins};
+//!
+//! static RUNS: AtomicU32 = AtomicU32::new(0);
+//!
+//! let supervisor = Supervisor {
+//!     min_backoff: Duration::ZERO,
+//!     ..default()
+//! };
+//! let exit = supervisor.run(|| {
+//!     let mut app = App::new();
+//!     app.add_plugins(RatatuiPlugins {
+//!         headless: true,
+//!         ..default()
+//!     })
+//!     .add_systems(Update, |mut exit: EventWriter<AppExit>| {
+//!         if RUNS.fetch_add(1, Ordering::SeqCst) == 0 {
+//!             panic!("the first run crashes");
+//!         }
+//!         exit.send(AppExit::Success);
+//!     });
+//!     app
+//! });
+//! assert_eq!(exit, AppExit::Success);
+//! assert_eq!(RUNS.load(Ordering::SeqCst), 2);
+//! ```
+//!
+//! Each restart builds a new [`App`], so nothing survives a crash that is not saved outside of it.
+//! An app that exits without panicking, including with an error, is not restarted.
+use std::{
+    any::Any,
+    fs::OpenOptions,
+    io::Write,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+
+use crate::{error::take_last_panic, exit::PANIC_EXIT_CODE};
+
+/// Runs an app again whenever it panics. See the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Supervisor {
+    /// How long to wait before the first restart after a crash. Defaults to one second.
+    pub min_backoff: Duration,
+    /// The longest time to wait before a restart. Defaults to one minute.
+    pub max_backoff: Duration,
+    /// How long an app must run for its crash to count as the first one again. Defaults to five
+    /// minutes.
+    pub reset_after: Duration,
+    /// How many times to restart the app before giving up, or `None` to never give up.
+    pub max_restarts: Option<u32>,
+    /// A file to append each panic to, with the time it happened.
+    pub log_file: Option<PathBuf>,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self {
+            min_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            reset_after: Duration::from_secs(5 * 60),
+            max_restarts: None,
+            log_file: None,
+        }
+    }
+}
+
+impl Supervisor {
+    /// Builds and runs the app with `build`, and builds and runs it again each time it panics.
+    ///
+    /// Returns how the app exited, or an exit with the [`PANIC_EXIT_CODE`] once the app has been
+    /// restarted [`Supervisor::max_restarts`] times and panics again.
+    pub fn run(&self, mut build: impl FnMut() -> App) -> AppExit {
+        let mut restarts = 0;
+        let mut backoff = self.min_backoff;
+        loop {
+            // A panic that was caught somewhere else must not be logged for this run.
+            take_last_panic();
+            let started = Instant::now();
+            let panic = match panic::catch_unwind(AssertUnwindSafe(|| build().run())) {
+                Ok(exit) => return exit,
+                Err(payload) => payload,
+            };
+            let report = take_last_panic().unwrap_or_else(|| panic_message(&*panic));
+            self.log_panic(&report);
+            if self.max_restarts.is_some_and(|max| restarts >= max) {
+                eprintln!("The app crashed {} times, giving up", restarts + 1);
+                return AppExit::from_code(PANIC_EXIT_CODE);
+            }
+            if started.elapsed() >= self.reset_after {
+                backoff = self.min_backoff;
+            }
+            eprintln!("The app crashed, restarting in {backoff:?}");
+            thread::sleep(backoff);
+            backoff = backoff.saturating_mul(2).min(self.max_backoff);
+            restarts += 1;
+        }
+    }
+
+    fn log_panic(&self, report: &str) {
+        let Some(path) = &self.log_file else {
+            return;
+        };
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let logged = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| writeln!(file, "[{time}] {report}"));
+        if let Err(err) = logged {
+            eprintln!("Failed to log the crash to {}: {err}", path.display());
+        }
+    }
+}
+
+/// The message of a panic that the panic hook did not see, e.g. because the app replaced it.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "the app panicked".to_string()
+    }
+}
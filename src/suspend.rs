@@ -0,0 +1,148 @@
+//! Suspending the app with `Ctrl+Z`.
+//!
+//! In raw mode the terminal does not turn `Ctrl+Z` into a `SIGTSTP` signal, and a `SIGTSTP` sent
+//! some other way, e.g. with `kill -TSTP`, would stop the app with the terminal still in raw mode
+//! and on the alternate screen, leaving the shell unusable. [`SuspendPlugin`] handles both: it
+//! restores the terminal as [`run_external`](crate::terminal::run_external) does, leaving the
+//! alternate screen, disabling raw mode, the mouse capture and bracketed paste and popping the
+//! kitty keyboard flags, and then stops the process. Once the shell continues it, e.g. with `fg`,
+//! the terminal is set up again, the next frame is drawn in full and a [`Resumed`] event is sent.
+//!
+//! A `SIGCONT` after the app was stopped some other way, such as with `SIGSTOP`, which cannot be
+//! handled, sets the terminal up again in the same way.
+//!
+//! The plugin is part of the [`RatatuiPlugins`](crate::RatatuiPlugins). It does nothing on
+//! platforms other than unix, which have no job control. To handle `Ctrl+Z` in the app instead,
+//! disable it:
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{suspend::SuspendPlugin, RatatuiPlugins};
+//!
+//! App::new().add_plugins(RatatuiPlugins::default().build().disable::<SuspendPlugin>());
+//! ```
+#[cfg(unix)]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use bevy::prelude::*;
+#[cfg(unix)]
+use bevy::{ecs::event::EventCursor, input::InputSystem};
+#[cfg(unix)]
+use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+
+#[cfg(unix)]
+use crate::{
+    event::{InputSet, KeyEvent},
+    exit::ExitCodes,
+    terminal::{release_terminal, TerminalSet, TerminalStartup},
+};
+
+/// A plugin that suspends the app on `Ctrl+Z` or `SIGTSTP`, restoring the terminal first.
+pub struct SuspendPlugin;
+
+impl Plugin for SuspendPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Resumed>();
+        #[cfg(unix)]
+        app.add_systems(
+            TerminalStartup,
+            install_signal_handlers.in_set(TerminalSet::Features),
+        )
+        .add_systems(
+            PreUpdate,
+            suspend_system.after(InputSet::Post).after(InputSystem),
+        );
+    }
+}
+
+/// Sent when the app continues after it was suspended, once the terminal has been set up again.
+#[derive(Event, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Resumed;
+
+/// Set by the signal handler when a `SIGTSTP` arrives.
+#[cfg(unix)]
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Set by the signal handler when a `SIGCONT` arrives.
+#[cfg(unix)]
+static CONTINUED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(unix)]
+extern "C" fn on_signal(signal: libc::c_int) {
+    // Only async-signal-safe work is allowed here, so the signals are handled by the next frame.
+    match signal {
+        libc::SIGTSTP => STOP_REQUESTED.store(true, Ordering::SeqCst),
+        libc::SIGCONT => CONTINUED.store(true, Ordering::SeqCst),
+        _ => {}
+    }
+}
+
+#[cfg(unix)]
+fn set_handler(signal: libc::c_int, handler: libc::sighandler_t) {
+    // SAFETY: the action is fully initialized, and the handler only stores to atomics.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(signal, &action, std::ptr::null_mut());
+    }
+}
+
+#[cfg(unix)]
+fn on_signal_handler() -> libc::sighandler_t {
+    on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t
+}
+
+#[cfg(unix)]
+fn install_signal_handlers() {
+    set_handler(libc::SIGTSTP, on_signal_handler());
+    set_handler(libc::SIGCONT, on_signal_handler());
+}
+
+/// Stops the process as the default `SIGTSTP` action does, returning once it is continued.
+#[cfg(unix)]
+fn stop_process() {
+    set_handler(libc::SIGTSTP, libc::SIG_DFL);
+    // SAFETY: raising a signal has no memory safety requirements.
+    unsafe {
+        libc::raise(libc::SIGTSTP);
+    }
+    set_handler(libc::SIGTSTP, on_signal_handler());
+}
+
+/// Suspends the app when `Ctrl+Z` is pressed or a `SIGTSTP` arrived, and sets the terminal up
+/// again when a `SIGCONT` arrived without one.
+#[cfg(unix)]
+fn suspend_system(world: &mut World, mut keys: Local<EventCursor<KeyEvent>>) {
+    let ctrl_z = world
+        .get_resource::<Events<KeyEvent>>()
+        .is_some_and(|events| {
+            keys.read(events).any(|key| {
+                key.kind == KeyEventKind::Press
+                    && key.modifiers == KeyModifiers::CONTROL
+                    && key.code == KeyCode::Char('z')
+            })
+        });
+    let stop = STOP_REQUESTED.swap(false, Ordering::SeqCst) || ctrl_z;
+    let continued = CONTINUED.swap(false, Ordering::SeqCst);
+    if !stop && !continued {
+        return;
+    }
+    // When the app was stopped without restoring the terminal, releasing it and setting it up
+    // again undoes whatever the shell did to it meanwhile.
+    let result = release_terminal(world, || {
+        if stop {
+            stop_process();
+        }
+    });
+    // The `SIGCONT` that continued the process has been handled here.
+    CONTINUED.store(false, Ordering::SeqCst);
+    if let Err(err) = result {
+        error!("Error: {:?}", err);
+        let exit = ExitCodes::error_exit(world.get_resource::<ExitCodes>());
+        world.send_event(exit);
+        return;
+    }
+    world.send_event(Resumed);
+}
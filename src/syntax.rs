@@ -0,0 +1,284 @@
+//! Syntax highlighting for [`TextBuffer`]s.
+//!
+//! This module is only available with the `syntax-highlighting` feature, and uses [syntect] with
+//! its default syntaxes and themes.
+//!
+//! Add a [`SyntaxHighlight`] component next to a [`TextBuffer`] component and the
+//! [`SyntaxHighlightPlugin`] highlights the text on the [`AsyncComputeTaskPool`] whenever the buffer
+//! changes, storing the result in a [`HighlightedLines`] component. [`HighlightedTextView`] renders
+//! the highlighted lines, falling back to plain text for lines edited since the last highlight, so
+//! the frame never waits for the parser.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     syntax::{SyntaxHighlight, SyntaxHighlightPlugin},
+//!     text_buffer::TextBuffer,
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), SyntaxHighlightPlugin))
+//!     .add_systems(Startup, |mut commands: Commands| {
+//!         commands.spawn((
+//!             TextBuffer::from("fn main() {}\n"),
+//!             SyntaxHighlight::new("rs"),
+//!         ));
+//!     });
+//! ```
+//!
+//! [syntect]: https://docs.rs/syntect
+use std::sync::Arc;
+
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task, TaskPool},
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+use ropey::Rope;
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{FontStyle, ThemeSet},
+    parsing::SyntaxSet,
+};
+
+use crate::text_buffer::TextBuffer;
+
+/// A plugin that highlights [`TextBuffer`] components that have a [`SyntaxHighlight`] component.
+pub struct SyntaxHighlightPlugin;
+
+impl Plugin for SyntaxHighlightPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SyntaxSets>().add_systems(
+            PostUpdate,
+            (start_highlighting, finish_highlighting).chain(),
+        );
+    }
+}
+
+/// The syntaxes and themes available for highlighting. Defaults to those bundled with syntect.
+#[derive(Resource, Clone)]
+pub struct SyntaxSets {
+    pub syntaxes: Arc<SyntaxSet>,
+    pub themes: Arc<ThemeSet>,
+}
+
+impl Default for SyntaxSets {
+    fn default() -> Self {
+        Self {
+            syntaxes: Arc::new(SyntaxSet::load_defaults_newlines()),
+            themes: Arc::new(ThemeSet::load_defaults()),
+        }
+    }
+}
+
+/// Highlights the [`TextBuffer`] on the same entity.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxHighlight {
+    /// The syntax name or file extension, e.g. `"Rust"` or `"rs"`.
+    pub syntax: String,
+    /// The name of the theme, e.g. `"base16-ocean.dark"`.
+    pub theme: String,
+}
+
+impl SyntaxHighlight {
+    /// Highlights with the given syntax and the `base16-ocean.dark` theme.
+    pub fn new(syntax: impl Into<String>) -> Self {
+        Self {
+            syntax: syntax.into(),
+            theme: "base16-ocean.dark".into(),
+        }
+    }
+
+    /// Sets the theme.
+    pub fn with_theme(mut self, theme: impl Into<String>) -> Self {
+        self.theme = theme.into();
+        self
+    }
+}
+
+/// The styled lines of a [`TextBuffer`] as of a [`TextBuffer::revision`].
+#[derive(Component, Debug, Clone, Default)]
+pub struct HighlightedLines {
+    pub revision: u64,
+    pub lines: Vec<Line<'static>>,
+}
+
+/// A highlight that is being computed in the background.
+#[derive(Component)]
+struct HighlightTask {
+    revision: u64,
+    task: Task<Vec<Line<'static>>>,
+}
+
+type ChangedBuffers<'a> = (
+    Entity,
+    Ref<'a, TextBuffer>,
+    Ref<'a, SyntaxHighlight>,
+    Option<&'a HighlightedLines>,
+    Option<&'a HighlightTask>,
+);
+type BufferChanged = Or<(Changed<TextBuffer>, Changed<SyntaxHighlight>)>;
+
+fn start_highlighting(
+    mut commands: Commands,
+    sets: Res<SyntaxSets>,
+    buffers: Query<ChangedBuffers, BufferChanged>,
+) {
+    for (entity, buffer, highlight, highlighted, pending) in &buffers {
+        let revision = buffer.revision();
+        let up_to_date = pending.map(|pending| pending.revision) == Some(revision)
+            || (pending.is_none() && highlighted.map(|lines| lines.revision) == Some(revision));
+        if up_to_date && !buffer.is_added() && !highlight.is_changed() {
+            continue;
+        }
+        let rope = buffer.rope().clone();
+        let highlight = highlight.clone();
+        let sets = sets.clone();
+        let task = AsyncComputeTaskPool::get_or_init(TaskPool::new)
+            .spawn(async move { highlight_rope(&rope, &highlight, &sets) });
+        // Replacing a pending task drops and cancels it.
+        commands
+            .entity(entity)
+            .insert(HighlightTask { revision, task });
+    }
+}
+
+fn finish_highlighting(mut commands: Commands, mut tasks: Query<(Entity, &mut HighlightTask)>) {
+    for (entity, mut pending) in &mut tasks {
+        if let Some(lines) = block_on(future::poll_once(&mut pending.task)) {
+            commands
+                .entity(entity)
+                .remove::<HighlightTask>()
+                .insert(HighlightedLines {
+                    revision: pending.revision,
+                    lines,
+                });
+        }
+    }
+}
+
+/// Highlights each line of the text, falling back to plain text for unknown syntaxes and themes.
+fn highlight_rope(
+    rope: &Rope,
+    highlight: &SyntaxHighlight,
+    sets: &SyntaxSets,
+) -> Vec<Line<'static>> {
+    let syntaxes = &sets.syntaxes;
+    let syntax = syntaxes
+        .find_syntax_by_token(&highlight.syntax)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let Some(theme) = sets.themes.themes.get(&highlight.theme) else {
+        return rope
+            .lines()
+            .map(|line| Line::from(trim(line.to_string())))
+            .collect();
+    };
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    rope.lines()
+        .map(|line| {
+            let line = line.to_string();
+            match highlighter.highlight_line(&line, syntaxes) {
+                Ok(ranges) => ranges
+                    .into_iter()
+                    .map(|(style, text)| Span::styled(trim(text.to_string()), convert(style)))
+                    .collect(),
+                Err(_) => Line::from(trim(line)),
+            }
+        })
+        .collect()
+}
+
+fn trim(mut text: String) -> String {
+    let len = text.trim_end_matches(['\n', '\r']).len();
+    text.truncate(len);
+    text
+}
+
+fn convert(style: syntect::highlighting::Style) -> Style {
+    let fg = style.foreground;
+    let mut converted = Style::new().fg(Color::Rgb(fg.r, fg.g, fg.b));
+    for (font_style, modifier) in [
+        (FontStyle::BOLD, Modifier::BOLD),
+        (FontStyle::ITALIC, Modifier::ITALIC),
+        (FontStyle::UNDERLINE, Modifier::UNDERLINED),
+    ] {
+        if style.font_style.contains(font_style) {
+            converted = converted.add_modifier(modifier);
+        }
+    }
+    converted
+}
+
+/// Renders the visible part of a [`TextBuffer`] with its [`HighlightedLines`].
+///
+/// Lines whose text no longer matches the highlighted text, because they were edited after the
+/// highlight was computed, are rendered as plain text until the next highlight is ready.
+pub struct HighlightedTextView<'a> {
+    buffer: &'a TextBuffer,
+    highlighted: Option<&'a HighlightedLines>,
+    scroll: (usize, usize),
+}
+
+impl<'a> HighlightedTextView<'a> {
+    pub fn new(buffer: &'a TextBuffer, highlighted: Option<&'a HighlightedLines>) -> Self {
+        Self {
+            buffer,
+            highlighted,
+            scroll: (0, 0),
+        }
+    }
+
+    /// Sets the first line and column shown.
+    pub fn scroll(mut self, line: usize, column: usize) -> Self {
+        self.scroll = (line, column);
+        self
+    }
+}
+
+impl Widget for HighlightedTextView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (first, column) = self.scroll;
+        let lines = self.buffer.viewport(first, area.height as usize);
+        for ((y, index), text) in (area.top()..area.bottom()).zip(first..).zip(lines) {
+            let styled = self
+                .highlighted
+                .and_then(|highlighted| highlighted.lines.get(index))
+                .filter(|styled| line_text(styled) == text);
+            let line = match styled {
+                Some(styled) => skip_columns(styled, column),
+                None => Line::from(text.chars().skip(column).collect::<String>()),
+            };
+            line.render(Rect::new(area.x, y, area.width, 1), buf);
+        }
+    }
+}
+
+fn line_text(line: &Line) -> String {
+    line.spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect()
+}
+
+/// Drops the first `columns` characters of a styled line.
+fn skip_columns<'a>(line: &'a Line, mut columns: usize) -> Line<'a> {
+    let mut spans = Vec::new();
+    for span in &line.spans {
+        let len = span.content.chars().count();
+        if columns >= len {
+            columns -= len;
+            continue;
+        }
+        let content: String = span.content.chars().skip(columns).collect();
+        columns = 0;
+        spans.push(Span::styled(content, span.style));
+    }
+    Line::from(spans)
+}
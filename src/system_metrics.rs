@@ -0,0 +1,437 @@
+//! CPU, memory and network usage for operations dashboards.
+//!
+//! [`SystemMetricsPlugin`] samples the system with [sysinfo] every [`MetricsInterval`] into the
+//! [`CpuUsage`], [`MemoryUsage`] and [`NetworkUsage`] resources, which keep a history of recent
+//! samples. [`MetricGauge`] and [`MetricSparkline`] entities draw a [`Metric`] in their
+//! [`AnchoredArea`] as a gauge or a sparkline, so a dashboard needs no draw systems of its own:
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     anchor::AnchoredArea,
+//!     system_metrics::{Metric, MetricGauge, MetricSparkline, SystemMetricsPlugin},
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::layout::Rect;
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), SystemMetricsPlugin))
+//!     .add_systems(Startup, |mut commands: Commands| {
+//!         commands.spawn((MetricGauge::new(Metric::Cpu), AnchoredArea(Rect::new(0, 0, 40, 1))));
+//!         commands.spawn((MetricGauge::new(Metric::Memory), AnchoredArea(Rect::new(0, 1, 40, 1))));
+//!         commands.spawn((
+//!             MetricSparkline::new(Metric::NetworkReceived),
+//!             AnchoredArea(Rect::new(0, 2, 40, 4)),
+//!         ));
+//!     })
+//!     .run();
+//! ```
+//!
+//! The resources can also be read directly to build other widgets. Each one only changes when a
+//! sample is taken, which requests a redraw if there is a
+//! [`RedrawRequested`](crate::redraw::RedrawRequested) resource.
+//!
+//! [sysinfo]: https://crates.io/crates/sysinfo
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Style,
+    widgets::{Gauge, Sparkline, Widget},
+};
+use sysinfo::{Networks, System, MINIMUM_CPU_UPDATE_INTERVAL};
+
+use crate::{
+    anchor::AnchoredArea,
+    redraw::RedrawRequested,
+    render::{RenderBuffer, RenderPlugin, RenderSet},
+    widget::{draw_root_widget, WidgetVisibility},
+};
+
+/// How many samples of each metric are kept, which is enough for a sparkline across a wide
+/// terminal.
+const HISTORY: usize = 256;
+
+/// A plugin that samples the system into the metric resources and draws [`MetricGauge`]s and
+/// [`MetricSparkline`]s.
+///
+/// This requires the [`RenderPlugin`], and adds it if it is missing.
+pub struct SystemMetricsPlugin;
+
+impl Plugin for SystemMetricsPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RenderPlugin>() {
+            app.add_plugins(RenderPlugin);
+        }
+        app.init_resource::<MetricsInterval>()
+            .init_resource::<CpuUsage>()
+            .init_resource::<MemoryUsage>()
+            .init_resource::<NetworkUsage>()
+            .add_systems(PreUpdate, sample_system)
+            .add_systems(
+                PostUpdate,
+                draw_metrics
+                    .after(draw_root_widget)
+                    .in_set(RenderSet::Widgets),
+            );
+    }
+}
+
+/// How often the system is sampled. Defaults to one second.
+///
+/// Intervals shorter than sysinfo's [`MINIMUM_CPU_UPDATE_INTERVAL`] are raised to it, as the CPU
+/// usage cannot be measured more often.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct MetricsInterval(pub Duration);
+
+impl Default for MetricsInterval {
+    fn default() -> Self {
+        Self(Duration::from_secs(1))
+    }
+}
+
+/// How busy the CPUs were over the last sample interval, in percent.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct CpuUsage {
+    /// The usage of all CPUs together.
+    pub total: f32,
+    /// The usage of each CPU.
+    pub cores: Vec<f32>,
+    history: VecDeque<f32>,
+}
+
+impl CpuUsage {
+    /// The recent samples of [`CpuUsage::total`], oldest first.
+    pub fn history(&self) -> &VecDeque<f32> {
+        &self.history
+    }
+}
+
+/// Memory and swap in use, in bytes.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct MemoryUsage {
+    pub used: u64,
+    pub total: u64,
+    pub swap_used: u64,
+    pub swap_total: u64,
+    history: VecDeque<f32>,
+}
+
+impl MemoryUsage {
+    /// The fraction of memory in use, between `0.0` and `1.0`.
+    pub fn ratio(&self) -> f64 {
+        ratio(self.used, self.total)
+    }
+
+    /// The fraction of swap in use, between `0.0` and `1.0`.
+    pub fn swap_ratio(&self) -> f64 {
+        ratio(self.swap_used, self.swap_total)
+    }
+
+    /// The recent samples of the memory in use, in percent, oldest first.
+    pub fn history(&self) -> &VecDeque<f32> {
+        &self.history
+    }
+}
+
+/// The network traffic over all interfaces, in bytes per second.
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+pub struct NetworkUsage {
+    pub received: u64,
+    pub transmitted: u64,
+    received_history: VecDeque<u64>,
+    transmitted_history: VecDeque<u64>,
+}
+
+impl NetworkUsage {
+    /// The recent samples of [`NetworkUsage::received`], oldest first.
+    pub fn received_history(&self) -> &VecDeque<u64> {
+        &self.received_history
+    }
+
+    /// The recent samples of [`NetworkUsage::transmitted`], oldest first.
+    pub fn transmitted_history(&self) -> &VecDeque<u64> {
+        &self.transmitted_history
+    }
+}
+
+/// A value that [`MetricGauge`]s and [`MetricSparkline`]s show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    /// The [`CpuUsage::total`].
+    Cpu,
+    /// The memory in use.
+    Memory,
+    /// The swap in use. It has no history, so its sparkline is empty.
+    Swap,
+    /// The [`NetworkUsage::received`] rate. Its gauge is relative to the highest recent rate.
+    NetworkReceived,
+    /// The [`NetworkUsage::transmitted`] rate. Its gauge is relative to the highest recent rate.
+    NetworkTransmitted,
+}
+
+/// A widget entity that draws a [`Metric`] as a gauge in its [`AnchoredArea`], labeled with the
+/// current value, e.g. `CPU 42%` or `Mem 3.1 GiB / 15.6 GiB`.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[require(AnchoredArea)]
+pub struct MetricGauge {
+    pub metric: Metric,
+    /// The style of the filled part of the gauge.
+    pub style: Style,
+}
+
+impl MetricGauge {
+    pub fn new(metric: Metric) -> Self {
+        Self {
+            metric,
+            style: Style::default(),
+        }
+    }
+
+    pub fn with_style(mut self, style: impl Into<Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+}
+
+/// A widget entity that draws the history of a [`Metric`] as a sparkline in its
+/// [`AnchoredArea`], with the newest sample on the right.
+///
+/// Percentages are drawn against 100%, and network rates against the highest rate shown.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[require(AnchoredArea)]
+pub struct MetricSparkline {
+    pub metric: Metric,
+    pub style: Style,
+}
+
+impl MetricSparkline {
+    pub fn new(metric: Metric) -> Self {
+        Self {
+            metric,
+            style: Style::default(),
+        }
+    }
+
+    pub fn with_style(mut self, style: impl Into<Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+}
+
+/// The state that sysinfo needs between samples.
+struct Sampler {
+    system: System,
+    networks: Networks,
+    sampled_at: Instant,
+}
+
+fn sample_system(
+    mut sampler: Local<Option<Sampler>>,
+    interval: Res<MetricsInterval>,
+    mut cpu: ResMut<CpuUsage>,
+    mut memory: ResMut<MemoryUsage>,
+    mut network: ResMut<NetworkUsage>,
+    redraw: Option<ResMut<RedrawRequested>>,
+) {
+    let now = Instant::now();
+    let Some(sampler) = sampler.as_mut() else {
+        // CPU usage is measured between two refreshes, so the first one only sets the baseline.
+        let mut system = System::new();
+        system.refresh_cpu_usage();
+        *sampler = Some(Sampler {
+            system,
+            networks: Networks::new_with_refreshed_list(),
+            sampled_at: now,
+        });
+        return;
+    };
+    let elapsed = now.duration_since(sampler.sampled_at);
+    if elapsed < interval.0.max(MINIMUM_CPU_UPDATE_INTERVAL) {
+        return;
+    }
+    sampler.sampled_at = now;
+    let system = &mut sampler.system;
+    system.refresh_cpu_usage();
+    system.refresh_memory();
+    sampler.networks.refresh(true);
+
+    cpu.total = system.global_cpu_usage();
+    cpu.cores = system.cpus().iter().map(|cpu| cpu.cpu_usage()).collect();
+    let total = cpu.total;
+    push(&mut cpu.history, total);
+
+    memory.used = system.used_memory();
+    memory.total = system.total_memory();
+    memory.swap_used = system.used_swap();
+    memory.swap_total = system.total_swap();
+    let percent = (memory.ratio() * 100.0) as f32;
+    push(&mut memory.history, percent);
+
+    let per_second = |bytes: u64| (bytes as f64 / elapsed.as_secs_f64()) as u64;
+    let (received, transmitted) =
+        sampler
+            .networks
+            .values()
+            .fold((0, 0), |(received, transmitted), data| {
+                (received + data.received(), transmitted + data.transmitted())
+            });
+    let network = &mut *network;
+    network.received = per_second(received);
+    network.transmitted = per_second(transmitted);
+    push(&mut network.received_history, network.received);
+    push(&mut network.transmitted_history, network.transmitted);
+
+    if let Some(mut redraw) = redraw {
+        redraw.request();
+    }
+}
+
+fn push<T>(history: &mut VecDeque<T>, value: T) {
+    if history.len() == HISTORY {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+fn ratio(used: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        used as f64 / total as f64
+    }
+}
+
+/// Formats a number of bytes with a binary unit, e.g. `512 B` or `3.1 GiB`.
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// The resources that metrics are read from.
+struct Metrics<'a> {
+    cpu: &'a CpuUsage,
+    memory: &'a MemoryUsage,
+    network: &'a NetworkUsage,
+}
+
+impl Metrics<'_> {
+    /// The history of a metric, as whole percentages or bytes per second.
+    fn history(&self, metric: Metric) -> Vec<u64> {
+        let percentages = |history: &VecDeque<f32>| {
+            history
+                .iter()
+                .map(|percent| percent.round() as u64)
+                .collect()
+        };
+        match metric {
+            Metric::Cpu => percentages(&self.cpu.history),
+            Metric::Memory => percentages(&self.memory.history),
+            Metric::Swap => Vec::new(),
+            Metric::NetworkReceived => self.network.received_history.iter().copied().collect(),
+            Metric::NetworkTransmitted => {
+                self.network.transmitted_history.iter().copied().collect()
+            }
+        }
+    }
+
+    fn gauge(&self, metric: Metric) -> (f64, String) {
+        let rate = |rate: u64, history: &VecDeque<u64>| {
+            let peak = history.iter().copied().max().unwrap_or_default();
+            ratio(rate, peak)
+        };
+        match metric {
+            Metric::Cpu => (
+                f64::from(self.cpu.total) / 100.0,
+                format!("CPU {:.0}%", self.cpu.total),
+            ),
+            Metric::Memory => (
+                self.memory.ratio(),
+                format!(
+                    "Mem {} / {}",
+                    format_bytes(self.memory.used),
+                    format_bytes(self.memory.total)
+                ),
+            ),
+            Metric::Swap => (
+                self.memory.swap_ratio(),
+                format!(
+                    "Swap {} / {}",
+                    format_bytes(self.memory.swap_used),
+                    format_bytes(self.memory.swap_total)
+                ),
+            ),
+            Metric::NetworkReceived => (
+                rate(self.network.received, &self.network.received_history),
+                format!("Rx {}/s", format_bytes(self.network.received)),
+            ),
+            Metric::NetworkTransmitted => (
+                rate(self.network.transmitted, &self.network.transmitted_history),
+                format!("Tx {}/s", format_bytes(self.network.transmitted)),
+            ),
+        }
+    }
+
+    fn render_gauge(&self, gauge: &MetricGauge, area: Rect, buf: &mut Buffer) {
+        let (ratio, label) = self.gauge(gauge.metric);
+        Gauge::default()
+            .ratio(ratio.clamp(0.0, 1.0))
+            .label(label)
+            .gauge_style(gauge.style)
+            .use_unicode(true)
+            .render(area, buf);
+    }
+
+    fn render_sparkline(&self, sparkline: &MetricSparkline, area: Rect, buf: &mut Buffer) {
+        let history = self.history(sparkline.metric);
+        let shown = &history[history.len().saturating_sub(usize::from(area.width))..];
+        let mut widget = Sparkline::default().data(shown).style(sparkline.style);
+        if matches!(sparkline.metric, Metric::Cpu | Metric::Memory) {
+            widget = widget.max(100);
+        }
+        widget.render(area, buf);
+    }
+}
+
+fn draw_metrics(
+    mut buffer: ResMut<RenderBuffer>,
+    cpu: Res<CpuUsage>,
+    memory: Res<MemoryUsage>,
+    network: Res<NetworkUsage>,
+    gauges: Query<(&MetricGauge, &AnchoredArea, Option<&WidgetVisibility>)>,
+    sparklines: Query<(&MetricSparkline, &AnchoredArea, Option<&WidgetVisibility>)>,
+) {
+    let metrics = Metrics {
+        cpu: &cpu,
+        memory: &memory,
+        network: &network,
+    };
+    let bounds = buffer.area;
+    let visible = |visibility: Option<&WidgetVisibility>| {
+        visibility.is_none_or(|visibility| visibility.is_visible())
+    };
+    for (gauge, area, visibility) in &gauges {
+        if visible(visibility) {
+            metrics.render_gauge(gauge, area.intersection(bounds), &mut buffer);
+        }
+    }
+    for (sparkline, area, visibility) in &sparklines {
+        if visible(visibility) {
+            metrics.render_sparkline(sparkline, area.intersection(bounds), &mut buffer);
+        }
+    }
+}
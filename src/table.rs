@@ -0,0 +1,385 @@
+//! An editable table.
+//!
+//! [`EditableTable`] is a component holding a grid of text cells with a cell cursor. The
+//! [`EditableTablePlugin`] forwards key presses to focused tables:
+//!
+//! - The arrow keys, `Tab` and `Shift+Tab` move between cells.
+//! - `Enter` starts editing the cell. While editing, `Enter` commits the edit and `Esc` cancels it.
+//!
+//! Commits and cancellations are sent as [`TableEditEvent`]s, and the table keeps track of which
+//! cells have been changed since [`EditableTable::accept_changes`] was last called. Render a table
+//! with [`EditableTableView`].
+//!
+//! ```rust
+//! use bevy_ratatui::table::EditableTable;
+//!
+//! let mut table = EditableTable::new(["name", "value"], vec![vec!["width".into(), "80".into()]]);
+//! table.set_cell(0, 1, "120");
+//! assert_eq!(table.changes().collect::<Vec<_>>(), [(0, 1, "80", "120")]);
+//! ```
+use bevy::{prelude::*, utils::HashMap};
+use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Cell, Row, StatefulWidget, Table, TableState, Widget},
+};
+
+use crate::{
+    event::{InputSet, KeyEvent},
+    quit,
+    widget::{is_interactive, Interactive},
+};
+
+/// A plugin that forwards key presses to focused [`EditableTable`]s.
+pub struct EditableTablePlugin;
+
+impl Plugin for EditableTablePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TableEditEvent>().add_systems(
+            PreUpdate,
+            table_input_system
+                .in_set(InputSet::Post)
+                .after(quit::quit_system),
+        );
+    }
+}
+
+/// A run condition that is true while a cell of an interactive table is being edited.
+pub fn table_editing(tables: Query<Interactive<&EditableTable>>) -> bool {
+    tables.iter().any(|(table, visibility, disabled)| {
+        table.is_editing() && is_interactive(visibility, disabled)
+    })
+}
+
+/// Sent when a cell edit is committed or cancelled.
+#[derive(Debug, Clone, PartialEq, Eq, Event)]
+pub enum TableEditEvent {
+    /// A new value was entered in a cell.
+    Committed {
+        entity: Entity,
+        row: usize,
+        column: usize,
+        old: String,
+        new: String,
+    },
+    /// An edit was cancelled, leaving the cell unchanged.
+    Cancelled {
+        entity: Entity,
+        row: usize,
+        column: usize,
+    },
+}
+
+/// The text of a cell being edited and the cursor position in characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CellEditor {
+    text: String,
+    cursor: usize,
+}
+
+impl CellEditor {
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.text.len(), |(index, _)| index)
+    }
+}
+
+/// A table of text cells that can be edited from the keyboard.
+#[derive(Component, Debug, Clone, Default)]
+pub struct EditableTable {
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+    cursor: (usize, usize),
+    editing: Option<CellEditor>,
+    /// The original values of the cells changed since the changes were last accepted.
+    original: HashMap<(usize, usize), String>,
+    /// Whether the table takes key presses.
+    pub focused: bool,
+}
+
+impl EditableTable {
+    /// Creates a focused table with the given column headers and rows.
+    pub fn new<H: Into<String>>(
+        headers: impl IntoIterator<Item = H>,
+        rows: Vec<Vec<String>>,
+    ) -> Self {
+        Self {
+            headers: headers.into_iter().map(Into::into).collect(),
+            rows,
+            focused: true,
+            ..Default::default()
+        }
+    }
+
+    /// The number of columns.
+    pub fn columns(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// The rows of cells.
+    pub fn rows(&self) -> &[Vec<String>] {
+        &self.rows
+    }
+
+    /// The value of a cell, or an empty string if it does not exist.
+    pub fn cell(&self, row: usize, column: usize) -> &str {
+        self.rows
+            .get(row)
+            .and_then(|cells| cells.get(column))
+            .map_or("", String::as_str)
+    }
+
+    /// Sets the value of a cell, recording the change.
+    pub fn set_cell(&mut self, row: usize, column: usize, value: impl Into<String>) {
+        let Some(cells) = self.rows.get_mut(row) else {
+            return;
+        };
+        if cells.len() <= column {
+            cells.resize(column + 1, String::new());
+        }
+        let old = std::mem::replace(&mut cells[column], value.into());
+        let original = self.original.entry((row, column)).or_insert(old);
+        if *original == cells[column] {
+            self.original.remove(&(row, column));
+        }
+    }
+
+    /// The row and column of the cell cursor.
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// Returns true while a cell is being edited.
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    /// Returns true if the cell has changed since the changes were last accepted.
+    pub fn is_modified(&self, row: usize, column: usize) -> bool {
+        self.original.contains_key(&(row, column))
+    }
+
+    /// The changed cells as `(row, column, original, current)`, sorted by position.
+    pub fn changes(&self) -> impl Iterator<Item = (usize, usize, &str, &str)> {
+        let mut positions: Vec<_> = self.original.keys().copied().collect();
+        positions.sort_unstable();
+        positions.into_iter().map(|(row, column)| {
+            (
+                row,
+                column,
+                self.original[&(row, column)].as_str(),
+                self.cell(row, column),
+            )
+        })
+    }
+
+    /// Forgets the changes, making the current values the originals.
+    pub fn accept_changes(&mut self) {
+        self.original.clear();
+    }
+
+    /// Restores the original values of the changed cells.
+    pub fn revert_changes(&mut self) {
+        for ((row, column), value) in std::mem::take(&mut self.original) {
+            if let Some(cell) = self
+                .rows
+                .get_mut(row)
+                .and_then(|cells| cells.get_mut(column))
+            {
+                *cell = value;
+            }
+        }
+    }
+
+    fn move_cursor(&mut self, rows: isize, columns: isize) {
+        let (row, column) = self.cursor;
+        self.cursor = (
+            row.saturating_add_signed(rows)
+                .min(self.rows.len().saturating_sub(1)),
+            column
+                .saturating_add_signed(columns)
+                .min(self.columns().saturating_sub(1)),
+        );
+    }
+
+    /// Moves to the next cell, wrapping to the start of the next row.
+    fn next_cell(&mut self, forward: bool) {
+        let columns = self.columns().max(1);
+        let index = self.cursor.0 * columns + self.cursor.1;
+        let count = self.rows.len() * columns;
+        let index = if forward {
+            (index + 1).min(count.saturating_sub(1))
+        } else {
+            index.saturating_sub(1)
+        };
+        self.cursor = (index / columns, index % columns);
+    }
+
+    /// Handles a key press, returning an event if an edit was committed or cancelled.
+    fn handle_key(
+        &mut self,
+        entity: Entity,
+        key: &crossterm::event::KeyEvent,
+    ) -> Option<TableEditEvent> {
+        let (row, column) = self.cursor;
+        let Some(editor) = &mut self.editing else {
+            match key.code {
+                KeyCode::Up => self.move_cursor(-1, 0),
+                KeyCode::Down => self.move_cursor(1, 0),
+                KeyCode::Left => self.move_cursor(0, -1),
+                KeyCode::Right => self.move_cursor(0, 1),
+                KeyCode::Tab => self.next_cell(true),
+                KeyCode::BackTab => self.next_cell(false),
+                KeyCode::Enter if row < self.rows.len() => {
+                    let text = self.cell(row, column).to_string();
+                    let cursor = text.chars().count();
+                    self.editing = Some(CellEditor { text, cursor });
+                }
+                _ => {}
+            }
+            return None;
+        };
+        match key.code {
+            KeyCode::Enter => {
+                let new = self.editing.take()?.text;
+                let old = self.cell(row, column).to_string();
+                self.set_cell(row, column, new.clone());
+                return Some(TableEditEvent::Committed {
+                    entity,
+                    row,
+                    column,
+                    old,
+                    new,
+                });
+            }
+            KeyCode::Esc => {
+                self.editing = None;
+                return Some(TableEditEvent::Cancelled {
+                    entity,
+                    row,
+                    column,
+                });
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                let index = editor.byte_index(editor.cursor);
+                editor.text.insert(index, c);
+                editor.cursor += 1;
+            }
+            KeyCode::Backspace if editor.cursor > 0 => {
+                editor.cursor -= 1;
+                let index = editor.byte_index(editor.cursor);
+                editor.text.remove(index);
+            }
+            KeyCode::Delete if editor.cursor < editor.text.chars().count() => {
+                let index = editor.byte_index(editor.cursor);
+                editor.text.remove(index);
+            }
+            KeyCode::Left => editor.cursor = editor.cursor.saturating_sub(1),
+            KeyCode::Right => editor.cursor = (editor.cursor + 1).min(editor.text.chars().count()),
+            KeyCode::Home => editor.cursor = 0,
+            KeyCode::End => editor.cursor = editor.text.chars().count(),
+            _ => {}
+        }
+        None
+    }
+}
+
+fn table_input_system(
+    mut keys: EventReader<KeyEvent>,
+    mut tables: Query<(Entity, Interactive<&mut EditableTable>)>,
+    mut events: EventWriter<TableEditEvent>,
+) {
+    for key in keys.read().filter(|key| key.kind != KeyEventKind::Release) {
+        for (entity, (mut table, visibility, disabled)) in &mut tables {
+            if table.focused && is_interactive(visibility, disabled) {
+                if let Some(event) = table.handle_key(entity, key) {
+                    events.send(event);
+                }
+            }
+        }
+    }
+}
+
+/// Renders an [`EditableTable`], highlighting the cell cursor and the changed cells.
+pub struct EditableTableView<'a> {
+    table: &'a EditableTable,
+    cursor_style: Style,
+    modified_style: Style,
+    disabled: bool,
+}
+
+impl<'a> EditableTableView<'a> {
+    pub fn new(table: &'a EditableTable) -> Self {
+        Self {
+            table,
+            cursor_style: Style::new().add_modifier(Modifier::REVERSED),
+            modified_style: Style::new().add_modifier(Modifier::ITALIC),
+            disabled: false,
+        }
+    }
+
+    /// Sets the style of the cell under the cursor. Defaults to reversed.
+    pub fn cursor_style(mut self, style: Style) -> Self {
+        self.cursor_style = style;
+        self
+    }
+
+    /// Sets the style of the changed cells. Defaults to italic.
+    pub fn modified_style(mut self, style: Style) -> Self {
+        self.modified_style = style;
+        self
+    }
+
+    /// Draws the table dimmed and without the cell cursor, e.g. for a [`Disabled`] table.
+    ///
+    /// [`Disabled`]: crate::widget::Disabled
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    fn cell(&self, row: usize, column: usize) -> Cell<'a> {
+        let table = self.table;
+        let mut style = Style::new();
+        if table.is_modified(row, column) {
+            style = style.patch(self.modified_style);
+        }
+        if table.cursor != (row, column) || !table.focused || self.disabled {
+            return Cell::from(table.cell(row, column)).style(style);
+        }
+        let Some(editor) = &table.editing else {
+            return Cell::from(table.cell(row, column)).style(style.patch(self.cursor_style));
+        };
+        let (before, after) = editor.text.split_at(editor.byte_index(editor.cursor));
+        let mut after = after.chars();
+        let under_cursor = after.next().map_or(" ".to_string(), String::from);
+        Cell::from(Line::from(vec![
+            Span::raw(before.to_string()),
+            Span::styled(under_cursor, self.cursor_style),
+            Span::raw(after.as_str().to_string()),
+        ]))
+        .style(style)
+    }
+}
+
+impl Widget for EditableTableView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let columns = self.table.columns();
+        let header = Row::new(self.table.headers.iter().map(String::as_str))
+            .style(Style::new().add_modifier(Modifier::BOLD));
+        let rows = (0..self.table.rows.len())
+            .map(|row| Row::new((0..columns).map(|column| self.cell(row, column))));
+        let table = Table::new(rows, vec![Constraint::Fill(1); columns]).header(header);
+        // Selecting the cursor row scrolls it into view.
+        let mut state = TableState::new().with_selected(Some(self.table.cursor.0));
+        StatefulWidget::render(table, area, buf, &mut state);
+        if self.disabled {
+            buf.set_style(area, Style::new().add_modifier(Modifier::DIM));
+        }
+    }
+}
@@ -0,0 +1,398 @@
+//! Following a log file as it grows, like `tail -f`.
+//!
+//! A [`TailFile`] entity shows the end of a file in its [`AnchoredArea`], keeping the ANSI colors
+//! and styles of the lines, and shows new lines as they are written. [`TailFilePlugin`] checks the
+//! file for new data every [`TailPollInterval`], reading it on the [`IoTaskPool`] so that a slow
+//! disk never blocks a frame. A file that is truncated, as log rotation does, is followed from its
+//! start again, and a file that does not exist yet is waited for.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     anchor::AnchoredArea,
+//!     routing::FocusedPane,
+//!     tail_file::{TailFile, TailFilePlugin},
+//!     RatatuiPlugins,
+//! };
+//! use ratatui::layout::Rect;
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), TailFilePlugin))
+//!     .add_systems(Startup, |mut commands: Commands, mut focused: ResMut<FocusedPane>| {
+//!         let area = AnchoredArea(Rect::new(0, 0, 80, 24));
+//!         let log = commands.spawn((TailFile("server.log".into()), area)).id();
+//!         focused.0 = Some(log);
+//!     })
+//!     .run();
+//! ```
+//!
+//! While focused, `Up`/`k` and `Down`/`j` scroll by a line, `PageUp` and `PageDown` by a page, and
+//! `Home`/`g` jumps to the start. Scrolling up pauses the pane, keeping the same lines on screen
+//! while the file grows, and `End`/`G` follows the file again. `F` switches between the two, as in
+//! `less`.
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, IoTaskPool, Task, TaskPool},
+};
+use crossterm::event::{KeyCode, KeyEventKind, MouseEventKind};
+use ratatui::{buffer::Buffer, layout::Rect, text::Line, widgets::Widget};
+
+use crate::{
+    anchor::AnchoredArea,
+    event::InputSet,
+    output_pane::parse_line,
+    redraw::RedrawRequested,
+    render::{RenderBuffer, RenderPlugin, RenderSet},
+    routing::{InputRoutingPlugin, InputTarget, RoutedKeyEvent, RoutedMouseEvent},
+    widget::{draw_root_widget, is_interactive, Interactive, WidgetVisibility},
+};
+
+/// How much of the end of the file is shown when it is first read.
+const INITIAL_BYTES: u64 = 64 * 1024;
+
+/// The most that is read at a time, so that a file that grows quickly is caught up with over a
+/// few frames.
+const MAX_READ: u64 = 1024 * 1024;
+
+/// How many lines the mouse wheel scrolls by.
+const WHEEL_LINES: isize = 3;
+
+/// A plugin that reads, draws and scrolls [`TailFile`]s.
+///
+/// This requires the [`RenderPlugin`] and the [`InputRoutingPlugin`], and adds them if they are
+/// missing.
+pub struct TailFilePlugin;
+
+impl Plugin for TailFilePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RenderPlugin>() {
+            app.add_plugins(RenderPlugin);
+        }
+        if !app.is_plugin_added::<InputRoutingPlugin>() {
+            app.add_plugins(InputRoutingPlugin);
+        }
+        app.init_resource::<TailPollInterval>()
+            .add_systems(
+                PreUpdate,
+                (scroll_tail_panes, read_tail_files)
+                    .chain()
+                    .after(InputSet::Post),
+            )
+            .add_systems(
+                PostUpdate,
+                draw_tail_panes
+                    .after(draw_root_widget)
+                    .in_set(RenderSet::Widgets),
+            );
+    }
+}
+
+/// How often each [`TailFile`] is checked for new data. Defaults to 250 milliseconds.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct TailPollInterval(pub Duration);
+
+impl Default for TailPollInterval {
+    fn default() -> Self {
+        Self(Duration::from_millis(250))
+    }
+}
+
+/// A file whose end is shown in a pane, following it as it grows.
+///
+/// Changing the path starts over with the new file. The lines and the scroll position are kept in
+/// the [`TailPane`] that this requires.
+#[derive(Component, Debug, Clone, PartialEq, Eq, Hash, Deref)]
+#[require(TailPane, InputTarget, AnchoredArea)]
+pub struct TailFile(pub PathBuf);
+
+/// The lines read from a [`TailFile`] and the scroll position of its pane.
+///
+/// The pane takes the keys and mouse events routed to it, as an [`InputTarget`], unless it is
+/// [`Disabled`](crate::widget::Disabled) or hidden.
+#[derive(Component, Debug)]
+pub struct TailPane {
+    lines: VecDeque<Line<'static>>,
+    capacity: usize,
+    /// The index of the first line on screen, or `None` to follow the end of the file.
+    offset: Option<usize>,
+    /// The number of lines that fit in the pane, as of the last draw.
+    page_height: usize,
+    /// The start of a line that has not been ended yet.
+    partial: Vec<u8>,
+    /// Where to read from next, or `None` to start near the end of the file.
+    position: Option<u64>,
+    reading: Option<Task<io::Result<Chunk>>>,
+    /// When the file was last found to have no new data.
+    caught_up: Option<Instant>,
+    error: Option<String>,
+}
+
+impl Default for TailPane {
+    fn default() -> Self {
+        Self {
+            lines: VecDeque::new(),
+            capacity: 10_000,
+            offset: None,
+            page_height: 0,
+            partial: Vec::new(),
+            position: None,
+            reading: None,
+            caught_up: None,
+            error: None,
+        }
+    }
+}
+
+impl TailPane {
+    /// Keeps at most `capacity` lines, dropping the oldest first. Defaults to 10,000.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// The lines read so far, oldest first.
+    pub fn lines(&self) -> impl ExactSizeIterator<Item = &Line<'static>> {
+        self.lines.iter()
+    }
+
+    /// Why the file could not be read the last time it was checked, such as it not existing yet.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Whether the pane follows the end of the file, rather than being paused.
+    pub fn is_following(&self) -> bool {
+        self.offset.is_none()
+    }
+
+    /// Follows the end of the file, showing new lines as they are read.
+    pub fn follow(&mut self) {
+        self.offset = None;
+    }
+
+    /// Pauses the pane, keeping the lines on screen while the file grows.
+    pub fn pause(&mut self) {
+        self.offset = Some(self.offset.unwrap_or(usize::MAX).min(self.max_offset()));
+    }
+
+    /// Scrolls by `lines`, up if negative. Scrolling pauses the pane unless it reaches the end.
+    pub fn scroll_by(&mut self, lines: isize) {
+        let max_offset = self.max_offset();
+        let offset = self
+            .offset
+            .unwrap_or(max_offset)
+            .min(max_offset)
+            .saturating_add_signed(lines)
+            .min(max_offset);
+        self.offset = (offset < max_offset).then_some(offset);
+    }
+
+    /// Scrolls to the first line.
+    pub fn scroll_to_top(&mut self) {
+        self.offset = Some(0);
+    }
+
+    fn max_offset(&self) -> usize {
+        self.lines.len().saturating_sub(self.page_height)
+    }
+
+    fn push(&mut self, line: &[u8]) {
+        if self.lines.len() == self.capacity {
+            self.lines.pop_front();
+            // Keep the same lines on screen while paused.
+            self.offset = self.offset.map(|offset| offset.saturating_sub(1));
+        }
+        self.lines.push_back(parse_line(line));
+    }
+
+    /// Adds the lines in a chunk read from the file. Returns whether there were any.
+    fn append(&mut self, chunk: Chunk) -> bool {
+        if chunk.truncated {
+            self.partial.clear();
+        }
+        let mut data = chunk.data.as_slice();
+        if chunk.skip_partial {
+            // Reading started partway through a line.
+            let start = data
+                .iter()
+                .position(|&byte| byte == b'\n')
+                .map_or(0, |i| i + 1);
+            data = &data[start..];
+        }
+        self.position = Some(chunk.end);
+        self.partial.extend_from_slice(data);
+        let Some(end) = self.partial.iter().rposition(|&byte| byte == b'\n') else {
+            return false;
+        };
+        let rest = self.partial.split_off(end + 1);
+        let complete = std::mem::replace(&mut self.partial, rest);
+        for line in complete.split_inclusive(|&byte| byte == b'\n') {
+            self.push(line);
+        }
+        true
+    }
+
+    fn handle_key(&mut self, code: KeyCode) {
+        let page = self.page_height.max(1) as isize;
+        match code {
+            KeyCode::Char('k') | KeyCode::Up => self.scroll_by(-1),
+            KeyCode::Char('j') | KeyCode::Down => self.scroll_by(1),
+            KeyCode::PageUp => self.scroll_by(-page),
+            KeyCode::PageDown => self.scroll_by(page),
+            KeyCode::Char('g') | KeyCode::Home => self.scroll_to_top(),
+            KeyCode::Char('G') | KeyCode::End => self.follow(),
+            KeyCode::Char('F') if self.is_following() => self.pause(),
+            KeyCode::Char('F') => self.follow(),
+            _ => {}
+        }
+    }
+}
+
+impl Widget for &TailPane {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let first = self.offset.unwrap_or(usize::MAX).min(self.max_offset());
+        for (y, line) in (area.top()..area.bottom()).zip(self.lines.iter().skip(first)) {
+            line.render(Rect::new(area.x, y, area.width, 1), buf);
+        }
+    }
+}
+
+/// Data read from a file.
+#[derive(Debug)]
+struct Chunk {
+    data: Vec<u8>,
+    /// The position in the file after the data.
+    end: u64,
+    /// Whether the file was shorter than where the last read ended, so this was read from its
+    /// start.
+    truncated: bool,
+    /// Whether the data starts partway through a line.
+    skip_partial: bool,
+}
+
+/// Reads the file from `position` to its end, or the end of the file if `position` is `None`.
+fn read_chunk(path: &Path, position: Option<u64>) -> io::Result<Chunk> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let truncated = position.is_some_and(|position| position > len);
+    let start = match position {
+        None => len.saturating_sub(INITIAL_BYTES),
+        Some(_) if truncated => 0,
+        Some(position) => position,
+    };
+    file.seek(SeekFrom::Start(start))?;
+    let mut data = Vec::new();
+    file.take(MAX_READ).read_to_end(&mut data)?;
+    Ok(Chunk {
+        end: start + data.len() as u64,
+        data,
+        truncated,
+        skip_partial: position.is_none() && start > 0,
+    })
+}
+
+fn scroll_tail_panes(
+    mut keys: EventReader<RoutedKeyEvent>,
+    mut mouse: EventReader<RoutedMouseEvent>,
+    mut panes: Query<Interactive<&mut TailPane>>,
+) {
+    for key in keys.read() {
+        if key.event.kind == KeyEventKind::Release {
+            continue;
+        }
+        if let Ok((mut pane, visibility, disabled)) = panes.get_mut(key.target) {
+            if is_interactive(visibility, disabled) {
+                pane.handle_key(key.event.code);
+            }
+        }
+    }
+    for event in mouse.read() {
+        let lines = match event.event.kind {
+            MouseEventKind::ScrollUp => -WHEEL_LINES,
+            MouseEventKind::ScrollDown => WHEEL_LINES,
+            _ => continue,
+        };
+        if let Ok((mut pane, visibility, disabled)) = panes.get_mut(event.target) {
+            if is_interactive(visibility, disabled) {
+                pane.scroll_by(lines);
+            }
+        }
+    }
+}
+
+/// Starts a read of each file that is due to be checked, and takes the data of the reads that
+/// have finished.
+fn read_tail_files(
+    mut panes: Query<(Ref<TailFile>, &mut TailPane)>,
+    interval: Res<TailPollInterval>,
+    redraw: Option<ResMut<RedrawRequested>>,
+) {
+    let now = Instant::now();
+    let mut changed = false;
+    for (file, mut pane) in &mut panes {
+        // Bypassing change detection, as a pane whose file has not grown has not changed.
+        let pane = pane.bypass_change_detection();
+        if file.is_changed() && !file.is_added() {
+            *pane = TailPane {
+                capacity: pane.capacity,
+                ..default()
+            };
+            changed = true;
+        }
+        if let Some(task) = pane.reading.as_mut() {
+            let Some(result) = block_on(future::poll_once(task)) else {
+                continue;
+            };
+            pane.reading = None;
+            match result {
+                Ok(chunk) => {
+                    // More is waiting if the read stopped short of the end of the file.
+                    let more = chunk.data.len() as u64 == MAX_READ;
+                    pane.caught_up = (!more).then_some(now);
+                    pane.error = None;
+                    changed |= pane.append(chunk);
+                }
+                Err(err) => {
+                    pane.caught_up = Some(now);
+                    pane.error = Some(err.to_string());
+                }
+            }
+        }
+        if pane
+            .caught_up
+            .is_some_and(|caught_up| now.duration_since(caught_up) < interval.0)
+        {
+            continue;
+        }
+        let path = file.0.clone();
+        let position = pane.position;
+        let pool = IoTaskPool::get_or_init(TaskPool::new);
+        pane.reading = Some(pool.spawn(async move { read_chunk(&path, position) }));
+    }
+    if let (true, Some(mut redraw)) = (changed, redraw) {
+        redraw.request();
+    }
+}
+
+fn draw_tail_panes(
+    mut buffer: ResMut<RenderBuffer>,
+    mut panes: Query<(&mut TailPane, &AnchoredArea, Option<&WidgetVisibility>)>,
+) {
+    let bounds = buffer.area;
+    for (mut pane, area, visibility) in &mut panes {
+        if !visibility.is_none_or(|visibility| visibility.is_visible()) {
+            continue;
+        }
+        let area = area.intersection(bounds);
+        pane.page_height = usize::from(area.height);
+        pane.render(area, &mut buffer);
+    }
+}
@@ -5,46 +5,648 @@
 //!
 //! [`RatatuiContext`] is a wrapper [`Resource`] around ratatui::Terminal that automatically enters
 //! and leaves the alternate screen.
-use std::io::{self, stdout, Stdout};
+//!
+//! # Restoring the shell
+//!
+//! The cursor position is recorded before the alternate screen is entered, and the shell prompt
+//! resumes at that position when the app exits. Insert a [`StartupMarker`] resource to print a
+//! line to the shell's scrollback before the app starts, and set the [`RestorePolicy`] resource to
+//! [`RestorePolicy::KeepLastFrame`] to leave the last rendered frame visible after exit.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     terminal::{RestorePolicy, StartupMarker},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .insert_resource(RestorePolicy::KeepLastFrame)
+//!     .insert_resource(StartupMarker("-- my app --".into()))
+//!     .add_plugins(RatatuiPlugins::default());
+//! ```
+//!
+//! # Inline viewport
+//!
+//! Set the [`TerminalViewport`] resource to [`TerminalViewport::Inline`] to draw in a number of
+//! lines below the shell prompt, like ratatui's [`Viewport::Inline`], instead of taking over the
+//! whole screen. The alternate screen is not entered, and the viewport is moved and resized along
+//! with the terminal. On exit, [`RestorePolicy::Restore`] clears the viewport and
+//! [`RestorePolicy::KeepLastFrame`] leaves it in the scrollback with the prompt below it.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{terminal::TerminalViewport, RatatuiPlugins};
+//!
+//! App::new()
+//!     .insert_resource(TerminalViewport::Inline(8))
+//!     .add_plugins(RatatuiPlugins::default());
+//! ```
+//!
+//! Systems can print lines to the scrollback above an inline viewport with
+//! [`RatatuiContext::print_above`], or by sending a [`TerminalLog`] event:
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::terminal::TerminalLog;
+//!
+//! fn report_progress(mut log: EventWriter<TerminalLog>) {
+//!     log.send(TerminalLog::from("Downloaded 3 of 10 files"));
+//! }
+//! ```
+//!
+//! # Resizing
+//!
+//! Draws use the size from the last [`ResizeEvent`] rather than asking the terminal each time, and
+//! the terminal buffers are resized once, in [`InputSet::EmitBevy`](crate::event::InputSet), in
+//! the frame that the event is sent. Every draw in a frame therefore uses the same size as the
+//! layout code that read the event, even if the window is resized again while the frame runs.
+//! Set the [`ResizeDebounce`](crate::event::ResizeDebounce) resource to also wait for a resize
+//! storm to settle before the new size is used.
+//!
+//! Systems that only need the size of the terminal, its viewport or its cursor can read the
+//! [`TerminalInfo`] resource, which is updated after the resize, instead of the [`RatatuiContext`].
+//! Systems that access the context have to be ordered against every system that draws.
+//!
+//! # Synchronized output
+//!
+//! Terminals that support synchronized output hold a frame back until all of it has been written,
+//! so a fast updating UI never shows half a frame. Each [`RatatuiContext::draw`] is wrapped in
+//! `BeginSynchronizedUpdate` and `EndSynchronizedUpdate` when the [`SynchronizedOutput`] resource
+//! allows it. By default this is the case in terminals known to support it, based on the
+//! [`TerminalIdentity`].
+//!
+//! # Output
+//!
+//! The terminal is written to through stdout by default. When stdout is piped into another
+//! program, insert an [`OutputTarget`] to draw to stderr or the controlling terminal instead, and
+//! everything the crate writes to the terminal, such as the mouse capture and keyboard protocol
+//! escape codes, goes there too.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{terminal::OutputTarget, RatatuiPlugins};
+//!
+//! App::new()
+//!     .insert_resource(OutputTarget::Tty)
+//!     .add_plugins(RatatuiPlugins::default())
+//!     .run();
+//! ```
+//!
+//! # Headless mode
+//!
+//! With a [`HeadlessTerminal`] resource, which [`HeadlessTerminalPlugin`] or the `headless` option
+//! of [`RatatuiPlugins`](crate::RatatuiPlugins) inserts, the context draws to a ratatui
+//! [`TestBackend`] instead of stdout. Raw mode and the alternate screen are left alone and no
+//! terminal events are read, so the app runs in CI or as a background service without a TTY. The
+//! drawn frames are still available from [`RatatuiContext::last_frame`].
+//!
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{terminal::RatatuiContext, RatatuiPlugins};
+//!
+//! let mut app = App::new();
+//! app.add_plugins(RatatuiPlugins {
+//!     headless: true,
+//!     ..default()
+//! })
+//! .add_systems(Update, |mut context: ResMut<RatatuiContext>| {
+//!     context.draw(|frame| frame.render_widget("hello", frame.area()))
+//!         .unwrap();
+//! });
+//! app.update();
+//! let context = app.world().resource::<RatatuiContext>();
+//! assert_eq!(context.screen_lines()[0].trim_end(), "hello");
+//! ```
+//!
+//! # Deferred initialization
+//!
+//! The terminal is set up in [`Startup`] by default. Insert [`TerminalState::Deferred`] to leave it
+//! alone until an [`InitializeTerminal`] event is sent, e.g. until the command line arguments have
+//! been parsed and the app knows that it runs interactively. Until then there is no
+//! [`RatatuiContext`], no terminal events are read and nothing is written to the terminal, so a
+//! `--help` or `--version` flow can print to stdout and exit without touching the tty. The
+//! terminal is set up in [`PreUpdate`] of the frame after the event is sent, by running the
+//! [`TerminalStartup`] schedule.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     terminal::{InitializeTerminal, TerminalState},
+//!     RatatuiPlugins,
+//! };
+//!
+//! fn parse_args(mut exit: EventWriter<AppExit>, mut initialize: EventWriter<InitializeTerminal>) {
+//!     if std::env::args().any(|arg| arg == "--version") {
+//!         println!("{}", env!("CARGO_PKG_VERSION"));
+//!         exit.send_default();
+//!     } else {
+//!         initialize.send(InitializeTerminal);
+//!     }
+//! }
+//!
+//! App::new()
+//!     .insert_resource(TerminalState::Deferred)
+//!     .add_plugins(RatatuiPlugins::default())
+//!     .add_systems(Startup, parse_args)
+//!     .run();
+//! ```
+//!
+//! # Running external commands
+//!
+//! [`run_external`] hands the terminal to a child process, such as an editor or pager, and takes it
+//! back when the child exits, turning the mouse capture, bracketed paste and kitty keyboard flags
+//! back on and drawing the next frame in full. Queue a [`RunExternal`] command from a system to run
+//! it at the next command flush and get an [`ExternalCommandFinished`] event when it exits:
+//!
+//! ```rust,no_run
+//! use std::process::Command;
+//!
+//! use bevy::prelude::*;
+//! use bevy_ratatui::terminal::RunExternal;
+//!
+//! fn open_pager(mut commands: Commands) {
+//!     commands.queue(RunExternal::new(Command::new("less")));
+//! }
+//! ```
+//!
+//! [`release_terminal`] does the same around a callback, and a [`TerminalSuspendGuard`] until it
+//! is dropped.
+use std::{
+    env,
+    ffi::OsString,
+    fs::File,
+    io::{self, stderr, stdout, BufWriter, IsTerminal, Write},
+    path::Path,
+    process::{Command, ExitStatus},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicU8, Ordering},
+        Mutex, MutexGuard, OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
-use bevy::{app::AppExit, prelude::*};
+use bevy::{
+    app::AppExit,
+    ecs::{
+        event::{EventCursor, EventUpdates},
+        schedule::ScheduleLabel,
+    },
+    prelude::*,
+};
 use color_eyre::Result;
 use crossterm::{
     cursor,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-    ExecutableCommand,
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
+    terminal::{
+        disable_raw_mode, enable_raw_mode, BeginSynchronizedUpdate, Clear, EndSynchronizedUpdate,
+        EnterAlternateScreen, LeaveAlternateScreen,
+    },
+    ExecutableCommand, QueueableCommand,
+};
+use ratatui::{
+    backend::{Backend, ClearType, CrosstermBackend, TestBackend, WindowSize},
+    buffer::{Buffer, Cell},
+    layout::{Position, Size},
+    text::Text,
+    widgets::Widget,
+    CompletedFrame, Frame, TerminalOptions, Viewport,
 };
-use ratatui::backend::CrosstermBackend;
 
-use crate::{error::exit_on_error, kitty::KittyEnabled, mouse::MouseCaptureEnabled};
+#[cfg(feature = "termwiz")]
+use crate::termwiz::{TermwizTerminal, TermwizTerminalBackend};
+use crate::{
+    buffer,
+    color::ColorLevel,
+    error::exit_on_error,
+    event::{InputSet, ResizeEvent},
+    exit::ExitCodes,
+    extension::{TerminalIdentity, TerminalProgram},
+    handshake::HandshakeTimeout,
+    input_thread::InputThread,
+    kitty::{KeyboardEnhancementStack, KittyEnabled},
+    mouse::MouseCaptureEnabled,
+    paste::BracketedPasteEnabled,
+    redraw::RedrawRequested,
+    render::RenderBuffer,
+    stdio_guard::{self, CapturedStream},
+};
 
 /// A plugin that sets up the terminal.
 ///
-/// This plugin initializes the terminal, entering the alternate screen and enabling raw mode. It
-/// also restores the terminal when the app is dropped.
+/// This plugin initializes the terminal, entering the alternate screen and enabling raw mode, in
+/// the [`TerminalStartup`] schedule. It also restores the terminal when the app is dropped. The
+/// [`TerminalIdentity`] is detected from the environment unless the app has inserted one.
 pub struct TerminalPlugin;
 
 impl Plugin for TerminalPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup.pipe(exit_on_error))
-            .add_systems(PostUpdate, cleanup_system);
+        if !app.world().contains_resource::<TerminalIdentity>() {
+            app.insert_resource(TerminalIdentity::from_env());
+        }
+        let terminal_sets =
+            || (TerminalSet::Hooks, TerminalSet::Init, TerminalSet::Features).chain();
+        app.init_resource::<RestorePolicy>()
+            .init_resource::<TerminalState>()
+            .add_event::<InitializeTerminal>()
+            .add_event::<ExternalCommandFinished>()
+            .init_schedule(TerminalStartup)
+            .init_resource::<TerminalViewport>()
+            .init_resource::<OutputTarget>()
+            .init_resource::<SynchronizedOutput>()
+            .init_resource::<TerminalInfo>()
+            .configure_sets(TerminalStartup, terminal_sets())
+            .configure_sets(Startup, terminal_sets())
+            .add_systems(
+                TerminalStartup,
+                setup.pipe(exit_on_error).in_set(TerminalSet::Init),
+            )
+            .add_systems(Startup, start_terminal.in_set(TerminalSet::Init))
+            .add_systems(PreUpdate, start_deferred_terminal.before(InputSet::Pre))
+            .add_systems(
+                First,
+                (
+                    sync_synchronized_output.run_if(
+                        resource_changed::<SynchronizedOutput>.or(resource_added::<RatatuiContext>),
+                    ),
+                    sync_color_level.run_if(resource_exists_and_changed::<ColorLevel>),
+                )
+                    .chain()
+                    .after(EventUpdates),
+            )
+            .add_systems(
+                PreUpdate,
+                (apply_resize.pipe(exit_on_error), update_terminal_info)
+                    .chain()
+                    .in_set(InputSet::EmitBevy),
+            )
+            .add_event::<TerminalLog>()
+            .add_systems(
+                PostUpdate,
+                (print_terminal_log.pipe(exit_on_error), cleanup_system)
+                    .chain()
+                    .in_set(TerminalSet::Cleanup),
+            );
     }
 }
 
+/// The system sets that set up and restore the terminal.
+///
+/// The startup sets are chained in [`TerminalStartup`], so plugins that need the terminal, or that
+/// change its modes, can order their systems against them rather than relying on the order the
+/// plugins were added in. They are chained in [`Startup`] too, where [`TerminalStartup`] runs in
+/// [`TerminalSet::Init`], so that startup systems that need the [`RatatuiContext`] can run after
+/// it. Terminal input is read in the [`InputSet`](crate::event::InputSet)s.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum TerminalSet {
+    /// Install the panic and error hooks that restore the terminal, in [`TerminalStartup`].
+    Hooks,
+    /// Enter the alternate screen and raw mode and insert the [`RatatuiContext`], in
+    /// [`TerminalStartup`].
+    Init,
+    /// Enable optional terminal features, such as the kitty keyboard protocol and mouse capture,
+    /// in [`TerminalStartup`], and turn them on and off at runtime in [`PreUpdate`]. The features
+    /// are independent of each other, so the systems in this set are not ordered against each
+    /// other.
+    Features,
+    /// Print the [`TerminalLog`] lines and restore the terminal when the app exits, in
+    /// [`PostUpdate`]. Systems that draw in [`PostUpdate`] should run before this set so that the
+    /// last frame is complete.
+    Cleanup,
+}
+
+/// The schedule that sets up the terminal, made of the startup [`TerminalSet`]s.
+///
+/// It runs once, in [`Startup`], or when an [`InitializeTerminal`] event is sent if the
+/// [`TerminalState`] is [`TerminalState::Deferred`]. Plugins that touch the terminal when the app
+/// starts add their systems here rather than to [`Startup`], so that a deferred app leaves the
+/// terminal alone until it is set up.
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TerminalStartup;
+
+/// Whether the terminal has been set up.
+///
+/// Insert [`TerminalState::Deferred`] before the app starts to leave the terminal alone until an
+/// [`InitializeTerminal`] event is sent, e.g. so that `--help` can be printed without entering the
+/// alternate screen or raw mode. See [Deferred initialization](self#deferred-initialization).
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TerminalState {
+    /// The terminal is set up in [`Startup`].
+    #[default]
+    Startup,
+    /// The terminal is set up when an [`InitializeTerminal`] event is sent.
+    Deferred,
+    /// The terminal has been set up.
+    Initialized,
+}
+
+/// Sets up a [deferred](TerminalState::Deferred) terminal at the start of the next frame.
+///
+/// This does nothing once the terminal has been set up.
+#[derive(Event, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InitializeTerminal;
+
+/// Runs the [`TerminalStartup`] schedule unless the terminal is deferred.
+fn start_terminal(world: &mut World) {
+    if *world.resource::<TerminalState>() == TerminalState::Startup {
+        run_terminal_startup(world);
+    }
+}
+
+/// Runs the [`TerminalStartup`] schedule for a deferred terminal once an [`InitializeTerminal`]
+/// event is sent.
+fn start_deferred_terminal(world: &mut World, mut cursor: Local<EventCursor<InitializeTerminal>>) {
+    let requested = world
+        .get_resource::<Events<InitializeTerminal>>()
+        .is_some_and(|events| cursor.read(events).count() > 0);
+    if requested && *world.resource::<TerminalState>() == TerminalState::Deferred {
+        run_terminal_startup(world);
+    }
+}
+
+fn run_terminal_startup(world: &mut World) {
+    let transition = TerminalTransition::begin();
+    world.run_schedule(TerminalStartup);
+    *world.resource_mut::<TerminalState>() = TerminalState::Initialized;
+    if let Err(err) = transition.commit() {
+        error!("Error: {:?}", err);
+        let exit = ExitCodes::error_exit(world.get_resource::<ExitCodes>());
+        world.send_event(exit);
+    }
+}
+
+/// A plugin that makes the terminal headless by inserting a [`HeadlessTerminal`] resource, unless
+/// one was inserted already.
+pub struct HeadlessTerminalPlugin;
+
+impl Plugin for HeadlessTerminalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HeadlessTerminal>();
+    }
+}
+
+/// Draws to a [`TestBackend`] of the given size instead of the terminal when present at startup.
+///
+/// Defaults to 80 columns by 24 rows.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeadlessTerminal {
+    pub size: Size,
+}
+
+impl Default for HeadlessTerminal {
+    fn default() -> Self {
+        Self {
+            size: Size::new(80, 24),
+        }
+    }
+}
+
+/// A run condition that is true when the terminal is headless.
+pub fn is_headless(headless: Option<Res<HeadlessTerminal>>) -> bool {
+    headless.is_some()
+}
+
+/// Determines what is left on the screen when the app exits.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RestorePolicy {
+    /// Return the shell to exactly the state it was in before the app started. An inline viewport
+    /// is cleared.
+    #[default]
+    Restore,
+    /// Print the last rendered frame at the position where the app started, so that it remains
+    /// visible in the shell's scrollback after exit. An inline viewport is left as it is, with
+    /// the prompt below it.
+    KeepLastFrame,
+}
+
+/// Where the app draws, read when the terminal is set up.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TerminalViewport {
+    /// The whole screen, in the alternate screen.
+    #[default]
+    Fullscreen,
+    /// The given number of lines below the cursor, without entering the alternate screen.
+    Inline(u16),
+}
+
+/// Which stream the app writes to the terminal through, read when the terminal is set up.
+///
+/// Crossterm asks the terminal for the cursor position and the keyboard protocol through stdout,
+/// so with any other target those queries are skipped. The kitty keyboard protocol is left off,
+/// and an inline viewport, which needs the cursor position, is not supported.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OutputTarget {
+    /// Standard output.
+    #[default]
+    Stdout,
+    /// Standard error.
+    Stderr,
+    /// The controlling terminal, `/dev/tty`, which is there even when both stdout and stderr are
+    /// redirected. On Windows this is the console, `CONOUT$`.
+    Tty,
+}
+
+/// Whether draws are wrapped in synchronized updates, so that the terminal shows each frame at
+/// once rather than while it is being written.
+///
+/// Changes take effect from the next draw.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SynchronizedOutput {
+    /// Use synchronized output in terminals that are known to support it: kitty, WezTerm, iTerm2,
+    /// Alacritty and Ghostty.
+    #[default]
+    Auto,
+    /// Always use synchronized output. Terminals that don't support it ignore the escape
+    /// sequences.
+    Always,
+    /// Never use synchronized output.
+    Never,
+}
+
+impl SynchronizedOutput {
+    /// Whether to use synchronized output in the identified terminal.
+    pub fn is_enabled(self, identity: &TerminalIdentity) -> bool {
+        match self {
+            SynchronizedOutput::Auto => matches!(
+                identity.program,
+                TerminalProgram::Kitty
+                    | TerminalProgram::WezTerm
+                    | TerminalProgram::ITerm2
+                    | TerminalProgram::Alacritty
+                    | TerminalProgram::Ghostty
+            ),
+            SynchronizedOutput::Always => true,
+            SynchronizedOutput::Never => false,
+        }
+    }
+}
+
+/// A line printed to the shell before the app takes over the terminal.
+///
+/// Insert this resource before the app starts to leave a marker in the scrollback showing where the
+/// app was run.
+#[derive(Resource, Debug, Clone, Deref)]
+pub struct StartupMarker(pub String);
+
 /// A startup system that sets up the terminal.
-pub fn setup(mut commands: Commands) -> Result<()> {
-    let terminal = RatatuiContext::init()?;
+#[allow(clippy::too_many_arguments)]
+pub fn setup(
+    mut commands: Commands,
+    restore_policy: Res<RestorePolicy>,
+    marker: Option<Res<StartupMarker>>,
+    color_level: Option<Res<ColorLevel>>,
+    headless: Option<Res<HeadlessTerminal>>,
+    viewport: Res<TerminalViewport>,
+    target: Res<OutputTarget>,
+    handshake: Option<Res<HandshakeTimeout>>,
+    #[cfg(feature = "termwiz")] termwiz: Option<Res<TermwizTerminal>>,
+) -> Result<()> {
+    if headless.is_none() {
+        set_output_target(*target)?;
+    }
+    if let (None, Some(marker)) = (&headless, marker) {
+        writeln!(output(), "{}", **marker)?;
+    }
+    let mut terminal = match headless {
+        Some(headless) => RatatuiContext::headless(headless.size)?,
+        #[cfg(feature = "termwiz")]
+        None if termwiz.is_some() => RatatuiContext::termwiz()?,
+        None => {
+            // The handshake reads the answer to the query in the background, but it needs the
+            // query to be held back with the rest of the setup until raw mode is on.
+            let query = if cfg!(unix)
+                && handshake.is_some()
+                && *target == OutputTarget::Stdout
+                && stdout().is_terminal()
+                && in_transition()
+            {
+                StartPositionQuery::Handshake
+            } else {
+                StartPositionQuery::Blocking
+            };
+            RatatuiContext::init_terminal(*viewport, query)?
+        }
+    };
+    terminal.restore_policy = *restore_policy;
+    terminal.color_level = color_level.map(|level| *level);
+    commands.insert_resource(terminal.info());
     commands.insert_resource(terminal);
     Ok(())
 }
 
+/// Applies changes to the [`ColorLevel`] resource to the terminal.
+pub(crate) fn sync_color_level(
+    color_level: Res<ColorLevel>,
+    context: Option<ResMut<RatatuiContext>>,
+) {
+    if let Some(mut context) = context {
+        context.color_level = Some(*color_level);
+    }
+}
+
+/// Applies the [`SynchronizedOutput`] resource to the terminal when either of them is new, or the
+/// resource changes.
+fn sync_synchronized_output(
+    synchronized_output: Res<SynchronizedOutput>,
+    identity: Res<TerminalIdentity>,
+    context: Option<ResMut<RatatuiContext>>,
+) {
+    if let Some(mut context) = context {
+        context.synchronized_output = synchronized_output.is_enabled(&identity);
+    }
+}
+
+/// Resizes the terminal to the last [`ResizeEvent`] of the frame.
+fn apply_resize(
+    mut resize: EventReader<ResizeEvent>,
+    context: Option<ResMut<RatatuiContext>>,
+) -> Result<()> {
+    let Some(size) = resize.read().last() else {
+        return Ok(());
+    };
+    if let Some(mut context) = context {
+        context.resize_to(**size)?;
+    }
+    Ok(())
+}
+
+/// A copy of what systems commonly need to know about the terminal.
+///
+/// Reading this rather than the [`RatatuiContext`] lets a system run in parallel with the systems
+/// that draw. It is updated once per frame, after the terminal has been resized in
+/// [`InputSet::EmitBevy`](crate::event::InputSet::EmitBevy).
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TerminalInfo {
+    /// The size of the terminal that draws use.
+    pub size: Size,
+    /// Where the app draws.
+    pub viewport: TerminalViewport,
+    /// Where the cursor was left by the last draw, or `None` if it was hidden.
+    pub cursor: Option<Position>,
+}
+
+fn update_terminal_info(mut info: ResMut<TerminalInfo>, context: Option<Res<RatatuiContext>>) {
+    if let Some(context) = context {
+        info.set_if_neq(context.info());
+    }
+}
+
+/// Lines to print to the scrollback above an inline viewport with
+/// [`RatatuiContext::print_above`].
+///
+/// The lines are printed at the end of the frame.
+#[derive(Debug, Clone, Event, PartialEq, Eq, Deref)]
+pub struct TerminalLog(pub Text<'static>);
+
+impl<T: Into<Text<'static>>> From<T> for TerminalLog {
+    fn from(text: T) -> Self {
+        Self(text.into())
+    }
+}
+
+fn print_terminal_log(
+    mut log: EventReader<TerminalLog>,
+    context: Option<ResMut<RatatuiContext>>,
+) -> Result<()> {
+    let Some(mut context) = context else {
+        log.clear();
+        return Ok(());
+    };
+    for text in log.read() {
+        context.print_above(text.0.clone())?;
+    }
+    Ok(())
+}
+
 /// A cleanup system that ensures terminal enhancements are cleaned up in the correct order.
-pub fn cleanup_system(mut commands: Commands, mut exit_reader: EventReader<AppExit>) {
-    for _ in exit_reader.read() {
-        commands.remove_resource::<KittyEnabled>();
-        commands.remove_resource::<MouseCaptureEnabled>();
-        commands.remove_resource::<RatatuiContext>();
+pub fn cleanup_system(
+    mut commands: Commands,
+    mut exit_reader: EventReader<AppExit>,
+    context: Option<ResMut<RatatuiContext>>,
+    restore_policy: Res<RestorePolicy>,
+) {
+    if exit_reader.is_empty() {
+        return;
+    }
+    exit_reader.clear();
+    if let Some(mut context) = context {
+        context.restore_policy = *restore_policy;
     }
+    commands.queue(|world: &mut World| {
+        let transition = TerminalTransition::begin();
+        world.remove_resource::<KittyEnabled>();
+        world.remove_resource::<KeyboardEnhancementStack>();
+        world.remove_resource::<MouseCaptureEnabled>();
+        world.remove_resource::<BracketedPasteEnabled>();
+        world.remove_resource::<InputThread>();
+        #[cfg(unix)]
+        world.remove_resource::<crate::exit::ExitSignalHandlers>();
+        world.remove_resource::<RatatuiContext>();
+        if let Err(err) = transition.commit() {
+            eprintln!("Failed to restore terminal: {}", err);
+        }
+    });
 }
 
 /// A wrapper around ratatui::Terminal that automatically enters and leaves the alternate screen.
@@ -64,27 +666,425 @@ pub fn cleanup_system(mut commands: Commands, mut exit_reader: EventReader<AppEx
 ///     });
 /// }
 /// ```
-#[derive(Resource, Deref, DerefMut)]
-pub struct RatatuiContext(ratatui::Terminal<CrosstermBackend<Stdout>>);
+///
+/// # Other terminals
+///
+/// A context can also draw to any writer, such as the PTY of a second terminal, with
+/// [`RatatuiContext::with_writer`]. Such contexts are spawned as components rather than inserted as
+/// the resource, and each gets its own [`RenderBuffer`](crate::render::RenderBuffer) component
+/// that is drawn along with the resource's. They never touch the app's own terminal, and the app
+/// resizes them with [`RatatuiContext::resize_to`] when the other terminal changes size.
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_ratatui::{
+///     render::{RenderBuffer, RenderSet},
+///     terminal::RatatuiContext,
+///     RatatuiPlugins,
+/// };
+/// use ratatui::{layout::Size, widgets::Widget};
+///
+/// #[derive(Component)]
+/// struct StatusTerminal;
+///
+/// fn draw_status(mut buffer: Single<&mut RenderBuffer, With<StatusTerminal>>) {
+///     let area = buffer.area;
+///     "all good".render(area, &mut **buffer);
+/// }
+///
+/// let mut app = App::new();
+/// app.add_plugins(RatatuiPlugins {
+///     headless: true,
+///     ..default()
+/// })
+/// .add_systems(PostUpdate, draw_status.in_set(RenderSet::Widgets));
+/// let context = RatatuiContext::with_writer(Vec::new(), Size::new(20, 1)).unwrap();
+/// let status = app.world_mut().spawn((StatusTerminal, context)).id();
+/// app.update();
+/// let context = app.world().get::<RatatuiContext>(status).unwrap();
+/// assert_eq!(context.screen_lines(), ["all good"]);
+/// ```
+#[derive(Resource, Component, Deref, DerefMut)]
+#[require(RenderBuffer)]
+pub struct RatatuiContext {
+    #[deref]
+    terminal: ratatui::Terminal<TerminalBackend>,
+    last_frame: Buffer,
+    last_draw: Option<(Instant, Duration)>,
+    start_position: Option<Position>,
+    restore_policy: RestorePolicy,
+    color_level: Option<ColorLevel>,
+    synchronized_output: bool,
+    viewport: TerminalViewport,
+}
 
 impl RatatuiContext {
     /// Initializes the terminal, entering the alternate screen and enabling raw mode.
+    ///
+    /// The cursor position is recorded first so that the shell can be restored to it on exit.
     pub fn init() -> io::Result<Self> {
-        stdout().execute(EnterAlternateScreen)?;
+        RatatuiContext::init_with_viewport(TerminalViewport::Fullscreen)
+    }
+
+    /// Initializes the terminal with the given viewport, enabling raw mode and entering the
+    /// alternate screen if the viewport is [`TerminalViewport::Fullscreen`].
+    pub fn init_with_viewport(viewport: TerminalViewport) -> io::Result<Self> {
+        RatatuiContext::init_terminal(viewport, StartPositionQuery::Blocking)
+    }
+
+    fn init_terminal(viewport: TerminalViewport, query: StartPositionQuery) -> io::Result<Self> {
+        // ratatui asks for the cursor position of an inline viewport.
+        flush_transition()?;
+        let (start_position, ratatui_viewport) = match viewport {
+            TerminalViewport::Fullscreen => {
+                let start_position = match query {
+                    StartPositionQuery::Blocking => query_cursor_position().ok(),
+                    StartPositionQuery::Handshake => {
+                        output().write_all(START_POSITION_QUERY)?;
+                        START_POSITION_REQUESTED.store(true, Ordering::SeqCst);
+                        None
+                    }
+                };
+                enter_alternate_screen()?;
+                (start_position, Viewport::Fullscreen)
+            }
+            // ratatui finds the position of an inline viewport from the cursor itself, and the
+            // shell resumes below the viewport rather than at the start position.
+            TerminalViewport::Inline(height) => (None, Viewport::Inline(height)),
+        };
         enable_raw_mode()?;
-        let backend = CrosstermBackend::new(stdout());
+        let backend = CrosstermBackend::new(BufWriter::new(output()));
+        set_frame_size(backend.size()?);
+        let backend = TerminalBackend::Crossterm(backend);
+        let terminal = ratatui::Terminal::with_options(
+            backend,
+            TerminalOptions {
+                viewport: ratatui_viewport,
+            },
+        )?;
+        Ok(RatatuiContext {
+            terminal,
+            last_frame: Buffer::empty(Default::default()),
+            last_draw: None,
+            start_position,
+            restore_policy: RestorePolicy::default(),
+            color_level: None,
+            synchronized_output: false,
+            viewport,
+        })
+    }
+
+    /// Creates a context that draws to a [`TestBackend`] of the given size, without touching the
+    /// terminal.
+    pub fn headless(size: Size) -> io::Result<Self> {
+        let backend = TerminalBackend::Test(TestBackend::new(size.width, size.height));
         let terminal = ratatui::Terminal::new(backend)?;
-        Ok(RatatuiContext(terminal))
+        Ok(RatatuiContext {
+            terminal,
+            last_frame: Buffer::empty(Default::default()),
+            last_draw: None,
+            start_position: None,
+            restore_policy: RestorePolicy::default(),
+            color_level: None,
+            synchronized_output: false,
+            viewport: TerminalViewport::Fullscreen,
+        })
     }
 
-    /// Restores the terminal, leaving the alternate screen and disabling raw mode.
+    /// Initializes the terminal with termwiz rather than crossterm, enabling raw mode and entering
+    /// the alternate screen. See the [`termwiz`](crate::termwiz) module.
+    #[cfg(feature = "termwiz")]
+    pub fn termwiz() -> io::Result<Self> {
+        let backend = TerminalBackend::Termwiz(TermwizTerminalBackend::new()?);
+        let terminal = ratatui::Terminal::new(backend)?;
+        Ok(RatatuiContext {
+            terminal,
+            last_frame: Buffer::empty(Default::default()),
+            last_draw: None,
+            start_position: None,
+            restore_policy: RestorePolicy::default(),
+            color_level: None,
+            synchronized_output: false,
+            viewport: TerminalViewport::Fullscreen,
+        })
+    }
+
+    /// Creates a context that draws a terminal of the given size to `writer`, such as the PTY of
+    /// another terminal.
+    ///
+    /// Nothing is written until the first draw, and the app's own terminal is left alone, both now
+    /// and when the context is dropped.
+    pub fn with_writer(writer: impl Write + Send + Sync + 'static, size: Size) -> io::Result<Self> {
+        let backend = TerminalBackend::Writer(WriterBackend {
+            backend: CrosstermBackend::new(BufWriter::new(Box::new(writer))),
+            size,
+        });
+        let terminal = ratatui::Terminal::new(backend)?;
+        Ok(RatatuiContext {
+            terminal,
+            last_frame: Buffer::empty(Default::default()),
+            last_draw: None,
+            start_position: None,
+            restore_policy: RestorePolicy::default(),
+            color_level: None,
+            synchronized_output: false,
+            viewport: TerminalViewport::Fullscreen,
+        })
+    }
+
+    /// Where the context draws.
+    pub fn viewport(&self) -> TerminalViewport {
+        self.viewport
+    }
+
+    /// Resizes the terminal buffers to `size`, which every draw then uses until the next resize.
+    ///
+    /// This is called with the size from the last [`ResizeEvent`] of each frame. A headless
+    /// context resizes its [`TestBackend`], and a context that draws to a writer takes the size as
+    /// the size of the terminal it draws to.
+    pub fn resize_to(&mut self, size: Size) -> io::Result<()> {
+        match self.terminal.backend_mut() {
+            TerminalBackend::Crossterm(_) => set_frame_size(size),
+            TerminalBackend::Test(backend) => backend.resize(size.width, size.height),
+            TerminalBackend::Writer(backend) => backend.size = size,
+            #[cfg(feature = "termwiz")]
+            TerminalBackend::Termwiz(backend) => backend.resize(size),
+        }
+        self.terminal.autoresize()
+    }
+
+    /// The size, viewport and cursor of the terminal.
+    ///
+    /// This is also available from the [`TerminalInfo`] resource without accessing the context.
+    pub fn info(&self) -> TerminalInfo {
+        TerminalInfo {
+            size: self.terminal.size().unwrap_or_default(),
+            viewport: self.viewport,
+            cursor: cursor(),
+        }
+    }
+
+    /// Whether the context draws to a [`TestBackend`] rather than the terminal.
+    pub fn is_headless(&self) -> bool {
+        matches!(self.terminal.backend(), TerminalBackend::Test(_))
+    }
+
+    /// Whether the context draws to the app's own terminal through crossterm.
+    pub(crate) fn is_terminal(&self) -> bool {
+        matches!(self.terminal.backend(), TerminalBackend::Crossterm(_))
+    }
+
+    /// Restores the terminal, leaving the alternate screen if it was entered and disabling raw
+    /// mode. Output redirected by the [`StdioGuardPlugin`](crate::stdio_guard::StdioGuardPlugin)
+    /// goes to the terminal again.
     pub fn restore() -> io::Result<()> {
-        stdout()
-            .execute(LeaveAlternateScreen)?
-            .execute(cursor::Show)?;
+        stdio_guard::redirect::suspend();
+        if IN_ALTERNATE_SCREEN.swap(false, Ordering::SeqCst) {
+            output().execute(LeaveAlternateScreen)?;
+        }
+        output().execute(cursor::Show)?;
         disable_raw_mode()?;
         Ok(())
     }
+
+    /// Draws a single frame to the terminal.
+    ///
+    /// This is the same as [`ratatui::Terminal::draw`], but also keeps a copy of the rendered
+    /// buffer so that it can be used when the terminal is restored. If a [`ColorLevel`] is set,
+    /// the colors are converted to that level before the frame is written. The frame is written
+    /// as one synchronized update if the [`SynchronizedOutput`] resource allows it.
+    pub fn draw<F>(&mut self, render_callback: F) -> io::Result<CompletedFrame<'_>>
+    where
+        F: FnOnce(&mut Frame),
+    {
+        let color_level = self.color_level;
+        let synchronized = self.synchronized_output && self.is_terminal();
+        if synchronized {
+            output().queue(BeginSynchronizedUpdate)?;
+        }
+        // Only writing the frame is timed, as a frame that is slow to render says nothing about
+        // the terminal.
+        let mut rendered = None;
+        let frame = self.terminal.draw(|frame| {
+            render_callback(frame);
+            if let Some(color_level) = color_level {
+                color_level.convert_buffer(frame.buffer_mut());
+            }
+            rendered = Some(Instant::now());
+        })?;
+        if synchronized {
+            output().execute(EndSynchronizedUpdate)?;
+        }
+        let finished = Instant::now();
+        let written_in = finished.duration_since(rendered.unwrap_or(finished));
+        self.last_draw = Some((finished, written_in));
+        self.last_frame.clone_from(frame.buffer);
+        Ok(frame)
+    }
+
+    /// Prints text to the scrollback above an inline viewport, leaving the viewport below it.
+    ///
+    /// The last frame is drawn again afterwards, so the viewport is not left blank until the next
+    /// draw. The alternate screen has no scrollback to print to, so this does nothing
+    /// with a fullscreen viewport.
+    pub fn print_above<'a>(&mut self, text: impl Into<Text<'a>>) -> io::Result<()> {
+        if self.viewport == TerminalViewport::Fullscreen {
+            return Ok(());
+        }
+        let text = text.into();
+        let height = u16::try_from(text.height()).unwrap_or(u16::MAX);
+        self.terminal
+            .insert_before(height, |buffer| text.render(buffer.area, buffer))?;
+        let last_frame = self.last_frame.clone();
+        self.terminal.draw(|frame| {
+            if frame.area() == last_frame.area {
+                *frame.buffer_mut() = last_frame;
+            }
+        })?;
+        Ok(())
+    }
+
+    /// When the last [`RatatuiContext::draw`] finished, and how long writing the frame to the
+    /// terminal took, not counting rendering the widgets.
+    pub fn last_draw(&self) -> Option<(Instant, Duration)> {
+        self.last_draw
+    }
+
+    /// The last buffer drawn with [`RatatuiContext::draw`].
+    pub fn last_frame(&self) -> &Buffer {
+        &self.last_frame
+    }
+
+    /// Returns the last frame drawn with [`RatatuiContext::draw`] as plain text.
+    ///
+    /// This is handy for including what was on screen in logs and error reports. Use
+    /// [`buffer::to_ansi_string`] with [`RatatuiContext::last_frame`] to keep the styles.
+    pub fn render_to_string(&self) -> String {
+        buffer::to_plain_string(&self.last_frame)
+    }
+
+    /// Returns the last frame drawn with [`RatatuiContext::draw`] as plain text, one line per row.
+    ///
+    /// See [`buffer::to_plain_lines`] for how the text is extracted.
+    pub fn screen_lines(&self) -> Vec<String> {
+        buffer::to_plain_lines(&self.last_frame)
+    }
+
+    /// The cursor position in the shell at the time the terminal was initialized, if the terminal
+    /// reported it.
+    ///
+    /// When the terminal is set up by the [`TerminalPlugin`] on unix, the position is read by the
+    /// startup [handshake](crate::handshake), so it is `None` until the [`TerminalReady`] event
+    /// is sent.
+    ///
+    /// [`TerminalReady`]: crate::handshake::TerminalReady
+    pub fn start_position(&self) -> Option<Position> {
+        self.start_position
+    }
+
+    pub(crate) fn set_start_position(&mut self, position: Position) {
+        self.start_position = Some(position);
+    }
+
+    /// Sets what is left on the screen when the terminal is restored.
+    pub fn set_restore_policy(&mut self, restore_policy: RestorePolicy) {
+        self.restore_policy = restore_policy;
+    }
+
+    /// Runs a command with the terminal restored to its normal state, waiting for it to exit.
+    ///
+    /// The alternate screen is left and raw mode is disabled while the command runs, so that it can
+    /// use the terminal as if it were started from the shell. Afterwards the terminal is set up
+    /// again and cleared so that the next frame is drawn in full.
+    ///
+    /// This does not touch the mouse capture or the keyboard enhancement flags. Use
+    /// [`run_external`] to suspend those too.
+    pub fn run_external(&mut self, command: &mut Command) -> io::Result<ExitStatus> {
+        self.release(|| command.status())?
+    }
+
+    /// Restores the terminal to its normal state while `f` runs, and sets it up again afterwards
+    /// as [`RatatuiContext::run_external`] does.
+    fn release<T>(&mut self, f: impl FnOnce() -> T) -> io::Result<T> {
+        self.suspend()?;
+        let result = f();
+        self.resume()?;
+        Ok(result)
+    }
+
+    /// Leaves the alternate screen and disables raw mode until [`RatatuiContext::resume`].
+    fn suspend(&mut self) -> io::Result<()> {
+        #[cfg(feature = "termwiz")]
+        if let TerminalBackend::Termwiz(backend) = self.terminal.backend_mut() {
+            return backend.suspend();
+        }
+        if !self.is_terminal() {
+            return Ok(());
+        }
+        RatatuiContext::restore()
+    }
+
+    /// Sets the terminal up again after [`RatatuiContext::suspend`], and clears it so that the next
+    /// frame is drawn in full.
+    fn resume(&mut self) -> io::Result<()> {
+        #[cfg(feature = "termwiz")]
+        if let TerminalBackend::Termwiz(backend) = self.terminal.backend_mut() {
+            backend.resume()?;
+            self.terminal.autoresize()?;
+            return self.terminal.clear();
+        }
+        if !self.is_terminal() {
+            return Ok(());
+        }
+        if self.viewport == TerminalViewport::Fullscreen {
+            enter_alternate_screen()?;
+        }
+        enable_raw_mode()?;
+        // The terminal may have been resized while it was released.
+        set_frame_size(crossterm::terminal::size()?.into());
+        self.terminal.autoresize()?;
+        self.terminal.clear()
+    }
+
+    /// Restores the terminal and returns the shell prompt to where the app started, printing the
+    /// last frame first if the [`RestorePolicy`] asks for it.
+    fn restore_shell(&self) -> io::Result<()> {
+        if !self.is_terminal() {
+            return Ok(());
+        }
+        if let TerminalViewport::Inline(_) = self.viewport {
+            return self.restore_inline();
+        }
+        RatatuiContext::restore()?;
+        let mut stdout = BufWriter::new(output());
+        if let Some(position) = self.start_position {
+            stdout.queue(cursor::MoveTo(position.x, position.y))?;
+        }
+        if self.restore_policy == RestorePolicy::KeepLastFrame {
+            buffer::write_ansi(&mut stdout, &self.last_frame)?;
+        }
+        stdout.flush()
+    }
+
+    /// Disables raw mode and leaves the cursor below an inline viewport, clearing it first unless
+    /// the [`RestorePolicy`] keeps the last frame.
+    fn restore_inline(&self) -> io::Result<()> {
+        let area = self.last_frame.area;
+        let mut stdout = BufWriter::new(output());
+        match self.restore_policy {
+            RestorePolicy::Restore => {
+                stdout
+                    .queue(cursor::MoveTo(0, area.top()))?
+                    .queue(Clear(crossterm::terminal::ClearType::FromCursorDown))?;
+            }
+            RestorePolicy::KeepLastFrame => {
+                stdout.queue(cursor::MoveTo(0, area.bottom().saturating_sub(1)))?;
+                stdout.write_all(b"\r\n")?;
+            }
+        }
+        stdout.queue(cursor::Show)?.flush()?;
+        disable_raw_mode()
+    }
 }
 
 /// Restores the terminal when the app is dropped.
@@ -92,8 +1092,639 @@ impl RatatuiContext {
 /// Any errors that occur when restoring the terminal are logged and ignored.
 impl Drop for RatatuiContext {
     fn drop(&mut self) {
-        if let Err(err) = RatatuiContext::restore() {
+        if let Err(err) = self.restore_shell() {
             eprintln!("Failed to restore terminal: {}", err);
         }
     }
 }
+
+/// Whether the alternate screen is entered. Leaving it when it was not entered restores a cursor
+/// position that was never saved, which would move the cursor of an inline viewport.
+static IN_ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+
+fn enter_alternate_screen() -> io::Result<()> {
+    output().execute(EnterAlternateScreen)?;
+    IN_ALTERNATE_SCREEN.store(true, Ordering::SeqCst);
+    stdio_guard::redirect::resume();
+    Ok(())
+}
+
+pub(crate) fn in_alternate_screen() -> bool {
+    IN_ALTERNATE_SCREEN.load(Ordering::SeqCst)
+}
+
+/// Writes to the terminal.
+///
+/// This is the stream chosen by the [`OutputTarget`], stdout by default. If the
+/// [`StdioGuardPlugin`](crate::stdio_guard::StdioGuardPlugin) has redirected that stream, it is
+/// the terminal that the stream was before. Write escape sequences through this rather than
+/// [`stdout`] so that they reach the terminal either way.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TerminalOutput;
+
+/// Returns a writer to the terminal. See [`TerminalOutput`].
+pub fn output() -> TerminalOutput {
+    TerminalOutput
+}
+
+impl TerminalOutput {
+    fn with_writer<T>(self, write: impl FnOnce(&mut dyn Write) -> io::Result<T>) -> io::Result<T> {
+        let stream = match output_target() {
+            OutputTarget::Stdout => CapturedStream::Stdout,
+            OutputTarget::Stderr => CapturedStream::Stderr,
+            OutputTarget::Tty => {
+                let tty = TTY.get().ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::NotConnected, "The terminal is not open.")
+                })?;
+                return write(&mut &*tty);
+            }
+        };
+        match (stdio_guard::redirect::terminal(stream), stream) {
+            (Some(mut terminal), _) => write(&mut *terminal),
+            (None, CapturedStream::Stdout) => write(&mut stdout()),
+            (None, CapturedStream::Stderr) => write(&mut stderr()),
+        }
+    }
+}
+
+impl Write for TerminalOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(pending) = transition().as_mut() {
+            pending.extend_from_slice(buf);
+            return Ok(buf.len());
+        }
+        self.with_writer(|writer| writer.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if transition().is_some() {
+            return Ok(());
+        }
+        self.with_writer(|writer| writer.flush())
+    }
+}
+
+/// Groups changes to the terminal's state into a single write.
+///
+/// While a transition is open, everything written to the [`TerminalOutput`], from any thread, is
+/// held back, and flushing does nothing. Committing or dropping the transition writes it all at
+/// once, so that a slow terminal never shows half of the change, such as the normal screen between
+/// entering the alternate screen and clearing it, or the mouse still captured after the app has
+/// left the alternate screen. Changes that are not written, such as raw mode, take effect straight
+/// away.
+///
+/// The terminal is set up in one transition, and restored in another when the app exits, is
+/// suspended or runs an external command. Opening a transition while another one is open does
+/// nothing, so the outermost one writes everything.
+///
+/// ```rust,no_run
+/// use bevy_ratatui::terminal::{output, TerminalTransition};
+/// use crossterm::{
+///     cursor::Hide,
+///     event::EnableMouseCapture,
+///     ExecutableCommand,
+/// };
+///
+/// let transition = TerminalTransition::begin();
+/// output().execute(Hide)?;
+/// output().execute(EnableMouseCapture)?;
+/// transition.commit()?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[must_use = "the output is held back until the transition is committed or dropped"]
+#[derive(Debug)]
+pub struct TerminalTransition {
+    outermost: bool,
+}
+
+/// Whether a [`TerminalTransition`] is open.
+pub(crate) fn in_transition() -> bool {
+    transition().is_some()
+}
+
+/// The output held back by the open [`TerminalTransition`], if there is one.
+static TRANSITION: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+fn transition() -> MutexGuard<'static, Option<Vec<u8>>> {
+    // The buffer is always left in a consistent state, so a panic while it was locked does not
+    // matter.
+    TRANSITION.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+impl TerminalTransition {
+    /// Starts holding back the output.
+    pub fn begin() -> Self {
+        let mut pending = transition();
+        let outermost = pending.is_none();
+        if outermost {
+            *pending = Some(Vec::new());
+        }
+        Self { outermost }
+    }
+
+    /// Writes the output that was held back, returning any error rather than ignoring it.
+    pub fn commit(mut self) -> io::Result<()> {
+        self.finish()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        if !std::mem::take(&mut self.outermost) {
+            return Ok(());
+        }
+        let pending = transition().take().unwrap_or_default();
+        write_pending(&pending)
+    }
+}
+
+impl Drop for TerminalTransition {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+/// Writes the output held back by the open [`TerminalTransition`] so far, leaving it open. This
+/// must be done before asking the terminal something, as the question is not written through the
+/// [`TerminalOutput`] and must not overtake what was written before it.
+pub(crate) fn flush_transition() -> io::Result<()> {
+    let pending = transition().as_mut().map(std::mem::take);
+    write_pending(&pending.unwrap_or_default())
+}
+
+/// Writes the output held back by the open [`TerminalTransition`] and stops holding it back, e.g.
+/// so that the terminal can be restored before a panic is reported.
+pub(crate) fn end_transition() -> io::Result<()> {
+    let pending = transition().take();
+    write_pending(&pending.unwrap_or_default())
+}
+
+/// Writes past any open [`TerminalTransition`].
+fn write_pending(pending: &[u8]) -> io::Result<()> {
+    if pending.is_empty() {
+        return Ok(());
+    }
+    output().with_writer(|writer| {
+        writer.write_all(pending)?;
+        writer.flush()
+    })
+}
+
+/// The [`OutputTarget`] that [`TerminalOutput`] writes to, as its index.
+static OUTPUT_TARGET: AtomicU8 = AtomicU8::new(0);
+
+/// The controlling terminal, once opened for [`OutputTarget::Tty`].
+static TTY: OnceLock<File> = OnceLock::new();
+
+const TARGETS: [OutputTarget; 3] = [
+    OutputTarget::Stdout,
+    OutputTarget::Stderr,
+    OutputTarget::Tty,
+];
+
+/// The stream that [`TerminalOutput`] writes to.
+pub(crate) fn output_target() -> OutputTarget {
+    TARGETS[usize::from(OUTPUT_TARGET.load(Ordering::SeqCst))]
+}
+
+fn set_output_target(target: OutputTarget) -> io::Result<()> {
+    if target == OutputTarget::Tty && TTY.get().is_none() {
+        let path = if cfg!(windows) { "CONOUT$" } else { "/dev/tty" };
+        // Another thread opening it at the same time is harmless, as only one is kept.
+        let _ = TTY.set(File::options().write(true).open(path)?);
+    }
+    let index = TARGETS.iter().position(|&t| t == target).unwrap_or(0);
+    OUTPUT_TARGET.store(index as u8, Ordering::SeqCst);
+    Ok(())
+}
+
+/// The size of the terminal that draws use, as `width << 16 | height`, or zero if unknown.
+///
+/// Asking the terminal for its size on every draw would let draws in the same frame disagree with
+/// each other and with the last [`ResizeEvent`] while the window is being resized.
+static FRAME_SIZE: AtomicU32 = AtomicU32::new(0);
+
+fn set_frame_size(size: Size) {
+    let packed = (u32::from(size.width) << 16) | u32::from(size.height);
+    FRAME_SIZE.store(packed, Ordering::SeqCst);
+}
+
+fn frame_size() -> Option<Size> {
+    let packed = FRAME_SIZE.load(Ordering::SeqCst);
+    (packed != 0).then(|| Size::new((packed >> 16) as u16, packed as u16))
+}
+
+/// Where the cursor was last put, as `visible << 32 | x << 16 | y`.
+///
+/// Ratatui moves the cursor to where the frame asked for it, or hides it, at the end of each draw,
+/// but it does not keep track of that itself, and asking the terminal means waiting for an answer.
+static CURSOR: AtomicU64 = AtomicU64::new(0);
+
+const CURSOR_VISIBLE: u64 = 1 << 32;
+
+fn cursor() -> Option<Position> {
+    let packed = CURSOR.load(Ordering::SeqCst);
+    (packed & CURSOR_VISIBLE != 0).then(|| Position::new((packed >> 16) as u16, packed as u16))
+}
+
+fn set_cursor(position: Position) {
+    let packed = (u64::from(position.x) << 16) | u64::from(position.y);
+    // The update never fails as the closure always returns a value.
+    let _ = CURSOR.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |old| {
+        Some(old & CURSOR_VISIBLE | packed)
+    });
+}
+
+/// How long each flush to the terminal blocks for afterwards, in nanoseconds. See
+/// [`SimulatedLatency`](crate::latency::SimulatedLatency).
+static OUTPUT_DELAY: AtomicU64 = AtomicU64::new(0);
+
+pub(crate) fn set_output_delay(delay: Duration) {
+    let nanos = u64::try_from(delay.as_nanos()).unwrap_or(u64::MAX);
+    OUTPUT_DELAY.store(nanos, Ordering::SeqCst);
+}
+
+/// The backend of a [`RatatuiContext`]: the terminal, a [`TestBackend`] when headless, or
+/// another writer.
+#[derive(Debug)]
+pub enum TerminalBackend {
+    Crossterm(CrosstermBackend<BufWriter<TerminalOutput>>),
+    Test(TestBackend),
+    Writer(WriterBackend),
+    /// The terminal, through termwiz rather than crossterm.
+    #[cfg(feature = "termwiz")]
+    Termwiz(TermwizTerminalBackend),
+}
+
+/// A backend that writes escape sequences to any writer, for a terminal of a known size.
+///
+/// See [`RatatuiContext::with_writer`].
+pub struct WriterBackend {
+    backend: CrosstermBackend<BufWriter<Box<dyn Write + Send + Sync>>>,
+    size: Size,
+}
+
+impl std::fmt::Debug for WriterBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WriterBackend")
+            .field("size", &self.size)
+            .finish_non_exhaustive()
+    }
+}
+
+macro_rules! delegate {
+    ($self:ident, $backend:ident => $call:expr) => {
+        match $self {
+            TerminalBackend::Crossterm($backend) => $call,
+            TerminalBackend::Test($backend) => $call,
+            TerminalBackend::Writer(WriterBackend {
+                backend: $backend, ..
+            }) => $call,
+            #[cfg(feature = "termwiz")]
+            TerminalBackend::Termwiz(TermwizTerminalBackend { backend: $backend }) => {
+                let $backend = &mut **$backend;
+                $call
+            }
+        }
+    };
+}
+
+impl Backend for TerminalBackend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        delegate!(self, backend => backend.draw(content))
+    }
+
+    fn append_lines(&mut self, n: u16) -> io::Result<()> {
+        delegate!(self, backend => backend.append_lines(n))
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        if !matches!(self, TerminalBackend::Writer(_)) {
+            CURSOR.fetch_and(!CURSOR_VISIBLE, Ordering::SeqCst);
+        }
+        delegate!(self, backend => backend.hide_cursor())
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        if !matches!(self, TerminalBackend::Writer(_)) {
+            CURSOR.fetch_or(CURSOR_VISIBLE, Ordering::SeqCst);
+        }
+        delegate!(self, backend => backend.show_cursor())
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        match self {
+            // Asking would read the answer from the app's own terminal.
+            TerminalBackend::Writer(_) => Ok(Position::ORIGIN),
+            _ => delegate!(self, backend => backend.get_cursor_position()),
+        }
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        let position = position.into();
+        if !matches!(self, TerminalBackend::Writer(_)) {
+            set_cursor(position);
+        }
+        delegate!(self, backend => backend.set_cursor_position(position))
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        delegate!(self, backend => backend.clear())
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        delegate!(self, backend => backend.clear_region(clear_type))
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        match self {
+            TerminalBackend::Crossterm(backend) => frame_size().map_or_else(|| backend.size(), Ok),
+            TerminalBackend::Test(backend) => backend.size(),
+            TerminalBackend::Writer(backend) => Ok(backend.size),
+            #[cfg(feature = "termwiz")]
+            TerminalBackend::Termwiz(backend) => backend.backend.size(),
+        }
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        match self {
+            TerminalBackend::Writer(backend) => Ok(WindowSize {
+                columns_rows: backend.size,
+                pixels: Size::default(),
+            }),
+            _ => delegate!(self, backend => backend.window_size()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        delegate!(self, backend => Backend::flush(backend))?;
+        let output_delay = OUTPUT_DELAY.load(Ordering::SeqCst);
+        if output_delay != 0 && matches!(self, TerminalBackend::Crossterm(_)) {
+            thread::sleep(Duration::from_nanos(output_delay));
+        }
+        Ok(())
+    }
+}
+
+/// Runs a command with the terminal handed over to it, waiting for it to exit.
+///
+/// In addition to what [`RatatuiContext::run_external`] does, this disables the mouse capture and
+/// bracketed paste, pops the [`KeyboardEnhancementStack`] and pauses the [`InputThread`] while the
+/// command runs, and enables them again afterwards, requesting a [redraw](RedrawRequested).
+/// Without a [`RatatuiContext`] the command is simply run.
+pub fn run_external(world: &mut World, command: &mut Command) -> io::Result<ExitStatus> {
+    release_terminal(world, || command.status())?
+}
+
+/// Hands the terminal back to the shell while `f` runs, as [`run_external`] does, and sets it up
+/// again afterwards.
+///
+/// This is useful for anything that needs the terminal in its normal state other than running a
+/// command, e.g. reading a password from the shell.
+pub fn release_terminal<T>(world: &mut World, f: impl FnOnce() -> T) -> io::Result<T> {
+    let guard = TerminalSuspendGuard::new(world)?;
+    let result = f();
+    guard.resume()?;
+    Ok(result)
+}
+
+/// Keeps the terminal handed back to the shell until it is dropped or
+/// [resumed](TerminalSuspendGuard::resume).
+///
+/// Creating the guard disables the mouse capture and bracketed paste, pops the
+/// [`KeyboardEnhancementStack`], pauses the [`InputThread`], leaves the alternate screen and
+/// disables raw mode. Resuming undoes all of that, clears the terminal and requests a
+/// [redraw](RedrawRequested), so that the next frame is drawn in full. Dropping the guard resumes
+/// too, logging any error.
+///
+/// Each step is taken even if an earlier one fails, and the first error is returned, so that the
+/// terminal is never left with only some of its features on. If suspending fails, everything is
+/// turned back on before the error is returned.
+///
+/// ```rust,no_run
+/// use std::process::Command;
+///
+/// use bevy::prelude::*;
+/// use bevy_ratatui::terminal::TerminalSuspendGuard;
+///
+/// fn show_log(world: &mut World) -> std::io::Result<()> {
+///     let guard = TerminalSuspendGuard::new(world)?;
+///     Command::new("less").arg("app.log").status()?;
+///     guard.resume()
+/// }
+/// ```
+pub struct TerminalSuspendGuard<'w> {
+    world: &'w mut World,
+    mouse_capture: bool,
+    bracketed_paste: bool,
+    suspended: bool,
+}
+
+impl<'w> TerminalSuspendGuard<'w> {
+    /// Hands the terminal back to the shell. Without a [`RatatuiContext`] only the terminal
+    /// features that are enabled are turned off.
+    pub fn new(world: &'w mut World) -> io::Result<Self> {
+        let transition = TerminalTransition::begin();
+        let mouse_capture = world.contains_resource::<MouseCaptureEnabled>();
+        let bracketed_paste = world.contains_resource::<BracketedPasteEnabled>();
+        let guard = Self {
+            world,
+            mouse_capture,
+            bracketed_paste,
+            suspended: true,
+        };
+        // Every step is taken even if one fails. Returning the error drops the guard, which turns
+        // all the features back on.
+        let mut result = Ok(());
+        if mouse_capture {
+            keep_first_error(&mut result, output().execute(DisableMouseCapture).map(drop));
+        }
+        if bracketed_paste {
+            keep_first_error(
+                &mut result,
+                output().execute(DisableBracketedPaste).map(drop),
+            );
+        }
+        if let Some(mut stack) = guard.world.get_resource_mut::<KeyboardEnhancementStack>() {
+            keep_first_error(&mut result, stack.suspend());
+        }
+        if let Some(input_thread) = guard.world.get_resource::<InputThread>() {
+            input_thread.pause();
+        }
+        if let Some(mut context) = guard.world.get_resource_mut::<RatatuiContext>() {
+            keep_first_error(&mut result, context.suspend());
+        }
+        keep_first_error(&mut result, transition.commit());
+        result.map(|()| guard)
+    }
+
+    /// Sets the terminal up again, returning any error rather than logging it.
+    pub fn resume(mut self) -> io::Result<()> {
+        self.resume_terminal()
+    }
+
+    /// Takes every step of setting the terminal up again, even if one fails, and returns the first
+    /// error.
+    fn resume_terminal(&mut self) -> io::Result<()> {
+        if !std::mem::take(&mut self.suspended) {
+            return Ok(());
+        }
+        let world = &mut *self.world;
+        let transition = TerminalTransition::begin();
+        let mut result = Ok(());
+        if let Some(mut context) = world.get_resource_mut::<RatatuiContext>() {
+            keep_first_error(&mut result, context.resume());
+        }
+        if let Some(input_thread) = world.get_resource::<InputThread>() {
+            input_thread.resume();
+        }
+        if let Some(mut stack) = world.get_resource_mut::<KeyboardEnhancementStack>() {
+            keep_first_error(&mut result, stack.resume());
+        }
+        if self.bracketed_paste {
+            keep_first_error(
+                &mut result,
+                output().execute(EnableBracketedPaste).map(drop),
+            );
+        }
+        if self.mouse_capture {
+            keep_first_error(&mut result, output().execute(EnableMouseCapture).map(drop));
+        }
+        keep_first_error(&mut result, transition.commit());
+        if let Some(mut redraw) = world.get_resource_mut::<RedrawRequested>() {
+            redraw.request();
+        }
+        result
+    }
+}
+
+/// Stores `step` in `result` unless an earlier step has failed already.
+fn keep_first_error(result: &mut io::Result<()>, step: io::Result<()>) {
+    if result.is_ok() {
+        *result = step;
+    }
+}
+
+impl Drop for TerminalSuspendGuard<'_> {
+    fn drop(&mut self) {
+        if let Err(err) = self.resume_terminal() {
+            error!("Failed to set the terminal up again: {err}");
+        }
+    }
+}
+
+/// A command that runs an external program with [`run_external`] and sends an
+/// [`ExternalCommandFinished`] event once it exits.
+///
+/// ```rust,no_run
+/// use bevy::prelude::*;
+/// use bevy_ratatui::terminal::{ExternalCommandFinished, RunExternal};
+///
+/// fn edit_notes(mut commands: Commands) {
+///     commands.queue(RunExternal::editor("notes.md"));
+/// }
+///
+/// fn reload_notes(mut finished: EventReader<ExternalCommandFinished>) {
+///     for finished in finished.read() {
+///         if finished.result.is_ok_and(|status| status.success()) {
+///             // Read notes.md again.
+///         }
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RunExternal {
+    command: Command,
+}
+
+impl RunExternal {
+    /// Runs `command`.
+    pub fn new(command: Command) -> Self {
+        Self { command }
+    }
+
+    /// Opens `path` in the user's editor, from `$VISUAL` or `$EDITOR`, which may include
+    /// arguments, or `vi` if neither is set (`notepad` on Windows).
+    pub fn editor(path: impl AsRef<Path>) -> Self {
+        let editor = ["VISUAL", "EDITOR"]
+            .into_iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|editor| !editor.trim().is_empty());
+        let default = if cfg!(windows) { "notepad" } else { "vi" };
+        let editor = editor.as_deref().unwrap_or(default);
+        let mut words = editor.split_whitespace();
+        let mut command = Command::new(words.next().unwrap_or(default));
+        command.args(words).arg(path.as_ref());
+        Self::new(command)
+    }
+}
+
+impl bevy::ecs::world::Command for RunExternal {
+    fn apply(mut self, world: &mut World) {
+        let program = self.command.get_program().to_owned();
+        let result = run_external(world, &mut self.command);
+        if let Err(err) = &result {
+            error!("Failed to run {program:?}: {err}");
+        }
+        world.send_event(ExternalCommandFinished {
+            program,
+            result: result.map_err(|err| err.kind()),
+        });
+    }
+}
+
+/// Sent when a program run with [`RunExternal`] has exited, once the terminal has been set up
+/// again.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct ExternalCommandFinished {
+    /// The program that was run.
+    pub program: OsString,
+    /// How the program exited, or the kind of error if it could not be run or the terminal could
+    /// not be set up again. The error itself is logged.
+    pub result: Result<ExitStatus, io::ErrorKind>,
+}
+
+/// The device status report request that asks the terminal for the cursor position.
+const START_POSITION_QUERY: &[u8] = b"\x1b[6n";
+
+/// Whether the cursor position has been asked for with [`StartPositionQuery::Handshake`], and
+/// the answer not read yet.
+static START_POSITION_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the answer to a cursor position query is waiting for the handshake to read it. Only
+/// returns `true` once per query.
+pub(crate) fn take_start_position_request() -> bool {
+    START_POSITION_REQUESTED.swap(false, Ordering::SeqCst)
+}
+
+/// How [`RatatuiContext`] finds the cursor position in the shell when it enters the alternate
+/// screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StartPositionQuery {
+    /// Waits for the answer with [`query_cursor_position`].
+    Blocking,
+    /// Writes the query for the [handshake](crate::handshake) to read the answer to.
+    Handshake,
+}
+
+/// Queries the terminal for the current cursor position.
+///
+/// This sends the `CSI 6n` device status report request and parses the `CSI row ; column R` reply.
+/// The reply is consumed internally by crossterm's event reader, so it never shows up as a
+/// [`KeyEvent`](crate::event::KeyEvent). Raw mode is enabled for the duration of the query if it is
+/// not already enabled.
+///
+/// Returns an [`io::ErrorKind::Unsupported`] error straight away when stdout is not a terminal,
+/// rather than waiting for a reply that will never come, and when the [`OutputTarget`] is not
+/// stdout, as crossterm writes the request to stdout.
+pub fn query_cursor_position() -> io::Result<Position> {
+    if output_target() != OutputTarget::Stdout || !stdout().is_terminal() {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Cannot query the cursor position unless the terminal is drawn to through stdout.",
+        ));
+    }
+    flush_transition()?;
+    cursor::position().map(Position::from)
+}
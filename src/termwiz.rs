@@ -0,0 +1,287 @@
+//! Drawing and reading input through [termwiz] instead of crossterm.
+//!
+//! Termwiz reads the terminal's capabilities from terminfo and knows a few terminals that crossterm
+//! does not, which helps where crossterm's output or input falls short. [`TermwizTerminalPlugin`]
+//! sets up the [`RatatuiContext`] on ratatui's [`TermwizBackend`], and translates the input that
+//! termwiz reads into the crossterm events that the rest of the crate uses, so [`KeyEvent`]s,
+//! [`MouseEvent`]s, [`PasteEvent`]s and [`ResizeEvent`]s are sent as usual.
+//!
+//! Add it along with the [`RatatuiPlugins`](crate::RatatuiPlugins):
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{termwiz::TermwizTerminalPlugin, RatatuiPlugins};
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), TermwizTerminalPlugin))
+//!     .run();
+//! ```
+//!
+//! Termwiz enables mouse reporting and bracketed paste itself, so the crate's
+//! [`MousePlugin`](crate::mouse::MousePlugin) and [`PastePlugin`](crate::paste::PastePlugin) are
+//! not needed. The startup [handshake](crate::handshake) is skipped, which leaves the kitty
+//! keyboard protocol off, and the [`InputThread`](crate::input_thread::InputThread) is not used.
+//!
+//! [termwiz]: https://crates.io/crates/termwiz
+//! [`KeyEvent`]: crate::event::KeyEvent
+//! [`MouseEvent`]: crate::event::MouseEvent
+//! [`PasteEvent`]: crate::event::PasteEvent
+//! [`ResizeEvent`]: crate::event::ResizeEvent
+use std::{io, time::Duration};
+
+use bevy::prelude::*;
+use color_eyre::Result;
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::{
+    backend::TermwizBackend,
+    layout::Size,
+    termwiz::{
+        input::{self, InputEvent, Modifiers, MouseButtons},
+        terminal::Terminal,
+    },
+};
+
+use crate::{
+    error::exit_on_error,
+    event::InputSet,
+    terminal::{RatatuiContext, TerminalBackend},
+};
+
+/// A plugin that draws with termwiz and reads its input, in place of crossterm.
+///
+/// This inserts the [`TermwizTerminal`] resource, which makes the
+/// [`TerminalPlugin`](crate::terminal::TerminalPlugin) set up the terminal with
+/// [`RatatuiContext::termwiz`] and stops the crossterm input from being read.
+pub struct TermwizTerminalPlugin;
+
+impl Plugin for TermwizTerminalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TermwizTerminal>()
+            .init_resource::<TermwizInput>()
+            .add_systems(
+                PreUpdate,
+                read_termwiz_input.pipe(exit_on_error).in_set(InputSet::Pre),
+            );
+    }
+}
+
+/// Draws and reads input with termwiz instead of crossterm when present at startup.
+///
+/// A [`HeadlessTerminal`](crate::terminal::HeadlessTerminal) takes precedence over this.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TermwizTerminal;
+
+/// A run condition that is true when the terminal is set up with termwiz.
+pub fn is_termwiz(termwiz: Option<Res<TermwizTerminal>>) -> bool {
+    termwiz.is_some()
+}
+
+/// The events termwiz has read this frame, translated to crossterm events, which the
+/// [`crossterm_event_system`](crate::event::crossterm_event_system) sends in place of the events
+/// it would read from crossterm.
+#[derive(Resource, Debug, Default)]
+pub struct TermwizInput(pub(crate) Vec<Event>);
+
+/// The termwiz backend of a [`RatatuiContext`]. See [`RatatuiContext::termwiz`].
+pub struct TermwizTerminalBackend {
+    // Boxed, as the termwiz terminal is much larger than the other backends.
+    pub(crate) backend: Box<TermwizBackend>,
+}
+
+impl std::fmt::Debug for TermwizTerminalBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TermwizTerminalBackend")
+            .finish_non_exhaustive()
+    }
+}
+
+impl TermwizTerminalBackend {
+    /// Enters raw mode and the alternate screen.
+    pub(crate) fn new() -> io::Result<Self> {
+        let backend = TermwizBackend::new().map_err(|err| io::Error::other(err.to_string()))?;
+        Ok(Self {
+            backend: Box::new(backend),
+        })
+    }
+
+    pub(crate) fn resize(&mut self, size: Size) {
+        self.backend
+            .buffered_terminal_mut()
+            .resize(usize::from(size.width), usize::from(size.height));
+    }
+
+    /// Leaves raw mode and the alternate screen, so that another program can use the terminal.
+    pub(crate) fn suspend(&mut self) -> io::Result<()> {
+        let terminal = self.backend.buffered_terminal_mut().terminal();
+        terminal.set_cooked_mode().map_err(io::Error::other)?;
+        terminal.exit_alternate_screen().map_err(io::Error::other)
+    }
+
+    /// Enters raw mode and the alternate screen again after [`Self::suspend`], and picks up a
+    /// change of size that happened meanwhile.
+    pub(crate) fn resume(&mut self) -> io::Result<()> {
+        let buffered_terminal = self.backend.buffered_terminal_mut();
+        let terminal = buffered_terminal.terminal();
+        terminal.set_raw_mode().map_err(io::Error::other)?;
+        terminal
+            .enter_alternate_screen()
+            .map_err(io::Error::other)?;
+        buffered_terminal
+            .check_for_resize()
+            .map_err(io::Error::other)?;
+        Ok(())
+    }
+
+    fn poll_input(&mut self) -> io::Result<Option<InputEvent>> {
+        self.backend
+            .buffered_terminal_mut()
+            .terminal()
+            .poll_input(Some(Duration::ZERO))
+            .map_err(io::Error::other)
+    }
+}
+
+/// Reads the input waiting in the termwiz terminal into the [`TermwizInput`].
+fn read_termwiz_input(
+    context: Option<ResMut<RatatuiContext>>,
+    mut input: ResMut<TermwizInput>,
+    mut buttons: Local<MouseButtons>,
+) -> Result<()> {
+    let Some(mut context) = context else {
+        return Ok(());
+    };
+    let TerminalBackend::Termwiz(backend) = context.backend_mut() else {
+        return Ok(());
+    };
+    while let Some(event) = backend.poll_input()? {
+        input.0.extend(translate(event, &mut buttons));
+    }
+    Ok(())
+}
+
+/// Translates a termwiz event to a crossterm event. `buttons` are the mouse buttons held before
+/// the event, as termwiz reports which buttons are held rather than which were pressed or
+/// released.
+fn translate(event: InputEvent, buttons: &mut MouseButtons) -> Option<Event> {
+    match event {
+        InputEvent::Key(key) => {
+            let modifiers = modifiers(key.modifiers);
+            let code = match key.key {
+                input::KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+                code => key_code(code)?,
+            };
+            Some(Event::Key(KeyEvent::new(code, modifiers)))
+        }
+        InputEvent::Mouse(mouse) => {
+            let kind = mouse_kind(buttons, &mouse.mouse_buttons);
+            *buttons = mouse.mouse_buttons
+                - (MouseButtons::VERT_WHEEL
+                    | MouseButtons::HORZ_WHEEL
+                    | MouseButtons::WHEEL_POSITIVE);
+            Some(Event::Mouse(MouseEvent {
+                kind,
+                // Termwiz counts from 1, as terminals report the position.
+                column: mouse.x.saturating_sub(1),
+                row: mouse.y.saturating_sub(1),
+                modifiers: modifiers(mouse.modifiers),
+            }))
+        }
+        InputEvent::Resized { cols, rows } => Some(Event::Resize(
+            u16::try_from(cols).unwrap_or(u16::MAX),
+            u16::try_from(rows).unwrap_or(u16::MAX),
+        )),
+        InputEvent::Paste(text) => Some(Event::Paste(text)),
+        InputEvent::PixelMouse(_) | InputEvent::Wake => None,
+    }
+}
+
+fn modifiers(modifiers: Modifiers) -> KeyModifiers {
+    let mut translated = KeyModifiers::NONE;
+    translated.set(KeyModifiers::SHIFT, modifiers.contains(Modifiers::SHIFT));
+    translated.set(KeyModifiers::ALT, modifiers.contains(Modifiers::ALT));
+    translated.set(KeyModifiers::CONTROL, modifiers.contains(Modifiers::CTRL));
+    translated.set(KeyModifiers::SUPER, modifiers.contains(Modifiers::SUPER));
+    translated
+}
+
+fn key_code(code: input::KeyCode) -> Option<KeyCode> {
+    use input::KeyCode as K;
+    let code = match code {
+        K::Char(c) => KeyCode::Char(c),
+        K::Backspace => KeyCode::Backspace,
+        K::Tab => KeyCode::Tab,
+        K::Enter => KeyCode::Enter,
+        K::Escape => KeyCode::Esc,
+        K::PageUp | K::KeyPadPageUp => KeyCode::PageUp,
+        K::PageDown | K::KeyPadPageDown => KeyCode::PageDown,
+        K::End | K::KeyPadEnd => KeyCode::End,
+        K::Home | K::KeyPadHome => KeyCode::Home,
+        K::LeftArrow | K::ApplicationLeftArrow => KeyCode::Left,
+        K::RightArrow | K::ApplicationRightArrow => KeyCode::Right,
+        K::UpArrow | K::ApplicationUpArrow => KeyCode::Up,
+        K::DownArrow | K::ApplicationDownArrow => KeyCode::Down,
+        K::Insert => KeyCode::Insert,
+        K::Delete => KeyCode::Delete,
+        K::Function(n) => KeyCode::F(n),
+        K::Menu => KeyCode::Menu,
+        K::Pause => KeyCode::Pause,
+        K::CapsLock => KeyCode::CapsLock,
+        K::ScrollLock => KeyCode::ScrollLock,
+        K::NumLock => KeyCode::NumLock,
+        K::PrintScreen => KeyCode::PrintScreen,
+        K::KeyPadBegin => KeyCode::KeypadBegin,
+        K::Numpad0 => KeyCode::Char('0'),
+        K::Numpad1 => KeyCode::Char('1'),
+        K::Numpad2 => KeyCode::Char('2'),
+        K::Numpad3 => KeyCode::Char('3'),
+        K::Numpad4 => KeyCode::Char('4'),
+        K::Numpad5 => KeyCode::Char('5'),
+        K::Numpad6 => KeyCode::Char('6'),
+        K::Numpad7 => KeyCode::Char('7'),
+        K::Numpad8 => KeyCode::Char('8'),
+        K::Numpad9 => KeyCode::Char('9'),
+        K::Multiply => KeyCode::Char('*'),
+        K::Add => KeyCode::Char('+'),
+        K::Subtract => KeyCode::Char('-'),
+        K::Decimal => KeyCode::Char('.'),
+        K::Divide => KeyCode::Char('/'),
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// What happened between the mouse buttons that were held and the ones that are held now.
+fn mouse_kind(held: &MouseButtons, now: &MouseButtons) -> MouseEventKind {
+    let positive = now.contains(MouseButtons::WHEEL_POSITIVE);
+    if now.contains(MouseButtons::VERT_WHEEL) {
+        return if positive {
+            MouseEventKind::ScrollUp
+        } else {
+            MouseEventKind::ScrollDown
+        };
+    }
+    if now.contains(MouseButtons::HORZ_WHEEL) {
+        return if positive {
+            MouseEventKind::ScrollLeft
+        } else {
+            MouseEventKind::ScrollRight
+        };
+    }
+    let buttons = [
+        (MouseButtons::LEFT, MouseButton::Left),
+        (MouseButtons::RIGHT, MouseButton::Right),
+        (MouseButtons::MIDDLE, MouseButton::Middle),
+    ];
+    let mut dragged = None;
+    for (flag, button) in buttons {
+        match (held.contains(flag.clone()), now.contains(flag)) {
+            (false, true) => return MouseEventKind::Down(button),
+            (true, false) => return MouseEventKind::Up(button),
+            (true, true) => dragged = dragged.or(Some(button)),
+            (false, false) => {}
+        }
+    }
+    dragged.map_or(MouseEventKind::Moved, MouseEventKind::Drag)
+}
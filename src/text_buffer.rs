@@ -0,0 +1,171 @@
+//! Large editable text.
+//!
+//! [`TextBuffer`] stores text in a [rope](ropey::Rope), so that edits and looking up lines stay
+//! fast for editor-sized content, and only the lines in view are copied out for rendering. It can be
+//! used as a [`Resource`] or a [`Component`].
+//!
+//! ```rust
+//! use bevy_ratatui::text_buffer::TextBuffer;
+//!
+//! let mut text = TextBuffer::from("hello\nworld\n");
+//! text.insert(text.char_index(1, 0), "big ");
+//! assert_eq!(text.line(1), "big world");
+//! assert_eq!(text.viewport(1, 5).collect::<Vec<_>>(), ["big world", ""]);
+//! ```
+use std::ops::Range;
+
+use bevy::prelude::*;
+use ratatui::{buffer::Buffer, layout::Rect, text::Line, widgets::Widget};
+use ropey::Rope;
+
+/// Text stored in a rope, indexed by line.
+///
+/// Positions are character indices, or a line index and a column counted in characters. Every edit
+/// increments the [`TextBuffer::revision`], which can be used to skip work when nothing changed.
+#[derive(Resource, Component, Debug, Clone, Default)]
+pub struct TextBuffer {
+    rope: Rope,
+    revision: u64,
+}
+
+impl From<&str> for TextBuffer {
+    fn from(text: &str) -> Self {
+        Self {
+            rope: Rope::from_str(text),
+            revision: 0,
+        }
+    }
+}
+
+impl From<String> for TextBuffer {
+    fn from(text: String) -> Self {
+        Self::from(text.as_str())
+    }
+}
+
+impl TextBuffer {
+    /// Creates an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The underlying rope, for operations that are not wrapped here.
+    pub fn rope(&self) -> &Rope {
+        &self.rope
+    }
+
+    /// The number of edits made to the buffer.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// The number of characters in the buffer.
+    pub fn len_chars(&self) -> usize {
+        self.rope.len_chars()
+    }
+
+    /// The number of lines in the buffer. Text ending in a newline has an empty last line.
+    pub fn len_lines(&self) -> usize {
+        self.rope.len_lines()
+    }
+
+    /// Returns true if the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.rope.len_chars() == 0
+    }
+
+    /// The line at `index`, without its line ending, or an empty string if it is past the end.
+    pub fn line(&self, index: usize) -> String {
+        let Some(line) = self.rope.get_line(index) else {
+            return String::new();
+        };
+        let mut line = line.to_string();
+        let len = line.trim_end_matches(['\n', '\r']).len();
+        line.truncate(len);
+        line
+    }
+
+    /// The lines from `first` onwards, at most `height` of them.
+    ///
+    /// Only these lines are copied out of the rope, so this is cheap however large the buffer is.
+    pub fn viewport(&self, first: usize, height: usize) -> impl Iterator<Item = String> + '_ {
+        (first..self.len_lines().min(first.saturating_add(height))).map(|index| self.line(index))
+    }
+
+    /// The character index of a line and column, clamped to the end of the line and buffer.
+    pub fn char_index(&self, line: usize, column: usize) -> usize {
+        if line >= self.len_lines() {
+            return self.len_chars();
+        }
+        let start = self.rope.line_to_char(line);
+        let len = self.line(line).chars().count();
+        start + column.min(len)
+    }
+
+    /// The line and column of a character index.
+    pub fn position(&self, char_index: usize) -> (usize, usize) {
+        let char_index = char_index.min(self.len_chars());
+        let line = self.rope.char_to_line(char_index);
+        (line, char_index - self.rope.line_to_char(line))
+    }
+
+    /// Inserts text at a character index.
+    pub fn insert(&mut self, char_index: usize, text: &str) {
+        self.rope.insert(char_index.min(self.len_chars()), text);
+        self.revision += 1;
+    }
+
+    /// Removes a range of characters.
+    pub fn remove(&mut self, range: Range<usize>) {
+        let len = self.len_chars();
+        self.rope.remove(range.start.min(len)..range.end.min(len));
+        self.revision += 1;
+    }
+
+    /// Replaces a range of characters with text.
+    pub fn replace(&mut self, range: Range<usize>, text: &str) {
+        let start = range.start.min(self.len_chars());
+        self.remove(range);
+        self.insert(start, text);
+    }
+
+    /// Replaces all the text.
+    pub fn set_text(&mut self, text: &str) {
+        self.rope = Rope::from_str(text);
+        self.revision += 1;
+    }
+}
+
+/// Renders the visible part of a [`TextBuffer`], starting at a line and column.
+///
+/// Only the lines that fit in the area are read from the buffer.
+pub struct TextBufferView<'a> {
+    buffer: &'a TextBuffer,
+    scroll: (usize, usize),
+}
+
+impl<'a> TextBufferView<'a> {
+    pub fn new(buffer: &'a TextBuffer) -> Self {
+        Self {
+            buffer,
+            scroll: (0, 0),
+        }
+    }
+
+    /// Sets the first line and column shown.
+    pub fn scroll(mut self, line: usize, column: usize) -> Self {
+        self.scroll = (line, column);
+        self
+    }
+}
+
+impl Widget for TextBufferView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (first, column) = self.scroll;
+        let lines = self.buffer.viewport(first, area.height as usize);
+        for (y, line) in (area.top()..area.bottom()).zip(lines) {
+            let visible: String = line.chars().skip(column).collect();
+            Line::from(visible).render(Rect::new(area.x, y, area.width, 1), buf);
+        }
+    }
+}
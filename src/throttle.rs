@@ -0,0 +1,118 @@
+//! Drawing less often when the terminal can't keep up.
+//!
+//! Writing a frame to a slow remote terminal blocks until the connection has taken it. If every
+//! frame draws, the whole app slows down to the speed of the terminal. [`DrawThrottlePlugin`]
+//! watches how long each [`RatatuiContext::draw`] takes to write its frame, and when writes start
+//! to block it spaces draws out, so that the app keeps updating at full rate while the screen is
+//! refreshed less often. Once writes are fast again, the full draw rate returns.
+//!
+//! The plugin is part of [`RatatuiPlugins`](crate::RatatuiPlugins), and the
+//! [`RootWidget`](crate::widget::RootWidget) follows it. Add the [`draw_due`] run condition to
+//! your own draw systems:
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{terminal::RatatuiContext, throttle::draw_due, RatatuiPlugins};
+//!
+//! App::new()
+//!     .add_plugins(RatatuiPlugins::default())
+//!     .add_systems(Update, draw.run_if(draw_due));
+//!
+//! fn draw(mut context: ResMut<RatatuiContext>) {
+//!     let _ = context.draw(|frame| frame.render_widget("hello", frame.area()));
+//! }
+//! ```
+use std::time::{Duration, Instant};
+
+use bevy::{ecs::event::EventUpdates, prelude::*};
+
+use crate::{
+    redraw::RedrawRequested,
+    terminal::{sync_color_level, RatatuiContext},
+};
+
+/// A plugin that keeps the [`DrawThrottle`] up to date.
+pub struct DrawThrottlePlugin;
+
+impl Plugin for DrawThrottlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DrawThrottle>().add_systems(
+            First,
+            update_draw_throttle
+                .after(EventUpdates)
+                .after(sync_color_level),
+        );
+    }
+}
+
+/// How often to draw, based on how long the last draw took to write.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrawThrottle {
+    /// Draws that take longer than this are taken as a sign that the terminal is not keeping up.
+    /// Defaults to 16 milliseconds.
+    pub slow_draw: Duration,
+    /// The longest time between draws while throttled. Defaults to one second.
+    pub max_interval: Duration,
+    interval: Duration,
+    last_draw: Option<Instant>,
+}
+
+impl Default for DrawThrottle {
+    fn default() -> Self {
+        Self {
+            slow_draw: Duration::from_millis(16),
+            max_interval: Duration::from_secs(1),
+            interval: Duration::ZERO,
+            last_draw: None,
+        }
+    }
+}
+
+impl DrawThrottle {
+    /// The time to wait after a draw before the next one, or zero when not throttled.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Whether draws are being spaced out.
+    pub fn is_throttled(&self) -> bool {
+        !self.interval.is_zero()
+    }
+
+    /// Whether enough time has passed since the last draw to draw again.
+    pub fn is_due(&self) -> bool {
+        self.last_draw
+            .is_none_or(|last_draw| last_draw.elapsed() >= self.interval)
+    }
+
+    /// Adjusts the interval after a draw that finished at `finished` and took `took`.
+    ///
+    /// The interval doubles the time of each slow draw, and halves after each fast one.
+    pub fn record_draw(&mut self, finished: Instant, took: Duration) {
+        self.last_draw = Some(finished);
+        self.interval = if took > self.slow_draw {
+            (took * 2).max(self.interval).min(self.max_interval)
+        } else if self.interval > Duration::from_millis(1) {
+            self.interval / 2
+        } else {
+            Duration::ZERO
+        };
+    }
+}
+
+/// A run condition that is true when it is time to draw, which is always unless the terminal is
+/// slow to take frames, or the [`RedrawOnDemandPlugin`](crate::redraw::RedrawOnDemandPlugin) is
+/// waiting for a [`RedrawRequested`].
+pub fn draw_due(throttle: Option<Res<DrawThrottle>>, redraw: Option<Res<RedrawRequested>>) -> bool {
+    throttle.is_none_or(|throttle| throttle.is_due())
+        && redraw.is_none_or(|redraw| redraw.is_requested())
+}
+
+fn update_draw_throttle(context: Option<Res<RatatuiContext>>, mut throttle: ResMut<DrawThrottle>) {
+    let Some((finished, took)) = context.and_then(|context| context.last_draw()) else {
+        return;
+    };
+    if throttle.last_draw != Some(finished) {
+        throttle.record_draw(finished, took);
+    }
+}
@@ -0,0 +1,264 @@
+//! A timeline of recent terminal events for debugging.
+//!
+//! [`EventTimelinePlugin`] records the terminal events read each frame, with the frame number, in
+//! an [`EventTimeline`]. Pressing the [`EventTimelineKey`] (`F4` by default) shows the most recent
+//! events in a panel on the right of the screen. The panel does not take key presses, so the app
+//! can be used while watching its input, which is what the `bevy_keys` example does by hand.
+//!
+//! Set [`EventTimeline::filter`] to show only some kinds of events, e.g. to hide mouse moves.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     timeline::{EventKinds, EventTimeline, EventTimelinePlugin},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), EventTimelinePlugin))
+//!     .add_systems(Startup, |mut timeline: ResMut<EventTimeline>| {
+//!         timeline.filter = EventKinds::KEY | EventKinds::PASTE;
+//!     });
+//! ```
+use std::collections::VecDeque;
+
+use bevy::{core::FrameCount, prelude::*};
+use color_eyre::Result;
+use crossterm::event::{Event, KeyCode, KeyModifiers, MouseEventKind};
+use ratatui::{
+    layout::{Constraint, Layout},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
+};
+
+use crate::{
+    error::exit_on_error,
+    event::{CrosstermEvent, InputSet, KeyEvent},
+    quit::KeyBinding,
+    terminal::RatatuiContext,
+};
+
+/// A plugin that records terminal events in an [`EventTimeline`] and adds a panel to show them.
+pub struct EventTimelinePlugin;
+
+impl Plugin for EventTimelinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EventTimeline>()
+            .init_resource::<EventTimelineKey>()
+            .add_systems(
+                PreUpdate,
+                (record_events, timeline_input_system)
+                    .chain()
+                    .in_set(InputSet::Post),
+            )
+            .add_systems(
+                Last,
+                draw_timeline
+                    .pipe(exit_on_error)
+                    .run_if(timeline_open.and(resource_exists::<RatatuiContext>)),
+            );
+    }
+}
+
+/// A run condition that is true while the timeline panel is shown.
+pub fn timeline_open(timeline: Option<Res<EventTimeline>>) -> bool {
+    timeline.is_some_and(|timeline| timeline.open)
+}
+
+/// The key that shows and hides the timeline panel.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct EventTimelineKey(pub KeyBinding);
+
+impl Default for EventTimelineKey {
+    fn default() -> Self {
+        Self(KeyBinding::new(KeyCode::F(4)))
+    }
+}
+
+bitflags::bitflags! {
+    /// The kinds of terminal events, used to filter the [`EventTimeline`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct EventKinds: u8 {
+        const KEY = 0b0000_0001;
+        /// Mouse events other than moves.
+        const MOUSE = 0b0000_0010;
+        const MOUSE_MOVE = 0b0000_0100;
+        const FOCUS = 0b0000_1000;
+        const PASTE = 0b0001_0000;
+        const RESIZE = 0b0010_0000;
+    }
+}
+
+impl Default for EventKinds {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+impl EventKinds {
+    /// The kind of a terminal event.
+    pub fn of(event: &Event) -> Self {
+        match event {
+            Event::Key(_) => Self::KEY,
+            Event::Mouse(mouse) => match mouse.kind {
+                MouseEventKind::Moved | MouseEventKind::Drag(_) => Self::MOUSE_MOVE,
+                _ => Self::MOUSE,
+            },
+            Event::FocusGained | Event::FocusLost => Self::FOCUS,
+            Event::Paste(_) => Self::PASTE,
+            Event::Resize(..) => Self::RESIZE,
+        }
+    }
+}
+
+/// A terminal event recorded in the [`EventTimeline`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelineEntry {
+    /// The [`FrameCount`] when the event was read, or 0 without the `FrameCountPlugin`.
+    pub frame: u32,
+    pub event: Event,
+}
+
+impl TimelineEntry {
+    /// The kind of the event.
+    pub fn kind(&self) -> EventKinds {
+        EventKinds::of(&self.event)
+    }
+
+    /// A short description of the event, e.g. `key control+c press`.
+    pub fn describe(&self) -> String {
+        match &self.event {
+            Event::Key(key) => {
+                let modifiers = describe_modifiers(key.modifiers);
+                format!("key {modifiers}{} {:?}", key.code, key.kind).to_lowercase()
+            }
+            Event::Mouse(mouse) => {
+                let modifiers = describe_modifiers(mouse.modifiers);
+                format!(
+                    "mouse {modifiers}{:?} at {},{}",
+                    mouse.kind, mouse.column, mouse.row
+                )
+                .to_lowercase()
+            }
+            Event::FocusGained => "focus gained".into(),
+            Event::FocusLost => "focus lost".into(),
+            Event::Paste(text) => format!("paste {} bytes", text.len()),
+            Event::Resize(columns, rows) => format!("resize {columns}x{rows}"),
+        }
+    }
+}
+
+fn describe_modifiers(modifiers: KeyModifiers) -> String {
+    modifiers
+        .iter_names()
+        .map(|(name, _)| format!("{name}+"))
+        .collect()
+}
+
+/// The most recent terminal events, oldest first.
+#[derive(Resource, Debug, Clone)]
+pub struct EventTimeline {
+    /// The maximum number of events kept.
+    pub capacity: usize,
+    /// The kinds of events shown in the panel. All kinds are always recorded.
+    pub filter: EventKinds,
+    /// Whether the panel is shown.
+    pub open: bool,
+    entries: VecDeque<TimelineEntry>,
+}
+
+impl Default for EventTimeline {
+    fn default() -> Self {
+        Self {
+            capacity: 200,
+            filter: EventKinds::all(),
+            open: false,
+            entries: VecDeque::new(),
+        }
+    }
+}
+
+impl EventTimeline {
+    /// Records an event, dropping the oldest one if the timeline is full.
+    pub fn record(&mut self, frame: u32, event: Event) {
+        while self.entries.len() >= self.capacity.max(1) {
+            self.entries.pop_front();
+        }
+        if self.capacity > 0 {
+            self.entries.push_back(TimelineEntry { frame, event });
+        }
+    }
+
+    /// All the recorded events, oldest first.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &TimelineEntry> {
+        self.entries.iter()
+    }
+
+    /// The recorded events that match the [`EventTimeline::filter`], oldest first.
+    pub fn filtered(&self) -> impl DoubleEndedIterator<Item = &TimelineEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| self.filter.intersects(entry.kind()))
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+fn record_events(
+    mut events: EventReader<CrosstermEvent>,
+    frame: Option<Res<FrameCount>>,
+    mut timeline: ResMut<EventTimeline>,
+) {
+    let frame = frame.map_or(0, |frame| frame.0);
+    for event in events.read() {
+        timeline.record(frame, event.0.clone());
+    }
+}
+
+fn timeline_input_system(
+    mut keys: EventReader<KeyEvent>,
+    toggle: Res<EventTimelineKey>,
+    mut timeline: ResMut<EventTimeline>,
+) {
+    for key in keys.read() {
+        if toggle.matches(key) {
+            timeline.open = !timeline.open;
+        }
+    }
+}
+
+/// Draws the timeline panel on the right of the last frame, newest events at the bottom.
+pub fn draw_timeline(
+    mut context: ResMut<RatatuiContext>,
+    timeline: Res<EventTimeline>,
+) -> Result<()> {
+    let last_frame = context.last_frame().clone();
+    context.draw(|frame| {
+        let area = frame.area();
+        if last_frame.area == area {
+            *frame.buffer_mut() = last_frame;
+        }
+        let [_, panel] = Layout::horizontal([Constraint::Fill(1), Constraint::Max(48)]).areas(area);
+        let block = Block::bordered().title(" events ");
+        let rows = block.inner(panel).height as usize;
+        let frame_style = Style::new().fg(Color::DarkGray);
+        let mut lines: Vec<Line> = timeline
+            .filtered()
+            .rev()
+            .take(rows)
+            .map(|entry| {
+                Line::from(vec![
+                    Span::styled(format!("{:>6} ", entry.frame), frame_style),
+                    Span::raw(entry.describe()),
+                ])
+            })
+            .collect();
+        lines.reverse();
+        frame.render_widget(Clear, panel);
+        frame.render_widget(Paragraph::new(lines).block(block), panel);
+    })?;
+    Ok(())
+}
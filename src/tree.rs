@@ -0,0 +1,530 @@
+//! A tree view with lazily loaded children.
+//!
+//! [`TreeView`] is a component holding a tree of labelled nodes, identified by a [`TreeNodeId`]
+//! chosen by the app. Nodes added with [`NodeKind::Lazy`] load their children the first time they
+//! are expanded: the [`TreeViewPlugin`] sends a [`TreeViewEvent::LoadChildren`] event, and the app
+//! answers with [`TreeView::set_children`] whenever the children are ready, for example when an
+//! async task finishes. Render a tree with [`TreeViewWidget`].
+//!
+//! The [`TreeViewPlugin`] forwards key presses to focused trees:
+//!
+//! - `Up`/`Down` move the selection, `Home`/`End` jump to the first and last row.
+//! - `Right` expands the selected node, or moves to its first child if it is already expanded.
+//! - `Left` collapses the selected node, or moves to its parent if it is already collapsed.
+//! - `Space` toggles the selected node and `Enter` sends [`TreeViewEvent::Activated`].
+//!
+//! ```rust
+//! use bevy_ratatui::tree::{NodeKind, TreeNodeId, TreeView};
+//!
+//! let mut tree = TreeView::default();
+//! tree.add_root(TreeNodeId(1), "src", NodeKind::Lazy);
+//! assert!(tree.expand(TreeNodeId(1)), "the children need to be loaded");
+//! tree.set_children(TreeNodeId(1), [(TreeNodeId(2), "lib.rs", NodeKind::Leaf)]);
+//! assert_eq!(tree.visible_rows().len(), 2);
+//! ```
+use bevy::{prelude::*, utils::HashMap};
+use crossterm::event::{KeyCode, KeyEventKind};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::Widget,
+};
+
+use crate::{
+    event::{InputSet, KeyEvent},
+    widget::{is_interactive, Interactive},
+};
+
+/// A plugin that forwards key presses to focused [`TreeView`]s.
+pub struct TreeViewPlugin;
+
+impl Plugin for TreeViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TreeViewEvent>()
+            .add_systems(PreUpdate, tree_input_system.in_set(InputSet::Post));
+    }
+}
+
+/// The identifier of a node, chosen by the app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TreeNodeId(pub u64);
+
+/// Whether a node has children.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// The node has no children.
+    Leaf,
+    /// The node may have children, which are loaded when it is first expanded.
+    Lazy,
+}
+
+/// Sent by the [`TreeViewPlugin`] in response to key presses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub enum TreeViewEvent {
+    /// A lazy node was expanded for the first time. Answer with [`TreeView::set_children`].
+    LoadChildren { entity: Entity, node: TreeNodeId },
+    /// The selection moved to a node.
+    Selected { entity: Entity, node: TreeNodeId },
+    /// `Enter` was pressed on a node.
+    Activated { entity: Entity, node: TreeNodeId },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Children {
+    Leaf,
+    Unloaded,
+    Loading,
+    Loaded(Vec<TreeNodeId>),
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    label: Line<'static>,
+    parent: Option<TreeNodeId>,
+    children: Children,
+    expanded: bool,
+}
+
+/// A node on screen, in the order it is drawn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VisibleRow {
+    pub node: TreeNodeId,
+    pub depth: usize,
+    /// For each ancestor, whether it is the last of its siblings, which decides whether a guide
+    /// line continues past it.
+    ancestors_last: Vec<bool>,
+    /// Whether the node is the last of its siblings.
+    last: bool,
+}
+
+/// A tree of labelled nodes with a selection.
+#[derive(Component, Debug, Clone, Default)]
+pub struct TreeView {
+    nodes: HashMap<TreeNodeId, Node>,
+    roots: Vec<TreeNodeId>,
+    selected: Option<TreeNodeId>,
+    /// Whether the tree takes key presses.
+    pub focused: bool,
+}
+
+impl TreeView {
+    /// Creates an empty, focused tree.
+    pub fn new() -> Self {
+        Self {
+            focused: true,
+            ..Default::default()
+        }
+    }
+
+    /// Adds a top level node.
+    pub fn add_root(&mut self, id: TreeNodeId, label: impl Into<Line<'static>>, kind: NodeKind) {
+        self.insert(id, None, label.into(), kind);
+        self.roots.push(id);
+        self.selected.get_or_insert(id);
+    }
+
+    /// Adds a child to a node, marking the node's children as loaded.
+    pub fn add_child(
+        &mut self,
+        parent: TreeNodeId,
+        id: TreeNodeId,
+        label: impl Into<Line<'static>>,
+        kind: NodeKind,
+    ) {
+        let Some(node) = self.nodes.get_mut(&parent) else {
+            return;
+        };
+        match &mut node.children {
+            Children::Loaded(children) => children.push(id),
+            children => *children = Children::Loaded(vec![id]),
+        }
+        self.insert(id, Some(parent), label.into(), kind);
+    }
+
+    /// Replaces the children of a node, typically in answer to
+    /// [`TreeViewEvent::LoadChildren`].
+    pub fn set_children<L: Into<Line<'static>>>(
+        &mut self,
+        parent: TreeNodeId,
+        children: impl IntoIterator<Item = (TreeNodeId, L, NodeKind)>,
+    ) {
+        let Some(node) = self.nodes.get_mut(&parent) else {
+            return;
+        };
+        let old = std::mem::replace(&mut node.children, Children::Loaded(Vec::new()));
+        if let Children::Loaded(old) = old {
+            for id in old {
+                self.remove_subtree(id);
+            }
+        }
+        for (id, label, kind) in children {
+            self.add_child(parent, id, label, kind);
+        }
+    }
+
+    /// The label of a node.
+    pub fn label(&self, id: TreeNodeId) -> Option<&Line<'static>> {
+        self.nodes.get(&id).map(|node| &node.label)
+    }
+
+    /// The parent of a node.
+    pub fn parent(&self, id: TreeNodeId) -> Option<TreeNodeId> {
+        self.nodes.get(&id)?.parent
+    }
+
+    /// Returns true if the node is expanded.
+    pub fn is_expanded(&self, id: TreeNodeId) -> bool {
+        self.nodes.get(&id).is_some_and(|node| node.expanded)
+    }
+
+    /// Returns true while the node's children are being loaded.
+    pub fn is_loading(&self, id: TreeNodeId) -> bool {
+        self.nodes
+            .get(&id)
+            .is_some_and(|node| node.children == Children::Loading)
+    }
+
+    /// The selected node.
+    pub fn selected(&self) -> Option<TreeNodeId> {
+        self.selected
+    }
+
+    /// Selects a node, expanding its ancestors so that it is visible.
+    pub fn select(&mut self, id: TreeNodeId) {
+        let mut parent = self.parent(id);
+        while let Some(ancestor) = parent {
+            if let Some(node) = self.nodes.get_mut(&ancestor) {
+                node.expanded = true;
+            }
+            parent = self.parent(ancestor);
+        }
+        self.selected = Some(id);
+    }
+
+    /// Expands a node. Returns true if its children need to be loaded, in which case the node is
+    /// marked as loading until [`TreeView::set_children`] is called.
+    pub fn expand(&mut self, id: TreeNodeId) -> bool {
+        let Some(node) = self.nodes.get_mut(&id) else {
+            return false;
+        };
+        if node.children == Children::Leaf {
+            return false;
+        }
+        node.expanded = true;
+        if node.children == Children::Unloaded {
+            node.children = Children::Loading;
+            return true;
+        }
+        false
+    }
+
+    /// Collapses a node.
+    pub fn collapse(&mut self, id: TreeNodeId) {
+        if let Some(node) = self.nodes.get_mut(&id) {
+            node.expanded = false;
+        }
+    }
+
+    /// Copies the expanded nodes, the selection and the focus from another tree, for the nodes
+    /// that exist in both. Use this to keep the user's place when rebuilding a tree.
+    pub fn keep_state_from(&mut self, previous: &TreeView) {
+        for (id, node) in &mut self.nodes {
+            if let Some(old) = previous.nodes.get(id) {
+                node.expanded = old.expanded;
+            }
+        }
+        if let Some(selected) = previous.selected.filter(|id| self.nodes.contains_key(id)) {
+            self.selected = Some(selected);
+        }
+        self.focused = previous.focused;
+    }
+
+    /// The nodes that are on screen: the roots and the descendants of expanded nodes.
+    pub fn visible_rows(&self) -> Vec<VisibleRow> {
+        let mut rows = Vec::new();
+        self.push_rows(&self.roots, &mut Vec::new(), &mut rows);
+        rows
+    }
+
+    fn push_rows(
+        &self,
+        ids: &[TreeNodeId],
+        ancestors_last: &mut Vec<bool>,
+        rows: &mut Vec<VisibleRow>,
+    ) {
+        for (index, id) in ids.iter().enumerate() {
+            let last = index + 1 == ids.len();
+            rows.push(VisibleRow {
+                node: *id,
+                depth: ancestors_last.len(),
+                ancestors_last: ancestors_last.clone(),
+                last,
+            });
+            let Some(node) = self.nodes.get(id) else {
+                continue;
+            };
+            if let (true, Children::Loaded(children)) = (node.expanded, &node.children) {
+                ancestors_last.push(last);
+                self.push_rows(children, ancestors_last, rows);
+                ancestors_last.pop();
+            }
+        }
+    }
+
+    fn insert(
+        &mut self,
+        id: TreeNodeId,
+        parent: Option<TreeNodeId>,
+        label: Line<'static>,
+        kind: NodeKind,
+    ) {
+        let children = match kind {
+            NodeKind::Leaf => Children::Leaf,
+            NodeKind::Lazy => Children::Unloaded,
+        };
+        self.nodes.insert(
+            id,
+            Node {
+                label,
+                parent,
+                children,
+                expanded: false,
+            },
+        );
+    }
+
+    fn remove_subtree(&mut self, id: TreeNodeId) {
+        if let Some(Node {
+            children: Children::Loaded(children),
+            ..
+        }) = self.nodes.remove(&id)
+        {
+            for child in children {
+                self.remove_subtree(child);
+            }
+        }
+        if self.selected == Some(id) {
+            self.selected = None;
+        }
+    }
+
+    /// Handles a key press, returning the events it causes.
+    fn handle_key(
+        &mut self,
+        entity: Entity,
+        key: &crossterm::event::KeyEvent,
+    ) -> Vec<TreeViewEvent> {
+        let rows = self.visible_rows();
+        let Some(selected) = self.selected.filter(|id| self.nodes.contains_key(id)) else {
+            self.selected = rows.first().map(|row| row.node);
+            return Vec::new();
+        };
+        let index = rows
+            .iter()
+            .position(|row| row.node == selected)
+            .unwrap_or(0);
+        let mut events = Vec::new();
+        let mut target = None;
+        match key.code {
+            KeyCode::Up => target = Some(rows[index.saturating_sub(1)].node),
+            KeyCode::Down => target = Some(rows[(index + 1).min(rows.len() - 1)].node),
+            KeyCode::Home => target = Some(rows[0].node),
+            KeyCode::End => target = Some(rows[rows.len() - 1].node),
+            KeyCode::Right if self.is_expanded(selected) => {
+                target = rows
+                    .get(index + 1)
+                    .filter(|row| row.depth > rows[index].depth)
+                    .map(|row| row.node);
+            }
+            KeyCode::Left if self.is_expanded(selected) => self.collapse(selected),
+            KeyCode::Left => target = self.parent(selected),
+            KeyCode::Char(' ') if self.is_expanded(selected) => self.collapse(selected),
+            KeyCode::Right | KeyCode::Char(' ') => {
+                let load = self.expand(selected);
+                if load {
+                    events.push(TreeViewEvent::LoadChildren {
+                        entity,
+                        node: selected,
+                    });
+                }
+            }
+            KeyCode::Enter => events.push(TreeViewEvent::Activated {
+                entity,
+                node: selected,
+            }),
+            _ => {}
+        }
+        if let Some(node) = target.filter(|node| *node != selected) {
+            self.selected = Some(node);
+            events.push(TreeViewEvent::Selected { entity, node });
+        }
+        events
+    }
+}
+
+fn tree_input_system(
+    mut keys: EventReader<KeyEvent>,
+    mut trees: Query<(Entity, Interactive<&mut TreeView>)>,
+    mut events: EventWriter<TreeViewEvent>,
+) {
+    for key in keys.read().filter(|key| key.kind != KeyEventKind::Release) {
+        for (entity, (mut tree, visibility, disabled)) in &mut trees {
+            if tree.focused && is_interactive(visibility, disabled) {
+                events.send_batch(tree.handle_key(entity, key));
+            }
+        }
+    }
+}
+
+/// The characters used to draw the guide lines of a [`TreeViewWidget`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeGuides {
+    /// Drawn before a node that has siblings after it.
+    pub branch: &'static str,
+    /// Drawn before the last node of its siblings.
+    pub last_branch: &'static str,
+    /// Drawn below an ancestor that has siblings after it.
+    pub vertical: &'static str,
+    /// Drawn below an ancestor that is the last of its siblings.
+    pub blank: &'static str,
+}
+
+impl TreeGuides {
+    /// Box drawing lines: `├── `, `└── ` and `│   `.
+    pub const LINES: Self = Self {
+        branch: "├── ",
+        last_branch: "└── ",
+        vertical: "│   ",
+        blank: "    ",
+    };
+
+    /// ASCII lines: `|-- `, `` `-- `` and `|   `.
+    pub const ASCII: Self = Self {
+        branch: "|-- ",
+        last_branch: "`-- ",
+        vertical: "|   ",
+        blank: "    ",
+    };
+
+    /// Indentation only.
+    pub const INDENT: Self = Self {
+        branch: "  ",
+        last_branch: "  ",
+        vertical: "  ",
+        blank: "  ",
+    };
+}
+
+impl Default for TreeGuides {
+    fn default() -> Self {
+        Self::LINES
+    }
+}
+
+/// Renders a [`TreeView`], scrolled so that the selected node is visible.
+pub struct TreeViewWidget<'a> {
+    tree: &'a TreeView,
+    guides: TreeGuides,
+    guide_style: Style,
+    highlight_style: Style,
+    disabled: bool,
+}
+
+impl<'a> TreeViewWidget<'a> {
+    pub fn new(tree: &'a TreeView) -> Self {
+        Self {
+            tree,
+            guides: TreeGuides::default(),
+            guide_style: Style::new().add_modifier(Modifier::DIM),
+            highlight_style: Style::new().add_modifier(Modifier::REVERSED),
+            disabled: false,
+        }
+    }
+
+    /// Sets the guide line characters. Defaults to [`TreeGuides::LINES`].
+    pub fn guides(mut self, guides: TreeGuides) -> Self {
+        self.guides = guides;
+        self
+    }
+
+    /// Sets the style of the guide lines. Defaults to dim.
+    pub fn guide_style(mut self, style: Style) -> Self {
+        self.guide_style = style;
+        self
+    }
+
+    /// Sets the style of the selected node's label. Defaults to reversed.
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    /// Draws the tree dimmed and without the selection, e.g. for a [`Disabled`] tree.
+    ///
+    /// [`Disabled`]: crate::widget::Disabled
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = disabled;
+        self
+    }
+
+    fn row_line(&self, row: &VisibleRow) -> Line<'a> {
+        let tree = self.tree;
+        let guides = &self.guides;
+        let mut spans = Vec::new();
+        // Top level nodes have no guide before them, so the roots' own flags are skipped.
+        for last in row.ancestors_last.iter().skip(1) {
+            let guide = if *last { guides.blank } else { guides.vertical };
+            spans.push(Span::styled(guide, self.guide_style));
+        }
+        if row.depth > 0 {
+            let guide = if row.last {
+                guides.last_branch
+            } else {
+                guides.branch
+            };
+            spans.push(Span::styled(guide, self.guide_style));
+        }
+        let marker = match tree.nodes.get(&row.node).map(|node| &node.children) {
+            Some(Children::Leaf) | None => "  ",
+            Some(_) if tree.is_expanded(row.node) => "▾ ",
+            Some(_) => "▸ ",
+        };
+        spans.push(Span::raw(marker));
+        let selected = tree.selected == Some(row.node) && !self.disabled;
+        if let Some(label) = tree.label(row.node) {
+            spans.extend(label.spans.iter().map(|span| {
+                let style = label.style.patch(span.style);
+                if selected {
+                    span.clone().style(style.patch(self.highlight_style))
+                } else {
+                    span.clone().style(style)
+                }
+            }));
+        }
+        if tree.is_loading(row.node) {
+            spans.push(Span::raw(" …"));
+        }
+        Line::from(spans)
+    }
+}
+
+impl Widget for TreeViewWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rows = self.tree.visible_rows();
+        let height = area.height as usize;
+        let selected = rows
+            .iter()
+            .position(|row| Some(row.node) == self.tree.selected)
+            .unwrap_or(0);
+        let offset = (selected + 1).saturating_sub(height);
+        for (y, row) in (area.top()..area.bottom()).zip(rows.iter().skip(offset)) {
+            self.row_line(row)
+                .render(Rect::new(area.x, y, area.width, 1), buf);
+        }
+        if self.disabled {
+            buf.set_style(area, Style::new().add_modifier(Modifier::DIM));
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Lists that only render the visible rows.
+//!
+//! [`VirtualList`] takes the number of items and a function that builds the [`Line`] for an index,
+//! and only calls it for the rows that are on screen. This keeps drawing cheap for lists, tables
+//! and logs with millions of rows, where building a full [`Text`](ratatui::text::Text) each frame
+//! would not be.
+//!
+//! [`VirtualListState`] keeps the scroll position anchored to the items on screen when items are
+//! inserted or removed above them, and can follow the end of the list as items are appended.
+//!
+//! ```rust
+//! use bevy_ratatui::virtual_list::{VirtualList, VirtualListState};
+//! use ratatui::{buffer::Buffer, layout::Rect, text::Line, widgets::StatefulWidget};
+//!
+//! let mut state = VirtualListState::default();
+//! state.select(Some(500_000));
+//! let list = VirtualList::new(1_000_000, |index| Line::from(format!("row {index}")));
+//! let mut buffer = Buffer::empty(Rect::new(0, 0, 20, 3));
+//! list.render(buffer.area, &mut buffer, &mut state);
+//! assert_eq!(state.offset(), 499_998);
+//! ```
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{StatefulWidget, Widget},
+};
+
+/// The scroll position and selection of a [`VirtualList`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VirtualListState {
+    offset: usize,
+    selected: Option<usize>,
+    follow: bool,
+    /// The number of items and visible rows as of the last render.
+    len: usize,
+    height: usize,
+}
+
+impl VirtualListState {
+    /// The index of the first visible item.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The index of the selected item, if any.
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Selects an item. The list scrolls to it the next time it is rendered.
+    pub fn select(&mut self, index: Option<usize>) {
+        self.selected = index;
+        self.follow = false;
+    }
+
+    /// Moves the selection by the given number of items, selecting the first item if nothing was
+    /// selected.
+    pub fn select_relative(&mut self, by: isize) {
+        let last = self.len.saturating_sub(1);
+        let selected = match self.selected {
+            Some(selected) => selected.saturating_add_signed(by).min(last),
+            None => 0,
+        };
+        self.select(Some(selected));
+    }
+
+    /// Moves the selection by a page.
+    pub fn page(&mut self, pages: isize) {
+        self.select_relative(pages.saturating_mul(self.height.max(1) as isize));
+    }
+
+    /// Scrolls without changing the selection.
+    pub fn scroll_by(&mut self, by: isize) {
+        self.offset = self
+            .offset
+            .saturating_add_signed(by)
+            .min(self.len.saturating_sub(self.height));
+        self.follow = false;
+    }
+
+    /// Keeps the end of the list in view as items are appended, until the list is scrolled or an
+    /// item is selected.
+    pub fn follow(&mut self) {
+        self.follow = true;
+    }
+
+    /// Returns true if the list is following the end.
+    pub fn is_following(&self) -> bool {
+        self.follow
+    }
+
+    /// Adjusts the scroll position and selection after `count` items were inserted at `index`, so
+    /// that the same items stay on screen.
+    pub fn items_inserted(&mut self, index: usize, count: usize) {
+        if index < self.offset || (index == self.offset && self.offset > 0) {
+            self.offset += count;
+        }
+        if let Some(selected) = &mut self.selected {
+            if index <= *selected {
+                *selected += count;
+            }
+        }
+        self.len += count;
+    }
+
+    /// Adjusts the scroll position and selection after `count` items were removed from `index`,
+    /// so that the same items stay on screen.
+    pub fn items_removed(&mut self, index: usize, count: usize) {
+        let shift = move |position: usize| {
+            if position >= index + count {
+                position - count
+            } else {
+                position.min(index)
+            }
+        };
+        self.selected = self.selected.map(shift);
+        self.offset = shift(self.offset);
+        self.len = self.len.saturating_sub(count);
+    }
+
+    /// Clamps the state to the list and scrolls the selection into view.
+    fn update(&mut self, len: usize, height: usize) {
+        self.len = len;
+        self.height = height;
+        self.selected = self.selected.filter(|_| len > 0).map(|s| s.min(len - 1));
+        let max_offset = len.saturating_sub(height);
+        if self.follow {
+            self.offset = max_offset;
+        }
+        if let Some(selected) = self.selected {
+            if selected < self.offset {
+                self.offset = selected;
+            } else if selected >= self.offset + height {
+                self.offset = selected + 1 - height;
+            }
+        }
+        self.offset = self.offset.min(max_offset);
+    }
+}
+
+/// A list that only builds the rows that are visible.
+///
+/// Each item is a single row. The function is called with the index of each visible item.
+pub struct VirtualList<F> {
+    len: usize,
+    item: F,
+    style: Style,
+    highlight_style: Style,
+}
+
+impl<'a, F> VirtualList<F>
+where
+    F: Fn(usize) -> Line<'a>,
+{
+    /// Creates a list with `len` items, built by `item`.
+    pub fn new(len: usize, item: F) -> Self {
+        Self {
+            len,
+            item,
+            style: Style::new(),
+            highlight_style: Style::new().add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    /// Sets the style of the whole list.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the style of the selected item. Defaults to reversed.
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+}
+
+impl<'a, F> StatefulWidget for VirtualList<F>
+where
+    F: Fn(usize) -> Line<'a>,
+{
+    type State = VirtualListState;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut Self::State) {
+        buf.set_style(area, self.style);
+        state.update(self.len, area.height as usize);
+        for (y, index) in (area.top()..area.bottom()).zip(state.offset..self.len) {
+            let row = Rect::new(area.x, y, area.width, 1);
+            (self.item)(index).render(row, buf);
+            if state.selected == Some(index) {
+                buf.set_style(row, self.highlight_style);
+            }
+        }
+    }
+}
@@ -0,0 +1,535 @@
+//! Serving the app to browser terminals over WebSocket.
+//!
+//! [`WebSocketServerPlugin`] accepts WebSocket connections on a background thread, e.g. from a
+//! page that shows the app in [xterm.js]. Every connection gets an entity with a
+//! [`WebSocketClient`] and a [`RatatuiContext`] that draws to the browser terminal, so systems
+//! render into the entity's [`RenderBuffer`](crate::render::RenderBuffer) like they do for
+//! [other terminals](crate::terminal::RatatuiContext#other-terminals). The keys and mouse events
+//! of the browser terminal are sent as [`WebSocketKeyEvent`]s and [`WebSocketMouseEvent`]s.
+//! Despawning the entity closes the connection.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     render::{RenderBuffer, RenderSet},
+//!     websocket::{WebSocketClient, WebSocketKeyEvent, WebSocketServerPlugin},
+//!     RatatuiPlugins,
+//! };
+//! use crossterm::event::KeyCode;
+//! use ratatui::widgets::Widget;
+//!
+//! App::new()
+//!     .add_plugins((
+//!         RatatuiPlugins {
+//!             headless: true,
+//!             ..default()
+//!         },
+//!         WebSocketServerPlugin,
+//!     ))
+//!     .add_systems(Update, quit_on_q)
+//!     .add_systems(PostUpdate, greet.in_set(RenderSet::Widgets))
+//!     .run();
+//!
+//! fn quit_on_q(mut keys: EventReader<WebSocketKeyEvent>, mut commands: Commands) {
+//!     for key in keys.read() {
+//!         if key.code == KeyCode::Char('q') {
+//!             commands.entity(key.client).despawn();
+//!         }
+//!     }
+//! }
+//!
+//! fn greet(mut clients: Query<(&WebSocketClient, &mut RenderBuffer)>) {
+//!     for (client, mut buffer) in &mut clients {
+//!         let area = buffer.area;
+//!         format!("hello {}, press q to leave", client.peer).render(area, &mut buffer);
+//!     }
+//! }
+//! ```
+//!
+//! # Protocol
+//!
+//! The server sends the terminal output as binary messages. The browser sends what the terminal
+//! reports as text messages, and its size, as columns and rows, as a binary message of two
+//! big-endian `u16`s, once when it connects and again whenever the terminal is resized. With
+//! xterm.js:
+//!
+//! ```js
+//! const socket = new WebSocket("ws://localhost:8080");
+//! socket.binaryType = "arraybuffer";
+//! socket.onmessage = (message) => term.write(new Uint8Array(message.data));
+//! term.onData((data) => socket.send(data));
+//! const sendSize = () => {
+//!     const size = new DataView(new ArrayBuffer(4));
+//!     size.setUint16(0, term.cols);
+//!     size.setUint16(2, term.rows);
+//!     socket.send(size.buffer);
+//! };
+//! socket.onopen = sendSize;
+//! term.onResize(sendSize);
+//! ```
+//!
+//! Until the size arrives, the terminal is taken to be 80 by 24. The [`WebSocketServer`] resource
+//! sets the address to listen on, which pages may connect and whether the mouse is captured. The
+//! server speaks plain `ws`, so put it behind a proxy that terminates TLS to serve it on the
+//! internet. This module requires the `websocket` feature.
+//!
+//! # Allowed origins
+//!
+//! Browsers let any page open a WebSocket to any address, including one on `localhost`, and only
+//! tell the server which page it is through the `Origin` header. Without a check, every page the
+//! user visits could connect to the app and type into it. By default, only pages served from the
+//! host that the client connected to, on any port, may connect, and connections without an
+//! `Origin` are refused too. Other pages are answered with `403 Forbidden`. List the pages that
+//! serve the terminal with [`AllowedOrigins::List`], or allow any client, e.g. one that is not a
+//! browser, with [`AllowedOrigins::Any`]:
+//!
+//! ```rust
+//! use bevy_ratatui::websocket::{AllowedOrigins, WebSocketServer};
+//!
+//! let server = WebSocketServer {
+//!     allowed_origins: AllowedOrigins::List(vec!["https://example.com".into()]),
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! [xterm.js]: https://xtermjs.org
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use crossterm::{
+    cursor,
+    event::{DisableMouseCapture, EnableMouseCapture, Event, KeyEvent, MouseEvent},
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+    QueueableCommand,
+};
+use ratatui::layout::Size;
+use tungstenite::{
+    handshake::server::{ErrorResponse, Request},
+    http::{header, StatusCode},
+    protocol::{frame::coding::CloseCode, CloseFrame},
+    Message, WebSocket,
+};
+
+use crate::{event::InputSet, remote_input::parse_input, terminal::RatatuiContext};
+
+/// How long a connection waits for a message before it sends the output of the app.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A plugin that serves the app to browser terminals. See the [module docs](self).
+pub struct WebSocketServerPlugin;
+
+impl Plugin for WebSocketServerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WebSocketServer>()
+            .add_event::<WebSocketKeyEvent>()
+            .add_event::<WebSocketMouseEvent>()
+            .add_event::<WebSocketClientConnected>()
+            .add_event::<WebSocketClientDisconnected>()
+            .add_systems(Startup, start_websocket_server)
+            .add_systems(
+                PreUpdate,
+                receive_websocket_messages.in_set(InputSet::EmitCrossterm),
+            );
+    }
+}
+
+/// Where and how the [`WebSocketServerPlugin`] listens.
+#[derive(Resource, Debug, Clone, PartialEq, Eq)]
+pub struct WebSocketServer {
+    /// The address to listen on. Defaults to `127.0.0.1:8080`.
+    pub address: String,
+    /// Which pages may connect. Defaults to [`AllowedOrigins::SameHost`].
+    pub allowed_origins: AllowedOrigins,
+    /// Asks the browser terminals to report mouse events. Defaults to `true`.
+    pub enable_mouse_capture: bool,
+}
+
+impl Default for WebSocketServer {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:8080".into(),
+            allowed_origins: AllowedOrigins::default(),
+            enable_mouse_capture: true,
+        }
+    }
+}
+
+/// Which pages may connect to the [`WebSocketServer`], by the `Origin` header that browsers send.
+/// See [Allowed origins](self#allowed-origins).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum AllowedOrigins {
+    /// Pages served from the host that the client connected to, on any port, e.g.
+    /// `http://localhost:3000` for `ws://localhost:8080`.
+    #[default]
+    SameHost,
+    /// Pages with one of these origins, e.g. `https://example.com` or `http://localhost:3000`.
+    List(Vec<String>),
+    /// Any client, with or without an `Origin`. Any page the user visits can then connect.
+    Any,
+}
+
+impl AllowedOrigins {
+    /// Whether a client that sent `origin` in its request for `host` may connect.
+    fn allows(&self, origin: Option<&str>, host: Option<&str>) -> bool {
+        match self {
+            Self::SameHost => {
+                let origin_host = origin
+                    .and_then(|origin| origin.split_once("://"))
+                    .map(|(_, authority)| host_name(authority));
+                let host = host.map(host_name);
+                origin_host.is_some_and(|origin_host| {
+                    host.is_some_and(|host| origin_host.eq_ignore_ascii_case(host))
+                })
+            }
+            Self::List(origins) => origin.is_some_and(|origin| {
+                origins
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(origin))
+            }),
+            Self::Any => true,
+        }
+    }
+}
+
+/// The host name of a `host:port` authority, e.g. `localhost` or `[::1]`.
+fn host_name(authority: &str) -> &str {
+    match authority.find(']') {
+        Some(end) => &authority[..=end],
+        None => authority
+            .rsplit_once(':')
+            .map_or(authority, |(host, _)| host),
+    }
+}
+
+/// A connected browser terminal. The entity also has a [`RatatuiContext`] that draws to it.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct WebSocketClient {
+    /// The address the client connected from.
+    pub peer: SocketAddr,
+    /// The path of the URL the client connected to, e.g. `/` or `/game`.
+    pub path: String,
+}
+
+/// A key pressed in a browser terminal.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct WebSocketKeyEvent {
+    /// The [`WebSocketClient`] entity.
+    pub client: Entity,
+    #[deref]
+    pub key: KeyEvent,
+}
+
+/// A mouse event in a browser terminal, in cells of the client's terminal.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct WebSocketMouseEvent {
+    /// The [`WebSocketClient`] entity.
+    pub client: Entity,
+    #[deref]
+    pub mouse: MouseEvent,
+}
+
+/// Sent when a browser terminal has connected.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct WebSocketClientConnected(pub Entity);
+
+/// Sent when a browser terminal has disconnected. The entity has been despawned by then.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct WebSocketClientDisconnected(pub Entity);
+
+/// What the connection threads tell the app.
+enum WebSocketMessage {
+    Opened {
+        id: u64,
+        peer: SocketAddr,
+        path: String,
+        output: WebSocketOutput,
+    },
+    Resized {
+        id: u64,
+        size: Size,
+    },
+    Input {
+        id: u64,
+        bytes: Vec<u8>,
+    },
+    Closed {
+        id: u64,
+    },
+}
+
+/// The running server, and the clients it has connected.
+#[derive(Resource)]
+struct WebSocketConnections {
+    messages: Mutex<Receiver<WebSocketMessage>>,
+    thread: Option<JoinHandle<io::Result<()>>>,
+    clients: HashMap<u64, Entity>,
+    enable_mouse_capture: bool,
+}
+
+fn start_websocket_server(mut commands: Commands, settings: Res<WebSocketServer>) {
+    let address = settings.address.clone();
+    let allowed_origins = settings.allowed_origins.clone();
+    let (sender, messages) = mpsc::channel();
+    let thread = thread::spawn(move || {
+        let listener = TcpListener::bind(address)?;
+        for (id, stream) in (0..).zip(listener.incoming()) {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    warn!("Failed to accept a WebSocket connection: {err}");
+                    continue;
+                }
+            };
+            let messages = sender.clone();
+            let allowed_origins = allowed_origins.clone();
+            thread::spawn(move || {
+                if let Err(err) = serve_client(id, stream, &allowed_origins, &messages) {
+                    warn!("WebSocket connection {id} failed: {err}");
+                }
+                let _ = messages.send(WebSocketMessage::Closed { id });
+            });
+        }
+        Ok(())
+    });
+    commands.insert_resource(WebSocketConnections {
+        messages: Mutex::new(messages),
+        thread: Some(thread),
+        clients: HashMap::new(),
+        enable_mouse_capture: settings.enable_mouse_capture,
+    });
+}
+
+/// Relays messages between one client and the app until either side closes the connection.
+fn serve_client(
+    id: u64,
+    stream: TcpStream,
+    allowed_origins: &AllowedOrigins,
+    messages: &Sender<WebSocketMessage>,
+) -> io::Result<()> {
+    let peer = stream.peer_addr()?;
+    // The callback must be 'static, so the path comes back through a channel.
+    let (path_sender, path) = mpsc::channel();
+    let allowed_origins = allowed_origins.clone();
+    // The error type of the callback is set by tungstenite.
+    #[allow(clippy::result_large_err)]
+    let mut socket = tungstenite::accept_hdr(stream, move |request: &Request, response| {
+        let header = |name| {
+            request
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+        };
+        let origin = header(header::ORIGIN);
+        if !allowed_origins.allows(origin, header(header::HOST)) {
+            return Err(forbidden(origin));
+        }
+        let _ = path_sender.send(request.uri().path().to_string());
+        Ok(response)
+    })
+    .map_err(io::Error::other)?;
+    socket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+    let (sender, output) = mpsc::channel();
+    let opened = WebSocketMessage::Opened {
+        id,
+        peer,
+        path: path.try_recv().unwrap_or_else(|_| "/".into()),
+        output: WebSocketOutput {
+            sender,
+            pending: Vec::new(),
+        },
+    };
+    if messages.send(opened).is_err() {
+        return Ok(());
+    }
+    loop {
+        match socket.read() {
+            Ok(Message::Text(text)) => {
+                let bytes = text.as_bytes().to_vec();
+                let _ = messages.send(WebSocketMessage::Input { id, bytes });
+            }
+            Ok(Message::Binary(data)) => {
+                if let &[c0, c1, r0, r1] = &data[..] {
+                    let size =
+                        Size::new(u16::from_be_bytes([c0, c1]), u16::from_be_bytes([r0, r1]));
+                    let _ = messages.send(WebSocketMessage::Resized { id, size });
+                }
+            }
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(err))
+                if matches!(
+                    err.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) => {}
+            Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+                return Ok(());
+            }
+            Err(err) => return Err(io::Error::other(err)),
+        }
+        loop {
+            match output.try_recv() {
+                Ok(data) => send(&mut socket, data)?,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => return close(&mut socket),
+            }
+        }
+    }
+}
+
+/// The answer to a client from an origin that is not allowed.
+fn forbidden(origin: Option<&str>) -> ErrorResponse {
+    let message = match origin {
+        Some(origin) => format!("Pages from {origin} may not connect."),
+        None => "Clients without an Origin may not connect.".into(),
+    };
+    let mut response = ErrorResponse::new(Some(message));
+    *response.status_mut() = StatusCode::FORBIDDEN;
+    response
+}
+
+fn send(socket: &mut WebSocket<TcpStream>, data: Vec<u8>) -> io::Result<()> {
+    socket.send(Message::binary(data)).map_err(io::Error::other)
+}
+
+/// Gives the client its terminal back and closes the connection, once the app has despawned the
+/// client.
+fn close(socket: &mut WebSocket<TcpStream>) -> io::Result<()> {
+    let mut restore = Vec::new();
+    restore
+        .queue(DisableMouseCapture)?
+        .queue(cursor::Show)?
+        .queue(LeaveAlternateScreen)?;
+    send(socket, restore)?;
+    let frame = CloseFrame {
+        code: CloseCode::Normal,
+        reason: "".into(),
+    };
+    socket.close(Some(frame)).map_err(io::Error::other)?;
+    socket.flush().map_err(io::Error::other)
+}
+
+fn receive_websocket_messages(
+    mut commands: Commands,
+    mut connections: ResMut<WebSocketConnections>,
+    mut clients: Query<(&WebSocketClient, &mut RatatuiContext)>,
+    mut keys: EventWriter<WebSocketKeyEvent>,
+    mut mice: EventWriter<WebSocketMouseEvent>,
+    mut connected: EventWriter<WebSocketClientConnected>,
+    mut disconnected: EventWriter<WebSocketClientDisconnected>,
+) {
+    let connections = &mut *connections;
+    if connections
+        .thread
+        .as_ref()
+        .is_some_and(JoinHandle::is_finished)
+    {
+        match connections.thread.take().map(JoinHandle::join) {
+            Some(Ok(Err(err))) => error!("The WebSocket server stopped: {err}"),
+            Some(Err(_)) => error!("The WebSocket server panicked"),
+            _ => error!("The WebSocket server stopped"),
+        }
+    }
+    let messages = connections
+        .messages
+        .get_mut()
+        .unwrap_or_else(PoisonError::into_inner);
+    while let Ok(message) = messages.try_recv() {
+        match message {
+            WebSocketMessage::Opened {
+                id,
+                peer,
+                path,
+                output,
+            } => {
+                let context = match client_context(output, connections.enable_mouse_capture) {
+                    Ok(context) => context,
+                    Err(err) => {
+                        warn!("Failed to set up the terminal of {peer}: {err}");
+                        continue;
+                    }
+                };
+                let entity = commands
+                    .spawn((WebSocketClient { peer, path }, context))
+                    .id();
+                connections.clients.insert(id, entity);
+                connected.send(WebSocketClientConnected(entity));
+            }
+            WebSocketMessage::Resized { id, size } => {
+                let Some(&entity) = connections.clients.get(&id) else {
+                    continue;
+                };
+                if let Ok((client, mut context)) = clients.get_mut(entity) {
+                    if let Err(err) = context.resize_to(size) {
+                        warn!("Failed to resize the terminal of {}: {err}", client.peer);
+                    }
+                }
+            }
+            WebSocketMessage::Input { id, bytes } => {
+                let Some(&client) = connections.clients.get(&id) else {
+                    continue;
+                };
+                for event in parse_input(&bytes) {
+                    match event {
+                        Event::Key(key) => {
+                            keys.send(WebSocketKeyEvent { client, key });
+                        }
+                        Event::Mouse(mouse) => {
+                            mice.send(WebSocketMouseEvent { client, mouse });
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            WebSocketMessage::Closed { id } => {
+                let Some(entity) = connections.clients.remove(&id) else {
+                    continue;
+                };
+                if let Some(mut entity) = commands.get_entity(entity) {
+                    entity.despawn();
+                }
+                disconnected.send(WebSocketClientDisconnected(entity));
+            }
+        }
+    }
+}
+
+/// A context that draws to the client, with the size a client has before it sends its own.
+fn client_context(mut output: WebSocketOutput, mouse: bool) -> io::Result<RatatuiContext> {
+    output.queue(EnterAlternateScreen)?.queue(cursor::Hide)?;
+    if mouse {
+        output.queue(EnableMouseCapture)?;
+    }
+    RatatuiContext::with_writer(output, Size::new(80, 24))
+}
+
+/// Writes to a WebSocket connection. The bytes are sent when the writer is flushed, and the
+/// connection is closed when the writer is dropped.
+struct WebSocketOutput {
+    sender: Sender<Vec<u8>>,
+    pending: Vec<u8>,
+}
+
+impl Write for WebSocketOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        self.sender
+            .send(std::mem::take(&mut self.pending))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+}
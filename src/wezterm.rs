@@ -0,0 +1,204 @@
+//! Sharing app state with WezTerm.
+//!
+//! WezTerm keeps a set of user vars per pane, which the app sets with the `SetUserVar` escape
+//! sequence. Lua config code sees them in `pane:get_user_vars()` and the `user-var-changed` event,
+//! so the status bar, tab titles or key bindings can react to what the app is doing.
+//! [`WezTermIntegration`] is a [terminal extension](crate::extension) that writes each
+//! [`WezTermUserVar`] event to the terminal, and answers [`QueryWezTermPanes`] events with the
+//! panes of the WezTerm window, from `wezterm cli list`.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{
+//!     extension::ExtensionPlugin,
+//!     wezterm::{WezTermIntegration, WezTermUserVar},
+//!     RatatuiPlugins,
+//! };
+//!
+//! App::new()
+//!     .add_plugins((RatatuiPlugins::default(), ExtensionPlugin(WezTermIntegration)))
+//!     .add_systems(Update, |mut vars: EventWriter<WezTermUserVar>| {
+//!         vars.send(WezTermUserVar::new("build_status", "passing"));
+//!     });
+//! ```
+//!
+//! The [`SetUserVar`] command writes the escape sequence directly, e.g. with crossterm's
+//! `execute!` on [`terminal::output`](crate::terminal::output).
+use std::{
+    env, fmt,
+    io::Write,
+    process::Command,
+    thread::{self, JoinHandle},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    extension::{self, TerminalExtension, TerminalIdentity, TerminalProgram},
+    terminal::{is_headless, output, TerminalSet},
+};
+
+/// A [`TerminalExtension`] that sets user vars and lists panes in WezTerm.
+pub struct WezTermIntegration;
+
+impl TerminalExtension for WezTermIntegration {
+    fn name(&self) -> &'static str {
+        "wezterm"
+    }
+
+    fn supports(&self, identity: &TerminalIdentity) -> bool {
+        identity.program == TerminalProgram::WezTerm
+    }
+
+    fn register(&self, app: &mut App) {
+        app.add_event::<WezTermUserVar>()
+            .add_event::<QueryWezTermPanes>()
+            .add_event::<WezTermPanes>();
+    }
+
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunningPaneQueries>().add_systems(
+            PostUpdate,
+            (set_user_vars, query_panes, finish_pane_queries)
+                .chain()
+                .run_if(not(is_headless))
+                .before(TerminalSet::Cleanup),
+        );
+    }
+}
+
+/// Sets a user var of the WezTerm pane the app runs in.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct WezTermUserVar {
+    pub name: String,
+    pub value: String,
+}
+
+impl WezTermUserVar {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+}
+
+/// A crossterm command that sets a WezTerm user var, with `OSC 1337 ; SetUserVar=name=value BEL`.
+///
+/// The value is base64 encoded, as WezTerm expects.
+///
+/// ```rust
+/// use bevy_ratatui::wezterm::SetUserVar;
+/// use crossterm::Command;
+///
+/// let mut ansi = String::new();
+/// SetUserVar("mode", "insert").write_ansi(&mut ansi).unwrap();
+/// assert_eq!(ansi, "\x1b]1337;SetUserVar=mode=aW5zZXJ0\x07");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetUserVar<'a>(pub &'a str, pub &'a str);
+
+impl crossterm::Command for SetUserVar<'_> {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1b]1337;SetUserVar={}=", self.0)?;
+        extension::write_base64(f, self.1.as_bytes())?;
+        f.write_char('\x07')
+    }
+
+    #[cfg(windows)]
+    fn execute_winapi(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Asks WezTerm for its panes, which are sent back as a [`WezTermPanes`] event.
+#[derive(Event, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryWezTermPanes;
+
+/// The answer to a [`QueryWezTermPanes`].
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct WezTermPanes {
+    /// The id of the pane the app runs in, from `WEZTERM_PANE`.
+    pub own_pane: Option<u64>,
+    /// The JSON printed by `wezterm cli list --format json`, or why it failed.
+    pub output: Result<String, String>,
+}
+
+#[cfg(feature = "json")]
+impl WezTermPanes {
+    /// The panes as parsed JSON objects, with fields such as `pane_id`, `title`, `cwd` and `size`.
+    pub fn panes(&self) -> Option<Vec<serde_json::Value>> {
+        let output = self.output.as_ref().ok()?;
+        match serde_json::from_str(output).ok()? {
+            serde_json::Value::Array(panes) => Some(panes),
+            _ => None,
+        }
+    }
+
+    /// The pane the app runs in.
+    pub fn own(&self) -> Option<serde_json::Value> {
+        let own_pane = self.own_pane?;
+        self.panes()?
+            .into_iter()
+            .find(|pane| pane["pane_id"].as_u64() == Some(own_pane))
+    }
+}
+
+/// The pane queries that are still running.
+#[derive(Resource, Debug, Default)]
+struct RunningPaneQueries(Vec<JoinHandle<Result<String, String>>>);
+
+fn set_user_vars(mut vars: EventReader<WezTermUserVar>) {
+    if vars.is_empty() {
+        return;
+    }
+    let mut output = output();
+    for var in vars.read() {
+        if let Err(err) = crossterm::queue!(output, SetUserVar(&var.name, &var.value)) {
+            warn!("Failed to set the WezTerm user var {}: {err}", var.name);
+        }
+    }
+    if let Err(err) = output.flush() {
+        warn!("Failed to set WezTerm user vars: {err}");
+    }
+}
+
+fn query_panes(
+    mut queries: EventReader<QueryWezTermPanes>,
+    mut running: ResMut<RunningPaneQueries>,
+) {
+    // Every query gets the same answer, so one command is enough.
+    if queries.read().count() == 0 {
+        return;
+    }
+    running.0.push(thread::spawn(|| {
+        let output = Command::new("wezterm")
+            .args(["cli", "list", "--format", "json"])
+            .output()
+            .map_err(|err| format!("could not run wezterm: {err}"))?;
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            Err(String::from_utf8_lossy(&output.stderr)
+                .trim_end()
+                .to_string())
+        }
+    }));
+}
+
+fn finish_pane_queries(
+    mut running: ResMut<RunningPaneQueries>,
+    mut panes: EventWriter<WezTermPanes>,
+) {
+    let (finished, still_running) = running.0.drain(..).partition(JoinHandle::is_finished);
+    running.0 = still_running;
+    let own_pane = env::var("WEZTERM_PANE")
+        .ok()
+        .and_then(|pane| pane.parse().ok());
+    for thread in finished {
+        let output = thread
+            .join()
+            .unwrap_or_else(|_| Err("the query panicked".to_string()));
+        panes.send(WezTermPanes { own_pane, output });
+    }
+}
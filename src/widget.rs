@@ -0,0 +1,132 @@
+//! Drawing without a draw system.
+//!
+//! Insert a [`RootWidget`] resource and it is drawn to the terminal every frame. Simple apps only
+//! need to keep the resource up to date and never have to call
+//! [`RatatuiContext::draw`](crate::terminal::RatatuiContext::draw) themselves.
+//!
+//! ```rust,no_run
+//! use bevy::prelude::*;
+//! use bevy_ratatui::{widget::RootWidget, RatatuiPlugins};
+//! use ratatui::text::Line;
+//!
+//! App::new()
+//!     .add_plugins(RatatuiPlugins::default())
+//!     .insert_resource(RootWidget::new(Line::from("hello world")));
+//! ```
+//!
+//! # Visibility and disabled widgets
+//!
+//! Widget entities, such as [`EditableTable`](crate::table::EditableTable)s and
+//! [`TreeView`](crate::tree::TreeView)s, can be hidden with a [`WidgetVisibility`] component and
+//! disabled with a [`Disabled`] component. Neither takes key presses, and the views of disabled
+//! widgets are dimmed. Use [`WidgetVisibility::constraint`] when laying out widget entities so that
+//! collapsed widgets give up their space while hidden ones keep it.
+//!
+//! ```rust
+//! use bevy_ratatui::widget::WidgetVisibility;
+//! use ratatui::layout::{Constraint, Layout, Rect};
+//!
+//! let panels = [WidgetVisibility::Visible, WidgetVisibility::Collapsed];
+//! let constraints = panels
+//!     .iter()
+//!     .filter_map(|visibility| visibility.constraint(Constraint::Fill(1)));
+//! let areas = Layout::vertical(constraints).split(Rect::new(0, 0, 10, 10));
+//! assert_eq!(areas.len(), 1);
+//! ```
+use bevy::prelude::*;
+use ratatui::{layout::Constraint, widgets::WidgetRef};
+
+use crate::{
+    history::history_open,
+    loading::loading_finished,
+    pager::pager_closed,
+    profiling::WidgetProfiler,
+    render::{RenderBuffer, RenderPlugin, RenderSet},
+};
+
+/// A plugin that draws the [`RootWidget`] resource every frame, if it exists.
+///
+/// This requires the [`RenderPlugin`], and adds it if it is missing.
+pub struct RootWidgetPlugin;
+
+impl Plugin for RootWidgetPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<RenderPlugin>() {
+            app.add_plugins(RenderPlugin);
+        }
+        app.add_systems(
+            PostUpdate,
+            draw_root_widget
+                .run_if(
+                    resource_exists::<RootWidget>
+                        .and(pager_closed)
+                        .and(not(history_open))
+                        .and(loading_finished),
+                )
+                .in_set(RenderSet::Widgets),
+        );
+    }
+}
+
+/// A widget that is drawn over the whole terminal every frame.
+#[derive(Resource, Deref, DerefMut)]
+pub struct RootWidget(pub Box<dyn WidgetRef + Send + Sync>);
+
+impl RootWidget {
+    /// Creates a root widget from any widget that can be rendered by reference.
+    pub fn new<W: WidgetRef + Send + Sync + 'static>(widget: W) -> Self {
+        Self(Box::new(widget))
+    }
+}
+
+/// Renders the [`RootWidget`] over the whole [`RenderBuffer`], in [`RenderSet::Widgets`].
+///
+/// The render time is recorded as `root` if there is a [`WidgetProfiler`].
+pub fn draw_root_widget(
+    mut buffer: ResMut<RenderBuffer>,
+    root: Res<RootWidget>,
+    profiler: Option<ResMut<WidgetProfiler>>,
+) {
+    let area = buffer.area;
+    match profiler {
+        Some(mut profiler) => profiler.time("root", || root.render_ref(area, &mut buffer)),
+        None => root.render_ref(area, &mut buffer),
+    }
+}
+
+/// Whether a widget entity is drawn and takes part in layout. Widgets without this component are
+/// visible.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WidgetVisibility {
+    #[default]
+    Visible,
+    /// Not drawn, but keeps its space in the layout.
+    Hidden,
+    /// Not drawn, and left out of the layout.
+    Collapsed,
+}
+
+impl WidgetVisibility {
+    /// Returns true if the widget is drawn.
+    pub fn is_visible(self) -> bool {
+        self == Self::Visible
+    }
+
+    /// The constraint the widget takes in a layout, or `None` if it is collapsed.
+    pub fn constraint(self, constraint: Constraint) -> Option<Constraint> {
+        (self != Self::Collapsed).then_some(constraint)
+    }
+}
+
+/// Marks a widget entity as disabled. Disabled widgets ignore key presses and are drawn dimmed.
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Disabled;
+
+/// Query data for a widget entity together with the components that decide whether it takes key
+/// presses. Pass the last two items to [`is_interactive`].
+pub type Interactive<'a, D> = (D, Option<&'a WidgetVisibility>, Has<Disabled>);
+
+/// Returns true if a widget entity with these components takes key presses.
+pub fn is_interactive(visibility: Option<&WidgetVisibility>, disabled: bool) -> bool {
+    !disabled && visibility.is_none_or(|visibility| visibility.is_visible())
+}
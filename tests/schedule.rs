@@ -0,0 +1,97 @@
+//! Checks that the systems added by the plugins are explicitly ordered.
+
+use bevy::{
+    ecs::schedule::{LogLevel, ScheduleBuildSettings, ScheduleLabel},
+    prelude::*,
+};
+use bevy_ratatui::{
+    event::{InputSet, KeyEvent},
+    quit::QuitPlugin,
+    RatatuiPlugins,
+};
+use crossterm::event::KeyCode as CrosstermKeyCode;
+
+/// Builds every schedule with ambiguity detection set to error, without running any systems.
+///
+/// Two systems are ambiguous when they access the same data without an order between them, so the
+/// order they run in would depend on the order the plugins were added in.
+#[test]
+fn schedules_have_no_ambiguities() {
+    assert_no_ambiguities(PreUpdate);
+}
+
+/// The same check, with terminal events read in [`First`].
+#[test]
+fn schedules_have_no_ambiguities_reading_events_in_first() {
+    assert_no_ambiguities(First);
+}
+
+/// Forwarded input must be in bevy's `ButtonInput` resources by the time `Update` runs, even when
+/// terminal events are read in [`First`].
+#[test]
+fn forwarded_input_is_available_in_update_reading_events_in_first() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        RatatuiPlugins {
+            enable_input_forwarding: true,
+            event_schedule: First.intern(),
+            headless: true,
+            ..default()
+        },
+    ))
+    .init_resource::<PressedInUpdate>()
+    .add_systems(First, send_key.in_set(InputSet::EmitCrossterm))
+    .add_systems(Update, record_pressed);
+    app.update();
+
+    assert!(app.world().resource::<PressedInUpdate>().0);
+}
+
+fn assert_no_ambiguities(event_schedule: impl ScheduleLabel) {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        RatatuiPlugins {
+            enable_kitty_protocol: true,
+            enable_mouse_capture: true,
+            enable_input_forwarding: true,
+            enable_bracketed_paste: true,
+            event_schedule: event_schedule.intern(),
+            ..default()
+        },
+        QuitPlugin,
+    ));
+    app.finish();
+    app.cleanup();
+
+    let world = app.world_mut();
+    let labels: Vec<_> = world
+        .resource::<Schedules>()
+        .iter()
+        .map(|(_, schedule)| schedule.label())
+        .collect();
+    for label in labels {
+        world.schedule_scope(label, |world, schedule| {
+            schedule.set_build_settings(ScheduleBuildSettings {
+                ambiguity_detection: LogLevel::Error,
+                ..default()
+            });
+            if let Err(err) = schedule.initialize(world) {
+                panic!("{label:?} has ambiguous systems: {err}");
+            }
+        });
+    }
+}
+
+#[derive(Resource, Default)]
+struct PressedInUpdate(bool);
+
+/// Stands in for the terminal, which a headless app does not read.
+fn send_key(mut keys: EventWriter<KeyEvent>) {
+    keys.send(KeyEvent(CrosstermKeyCode::Char('a').into()));
+}
+
+fn record_pressed(keys: Res<ButtonInput<KeyCode>>, mut pressed: ResMut<PressedInUpdate>) {
+    pressed.0 = keys.pressed(KeyCode::KeyA);
+}